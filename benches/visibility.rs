@@ -0,0 +1,50 @@
+//! Benchmarks of the computation of what the player sees.
+//!
+//! `cargo bench --no-default-features` runs them.
+
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::IVec2;
+use pushdg::{
+	field_of_view,
+	generation::{generate_level, RunConfig},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Obstructions scattered on a square map of the given side, none at its center.
+fn scattered_obstructions(side: i32, density: f64) -> HashSet<IVec2> {
+	let mut rng = StdRng::seed_from_u64(42);
+	let center = IVec2::splat(side / 2);
+	(0..side)
+		.flat_map(|y| (0..side).map(move |x| IVec2::new(x, y)))
+		.filter(|coords| *coords != center && rng.gen_bool(density))
+		.collect()
+}
+
+fn field_of_view_benchmarks(criterion: &mut Criterion) {
+	let obstructions = scattered_obstructions(200, 0.1);
+	let center = IVec2::splat(100);
+	criterion.bench_function("field of view, radius 6.5, 200x200 map", |bencher| {
+		bencher.iter(|| field_of_view::visible_coords(black_box(center), 6.5, &obstructions))
+	});
+	criterion.bench_function("field of view, radius 40, 200x200 map", |bencher| {
+		bencher.iter(|| field_of_view::visible_coords(black_box(center), 40.0, &obstructions))
+	});
+}
+
+fn updated_visibility_benchmarks(criterion: &mut Criterion) {
+	// The lines of sight are cached, so this measures the visibility of a move that goes back
+	// to a known situation, which is what most moves of a turn-based game end up doing.
+	let lw = generate_level(&RunConfig::new());
+	criterion.bench_function("updated visibility, generated level", |bencher| {
+		bencher.iter(|| black_box(lw.clone()).updated_visibility())
+	});
+}
+
+criterion_group!(
+	benches,
+	field_of_view_benchmarks,
+	updated_visibility_benchmarks
+);
+criterion_main!(benches);
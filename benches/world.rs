@@ -0,0 +1,43 @@
+//! Benchmarks of the snapshots of the world that the transitions take, on a big map.
+//!
+//! `cargo bench --no-default-features` runs them.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::IVec2;
+use pushdg::{gameplay::LogicalWorld, generation::RunConfig, simulation::play_game_turn};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// A square open map of the given side with the bunny at the center and slimes scattered around.
+fn big_world(side: usize, slime_count: usize) -> LogicalWorld {
+	let mut rows = vec![vec!['.'; side]; side];
+	rows[side / 2][side / 2] = '@';
+	let mut rng = StdRng::seed_from_u64(42);
+	let mut placed = 0;
+	while placed < slime_count {
+		let (x, y) = (rng.gen_range(0..side), rng.gen_range(0..side));
+		if rows[y][x] == '.' {
+			rows[y][x] = 's';
+			placed += 1;
+		}
+	}
+	let grid: Vec<String> = rows.into_iter().map(|row| row.into_iter().collect()).collect();
+	let level_str = format!("s = floor slime:2\n---\n{}\n", grid.join("\n"));
+	LogicalWorld::from_level_str(&level_str).unwrap().generated_walls_outside()
+}
+
+fn world_benchmarks(criterion: &mut Criterion) {
+	let lw = big_world(200, 100);
+	criterion.bench_function("clone, 200x200 map with 100 agents", |bencher| {
+		bencher.iter(|| black_box(&lw).clone())
+	});
+	let run_config = RunConfig::new();
+	criterion.bench_function("move and turn, 200x200 map with 100 agents", |bencher| {
+		bencher.iter(|| {
+			let transition = black_box(&lw).player_move(IVec2::new(1, 0));
+			play_game_turn(transition, &run_config)
+		})
+	});
+}
+
+criterion_group!(benches, world_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+//! Assembles the spritesheet from the individual sprites in `assets/sprites/`.
+//!
+//! Every PNG in that folder (but not in its subfolders, `unused/` keeps the art nothing uses yet)
+//! is packed into a spritesheet written to `OUT_DIR`. The rect of each sprite in the sheet is
+//! written alongside it as a Rust constant named after the file, for `src/spritesheet.rs`
+//! to include, so that no one has to do rect math by hand.
+//!
+//! A PNG whose name ends with `_strip` holds the square frames of an animation side by side,
+//! its constant is the array of the rects of its frames (named without the `_strip`).
+
+use std::{fmt::Write, path::Path};
+
+#[path = "src/sprite_packing.rs"]
+mod sprite_packing;
+
+use sprite_packing::{pack_sprites, SHEET_SIZE};
+
+fn main() {
+	let sprites_dir = Path::new("assets/sprites");
+	println!("cargo:rerun-if-changed={}", sprites_dir.display());
+	for entry in std::fs::read_dir(sprites_dir).unwrap() {
+		println!("cargo:rerun-if-changed={}", entry.unwrap().path().display());
+	}
+
+	let (sheet, layout) = pack_sprites(sprites_dir).unwrap();
+
+	let rect_code = |x: u32, y: u32, w: u32, h: u32| {
+		format!(
+			"Rect::new({x}.0 / {SHEET_SIZE}.0, {y}.0 / {SHEET_SIZE}.0, \
+			{w}.0 / {SHEET_SIZE}.0, {h}.0 / {SHEET_SIZE}.0)"
+		)
+	};
+	let mut layout_code = String::new();
+	for sprite in layout.iter() {
+		let [x, y, w, h] = [sprite.x, sprite.y, sprite.w, sprite.h];
+		if let Some(name) = sprite.name.strip_suffix("_strip") {
+			let frame_count = w / h;
+			let frames: Vec<_> =
+				(0..frame_count).map(|frame| rect_code(x + frame * h, y, h, h)).collect();
+			writeln!(
+				layout_code,
+				"pub const {}: [Rect; {frame_count}] = [{}];",
+				name.to_uppercase(),
+				frames.join(", "),
+			)
+			.unwrap();
+		} else {
+			writeln!(
+				layout_code,
+				"pub const {}: Rect = {};",
+				sprite.name.to_uppercase(),
+				rect_code(x, y, w, h),
+			)
+			.unwrap();
+		}
+	}
+	// Hot-reloading checks that the sprites still pack like this, which the constants above assume.
+	writeln!(layout_code, "#[cfg(debug_assertions)]").unwrap();
+	writeln!(layout_code, "pub const PACKED: &[(&str, [u32; 4])] = &[").unwrap();
+	for sprite in layout.iter() {
+		let [x, y, w, h] = [sprite.x, sprite.y, sprite.w, sprite.h];
+		writeln!(
+			layout_code,
+			"\t(\"{}\", [{x}, {y}, {w}, {h}]),",
+			sprite.name
+		)
+		.unwrap();
+	}
+	writeln!(layout_code, "];").unwrap();
+
+	let out_dir = std::env::var("OUT_DIR").unwrap();
+	sheet.save(Path::new(&out_dir).join("spritesheet.png")).unwrap();
+	std::fs::write(Path::new(&out_dir).join("sprite_layout.rs"), layout_code).unwrap();
+}
@@ -0,0 +1,33 @@
+//! A bot that plays a run with random moves, headlessly, to show how to drive the simulation.
+//!
+//! `cargo run --example random_bot --no-default-features -- 1234` plays on the seed 1234.
+
+use glam::IVec2;
+use pushdg::{gameplay::four_directions, Action, Generator, Simulation};
+use rand::seq::SliceRandom;
+
+fn main() {
+	let mut run_config = Generator::new();
+	if let Some(seed) = std::env::args().nth(1).and_then(|seed| seed.parse().ok()) {
+		run_config.seed = seed;
+	}
+	let mut simulation = Simulation::new(run_config);
+	let mut rng = rand::thread_rng();
+	let mut turns = 0;
+	while turns < 1000 && !simulation.is_won() && !simulation.is_lost() {
+		let direction: IVec2 = *four_directions().choose(&mut rng).unwrap();
+		simulation.player_act(Action::Move(direction));
+		turns += 1;
+	}
+	let outcome = if simulation.is_won() {
+		"won"
+	} else if simulation.is_lost() {
+		"lost"
+	} else {
+		"still going"
+	};
+	println!(
+		"Seed {}: {outcome} after {turns} turns, at depth {}",
+		simulation.run_config.seed, simulation.run_config.depth
+	);
+}
@@ -0,0 +1,240 @@
+//! Sound effects, played when the events of a transition start being animated,
+//! and the background music, one looping track per depth.
+//!
+//! The sounds are the WAVs of `assets/sounds/` and the tracks are the WAVs of `assets/music/`,
+//! embedded in the executable.
+//!
+//! The audio is owned by the game for its whole duration, runs can start, be loaded and end
+//! without the music being cut. When the depth changes, the track of the previous depth fades
+//! out while the track of the new depth fades in.
+
+use std::time::Duration;
+
+use ggez::{
+	audio::{SoundData, SoundSource, Source},
+	Context,
+};
+
+use crate::gameplay::{LogicalEvent, LogicalTransition};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SoundEffect {
+	Step,
+	Bump,
+	Hit,
+	Kill,
+	Mine,
+	Unlock,
+	Pickup,
+	Explosion,
+	Fall,
+	Splash,
+	Exit,
+	Magic,
+}
+
+impl SoundEffect {
+	fn all() -> [SoundEffect; 12] {
+		[
+			SoundEffect::Step,
+			SoundEffect::Bump,
+			SoundEffect::Hit,
+			SoundEffect::Kill,
+			SoundEffect::Mine,
+			SoundEffect::Unlock,
+			SoundEffect::Pickup,
+			SoundEffect::Explosion,
+			SoundEffect::Fall,
+			SoundEffect::Splash,
+			SoundEffect::Exit,
+			SoundEffect::Magic,
+		]
+	}
+
+	fn wav(self) -> &'static [u8] {
+		match self {
+			SoundEffect::Step => include_bytes!("../assets/sounds/step.wav"),
+			SoundEffect::Bump => include_bytes!("../assets/sounds/bump.wav"),
+			SoundEffect::Hit => include_bytes!("../assets/sounds/hit.wav"),
+			SoundEffect::Kill => include_bytes!("../assets/sounds/kill.wav"),
+			SoundEffect::Mine => include_bytes!("../assets/sounds/mine.wav"),
+			SoundEffect::Unlock => include_bytes!("../assets/sounds/unlock.wav"),
+			SoundEffect::Pickup => include_bytes!("../assets/sounds/pickup.wav"),
+			SoundEffect::Explosion => include_bytes!("../assets/sounds/explosion.wav"),
+			SoundEffect::Fall => include_bytes!("../assets/sounds/fall.wav"),
+			SoundEffect::Splash => include_bytes!("../assets/sounds/splash.wav"),
+			SoundEffect::Exit => include_bytes!("../assets/sounds/exit.wav"),
+			SoundEffect::Magic => include_bytes!("../assets/sounds/magic.wav"),
+		}
+	}
+
+	fn of_event(logical_event: &LogicalEvent) -> Option<SoundEffect> {
+		Some(match logical_event {
+			LogicalEvent::Move { .. } | LogicalEvent::BombPlaced { .. } => SoundEffect::Step,
+			LogicalEvent::FailToMove { .. }
+			| LogicalEvent::MoveInto { .. }
+			| LogicalEvent::ArrowShot { .. }
+			| LogicalEvent::Shot { .. } => SoundEffect::Bump,
+			LogicalEvent::Hit { .. }
+			| LogicalEvent::BarrelSetOff { .. }
+			| LogicalEvent::Squelched { .. }
+			| LogicalEvent::Stomped { .. } => SoundEffect::Hit,
+			LogicalEvent::Killed { .. } => SoundEffect::Kill,
+			LogicalEvent::Mined { .. }
+			| LogicalEvent::CrateBroken { .. }
+			| LogicalEvent::Disarmed { .. } => SoundEffect::Mine,
+			LogicalEvent::DoorOpenedWithKey { .. }
+			| LogicalEvent::Crafted { .. }
+			| LogicalEvent::GateOpened { .. }
+			| LogicalEvent::GateClosed { .. } => SoundEffect::Unlock,
+			LogicalEvent::Healed { .. }
+			| LogicalEvent::RedoGained { .. }
+			| LogicalEvent::RewindGained { .. }
+			| LogicalEvent::BombGained { .. }
+			| LogicalEvent::GoldGained { .. }
+			| LogicalEvent::KeyShardGained { .. }
+			| LogicalEvent::StrengthGained { .. }
+			| LogicalEvent::Bought { .. }
+			| LogicalEvent::PickedUp { .. }
+			| LogicalEvent::Dropped { .. }
+			| LogicalEvent::NoteRead { .. } => SoundEffect::Pickup,
+			LogicalEvent::Exploded { .. } | LogicalEvent::BurntUp { .. } => SoundEffect::Explosion,
+			LogicalEvent::FellIntoHole { .. } | LogicalEvent::FloorCollapsed { .. } =>
+				SoundEffect::Fall,
+			LogicalEvent::Sank { .. } | LogicalEvent::RaftMoored { .. } => SoundEffect::Splash,
+			LogicalEvent::Exit { .. } => SoundEffect::Exit,
+			LogicalEvent::Revived { .. }
+			| LogicalEvent::Teleported { .. }
+			| LogicalEvent::StatusInflicted { .. }
+			| LogicalEvent::MirrorCursed { .. }
+			| LogicalEvent::LaserFired { .. }
+			| LogicalEvent::AltarAwakened { .. } => SoundEffect::Magic,
+			LogicalEvent::FuseBurned { .. }
+			| LogicalEvent::SpikesRose { .. }
+			| LogicalEvent::SpikesRetracted { .. }
+			| LogicalEvent::WokeUp { .. }
+			| LogicalEvent::FellDormant { .. } => return None,
+		})
+	}
+}
+
+/// The highest volume, the volume goes from 0 (muted) to it.
+pub const MAX_VOLUME: i32 = 10;
+
+/// The music is quieter than the sound effects, to remain in the background.
+const MUSIC_VOLUME_FACTOR: f32 = 0.4;
+
+/// How long it takes for a track to fade into the next.
+const CROSSFADE_DURATION: Duration = Duration::from_secs(2);
+
+/// The track of each depth, from depth 1. The deeper depths reuse the last one.
+fn music_track_wavs() -> [&'static [u8]; 3] {
+	[
+		include_bytes!("../assets/music/depth_1.wav"),
+		include_bytes!("../assets/music/depth_2.wav"),
+		include_bytes!("../assets/music/depth_3.wav"),
+	]
+}
+
+/// A track that is playing, in a loop.
+struct PlayingTrack {
+	depth: i32,
+	source: Source,
+	/// From 0 (silent) to 1 (full volume), as they fade in or out.
+	fade: f32,
+}
+
+pub struct Audio {
+	/// The decoded sound of each sound effect, in the order of `SoundEffect::all`.
+	sounds: Vec<SoundData>,
+	/// The sound effects of the transitions that started being animated since the last update.
+	queued: Vec<SoundEffect>,
+	/// From 0 to `MAX_VOLUME`.
+	pub volume: i32,
+	music_tracks: Vec<SoundData>,
+	/// The track of the current depth, fading in until it is at full volume.
+	current_track: Option<PlayingTrack>,
+	/// The tracks of the previous depths, fading out until they stop.
+	fading_out_tracks: Vec<PlayingTrack>,
+	pub music_muted: bool,
+}
+
+impl Audio {
+	pub fn new() -> Audio {
+		let sounds = SoundEffect::all()
+			.into_iter()
+			.map(|sound_effect| SoundData::from_bytes(sound_effect.wav()))
+			.collect();
+		let music_tracks = music_track_wavs().into_iter().map(SoundData::from_bytes).collect();
+		Audio {
+			sounds,
+			queued: vec![],
+			volume: MAX_VOLUME / 2,
+			music_tracks,
+			current_track: None,
+			fading_out_tracks: vec![],
+			music_muted: false,
+		}
+	}
+
+	/// Queues the sound effects of the events of the transition,
+	/// each sound effect is played at most once per transition.
+	pub fn queue_transition(&mut self, transition: &LogicalTransition) {
+		for sound_effect in transition.logical_events.iter().filter_map(SoundEffect::of_event) {
+			if !self.queued.contains(&sound_effect) {
+				self.queued.push(sound_effect);
+			}
+		}
+	}
+
+	pub fn play_queued(&mut self, ctx: &Context) {
+		let queued = std::mem::take(&mut self.queued);
+		if self.volume == 0 {
+			return;
+		}
+		for sound_effect in queued {
+			let index = SoundEffect::all().iter().position(|&other| other == sound_effect).unwrap();
+			// Failing to play a sound is not a big deal, the game can be played without sound.
+			if let Ok(mut source) = Source::from_data(ctx, self.sounds[index].clone()) {
+				source.set_volume(self.volume as f32 / MAX_VOLUME as f32);
+				let _ = source.play_detached(ctx);
+			}
+		}
+	}
+
+	/// Crossfades to the track of the given depth if it is not the one playing already,
+	/// and carries on the fading.
+	pub fn update_music(&mut self, ctx: &Context, depth: i32, delta: Duration) {
+		if self.current_track.as_ref().map(|track| track.depth) != Some(depth) {
+			self.fading_out_tracks.extend(self.current_track.take());
+			let track_index = (depth.max(1) as usize - 1).min(self.music_tracks.len() - 1);
+			// Failing to play the music is not a big deal, the game can be played without it.
+			if let Ok(mut source) = Source::from_data(ctx, self.music_tracks[track_index].clone()) {
+				source.set_repeat(true);
+				source.set_volume(0.0);
+				if source.play(ctx).is_ok() {
+					self.current_track = Some(PlayingTrack { depth, source, fade: 0.0 });
+				}
+			}
+		}
+
+		let fade_step = delta.as_secs_f32() / CROSSFADE_DURATION.as_secs_f32();
+		let volume = if self.music_muted {
+			0.0
+		} else {
+			self.volume as f32 / MAX_VOLUME as f32 * MUSIC_VOLUME_FACTOR
+		};
+		if let Some(track) = self.current_track.as_mut() {
+			track.fade = (track.fade + fade_step).min(1.0);
+			track.source.set_volume(track.fade * volume);
+		}
+		for track in self.fading_out_tracks.iter_mut() {
+			track.fade = (track.fade - fade_step).max(0.0);
+			track.source.set_volume(track.fade * volume);
+			if track.fade == 0.0 {
+				let _ = track.source.stop(ctx);
+			}
+		}
+		self.fading_out_tracks.retain(|track| track.fade > 0.0);
+	}
+}
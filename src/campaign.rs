@@ -0,0 +1,253 @@
+//! Puzzle campaign, a sequence of handcrafted levels to solve one after the other.
+//!
+//! The levels are the files of `assets/levels/` (in the level format, see the `level_format`
+//! module), embedded in the executable. A level is solved by going through its exit, which is
+//! remembered in the player profile, and a level can be played once the previous one is solved.
+//! The campaign screen (opened from the title screen) lists the levels to pick one.
+//!
+//! A level that has a title in its metadata is introduced by a card before it is played, and
+//! one that has a par earns a medal when solved, the best ones are listed in the campaign screen.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+};
+
+use crate::{
+	gameplay::{LogicalWorld, Obj},
+	graphics::{obj_to_sprite, DepthLayer},
+	level_format::{LevelMetadata, Medal},
+	profile::Profile,
+	spritesheet::{SpriteFromSheet, SpritesheetStuff},
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+pub struct CampaignLevel {
+	/// What the profile remembers the level by, it should not change once released.
+	pub id: &'static str,
+	level_str: &'static str,
+}
+
+impl CampaignLevel {
+	/// The level, with the walls around it.
+	pub fn logical_world(&self) -> LogicalWorld {
+		LogicalWorld::from_level_str(self.level_str)
+			.unwrap_or_else(|error| panic!("The level \"{}\" is invalid: {error}", self.id))
+			.generated_walls_outside()
+	}
+
+	pub fn metadata(&self) -> LevelMetadata {
+		LevelMetadata::from_level_str(self.level_str)
+			.unwrap_or_else(|error| panic!("The level \"{}\" is invalid: {error}", self.id))
+	}
+
+	/// The title of the level, or its id if it has none.
+	pub fn name(&self) -> String {
+		self.metadata().title.unwrap_or_else(|| self.id.to_string())
+	}
+
+	/// What the level is about, its description or else the first comment of the file.
+	fn hint(&self) -> String {
+		self.metadata().description.unwrap_or_else(|| {
+			self
+				.level_str
+				.lines()
+				.find_map(|line| line.strip_prefix(';'))
+				.map_or("", |comment| comment.trim())
+				.to_string()
+		})
+	}
+}
+
+pub fn medal_to_sprite(medal: Medal) -> SpriteFromSheet {
+	match medal {
+		Medal::Bronze => SpriteFromSheet::MedalBronze,
+		Medal::Silver => SpriteFromSheet::MedalSilver,
+		Medal::Gold => SpriteFromSheet::MedalGold,
+	}
+}
+
+/// The levels of the campaign, in the order in which they are to be solved.
+pub fn campaign_levels() -> [CampaignLevel; 6] {
+	[
+		CampaignLevel {
+			id: "push",
+			level_str: include_str!("../assets/levels/1_push.txt"),
+		},
+		CampaignLevel {
+			id: "raft",
+			level_str: include_str!("../assets/levels/2_raft.txt"),
+		},
+		CampaignLevel {
+			id: "plate",
+			level_str: include_str!("../assets/levels/3_plate.txt"),
+		},
+		CampaignLevel { id: "ice", level_str: include_str!("../assets/levels/4_ice.txt") },
+		CampaignLevel {
+			id: "golem",
+			level_str: include_str!("../assets/levels/5_golem.txt"),
+		},
+		CampaignLevel {
+			id: "two_plates",
+			level_str: include_str!("../assets/levels/6_two_plates.txt"),
+		},
+	]
+}
+
+/// What the game should do following a key press in the campaign screen.
+pub enum CampaignAction {
+	Back,
+	/// Play the level at that index in the campaign.
+	Play(usize),
+}
+
+/// Lists the levels of the campaign, the ones that can be played and the ones already solved.
+pub struct CampaignScreen {
+	/// How many levels can be played, the first ones.
+	playable_count: usize,
+	menu: Menu,
+}
+
+impl CampaignScreen {
+	/// The levels solved so far and their best turns are those of the given profile.
+	pub fn new(profile: Option<&Profile>) -> CampaignScreen {
+		let levels = campaign_levels();
+		let solved_ids = profile.map_or(&[][..], |profile| &profile.solved_levels);
+		let is_solved = |level: &CampaignLevel| solved_ids.iter().any(|id| id == level.id);
+		let best_turns = |level: &CampaignLevel| profile?.best_turns(level.id);
+		let playable_count = levels
+			.iter()
+			.position(|level| !is_solved(level))
+			.map_or(levels.len(), |first_unsolved| first_unsolved + 1);
+		let mut widgets: Vec<_> = levels
+			.iter()
+			.enumerate()
+			.map(|(index, level)| Widget::Button {
+				label: format!("{}. {}", index + 1, level.name()),
+				description: Some(if index >= playable_count {
+					"Solve the previous level first.".to_string()
+				} else if let Some(turns) = best_turns(level) {
+					format!("{}. {}", level.metadata().score(turns), level.hint())
+				} else if is_solved(level) {
+					format!("Solved. {}", level.hint())
+				} else {
+					level.hint()
+				}),
+				icon: is_solved(level).then(|| {
+					best_turns(level)
+						.and_then(|turns| level.metadata().medal(turns))
+						.map_or(obj_to_sprite(&Obj::Exit), medal_to_sprite)
+				}),
+			})
+			.collect();
+		widgets.push(Widget::button("Back"));
+		CampaignScreen { playable_count, menu: Menu::new(widgets) }
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<CampaignAction> {
+		if keycode == VirtualKeyCode::Escape {
+			return Some(CampaignAction::Back);
+		}
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(index) if index == campaign_levels().len() =>
+				Some(CampaignAction::Back),
+			MenuEvent::Activated(index) =>
+				(index < self.playable_count).then_some(CampaignAction::Play(index)),
+			MenuEvent::Changed(_) => None,
+		}
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 600.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let mut title_text = Text::new("Campaign");
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 70.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
+
+/// The card that introduces a handcrafted level before it is played, and waits for the player
+/// to dismiss it.
+pub struct LevelIntro {
+	title: String,
+	/// Who made the level, what it is about, and the turns and redos it comes with.
+	lines: Vec<String>,
+}
+
+impl LevelIntro {
+	/// There is no card for a level without a title, like most of those made in the editor.
+	pub fn if_titled(metadata: &LevelMetadata, best_turns: Option<i32>) -> Option<LevelIntro> {
+		let title = metadata.title.clone()?;
+		let mut lines = vec![];
+		if let Some(author) = &metadata.author {
+			lines.push(format!("By {author}"));
+		}
+		if let Some(description) = &metadata.description {
+			lines.push(description.clone());
+		}
+		if let Some(par_turns) = metadata.par_turns {
+			lines.push(format!("Par: {par_turns} turns"));
+		}
+		if let Some(redos) = metadata.redos {
+			lines.push(format!("Redos: {redos}"));
+		}
+		if let Some(turns) = best_turns {
+			lines.push(format!("Best: {}", metadata.score(turns)));
+		}
+		Some(LevelIntro { title, lines })
+	}
+
+	pub fn draw(&self, canvas: &mut Canvas, screen_size: Vec2) {
+		let panel_rect = centered_rect(500.0, 360.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let z = DepthLayer::Popup.to_z_value();
+		let mut title_text = Text::new(self.title.as_str());
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+		);
+		let mut lines_text = Text::new(self.lines.join("\n\n"));
+		lines_text.set_scale(20.0).set_bounds(Vec2::new(panel_rect.w - margin * 2.0, 220.0));
+		canvas.draw(
+			&lines_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 70.0,
+				))
+				.z(z),
+		);
+		let mut start_text = Text::new("Press Enter to start");
+		start_text.set_scale(14.0);
+		canvas.draw(
+			&start_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.bottom() - margin,
+				))
+				.color(Color::new(0.6, 0.6, 0.6, 1.0))
+				.z(z),
+		);
+	}
+}
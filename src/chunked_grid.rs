@@ -0,0 +1,198 @@
+//! A map from coords to values, stored in square chunks that are shared between clones.
+//!
+//! Every transition holds a snapshot of the world, and most of them only change a few tiles,
+//! so cloning a grid only clones a reference to each of its chunks, and a chunk is copied only
+//! when something in it is modified while it is still shared with an other grid.
+
+use std::{collections::HashMap, ops::Index, sync::Arc};
+
+use glam::IVec2;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The side of a chunk, in tiles.
+const CHUNK_SIDE: i32 = 16;
+
+fn chunk_coords(coords: IVec2) -> IVec2 {
+	IVec2::new(
+		coords.x.div_euclid(CHUNK_SIDE),
+		coords.y.div_euclid(CHUNK_SIDE),
+	)
+}
+
+#[derive(Clone)]
+pub struct ChunkedGrid<T> {
+	/// There is no empty chunk, a chunk is removed when its last value is.
+	chunks: HashMap<IVec2, Arc<HashMap<IVec2, T>>>,
+	len: usize,
+}
+
+impl<T> Default for ChunkedGrid<T> {
+	fn default() -> ChunkedGrid<T> {
+		ChunkedGrid { chunks: HashMap::new(), len: 0 }
+	}
+}
+
+impl<T> ChunkedGrid<T> {
+	pub fn new() -> ChunkedGrid<T> {
+		ChunkedGrid::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn get(&self, coords: &IVec2) -> Option<&T> {
+		self.chunks.get(&chunk_coords(*coords))?.get(coords)
+	}
+
+	pub fn contains_key(&self, coords: &IVec2) -> bool {
+		self.get(coords).is_some()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&IVec2, &T)> {
+		self.chunks.values().flat_map(|chunk| chunk.iter())
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &IVec2> {
+		self.iter().map(|(coords, _value)| coords)
+	}
+
+	pub fn values(&self) -> impl Iterator<Item = &T> {
+		self.iter().map(|(_coords, value)| value)
+	}
+}
+
+impl<T: Clone> ChunkedGrid<T> {
+	/// Copies the chunk of the value first if it is shared, as does every mutable access.
+	pub fn get_mut(&mut self, coords: &IVec2) -> Option<&mut T> {
+		let chunk = self.chunks.get_mut(&chunk_coords(*coords))?;
+		if !chunk.contains_key(coords) {
+			return None;
+		}
+		Arc::make_mut(chunk).get_mut(coords)
+	}
+
+	pub fn insert(&mut self, coords: IVec2, value: T) -> Option<T> {
+		let chunk = self.chunks.entry(chunk_coords(coords)).or_default();
+		let previous_value = Arc::make_mut(chunk).insert(coords, value);
+		if previous_value.is_none() {
+			self.len += 1;
+		}
+		previous_value
+	}
+
+	pub fn remove(&mut self, coords: &IVec2) -> Option<T> {
+		let chunk_coords = chunk_coords(*coords);
+		let chunk = self.chunks.get_mut(&chunk_coords)?;
+		if !chunk.contains_key(coords) {
+			return None;
+		}
+		let value = Arc::make_mut(chunk).remove(coords);
+		if chunk.is_empty() {
+			self.chunks.remove(&chunk_coords);
+		}
+		self.len -= 1;
+		value
+	}
+
+	/// Modifies the values that pass the filter, only copying the shared chunks that have some.
+	/// There is no mutable iteration over all the values, which would copy all the chunks.
+	pub fn modify_where(
+		&mut self,
+		filter: impl Fn(&IVec2, &T) -> bool,
+		mut modify: impl FnMut(&IVec2, &mut T),
+	) {
+		for chunk in self.chunks.values_mut() {
+			if chunk.iter().any(|(coords, value)| filter(coords, value)) {
+				for (coords, value) in Arc::make_mut(chunk).iter_mut() {
+					if filter(coords, value) {
+						modify(coords, value);
+					}
+				}
+			}
+		}
+	}
+}
+
+impl<T> Index<&IVec2> for ChunkedGrid<T> {
+	type Output = T;
+
+	fn index(&self, coords: &IVec2) -> &T {
+		self.get(coords).expect("no value at these coords in the grid")
+	}
+}
+
+impl<T: Clone> FromIterator<(IVec2, T)> for ChunkedGrid<T> {
+	fn from_iter<I: IntoIterator<Item = (IVec2, T)>>(iter: I) -> ChunkedGrid<T> {
+		let mut grid = ChunkedGrid::new();
+		for (coords, value) in iter {
+			grid.insert(coords, value);
+		}
+		grid
+	}
+}
+
+/// The keys of a JSON object are strings, so the grid is serialized as a list of
+/// `[coords, value]` pairs, like a `HashMap` with `grid_as_list` would be.
+impl<T: Serialize> Serialize for ChunkedGrid<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(self.iter())
+	}
+}
+
+impl<'de, T: Clone + Deserialize<'de>> Deserialize<'de> for ChunkedGrid<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ChunkedGrid<T>, D::Error> {
+		Ok(Vec::<(IVec2, T)>::deserialize(deserializer)?.into_iter().collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::gameplay::{Ground, LogicalWorld, Tile};
+
+	/// Coords in a few chunks, negative ones included.
+	fn some_coords() -> Vec<IVec2> {
+		(-20..20)
+			.step_by(3)
+			.flat_map(|x| (-5..25).step_by(7).map(move |y| IVec2::new(x, y)))
+			.collect()
+	}
+
+	#[test]
+	fn clones_are_not_affected_by_writes_to_the_original() {
+		let mut grid: ChunkedGrid<i32> =
+			some_coords().into_iter().map(|coords| (coords, 0)).collect();
+		let clone = grid.clone();
+		*grid.get_mut(&IVec2::new(1, 2)).unwrap() = 1;
+		grid.modify_where(|coords, _value| coords.x < 0, |_coords, value| *value = 2);
+		grid.insert(IVec2::new(100, 100), 3);
+		grid.remove(&IVec2::new(-20, -5));
+		assert_eq!(grid[&IVec2::new(1, 2)], 1);
+		assert_eq!(grid[&IVec2::new(-17, 2)], 2);
+		assert_eq!(clone.len(), some_coords().len());
+		assert!(clone.values().all(|value| *value == 0));
+		assert!(!clone.contains_key(&IVec2::new(100, 100)));
+		assert!(clone.contains_key(&IVec2::new(-20, -5)));
+	}
+
+	#[test]
+	fn coords_where_does_not_depend_on_the_insertion_order() {
+		let world = |coords: &[IVec2]| {
+			let mut lw = LogicalWorld::new_empty();
+			for &coords in coords {
+				lw.place_tile(
+					coords,
+					Tile { ground: Ground::Floor, obj: None, visible: false },
+				);
+			}
+			lw
+		};
+		let coords = some_coords();
+		let reversed: Vec<_> = coords.iter().rev().copied().collect();
+		let mut sorted = coords.clone();
+		sorted.sort_unstable_by_key(|coords| (coords.y, coords.x));
+		assert_eq!(world(&coords).coords_where(|_tile| true), sorted);
+		assert_eq!(world(&reversed).coords_where(|_tile| true), sorted);
+	}
+}
@@ -0,0 +1,90 @@
+//! Daily challenge, the same run for every player on a given day, to be tried only once.
+//!
+//! Its seed is derived from the date (in UTC). It is played without assist options, unlocks
+//! or encounter director, and with a fixed number of redos, for the results to be compared.
+//! The result (won or lost, and in how many turns) is recorded in the player profile as soon as
+//! the challenge starts, so that a daily challenge left unfinished counts as lost.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+	gameplay::AssistOptions,
+	generation::{seed_from_text, RunConfig},
+};
+
+/// The date of today (in UTC), like `2024-03-09`.
+pub fn today() -> String {
+	let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+	date_of_day(seconds as i64 / (24 * 60 * 60))
+}
+
+/// The date of the given day counted from 1970-01-01, in the proleptic Gregorian calendar.
+fn date_of_day(day: i64) -> String {
+	// Counting from 0000-03-01 puts the leap day at the end of the years, in 400-year eras.
+	let day = day + 719468;
+	let era = day.div_euclid(146097);
+	let day_of_era = day.rem_euclid(146097);
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_from_march = (5 * day_of_year + 2) / 153;
+	let day_of_month = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+	let month = if month_from_march < 10 {
+		month_from_march + 3
+	} else {
+		month_from_march - 9
+	};
+	let year = year_of_era + era * 400 + i64::from(month <= 2);
+	format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+/// The run of the daily challenge of the given date.
+pub fn daily_run_config(date: &str) -> RunConfig {
+	RunConfig {
+		seed: seed_from_text(&format!("daily {date}")),
+		encounter_director: false,
+		assist: AssistOptions::default(),
+		daily_date: Some(date.to_string()),
+		..RunConfig::new()
+	}
+}
+
+#[derive(Clone)]
+pub struct DailyResult {
+	/// Like `2024-03-09`.
+	pub date: String,
+	pub won: bool,
+	pub turns: i32,
+}
+
+impl DailyResult {
+	/// Like `2024-03-09 won 123`, to be the value of a line of the profile.
+	pub fn to_text(&self) -> String {
+		format!(
+			"{} {} {}",
+			self.date,
+			if self.won { "won" } else { "lost" },
+			self.turns
+		)
+	}
+
+	pub fn from_text(text: &str) -> Option<DailyResult> {
+		let mut words = text.split_whitespace();
+		let date = words.next()?.to_string();
+		let won = match words.next()? {
+			"won" => true,
+			"lost" => false,
+			_ => return None,
+		};
+		let turns = words.next()?.parse().ok()?;
+		Some(DailyResult { date, won, turns })
+	}
+
+	pub fn description(&self) -> String {
+		if self.won {
+			format!("won in {} turns", self.turns)
+		} else {
+			format!("lost after {} turns", self.turns)
+		}
+	}
+}
@@ -0,0 +1,126 @@
+//! The encounter director, that keeps the tension of fights within some target band.
+//!
+//! It watches the damages recently taken by the player and the enemies in sight,
+//! and when it is time to give move tokens to the agents, it may hold back some enemies
+//! (if the player is overwhelmed) or send a reinforcement from out of sight
+//! (if nothing happened for a while).
+
+use std::collections::VecDeque;
+
+use glam::IVec2;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	gameplay::{Facing, Ground, LogicalEvent, LogicalTransition, LogicalWorld, Obj},
+	generation::RunConfig,
+};
+
+/// How many of the last player turns are considered to be recent.
+const RECENT_TURNS: usize = 5;
+/// Above that much tension, some enemies are held back.
+const MAX_TENSION: i32 = 6;
+/// After that many turns in a row without any tension, a reinforcement is sent.
+const CALM_TURNS_BEFORE_REINFORCEMENT: i32 = 25;
+
+fn manhattan_distance(a: IVec2, b: IVec2) -> i32 {
+	(a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+#[derive(Clone, Hash, Serialize, Deserialize)]
+pub struct EncounterDirector {
+	/// Damages taken by the player during each of the recent turns, most recent last.
+	recent_damages_taken: VecDeque<i32>,
+	/// How many turns in a row the tension was zero.
+	calm_turns: i32,
+}
+
+impl Default for EncounterDirector {
+	fn default() -> EncounterDirector {
+		EncounterDirector::new()
+	}
+}
+
+impl EncounterDirector {
+	pub fn new() -> EncounterDirector {
+		EncounterDirector { recent_damages_taken: VecDeque::new(), calm_turns: 0 }
+	}
+
+	/// Takes note of the damages taken by the player in a transition of the current turn.
+	pub fn observe(&mut self, transition: &LogicalTransition) {
+		let damages_taken: i32 = transition
+			.logical_events
+			.iter()
+			.map(|logical_event| match logical_event {
+				LogicalEvent::Hit { at, damages }
+					if matches!(transition.resulting_lw.obj(*at), Some(Obj::Bunny { .. })) =>
+					*damages,
+				LogicalEvent::Killed { obj: Obj::Bunny { .. }, damages, .. } => *damages,
+				_ => 0,
+			})
+			.sum();
+		if let Some(current_turn) = self.recent_damages_taken.back_mut() {
+			*current_turn += damages_taken;
+		}
+	}
+
+	fn start_turn(&mut self) {
+		self.recent_damages_taken.push_back(0);
+		if self.recent_damages_taken.len() > RECENT_TURNS {
+			self.recent_damages_taken.pop_front();
+		}
+	}
+
+	/// To be called after the agents were given their move tokens, it may take some back
+	/// or send a reinforcement, depending on how tense things are.
+	pub fn nudge_agents(&mut self, lw: &mut LogicalWorld, run_config: &RunConfig) {
+		self.start_turn();
+		let Some(player_coords) = lw.player_coords() else {
+			return;
+		};
+		let mut visible_enemies = lw
+			.coords_where(|tile| tile.visible && tile.obj.as_ref().is_some_and(|obj| obj.is_enemy()));
+		let recent_damages: i32 = self.recent_damages_taken.iter().sum();
+		let tension = recent_damages + visible_enemies.len() as i32;
+
+		if tension > MAX_TENSION {
+			// Hold back the enemies that are the farthest from the player,
+			// but never all of them, the player is still in a fight.
+			visible_enemies.sort_by_key(|coords| -manhattan_distance(*coords, player_coords));
+			let held_back_count =
+				((tension - MAX_TENSION) as usize).min(visible_enemies.len().saturating_sub(1));
+			for coords in visible_enemies.into_iter().take(held_back_count) {
+				lw.withhold_move_token(coords);
+			}
+		}
+
+		if tension == 0 {
+			self.calm_turns += 1;
+		} else {
+			self.calm_turns = 0;
+		}
+		if self.calm_turns >= CALM_TURNS_BEFORE_REINFORCEMENT {
+			// A slime shows up, somewhere close but out of sight. It does not move to get there,
+			// so it has to be on a plain floor, that no ground pass has to handle.
+			let spawn_candidates: Vec<IVec2> = lw
+				.coords_where(|tile| !tile.visible && matches!(tile.ground, Ground::Floor))
+				.into_iter()
+				.filter(|coords| lw.is_free(*coords))
+				.filter(|coords| (8..=12).contains(&manhattan_distance(*coords, player_coords)))
+				.collect();
+			if let Some(&coords) = spawn_candidates.choose(&mut lw.seeded_rng("reinforcement")) {
+				let mut tile = lw.tile(coords).unwrap().clone();
+				tile.obj = Some(Obj::Slime {
+					hp: run_config.enemy_hp(),
+					max_hp: run_config.enemy_hp(),
+					move_token: false,
+					home: coords,
+					statuses: vec![],
+					facing: Facing::Left,
+				});
+				lw.place_tile(coords, tile);
+				self.calm_turns = 0;
+			}
+		}
+	}
+}
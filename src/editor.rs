@@ -0,0 +1,395 @@
+//! Level editor, to craft levels by hand and play them.
+//!
+//! It is opened from the title screen, or right at launch with the `--editor` argument.
+//! A cursor is moved around the level to paint the ground or place objects with the current
+//! brush, the bunny being where the player starts. The level is saved as `level.txt` (in the
+//! level format, see the `level_format` module) in the user data directory with `F5` and loaded
+//! back with `F9`, and it can be playtested with `P` (`F2` goes back to the editor). The metadata
+//! of a loaded level (its title, par and such) is kept when saving it, and a playtest that
+//! solves the level tells how it scored against its par.
+
+use std::path::PathBuf;
+
+use ggez::{
+	glam::{IVec2, Vec2},
+	graphics::{Canvas, Color, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+	Context, GameResult,
+};
+
+use crate::{
+	gameplay::{
+		four_directions, Facing, Ground, LogicalTransition, LogicalWorld, MirrorSlant, Mirroring,
+		Obj, Tile, DEFAULT_BUNNY_FORCE,
+	},
+	generation::filled_rect,
+	graphics::{
+		draw_translucent_sprite, ground_to_sprite, obj_to_sprite, Camera, DepthLayer, DrawOptions,
+		GraphicalWorld,
+	},
+	level_format::LevelMetadata,
+	spritesheet::{SpriteFromSheet, SpritesheetStuff},
+};
+
+/// What gets painted on the tile under the cursor.
+#[derive(Clone)]
+enum Brush {
+	Ground(Ground),
+	Obj(Obj),
+}
+
+impl Brush {
+	fn name(&self) -> &'static str {
+		match self {
+			Brush::Ground(ground) => match ground {
+				Ground::Floor => "Floor",
+				Ground::PressurePlate { .. } => "Pressure plate",
+				Ground::OpenGate { .. } => "Open gate",
+				Ground::Hole => "Hole",
+				Ground::Ice => "Ice",
+				Ground::FragileFloor => "Fragile floor",
+				Ground::Water => "Water",
+				Ground::Raft => "Raft on water",
+				Ground::Conveyor { .. } => "Conveyor",
+				Ground::Lava => "Lava",
+				Ground::Spikes { .. } => "Spikes",
+				Ground::Rubble => "Rubble",
+				Ground::Teleporter { .. } => "Teleporter",
+				Ground::Price { .. } => "Price",
+			},
+			Brush::Obj(obj) => obj.name(),
+		}
+	}
+
+	fn sprite(&self) -> SpriteFromSheet {
+		match self {
+			Brush::Ground(ground) => ground_to_sprite(ground),
+			Brush::Obj(obj) => obj_to_sprite(obj),
+		}
+	}
+}
+
+/// Every brush, with the facing and link of those that have one.
+fn brushes(facing: IVec2, link_id: u32) -> Vec<Brush> {
+	let grounds = [
+		Ground::Floor,
+		Ground::Hole,
+		Ground::Ice,
+		Ground::FragileFloor,
+		Ground::Water,
+		Ground::Lava,
+		Ground::Spikes { armed: false },
+		Ground::Rubble,
+		Ground::Conveyor { direction: facing },
+		Ground::PressurePlate { link_id },
+		Ground::Teleporter { link_id },
+		Ground::Price { price: link_id as i32 + 1 },
+	];
+	let objs = [
+		Obj::Wall,
+		Obj::Bunny {
+			hp: 7,
+			max_hp: 7,
+			statuses: vec![],
+			facing: Facing::Left,
+			held: None,
+			force: DEFAULT_BUNNY_FORCE,
+		},
+		Obj::Exit,
+		Obj::Rock,
+		Obj::Sword { enchantment: None },
+		Obj::Shield,
+		Obj::Pickaxe { enchantment: None },
+		Obj::VisionGem,
+		Obj::Heart,
+		Obj::RedoHeart,
+		Obj::RewindToken,
+		Obj::Door,
+		Obj::Key,
+		Obj::Rope,
+		Obj::Bush,
+		Obj::Note,
+		Obj::Bow,
+		Obj::Torch,
+		Obj::Magnet,
+		Obj::Crate { content: None },
+		Obj::StrengthPotion,
+		Obj::Raft,
+		Obj::Bomb { fuse: None },
+		Obj::Barrel { set_off: false },
+		Obj::CursedMirror,
+		Obj::Coin,
+		Obj::KeyShard,
+		Obj::Piston { facing, extended: false, link_id },
+		Obj::LaserEmitter { facing },
+		Obj::Mirror { slant: MirrorSlant::Rising },
+		Obj::Mirror { slant: MirrorSlant::Falling },
+		Obj::Gate { link_id },
+		Obj::Statue { facing, group: link_id },
+		Obj::Altar { group: link_id },
+		Obj::Slime {
+			hp: 5,
+			max_hp: 5,
+			move_token: false,
+			home: IVec2::ZERO,
+			statuses: vec![],
+			facing: Facing::Left,
+		},
+		Obj::Shroomer {
+			hp: 5,
+			max_hp: 5,
+			move_token: false,
+			home: IVec2::ZERO,
+			statuses: vec![],
+			facing: Facing::Left,
+		},
+		Obj::Archer {
+			hp: 5,
+			max_hp: 5,
+			move_token: false,
+			statuses: vec![],
+			facing: Facing::Left,
+		},
+		Obj::Ghost { hp: 5, max_hp: 5, move_token: false, facing: Facing::Left },
+		Obj::Shroom { move_token: false },
+		Obj::Fish { direction: facing, move_token: false },
+		Obj::Golem { mirroring: Mirroring::Reversed, move_token: false },
+	];
+	grounds.into_iter().map(Brush::Ground).chain(objs.into_iter().map(Brush::Obj)).collect()
+}
+
+/// What the game should do following a key press in the editor.
+pub enum EditorAction {
+	Close,
+	/// Play the edited level.
+	Playtest(Box<LogicalWorld>, LevelMetadata),
+}
+
+pub struct LevelEditor {
+	/// The level being edited, without the walls that surround it in game.
+	lw: LogicalWorld,
+	/// The edited level, with everything visible.
+	graphical_world: GraphicalWorld,
+	camera: Camera,
+	cursor: IVec2,
+	brush_index: usize,
+	/// The direction of the next pistons, statues, fish and conveyors.
+	facing: IVec2,
+	/// The link of the next pressure plates, gates, pistons and teleporters,
+	/// and the group of the next altars and statues.
+	link_id: u32,
+	/// The metadata of the loaded level, kept as is when saving it.
+	metadata: LevelMetadata,
+	/// What happened last, like the level being saved.
+	message: String,
+	file_path: PathBuf,
+	/// While playtesting, the editor waits in the background.
+	pub playtesting: bool,
+}
+
+impl LevelEditor {
+	pub fn new(ctx: &Context) -> LevelEditor {
+		// A room to start from, with the bunny in the middle.
+		let mut lw = LogicalWorld::new_empty();
+		for coords in filled_rect(IVec2::new(-4, -4), IVec2::new(9, 9)) {
+			lw.place_tile(coords, Tile::floor());
+		}
+		lw.place_tile(
+			IVec2::ZERO,
+			Tile::obj(Obj::Bunny {
+				hp: 7,
+				max_hp: 7,
+				statuses: vec![],
+				facing: Facing::Left,
+				held: None,
+				force: DEFAULT_BUNNY_FORCE,
+			}),
+		);
+		let mut editor = LevelEditor {
+			lw,
+			graphical_world: GraphicalWorld::new(),
+			camera: Camera::new(),
+			cursor: IVec2::ZERO,
+			brush_index: 0,
+			facing: IVec2::new(1, 0),
+			link_id: 0,
+			metadata: LevelMetadata::default(),
+			message: String::new(),
+			file_path: ctx.fs.user_data_dir().join("level.txt"),
+			playtesting: false,
+		};
+		editor.update_graphical_world();
+		editor
+	}
+
+	fn update_graphical_world(&mut self) {
+		self.graphical_world = GraphicalWorld::from_logical_world_transition(
+			&LogicalTransition::from(self.lw.clone().with_everything_visible()),
+		);
+	}
+
+	fn brush(&self) -> Brush {
+		brushes(self.facing, self.link_id)[self.brush_index].clone()
+	}
+
+	fn move_cursor(&mut self, direction: IVec2) {
+		self.cursor += direction;
+		self.camera.move_target(direction.as_vec2());
+	}
+
+	fn paint(&mut self) {
+		let coords = self.cursor;
+		match self.brush() {
+			Brush::Ground(ground) => {
+				let obj = self.lw.tile(coords).and_then(|tile| tile.obj.clone());
+				self.lw.place_tile(coords, Tile { ground, obj, visible: false });
+			},
+			Brush::Obj(mut obj) => {
+				if matches!(obj, Obj::Bunny { .. }) {
+					// There is only one place where the player starts.
+					if let Some(player_coords) = self.lw.player_coords() {
+						self.lw.place_tile(player_coords, Tile::floor());
+					}
+				}
+				if let Obj::Slime { home, .. } | Obj::Shroomer { home, .. } = &mut obj {
+					*home = coords;
+				}
+				let ground = self.lw.tile(coords).map_or(Ground::Floor, |tile| tile.ground.clone());
+				self.lw.place_tile(coords, Tile { ground, obj: Some(obj), visible: false });
+			},
+		}
+		self.update_graphical_world();
+	}
+
+	/// Removes the object under the cursor, or the tile itself if there is no object,
+	/// where there will be walls in game.
+	fn erase(&mut self) {
+		let coords = self.cursor;
+		let Some(tile) = self.lw.tile(coords) else {
+			return;
+		};
+		if tile.obj.is_some() {
+			let ground = tile.ground.clone();
+			self.lw.place_tile(coords, Tile { ground, obj: None, visible: false });
+		} else {
+			self.lw.remove_tile(coords);
+		}
+		self.update_graphical_world();
+	}
+
+	fn save(&mut self) {
+		let content = self.metadata.to_level_str() + &self.lw.to_level_str();
+		let result = self
+			.file_path
+			.parent()
+			.map_or(Ok(()), std::fs::create_dir_all)
+			.and_then(|()| std::fs::write(&self.file_path, content));
+		self.message = match result {
+			Ok(()) => "Level saved".to_string(),
+			Err(error) => format!("Level not saved: {error}"),
+		};
+	}
+
+	fn load(&mut self) {
+		let result = std::fs::read_to_string(&self.file_path)
+			.map_err(|error| error.to_string())
+			.and_then(|content| {
+				Ok((
+					LogicalWorld::from_level_str(&content)?,
+					LevelMetadata::from_level_str(&content)?,
+				))
+			});
+		match result {
+			Ok((lw, metadata)) => {
+				self.lw = lw;
+				self.metadata = metadata;
+				self.update_graphical_world();
+				self.message = "Level loaded".to_string();
+			},
+			Err(error) => self.message = format!("Level not loaded: {error}"),
+		}
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<EditorAction> {
+		use VirtualKeyCode as K;
+		let brush_count = brushes(self.facing, self.link_id).len();
+		match keycode {
+			K::Escape => return Some(EditorAction::Close),
+			K::Z | K::W | K::Up => self.move_cursor(IVec2::new(0, -1)),
+			K::Q | K::A | K::Left => self.move_cursor(IVec2::new(-1, 0)),
+			K::S | K::Down => self.move_cursor(IVec2::new(0, 1)),
+			K::D | K::Right => self.move_cursor(IVec2::new(1, 0)),
+			K::Space | K::Return => self.paint(),
+			K::Back | K::Delete => self.erase(),
+			K::Tab | K::RBracket => self.brush_index = (self.brush_index + 1) % brush_count,
+			K::LBracket => self.brush_index = (self.brush_index + brush_count - 1) % brush_count,
+			K::R => {
+				let index = four_directions().iter().position(|&direction| direction == self.facing);
+				self.facing = four_directions()[(index.unwrap_or(0) + 1) % 4];
+			},
+			K::Equals | K::Plus | K::NumpadAdd => self.link_id += 1,
+			K::Minus | K::NumpadSubtract => self.link_id = self.link_id.saturating_sub(1),
+			K::F5 => self.save(),
+			K::F9 => self.load(),
+			K::P => {
+				if self.lw.has_player() {
+					let lw = self.lw.clone().generated_walls_outside();
+					self.message.clear();
+					return Some(EditorAction::Playtest(Box::new(lw), self.metadata.clone()));
+				}
+				self.message = "Place the bunny to playtest".to_string();
+			},
+			_ => {},
+		}
+		None
+	}
+
+	/// Tells how the playtest that just ended by solving the level went.
+	pub fn tell_score(&mut self, turns: i32) {
+		self.message = self.metadata.score(turns);
+	}
+
+	pub fn animate(&mut self, frame_dt: std::time::Duration) {
+		self.camera.animate(frame_dt);
+	}
+
+	pub fn draw(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) -> GameResult {
+		self.graphical_world.draw(
+			ctx,
+			canvas,
+			spritesheet_stuff,
+			&self.camera,
+			&DrawOptions::new(screen_size),
+		)?;
+		draw_translucent_sprite(
+			canvas,
+			spritesheet_stuff,
+			&self.brush().sprite(),
+			self.cursor,
+			Color::new(1.0, 1.0, 0.5, 0.5),
+			&self.camera,
+			screen_size,
+		);
+		let mut text = Text::new(format!(
+			"Brush: {} (facing {}, link {})\n\
+			Tab and [ to change brush, R to rotate, + and - to change link\n\
+			Space to paint, Backspace to erase, F5 to save, F9 to load, P to playtest\n{}",
+			self.brush().name(),
+			self.facing,
+			self.link_id,
+			self.message,
+		));
+		text.set_scale(18.0);
+		canvas.draw(
+			&text,
+			DrawParam::default().dest(Vec2::new(10.0, 10.0)).z(DepthLayer::Interface.to_z_value()),
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,170 @@
+//! Bookmarked seeds, to play the levels they generate again.
+//!
+//! A seed can be bookmarked with a note during a run (from the pause screen) or once the run
+//! is won (from the victory screen). The bookmarks are persisted in `favorites.txt` in the user
+//! data directory, with one `seed: note` line per bookmark, and they are listed on the
+//! favorites screen (opened from the title screen) where a bookmarked seed can be restarted.
+
+use std::path::PathBuf;
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+	Context,
+};
+
+use crate::{
+	generation::RunConfig,
+	graphics::DepthLayer,
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+pub struct Bookmark {
+	pub seed: u64,
+	/// What the player wanted to remember about the seed, may be empty.
+	pub note: String,
+}
+
+pub struct Favorites {
+	bookmarks: Vec<Bookmark>,
+	file_path: PathBuf,
+}
+
+impl Favorites {
+	pub fn load(ctx: &Context) -> Favorites {
+		let file_path = ctx.fs.user_data_dir().join("favorites.txt");
+		let content = std::fs::read_to_string(&file_path).unwrap_or_default();
+		// Lines that don't start with a seed are ignored.
+		let bookmarks = content
+			.lines()
+			.filter_map(|line| {
+				let (seed, note) = line.split_once(':')?;
+				Some(Bookmark { seed: seed.trim().parse().ok()?, note: note.trim().to_string() })
+			})
+			.collect();
+		Favorites { bookmarks, file_path }
+	}
+
+	/// Bookmarks the seed, and saves the bookmarks right away.
+	pub fn add(&mut self, seed: u64, note: &str) {
+		// Only the first line is kept, as a bookmark takes one line in the file.
+		let note = note.lines().next().unwrap_or_default().trim().to_string();
+		self.bookmarks.push(Bookmark { seed, note });
+		self.save();
+	}
+
+	fn save(&self) {
+		let content: String = self
+			.bookmarks
+			.iter()
+			.map(|bookmark| format!("{}: {}\n", bookmark.seed, bookmark.note))
+			.collect();
+		// Failing to persist is not a big deal, the seed can still be noted by hand.
+		if let Some(dir) = self.file_path.parent() {
+			let _ = std::fs::create_dir_all(dir);
+		}
+		let _ = std::fs::write(&self.file_path, content);
+	}
+}
+
+const TIER: usize = 0;
+const ENCOUNTER_DIRECTOR: usize = 1;
+/// The bookmarks come after the mode settings, and the back button comes last.
+const FIRST_BOOKMARK: usize = 2;
+
+/// What the game should do following a key press in the favorites screen.
+pub enum FavoritesAction {
+	Back,
+	Play(RunConfig),
+}
+
+/// Lists the bookmarks, most recent first, any of which can be played in the chosen mode.
+pub struct FavoritesScreen {
+	/// The seed of each bookmark button, in the order of the buttons.
+	seeds: Vec<u64>,
+	menu: Menu,
+}
+
+impl FavoritesScreen {
+	pub fn new(favorites: &Favorites) -> FavoritesScreen {
+		let bookmarks: Vec<_> = favorites.bookmarks.iter().rev().collect();
+		let mut widgets = vec![
+			// The generation depends on the tier, but any tier is fine with any seed.
+			Widget::Slider { label: "New Game Plus tier".to_string(), value: 0, min: 0, max: 9 },
+			Widget::Toggle { label: "Encounter director".to_string(), value: true },
+		];
+		widgets.extend(bookmarks.iter().map(|bookmark| Widget::Button {
+			label: format!("Seed {}", bookmark.seed),
+			description: Some(if bookmark.note.is_empty() {
+				"No note.".to_string()
+			} else {
+				bookmark.note.clone()
+			}),
+			icon: None,
+		}));
+		widgets.push(Widget::button("Back"));
+		let seeds = bookmarks.iter().map(|bookmark| bookmark.seed).collect();
+		FavoritesScreen { seeds, menu: Menu::new(widgets) }
+	}
+
+	fn run_config(&self, seed: u64) -> RunConfig {
+		let Widget::Slider { value: difficulty_tier, .. } = self.menu.widgets[TIER] else {
+			unreachable!()
+		};
+		let Widget::Toggle { value: encounter_director, .. } = self.menu.widgets[ENCOUNTER_DIRECTOR]
+		else {
+			unreachable!()
+		};
+		RunConfig { difficulty_tier, encounter_director, seed, ..RunConfig::new() }
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<FavoritesAction> {
+		if keycode == VirtualKeyCode::Escape {
+			return Some(FavoritesAction::Back);
+		}
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(index) => Some(
+				match index
+					.checked_sub(FIRST_BOOKMARK)
+					.and_then(|bookmark_index| self.seeds.get(bookmark_index))
+				{
+					Some(&seed) => FavoritesAction::Play(self.run_config(seed)),
+					None => FavoritesAction::Back,
+				},
+			),
+			MenuEvent::Changed(_) => None,
+		}
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 600.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let title = if self.seeds.is_empty() {
+			"No favorites yet"
+		} else {
+			"Favorites"
+		};
+		let mut title_text = Text::new(title);
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 70.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
@@ -0,0 +1,128 @@
+//! Field of view, computed with symmetric shadowcasting
+//! (see https://www.albertford.com/shadowcasting/).
+//!
+//! The four quadrants around the origin are scanned row by row, going away from the origin,
+//! each row only covering the part of it that is not in the shadow of the obstructions met in
+//! the rows before. A floor tile is seen if the line from the center of the origin to its center
+//! is not obstructed, so seeing is symmetric (if A sees B then B sees A), and obstructions are
+//! seen if any part of them is lit, so that the walls of a room look whole.
+
+use std::collections::HashSet;
+
+use glam::IVec2;
+
+use crate::gameplay::four_directions;
+
+/// The coords seen from the origin, at a distance of at most the given radius,
+/// the tiles at the given coords blocking the view.
+pub fn visible_coords(origin: IVec2, radius: f32, obstructions: &HashSet<IVec2>) -> HashSet<IVec2> {
+	let mut visible = HashSet::new();
+	visible.insert(origin);
+	let max_depth = radius.max(0.0).floor() as i32;
+	let mut rows = vec![];
+	for forward in four_directions() {
+		let quadrant = Quadrant { origin, forward, side: forward.perp() };
+		rows.push(Row {
+			depth: 1,
+			start_slope: Slope::new(-1, 1),
+			end_slope: Slope::new(1, 1),
+		});
+		while let Some(mut row) = rows.pop() {
+			if row.depth > max_depth {
+				continue;
+			}
+			let mut previous_is_obstruction = None;
+			for col in row.min_col()..=row.max_col() {
+				let coords = quadrant.coords(row.depth, col);
+				let is_obstruction = obstructions.contains(&coords);
+				if (is_obstruction || row.is_symmetric(col))
+					&& origin.as_vec2().distance(coords.as_vec2()) <= radius
+				{
+					visible.insert(coords);
+				}
+				match (previous_is_obstruction, is_obstruction) {
+					(Some(true), false) => row.start_slope = Slope::of_tile(row.depth, col),
+					(Some(false), true) => {
+						let mut next_row = row.next();
+						next_row.end_slope = Slope::of_tile(row.depth, col);
+						rows.push(next_row);
+					},
+					_ => {},
+				}
+				previous_is_obstruction = Some(is_obstruction);
+			}
+			if previous_is_obstruction == Some(false) {
+				rows.push(row.next());
+			}
+		}
+	}
+	visible
+}
+
+/// One of the four quarters of the view, the one that goes forward in the given direction,
+/// its rows going from one side to the other.
+struct Quadrant {
+	origin: IVec2,
+	forward: IVec2,
+	side: IVec2,
+}
+
+impl Quadrant {
+	fn coords(&self, depth: i32, col: i32) -> IVec2 {
+		self.origin + self.forward * depth + self.side * col
+	}
+}
+
+/// A slope as an exact fraction (with a positive denominator),
+/// for tiles that lie exactly on the edge of a shadow to always end up on the same side.
+#[derive(Clone, Copy)]
+struct Slope {
+	numerator: i32,
+	denominator: i32,
+}
+
+impl Slope {
+	fn new(numerator: i32, denominator: i32) -> Slope {
+		Slope { numerator, denominator }
+	}
+
+	/// The slope of the line that goes through the origin and the start edge of the tile.
+	fn of_tile(depth: i32, col: i32) -> Slope {
+		Slope::new(2 * col - 1, 2 * depth)
+	}
+}
+
+/// A part of a row of a quadrant, between two slopes.
+struct Row {
+	depth: i32,
+	start_slope: Slope,
+	end_slope: Slope,
+}
+
+impl Row {
+	/// The col of the leftmost tile, rounding the start slope up on ties.
+	fn min_col(&self) -> i32 {
+		let Slope { numerator, denominator } = self.start_slope;
+		(2 * self.depth * numerator + denominator).div_euclid(2 * denominator)
+	}
+
+	/// The col of the rightmost tile, rounding the end slope down on ties.
+	fn max_col(&self) -> i32 {
+		let Slope { numerator, denominator } = self.end_slope;
+		-(denominator - 2 * self.depth * numerator).div_euclid(2 * denominator)
+	}
+
+	/// Is the center of the tile at that col in the part of the row between the slopes?
+	fn is_symmetric(&self, col: i32) -> bool {
+		col * self.start_slope.denominator >= self.depth * self.start_slope.numerator
+			&& col * self.end_slope.denominator <= self.depth * self.end_slope.numerator
+	}
+
+	fn next(&self) -> Row {
+		Row {
+			depth: self.depth + 1,
+			start_slope: self.start_slope,
+			end_slope: self.end_slope,
+		}
+	}
+}
@@ -5,34 +5,141 @@
 //! it is rather used to produce state transitions that contain logical descriptions
 //! of what happen. These are used to animate the rendering of the state.
 
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+	cell::RefCell,
+	cmp::Reverse,
+	collections::{hash_map::DefaultHasher, BinaryHeap, HashMap, HashSet},
+	hash::{Hash, Hasher},
+	rc::Rc,
+	sync::Arc,
+	thread::LocalKey,
+};
 
-use ggez::glam::IVec2;
-use rand::seq::SliceRandom;
+use glam::IVec2;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use crate::generation::filled_rect;
+use crate::{
+	chunked_grid::ChunkedGrid, director::EncounterDirector, field_of_view, generation::filled_rect,
+	metrics, stable_hash::StableHasher,
+};
+
+/// How far the scent of the player spreads (in path cost),
+/// enemies that are farther away than that can't smell them.
+const SCENT_RADIUS: i32 = 12;
+
+/// Damages dealt by an explosion to everything around the bomb.
+const BOMB_DAMAGES: i32 = 3;
+
+/// Damages dealt by armed spikes to what steps on them or stands on them as they rise.
+const SPIKES_DAMAGES: i32 = 1;
+
+/// How far an archer can shoot, in tiles.
+const ARCHER_RANGE: i32 = 5;
+
+/// A ghost haunts the player only when they are at most that far (in steps) from it.
+const GHOST_CHASE_RADIUS: i32 = 8;
+
+/// How many tiles a ghost can go through at most in one move, to end it on a free tile.
+const GHOST_PHASE_DISTANCE: i32 = 3;
+
+/// The golem boss wakes up when the player gets at most that far (in steps) from it.
+const GOLEM_BOSS_AWAKE_RADIUS: i32 = 6;
+
+/// Damages dealt by the beam of a laser emitter, each turn, to what it ends on.
+const LASER_DAMAGES: i32 = 2;
+
+/// How far a magnet pulls metal objects, in tiles.
+const MAGNET_RANGE: i32 = 5;
+
+/// How far the player sees in a level that doesn't say otherwise.
+pub const DEFAULT_BASE_VISION_RADIUS: f32 = 6.5;
+
+/// How much farther the player sees with a torch in hand or next to them.
+const TORCH_VISION_BONUS: f32 = 2.0;
+
+fn default_base_vision_radius() -> f32 {
+	DEFAULT_BASE_VISION_RADIUS
+}
+
+/// The force of the bunny before it drinks any strength potion.
+pub const DEFAULT_BUNNY_FORCE: i32 = 2;
+
+fn default_bunny_force() -> i32 {
+	DEFAULT_BUNNY_FORCE
+}
+
+/// Beyond that many cached lines of sight, the cache is emptied to start again.
+const LINE_OF_SIGHT_CACHE_CAPACITY: usize = 256;
+
+thread_local! {
+	/// Visible coords, keyed by `LogicalWorld::line_of_sight_cache_key`.
+	static LINE_OF_SIGHT_CACHE: RefCell<HashMap<u64, Rc<HashSet<IVec2>>>> =
+		RefCell::new(HashMap::new());
+
+	/// Scratch buffers for the temporary data of transitions, kept across transitions
+	/// so that their memory is reused instead of allocated again every time.
+	static COORDS_BUFFER: RefCell<Vec<IVec2>> = const { RefCell::new(Vec::new()) };
+	static NEARBY_TILES_BUFFER: RefCell<Vec<(IVec2, bool)>> = const { RefCell::new(Vec::new()) };
+	static SCENT_FRONTIER_BUFFER: RefCell<BinaryHeap<Reverse<(i32, i32, i32)>>> =
+		const { RefCell::new(BinaryHeap::new()) };
+}
+
+trait ScratchBuffer: Default {
+	fn clear(&mut self);
+}
+
+impl<T> ScratchBuffer for Vec<T> {
+	fn clear(&mut self) {
+		Vec::clear(self);
+	}
+}
+
+impl<T: Ord> ScratchBuffer for BinaryHeap<T> {
+	fn clear(&mut self) {
+		BinaryHeap::clear(self);
+	}
+}
+
+/// Lends the given scratch buffer, emptied. It is taken out while lent,
+/// so that a nested use of the same buffer gets a new one instead of panicking.
+fn with_scratch_buffer<T: ScratchBuffer, R>(
+	buffer: &'static LocalKey<RefCell<T>>,
+	f: impl FnOnce(&mut T) -> R,
+) -> R {
+	let mut taken = buffer.with(|buffer| std::mem::take(&mut *buffer.borrow_mut()));
+	taken.clear();
+	let result = f(&mut taken);
+	buffer.with(|buffer| *buffer.borrow_mut() = taken);
+	result
+}
 
 /// A tile can have zero or one object on it, and these can be moved.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Obj {
 	/// Hard to move, it just stays there, being a wall.
 	Wall,
 	/// Does more damages. Great weapon, terrible for protection.
-	Sword,
+	Sword { enchantment: Option<Enchantment> },
 	/// Does zero damages. Great for protection, terrible weapon.
 	Shield,
 	/// Can mine walls.
-	Pickaxe,
+	Pickaxe { enchantment: Option<Enchantment> },
 	/// The average pushable object, has the default stat for every stat.
 	Rock,
 	/// An exit door that objects can go through to go to the next level.
 	Exit,
 	/// Gem that grants wall-through vision to the player if adjacent.
 	VisionGem,
+	/// A sword with a vision gem set in it, crafted from the two.
+	/// Deals as much damages as a sword and grants vision as a vision gem.
+	VisionSword,
 	/// Restores health when consumed.
 	Heart,
 	/// Grants a redo.
 	RedoHeart,
+	/// Grants a rewind, that replays the enemies' last moves without undoing the player's.
+	RewindToken,
 	/// Like a wall but can be opened by a key.
 	Door,
 	/// Can open a door.
@@ -41,19 +148,151 @@ pub enum Obj {
 	Rope,
 	/// Vision-blocking pushable object.
 	Bush,
+	/// A note with a story fragment written on it, can be read by the player.
+	Note,
+	/// Machine that extends a piston head in front of it when activated by a pressure plate,
+	/// pushing what is there, and that retracts the next turn.
+	Piston { facing: IVec2, extended: bool, link_id: u32 },
+	/// Blocks the way, until something is on a pressure plate linked to it.
+	Gate { link_id: u32 },
+	/// The extended part of a piston, in front of the piston.
+	PistonHead,
+	/// Turns to face where it is pushed. It is part of the group of statues of an altar.
+	Statue { facing: IVec2, group: u32 },
+	/// Awakens when all the statues of its group face it, leaving a reward behind.
+	Altar { group: u32 },
+	/// Shoots an arrow in front of it each time it is pushed.
+	Bow,
+	/// Lights up more of the level around the bunny holding it or standing next to it.
+	Torch,
+	/// At the beginning of the game's turn, pulls the nearest metal object in a straight line
+	/// from it by one tile, if nothing is in between.
+	Magnet,
+	/// Heavy to push, hitting it with a sword breaks it open, leaving what it held (if anything).
+	Crate { content: Option<Box<Obj>> },
+	/// The bunny walking into it drinks it, and can push one more mass at once from then on.
+	StrengthPotion,
+	/// Floats on water. Pushed onto water, it stays there for good as a way across.
+	Raft,
+	/// At the beginning of the game's turn, casts a beam in front of it that hurts what it
+	/// ends on. It can't be pushed.
+	LaserEmitter { facing: IVec2 },
+	/// Reflects the beams of laser emitters by a quarter turn, pushing it reroutes them.
+	Mirror { slant: MirrorSlant },
+	/// Flies in its direction, one tile per turn, and breaks on what it hits, hurting it.
+	Arrow {
+		direction: IVec2,
+		/// This token indicates that this agent has yet to make a move.
+		move_token: bool,
+	},
+	/// Picked up by the player when unlit, to be placed later. Once lit it explodes when
+	/// its fuse runs out, the fuse counting down the turns before that even as it is pushed.
+	Bomb { fuse: Option<i32> },
+	/// Explodes like a bomb when hit by something that hurts or caught in an explosion,
+	/// the turn after it is set off, so that barrels next to each other go off in a chain.
+	Barrel { set_off: bool },
+	/// Cursed item that mirrors the controls of the player for a few turns when touched.
+	CursedMirror,
+	/// Gold, collected by the bunny bumping into it, to spend in the shops.
+	Coin,
+	/// A piece of the seal of the exit, collected by the bunny bumping into it.
+	/// A sealed exit opens once all of its shards are collected.
+	KeyShard,
+	/// What is left of a killed slime. Squelchy, and it hits hard when pushed into something.
+	SlimeCorpse,
 	/// The player. We play as a bunny. It is cute! :3
-	Bunny { hp: i32, max_hp: i32 },
+	Bunny {
+		hp: i32,
+		max_hp: i32,
+		/// Lingering effects, like of a hit by an enchanted weapon.
+		#[serde(default)]
+		statuses: Vec<Status>,
+		/// Where it looks, updated as it moves left or right.
+		#[serde(default)]
+		facing: Facing,
+		/// The tool it carries (see `Obj::is_holdable`), that hits and interacts in its stead.
+		#[serde(default)]
+		held: Option<Box<Obj>>,
+		/// The mass it can push at once (before the force perks), strength potions add to it.
+		#[serde(default = "default_bunny_force")]
+		force: i32,
+	},
 	/// The basic enemy.
 	Slime {
 		hp: i32,
+		/// The HP it appeared with, its health bar showing how much it has left.
+		#[serde(default)]
+		max_hp: i32,
 		/// This token indicates that this agent has yet to make a move.
 		move_token: bool,
+		/// Where it came from, and where it goes back to when the player is far enough.
+		home: IVec2,
+		/// Lingering effects, like of a hit by an enchanted weapon.
+		#[serde(default)]
+		statuses: Vec<Status>,
+		/// Where it looks, updated as it moves left or right.
+		#[serde(default)]
+		facing: Facing,
 	},
 	/// An other enemy, mushroom themed.
 	Shroomer {
 		hp: i32,
+		/// The HP it appeared with, its health bar showing how much it has left.
+		#[serde(default)]
+		max_hp: i32,
+		/// This token indicates that this agent has yet to make a move.
+		move_token: bool,
+		/// Where it came from, and where it goes back to when the player is far enough.
+		home: IVec2,
+		/// Lingering effects, like of a hit by an enchanted weapon.
+		#[serde(default)]
+		statuses: Vec<Status>,
+		/// Where it looks, updated as it moves left or right.
+		#[serde(default)]
+		facing: Facing,
+	},
+	/// Enemy that keeps its distance and shoots at the player from afar.
+	Archer {
+		hp: i32,
+		/// The HP it appeared with, its health bar showing how much it has left.
+		#[serde(default)]
+		max_hp: i32,
+		/// This token indicates that this agent has yet to make a move.
+		move_token: bool,
+		/// Lingering effects, like of a hit by an enchanted weapon.
+		#[serde(default)]
+		statuses: Vec<Status>,
+		/// Where it looks, updated as it moves left or right.
+		#[serde(default)]
+		facing: Facing,
+	},
+	/// Enemy that floats through walls towards the player. It can't be pushed,
+	/// and only swords can hurt it.
+	Ghost {
+		hp: i32,
+		/// The HP it appeared with, its health bar showing how much it has left.
+		#[serde(default)]
+		max_hp: i32,
+		/// This token indicates that this agent has yet to make a move.
+		move_token: bool,
+		/// Where it looks, updated as it moves left or right.
+		#[serde(default)]
+		facing: Facing,
+	},
+	/// The boss guarding the exit of the last level, a huge golem that takes 2x2 tiles.
+	/// It can't be pushed, and it stomps towards the player every other turn.
+	GolemBoss {
+		hp: i32,
+		/// The HP it appeared with, its health bar showing how much it has left.
+		#[serde(default)]
+		max_hp: i32,
 		/// This token indicates that this agent has yet to make a move.
 		move_token: bool,
+		/// It skips its next turn to catch its breath.
+		resting: bool,
+		/// Lingering effects, like of a hit by an enchanted weapon.
+		#[serde(default)]
+		statuses: Vec<Status>,
 	},
 	/// Mushroom. A production of the shroomer.
 	Shroom {
@@ -66,36 +305,301 @@ pub enum Obj {
 		/// This token indicates that this agent has yet to make a move.
 		move_token: bool,
 	},
+	/// Puzzle enemy that copies the last move of the player, mirrored.
+	Golem {
+		mirroring: Mirroring,
+		/// This token indicates that this agent has yet to make a move.
+		move_token: bool,
+	},
+}
+
+/// Which way the bunny or an enemy looks, the sprites of which look left.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Facing {
+	#[default]
+	Left,
+	Right,
+}
+
+/// Which way a mirror is slanted, as seen from above.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MirrorSlant {
+	/// Like `/`, from the bottom left corner to the top right corner.
+	Rising,
+	/// Like `\`, from the top left corner to the bottom right corner.
+	Falling,
+}
+
+impl MirrorSlant {
+	/// The direction that a beam going in the given direction goes in once reflected.
+	fn reflect(self, direction: IVec2) -> IVec2 {
+		match self {
+			MirrorSlant::Rising => IVec2::new(-direction.y, -direction.x),
+			MirrorSlant::Falling => IVec2::new(direction.y, direction.x),
+		}
+	}
+}
+
+/// How a golem transforms the moves of the player that it copies.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mirroring {
+	/// Goes the opposite way.
+	Reversed,
+	/// Goes a quarter turn clockwise from the player's move.
+	Rotated,
 }
 
+impl Mirroring {
+	fn apply(self, direction: IVec2) -> IVec2 {
+		match self {
+			Mirroring::Reversed => -direction,
+			Mirroring::Rotated => direction.perp(),
+		}
+	}
+}
+
+/// A modifier on a weapon that adds a rider effect to its hits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Enchantment {
+	/// Hits ignite the target, that then burns for a few turns.
+	Flaming,
+	/// Hits freeze the target, that then skips a few turns.
+	Frost,
+}
+
+impl Enchantment {
+	/// The status inflicted to the targets hit by a weapon with this enchantment.
+	fn rider_status(self) -> Status {
+		match self {
+			Enchantment::Flaming => Status::Burning { turns_left: 3 },
+			Enchantment::Frost => Status::Frozen { turns_left: 2 },
+		}
+	}
+}
+
+/// A lingering effect on an object with HP, that wears off after some turns.
+/// An object can be afflicted by several statuses at once, but only one of each kind.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Status {
+	/// Takes 1 damage at the beginning of each turn.
+	Burning { turns_left: i32 },
+	/// Takes 1 damage at the beginning of each turn.
+	Poisoned { turns_left: i32 },
+	/// Loses its move token, so it doesn't get to move.
+	Stunned { turns_left: i32 },
+	/// Doesn't get to move, and it is heavier to push while encased in ice.
+	Frozen { turns_left: i32 },
+}
+
+impl Status {
+	fn turns_left_mut(&mut self) -> &mut i32 {
+		match self {
+			Status::Burning { turns_left }
+			| Status::Poisoned { turns_left }
+			| Status::Stunned { turns_left }
+			| Status::Frozen { turns_left } => turns_left,
+		}
+	}
+
+	pub fn turns_left(mut self) -> i32 {
+		*self.turns_left_mut()
+	}
+
+	fn is_same_kind_as(self, other: Status) -> bool {
+		std::mem::discriminant(&self) == std::mem::discriminant(&other)
+	}
+
+	fn damages_per_turn(self) -> i32 {
+		match self {
+			Status::Burning { .. } | Status::Poisoned { .. } => 1,
+			Status::Stunned { .. } | Status::Frozen { .. } => 0,
+		}
+	}
+
+	/// Agents skip their turns while afflicted.
+	fn skips_turns(self) -> bool {
+		matches!(self, Status::Stunned { .. } | Status::Frozen { .. })
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Status::Burning { .. } => "Burning",
+			Status::Poisoned { .. } => "Poisoned",
+			Status::Stunned { .. } => "Stunned",
+			Status::Frozen { .. } => "Frozen",
+		}
+	}
+}
+
+/// Being frozen makes an object that much heavier.
+const FROZEN_EXTRA_MASS: i32 = 2;
+
 impl Obj {
 	/// When a pusher wants to push one or more objects, the sum of the masses of the
 	/// objects that may be pushed is compared to the force of the pusher to see if the
 	/// pusher succeeds to push (force >= total mass) or fails to push (force < total mass).
-	fn mass(&self) -> i32 {
-		match self {
-			Obj::Wall | Obj::Door | Obj::Shroom { .. } => 10,
-			Obj::Bunny { .. } | Obj::Slime { .. } | Obj::Shroomer { .. } => 3,
+	pub fn mass(&self) -> i32 {
+		let mass = match self {
+			Obj::Wall
+			| Obj::Door
+			| Obj::Shroom { .. }
+			| Obj::Piston { .. }
+			| Obj::PistonHead
+			| Obj::LaserEmitter { .. }
+			| Obj::Gate { .. }
+			| Obj::Ghost { .. }
+			| Obj::GolemBoss { .. }
+			| Obj::Altar { .. } => 10,
+			Obj::Bunny { .. }
+			| Obj::Slime { .. }
+			| Obj::Shroomer { .. }
+			| Obj::Archer { .. }
+			| Obj::Statue { .. }
+			| Obj::Golem { .. } => 3,
+			Obj::Crate { .. } | Obj::Barrel { .. } => 2,
 			_ => 1,
+		};
+		let frozen = self.statuses().iter().any(|status| matches!(status, Status::Frozen { .. }));
+		if frozen {
+			mass + FROZEN_EXTRA_MASS
+		} else {
+			mass
+		}
+	}
+
+	/// Is it used up when picked up or used? These burn up in lava.
+	pub fn is_consumable(&self) -> bool {
+		matches!(
+			self,
+			Obj::Key
+				| Obj::Note
+				| Obj::Heart
+				| Obj::RedoHeart
+				| Obj::RewindToken
+				| Obj::Bomb { .. }
+				| Obj::Coin
+				| Obj::KeyShard
+				| Obj::StrengthPotion
+		)
+	}
+
+	/// Can the bunny pick it up to hold it?
+	pub fn is_holdable(&self) -> bool {
+		matches!(
+			self,
+			Obj::Sword { .. }
+				| Obj::VisionSword
+				| Obj::Shield
+				| Obj::Pickaxe { .. }
+				| Obj::Key
+				| Obj::Torch
+		)
+	}
+
+	/// Is it attracted by magnets?
+	pub fn is_metallic(&self) -> bool {
+		matches!(
+			self,
+			Obj::Sword { .. }
+				| Obj::VisionSword
+				| Obj::Shield
+				| Obj::Pickaxe { .. }
+				| Obj::Key
+				| Obj::Bomb { .. }
+		)
+	}
+
+	/// What hits and interacts when this object is pushed into something,
+	/// which is the tool held by the bunny if it holds one.
+	fn wielded(&self) -> &Obj {
+		match self {
+			Obj::Bunny { held: Some(held), .. } => held,
+			_ => self,
+		}
+	}
+
+	pub fn held(&self) -> Option<&Obj> {
+		match self {
+			Obj::Bunny { held, .. } => held.as_deref(),
+			_ => None,
 		}
 	}
 
+	/// Does it sink when it gets in water? The bunny swims and fishes are at home there.
+	pub fn sinks(&self) -> bool {
+		matches!(self, Obj::Rock)
+			|| (self.mass() >= 3
+				&& !matches!(
+					self,
+					Obj::Bunny { .. } | Obj::Fish { .. } | Obj::Ghost { .. }
+				))
+	}
+
 	/// When an object W is failed to be pushed into an object T, W may deal damages to T
 	/// if T is the kind of object that may take damages.
-	fn damages(&self) -> i32 {
+	pub fn damages(&self) -> i32 {
 		match self {
-			Obj::Sword => 3,
-			Obj::Shield | Obj::Exit | Obj::Heart | Obj::RedoHeart => 0,
+			Obj::Sword { .. } | Obj::VisionSword | Obj::SlimeCorpse => 3,
+			Obj::Shield
+			| Obj::Exit
+			| Obj::Heart
+			| Obj::RedoHeart
+			| Obj::RewindToken
+			| Obj::Note
+			| Obj::CursedMirror
+			| Obj::Coin
+			| Obj::KeyShard
+			| Obj::StrengthPotion
+			| Obj::Barrel { .. } => 0,
 			Obj::Slime { .. } => 2,
 			Obj::Shroomer { .. } => 2,
+			Obj::Arrow { .. } => 2,
+			Obj::GolemBoss { .. } => 2,
+			// Even a shield hurts when the bunny rams it into something.
+			Obj::Bunny { held: Some(held), .. } => held.damages().max(1),
 			_ => 1,
 		}
 	}
 
+	/// Experience gained by the player when this object is killed.
+	fn experience_value(&self) -> i32 {
+		match self {
+			Obj::Slime { .. } => 1,
+			Obj::Shroomer { .. } | Obj::Archer { .. } | Obj::Ghost { .. } => 2,
+			Obj::GolemBoss { .. } => 10,
+			_ => 0,
+		}
+	}
+
+	/// What a killed object leaves behind, if anything.
+	fn corpse(&self) -> Option<Obj> {
+		match self {
+			Obj::Slime { .. } => Some(Obj::SlimeCorpse),
+			_ => None,
+		}
+	}
+
 	/// An object may take damages if it has some HP.
-	fn hp(&self) -> Option<i32> {
+	pub fn hp(&self) -> Option<i32> {
+		match self {
+			Obj::Bunny { hp, .. }
+			| Obj::Slime { hp, .. }
+			| Obj::Shroomer { hp, .. }
+			| Obj::Archer { hp, .. }
+			| Obj::Ghost { hp, .. }
+			| Obj::GolemBoss { hp, .. } => Some(*hp),
+			_ => None,
+		}
+	}
+
+	pub fn max_hp(&self) -> Option<i32> {
 		match self {
-			Obj::Bunny { hp, .. } | Obj::Slime { hp, .. } | Obj::Shroomer { hp, .. } => Some(*hp),
+			Obj::Bunny { max_hp, .. }
+			| Obj::Slime { max_hp, .. }
+			| Obj::Shroomer { max_hp, .. }
+			| Obj::Archer { max_hp, .. }
+			| Obj::Ghost { max_hp, .. }
+			| Obj::GolemBoss { max_hp, .. } => Some(*max_hp),
 			_ => None,
 		}
 	}
@@ -104,27 +608,212 @@ impl Obj {
 	/// killing hits should be handled by hand.
 	fn take_damage(&mut self, damages: i32) {
 		match self {
-			Obj::Bunny { hp, .. } | Obj::Slime { hp, .. } | Obj::Shroomer { hp, .. } => *hp -= damages,
+			Obj::Bunny { hp, .. }
+			| Obj::Slime { hp, .. }
+			| Obj::Shroomer { hp, .. }
+			| Obj::Archer { hp, .. }
+			| Obj::Ghost { hp, .. }
+			| Obj::GolemBoss { hp, .. } => *hp -= damages,
 			_ => {},
 		}
 	}
 
+	/// How many tiles it takes, from its anchor tile (that holds it) to the right and down.
+	/// The other tiles it takes are listed in the footprints of the world.
+	pub fn footprint(&self) -> IVec2 {
+		match self {
+			Obj::GolemBoss { .. } => IVec2::new(2, 2),
+			_ => IVec2::new(1, 1),
+		}
+	}
+
 	/// Can the player see over it?
 	fn blocks_vision(&self) -> bool {
 		matches!(self, Obj::Wall | Obj::Bush)
 	}
 
+	/// Objects that move on their own (or that are the player), as opposed to items.
+	pub fn is_agent(&self) -> bool {
+		matches!(
+			self,
+			Obj::Bunny { .. }
+				| Obj::Slime { .. }
+				| Obj::Shroomer { .. }
+				| Obj::Archer { .. }
+				| Obj::Ghost { .. }
+				| Obj::GolemBoss { .. }
+				| Obj::Shroom { .. }
+				| Obj::Fish { .. }
+				| Obj::Golem { .. }
+				| Obj::Arrow { .. }
+		)
+	}
+
 	/// Some agents may be neutral, this only flags agents that are hostile to the player.
-	fn is_enemy(&self) -> bool {
-		matches!(self, Obj::Slime { .. } | Obj::Shroomer { .. })
+	pub fn is_enemy(&self) -> bool {
+		matches!(
+			self,
+			Obj::Slime { .. }
+				| Obj::Shroomer { .. }
+				| Obj::Archer { .. }
+				| Obj::Ghost { .. }
+				| Obj::GolemBoss { .. }
+				| Obj::Golem { .. }
+		)
+	}
+
+	/// An enemy stops chasing the player and goes back home when the player
+	/// is farther than this from the enemy's home.
+	fn leash_radius(&self) -> Option<i32> {
+		match self {
+			Obj::Slime { .. } => Some(9),
+			Obj::Shroomer { .. } => Some(6),
+			_ => None,
+		}
+	}
+
+	fn home(&self) -> Option<IVec2> {
+		match self {
+			Obj::Slime { home, .. } | Obj::Shroomer { home, .. } => Some(*home),
+			_ => None,
+		}
+	}
+
+	/// The name of the kind of object, the same for all the objects of a kind.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Obj::Wall => "Wall",
+			Obj::Sword { enchantment: None } => "Sword",
+			Obj::Sword { enchantment: Some(Enchantment::Flaming) } => "Flaming sword",
+			Obj::Sword { enchantment: Some(Enchantment::Frost) } => "Frost sword",
+			Obj::Shield => "Shield",
+			Obj::Pickaxe { enchantment: None } => "Pickaxe",
+			Obj::Pickaxe { enchantment: Some(Enchantment::Flaming) } => "Flaming pickaxe",
+			Obj::Pickaxe { enchantment: Some(Enchantment::Frost) } => "Frost pickaxe",
+			Obj::Rock => "Rock",
+			Obj::Exit => "Exit",
+			Obj::VisionGem => "Vision gem",
+			Obj::VisionSword => "Vision sword",
+			Obj::Heart => "Heart",
+			Obj::RedoHeart => "Redo heart",
+			Obj::RewindToken => "Rewind token",
+			Obj::Door => "Door",
+			Obj::Key => "Key",
+			Obj::Rope => "Rope",
+			Obj::Bush => "Bush",
+			Obj::Note => "Note",
+			Obj::Piston { .. } => "Piston",
+			Obj::PistonHead => "Piston head",
+			Obj::Gate { .. } => "Gate",
+			Obj::Statue { .. } => "Statue",
+			Obj::Altar { .. } => "Altar",
+			Obj::Bow => "Bow",
+			Obj::Torch => "Torch",
+			Obj::Magnet => "Magnet",
+			Obj::Crate { .. } => "Crate",
+			Obj::Barrel { .. } => "Barrel",
+			Obj::StrengthPotion => "Strength potion",
+			Obj::Raft => "Raft",
+			Obj::LaserEmitter { .. } => "Laser emitter",
+			Obj::Mirror { .. } => "Mirror",
+			Obj::Arrow { .. } => "Arrow",
+			Obj::Bomb { .. } => "Bomb",
+			Obj::CursedMirror => "Cursed mirror",
+			Obj::Coin => "Coin",
+			Obj::KeyShard => "Key shard",
+			Obj::SlimeCorpse => "Slime corpse",
+			Obj::Bunny { .. } => "Bunny",
+			Obj::Slime { .. } => "Slime",
+			Obj::Shroomer { .. } => "Shroomer",
+			Obj::Archer { .. } => "Archer",
+			Obj::Ghost { .. } => "Ghost",
+			Obj::GolemBoss { .. } => "Golem boss",
+			Obj::Shroom { .. } => "Shroom",
+			Obj::Fish { .. } => "Fish",
+			Obj::Golem { .. } => "Golem",
+		}
+	}
+
+	pub fn enchantment(&self) -> Option<Enchantment> {
+		match self {
+			Obj::Sword { enchantment } | Obj::Pickaxe { enchantment } => *enchantment,
+			_ => None,
+		}
+	}
+
+	pub fn statuses(&self) -> &[Status] {
+		match self {
+			Obj::Bunny { statuses, .. }
+			| Obj::Slime { statuses, .. }
+			| Obj::Shroomer { statuses, .. }
+			| Obj::Archer { statuses, .. }
+			| Obj::GolemBoss { statuses, .. } => statuses,
+			_ => &[],
+		}
+	}
+
+	fn statuses_mut(&mut self) -> Option<&mut Vec<Status>> {
+		match self {
+			Obj::Bunny { statuses, .. }
+			| Obj::Slime { statuses, .. }
+			| Obj::Shroomer { statuses, .. }
+			| Obj::Archer { statuses, .. }
+			| Obj::GolemBoss { statuses, .. } => Some(statuses),
+			_ => None,
+		}
+	}
+
+	/// The new status replaces the previous one of the same kind if any.
+	/// The player can only be burnt or poisoned, nothing stops them from playing.
+	/// Returns `false` if the object can't be afflicted by the status.
+	fn inflict_status(&mut self, new_status: Status) -> bool {
+		if matches!(self, Obj::Bunny { .. }) && new_status.skips_turns() {
+			return false;
+		}
+		let Some(statuses) = self.statuses_mut() else {
+			return false;
+		};
+		statuses.retain(|status| !status.is_same_kind_as(new_status));
+		statuses.push(new_status);
+		if matches!(new_status, Status::Stunned { .. }) {
+			self.take_move_token();
+		}
+		true
+	}
+
+	/// One turn passes for the statuses, that are removed when they wear off.
+	fn tick_statuses(&mut self) {
+		if let Some(statuses) = self.statuses_mut() {
+			for status in statuses.iter_mut() {
+				*status.turns_left_mut() -= 1;
+			}
+			statuses.retain(|status| status.turns_left() > 0);
+		}
+	}
+
+	/// The status that this object inflicts to what it hits, if any.
+	fn inflicted_status(&self) -> Option<Status> {
+		match self {
+			Obj::Shroomer { .. } => Some(Status::Poisoned { turns_left: 3 }),
+			_ => None,
+		}
 	}
 
 	fn give_move_token(&mut self) {
+		if self.statuses().iter().any(|status| status.skips_turns()) {
+			// Stunned and frozen agents skip their turn.
+			return;
+		}
 		match self {
 			Obj::Slime { move_token, .. }
 			| Obj::Shroomer { move_token, .. }
+			| Obj::Archer { move_token, .. }
+			| Obj::Ghost { move_token, .. }
+			| Obj::GolemBoss { move_token, .. }
 			| Obj::Shroom { move_token }
-			| Obj::Fish { move_token, .. } => *move_token = true,
+			| Obj::Fish { move_token, .. }
+			| Obj::Golem { move_token, .. }
+			| Obj::Arrow { move_token, .. } => *move_token = true,
 			_ => {},
 		}
 	}
@@ -133,8 +822,13 @@ impl Obj {
 		match self {
 			Obj::Slime { move_token, .. }
 			| Obj::Shroomer { move_token, .. }
+			| Obj::Archer { move_token, .. }
+			| Obj::Ghost { move_token, .. }
+			| Obj::GolemBoss { move_token, .. }
 			| Obj::Shroom { move_token }
-			| Obj::Fish { move_token, .. } => *move_token,
+			| Obj::Fish { move_token, .. }
+			| Obj::Golem { move_token, .. }
+			| Obj::Arrow { move_token, .. } => *move_token,
 			_ => false,
 		}
 	}
@@ -143,8 +837,13 @@ impl Obj {
 		match self {
 			Obj::Slime { move_token, .. }
 			| Obj::Shroomer { move_token, .. }
+			| Obj::Archer { move_token, .. }
+			| Obj::Ghost { move_token, .. }
+			| Obj::GolemBoss { move_token, .. }
 			| Obj::Shroom { move_token }
-			| Obj::Fish { move_token, .. } => {
+			| Obj::Fish { move_token, .. }
+			| Obj::Golem { move_token, .. }
+			| Obj::Arrow { move_token, .. } => {
 				let had_move_token = *move_token;
 				*move_token = false;
 				had_move_token
@@ -152,17 +851,76 @@ impl Obj {
 			_ => false,
 		}
 	}
+
+	pub fn facing(&self) -> Option<Facing> {
+		match self {
+			Obj::Bunny { facing, .. }
+			| Obj::Slime { facing, .. }
+			| Obj::Shroomer { facing, .. }
+			| Obj::Archer { facing, .. }
+			| Obj::Ghost { facing, .. } => Some(*facing),
+			_ => None,
+		}
+	}
+
+	/// Makes the object look the way it moves (or tries to), moving up or down keeping it
+	/// looking the way it did.
+	fn turn_towards(&mut self, direction: IVec2) {
+		let (Obj::Bunny { facing, .. }
+		| Obj::Slime { facing, .. }
+		| Obj::Shroomer { facing, .. }
+		| Obj::Archer { facing, .. }
+		| Obj::Ghost { facing, .. }) = self
+		else {
+			return;
+		};
+		match direction.x.signum() {
+			1 => *facing = Facing::Right,
+			-1 => *facing = Facing::Left,
+			_ => {},
+		}
+	}
 }
 
 /// Every tile has a ground, below the potential object. The ground does not move.
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Ground {
 	/// The classic ground, nothing special.
 	Floor,
-	// TODO: Hole, Ice, FragileFloor
+	/// Activates the pistons and gates that share its link when something is on it.
+	PressurePlate { link_id: u32 },
+	/// Where a gate was, until nothing is on any of its pressure plates anymore.
+	OpenGate { link_id: u32 },
+	/// Objects that get on it fall in, never to be seen again. Nothing can stay on it.
+	Hole,
+	/// Objects that get on it keep sliding in the same direction,
+	/// until they hit something or get off the ice.
+	Ice,
+	/// It cracks under the weight of an object, and collapses into a hole when the object leaves.
+	FragileFloor,
+	/// Rocks and heavy objects that get in it sink, never to be seen again, and the others float.
+	/// The bunny does not walk into it, it has to be pushed in.
+	Water,
+	/// A raft that was pushed onto water, floating there and holding whatever is on it.
+	Raft,
+	/// Carries what is on it one tile in its direction at each turn of the game.
+	Conveyor { direction: IVec2 },
+	/// Burns what stands on it at each turn of the game, and consumables that get on it.
+	Lava,
+	/// What moves onto it is moved right away to the other teleporter that shares its link,
+	/// if nothing is there.
+	Teleporter { link_id: u32 },
+	/// Rises and retracts at each turn of the game, hurting what steps on it while risen and
+	/// what stands on it as it rises. A rock pushed onto it keeps it down for as long as it stays.
+	Spikes { armed: bool },
+	/// What is left of spikes that were disarmed with a tool, as harmless as a floor.
+	Rubble,
+	/// In a shop, the object on it is for sale at that price in gold. Only the bunny can push
+	/// it off, paying for it, and it is a floor from then on.
+	Price { price: i32 },
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Tile {
 	pub ground: Ground,
 	pub obj: Option<Obj>,
@@ -178,258 +936,1738 @@ impl Tile {
 	}
 }
 
+/// How a handcrafted level presents one of its objects, instead of how it usually looks,
+/// like a rock that is a boulder there. It changes nothing to how the object plays.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Look {
+	/// The name of the kind of object it was given to, the object losing its look if it turns
+	/// into an other kind (like when it dies and leaves a corpse).
+	given_to: String,
+	/// The name displayed in place of the usual name of the object.
+	pub name: Option<String>,
+	/// The object that is drawn in its place.
+	pub drawn_as: Option<Obj>,
+}
+
+impl Look {
+	pub fn new(obj: &Obj, name: Option<String>, drawn_as: Option<Obj>) -> Look {
+		Look { given_to: obj.name().to_string(), name, drawn_as }
+	}
+
+	pub fn is_for(&self, obj: &Obj) -> bool {
+		self.given_to == obj.name()
+	}
+}
+
+/// The part of the world in which agents take turns, around the player.
+#[derive(Clone, Copy, Hash, Serialize, Deserialize)]
+struct ActivityArea {
+	center: IVec2,
+	/// In steps.
+	radius: i32,
+}
+
+impl ActivityArea {
+	fn contains(self, coords: IVec2) -> bool {
+		let to_coords = coords - self.center;
+		to_coords.x.abs() + to_coords.y.abs() <= self.radius
+	}
+}
+
 /// A logical state of the world, with no regards to rendering or animation.
 /// The world is a grid of tiles.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LogicalWorld {
-	grid: HashMap<IVec2, Tile>,
+	grid: ChunkedGrid<Tile>,
 	pub redo_count: i32,
 	pub max_redo_count: i32,
+	/// How deep in the dungeon this level is, the first level being at depth 1.
+	pub depth: i32,
+	/// Objects that went through an exit door, from first to last.
+	pub exited_objs: Vec<Obj>,
+	/// While cursed, the player's left and right controls are swapped
+	/// for this many of their moves.
+	pub mirror_curse_turns_left: i32,
+	pub progression: Progression,
+	/// What the player did so far in the run.
+	#[serde(default)]
+	pub stats: RunStats,
+	/// Each rewind token allows to replay the moves of the game's last turn once.
+	pub rewind_tokens: i32,
+	/// Unlit bombs carried by the player, that they can place around them.
+	pub bombs: i32,
+	/// Coins collected by the player, to spend in the shops.
+	#[serde(default)]
+	pub gold: i32,
+	/// Scrap material collected by the player, from the traps they disarmed.
+	#[serde(default)]
+	pub scrap: i32,
+	/// Key shards collected by the player in this level.
+	#[serde(default)]
+	pub key_shards: i32,
+	/// The exit is sealed until the player collects that many key shards in this level.
+	#[serde(default)]
+	pub key_shards_needed: i32,
+	/// How far the player sees in this level without any torch or perk,
+	/// the deeper levels being darker.
+	#[serde(default = "default_base_vision_radius")]
+	pub base_vision_radius: f32,
+	/// The direction of the last move of the player, if their last action was a move,
+	/// for golems to copy.
+	last_player_move: Option<IVec2>,
+	/// Seeds the pick of the next agent to move, it changes with every pick.
+	/// It is part of the world so that the game's turns are reproducible.
+	agent_order_seed: u64,
+	pub assist: AssistOptions,
+	/// Can the player still be saved from a killing blow on this floor?
+	pub revive_available: bool,
+	/// Where the agents take turns, if only some of them do (see `update_activity_area`).
+	#[serde(default)]
+	activity_area: Option<ActivityArea>,
+	/// It is part of the world so that going back to a previous state of the world (or loading
+	/// a save) also goes back to what the director knew then.
+	#[serde(default)]
+	pub director: EncounterDirector,
+	/// The cost of the cheapest path from each tile to the player, for the enemies to follow,
+	/// as spread at the beginning of the game's turn for all the enemies to share.
+	#[serde(skip)]
+	scent_map: Arc<HashMap<IVec2, i32>>,
+	/// The tiles taken by multi-tile objects other than their anchor tiles (that hold them),
+	/// each with the coords of the anchor of the object that takes it.
+	#[serde(with = "grid_as_list", default)]
+	footprints: HashMap<IVec2, IVec2>,
+	/// The looks that the level gave to some of its objects, at the coords of these objects
+	/// (of their anchors, for multi-tile objects).
+	#[serde(with = "grid_as_list", default)]
+	pub looks: HashMap<IVec2, Look>,
+	/// What each tile that the player saw looked like the last time they saw it,
+	/// for the tiles out of sight to still be displayed as they were remembered.
+	#[serde(default)]
+	last_seen: ChunkedGrid<Tile>,
 }
 
-impl LogicalWorld {
-	pub fn new_empty() -> LogicalWorld {
-		LogicalWorld { grid: HashMap::new(), redo_count: 3, max_redo_count: 9 }
+/// The keys of a JSON object are strings, so the grid is serialized as a list of
+/// (coords, tile) pairs instead.
+mod grid_as_list {
+	use std::collections::HashMap;
+
+	use glam::IVec2;
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	use serde::Serialize;
+
+	pub fn serialize<S: Serializer, T: Serialize>(
+		grid: &HashMap<IVec2, T>,
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(grid.iter())
 	}
 
-	pub fn place_tile(&mut self, coords: IVec2, tile: Tile) {
-		self.grid.insert(coords, tile);
+	pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+		deserializer: D,
+	) -> Result<HashMap<IVec2, T>, D::Error> {
+		Ok(Vec::<(IVec2, T)>::deserialize(deserializer)?.into_iter().collect())
 	}
-	pub fn place_tile_no_overwrite(&mut self, coords: IVec2, tile: Tile) {
-		if let Entry::Vacant(vacant) = self.grid.entry(coords) {
-			vacant.insert(tile);
+}
+
+/// Options that make the game easier, each can be enabled on its own.
+#[derive(Clone, Copy, Default, Hash, Serialize, Deserialize)]
+pub struct AssistOptions {
+	/// Damages dealt to the player are halved, rounded up.
+	pub reduced_enemy_damage: bool,
+	/// Runs start with more redos.
+	pub extra_starting_redos: bool,
+	/// Hazards that last some turns go away sooner, like the mirror curse.
+	pub slower_hazards: bool,
+	/// Once per floor, a killing blow leaves the player with full HP instead.
+	pub free_revive: bool,
+}
+
+impl AssistOptions {
+	/// Reads the options from the `PUSHDG_ASSIST` environment variable, if it is set,
+	/// that overrides the options of the player profile.
+	pub fn from_env() -> Option<AssistOptions> {
+		std::env::var("PUSHDG_ASSIST").ok().map(|list| AssistOptions::from_names(&list))
+	}
+
+	/// Reads the options from a comma-separated list of option names,
+	/// like `damage,redos`, or `all` to enable all of them.
+	pub fn from_names(list: &str) -> AssistOptions {
+		let enabled =
+			|name: &str| list.split(',').map(str::trim).any(|item| item == name || item == "all");
+		AssistOptions {
+			reduced_enemy_damage: enabled("damage"),
+			extra_starting_redos: enabled("redos"),
+			slower_hazards: enabled("hazards"),
+			free_revive: enabled("revive"),
 		}
 	}
 
-	pub fn tiles(&self) -> impl Iterator<Item = (IVec2, &Tile)> {
-		self.grid.iter().map(|(&coords, tile)| (coords, tile))
+	/// The names of the enabled options, as `from_names` reads them.
+	pub fn names(&self) -> Vec<&'static str> {
+		[
+			(self.reduced_enemy_damage, "damage"),
+			(self.extra_starting_redos, "redos"),
+			(self.slower_hazards, "hazards"),
+			(self.free_revive, "revive"),
+		]
+		.into_iter()
+		.filter_map(|(enabled, name)| enabled.then_some(name))
+		.collect()
 	}
-	pub fn tile(&self, coords: IVec2) -> Option<&Tile> {
-		self.grid.get(&coords)
+
+	/// Descriptions of the enabled options, for them to be disclosed.
+	pub fn enabled_descriptions(&self) -> Vec<&'static str> {
+		[
+			(self.reduced_enemy_damage, "reduced enemy damage"),
+			(self.extra_starting_redos, "extra starting redos"),
+			(self.slower_hazards, "slower hazards"),
+			(self.free_revive, "free revive"),
+		]
+		.into_iter()
+		.filter_map(|(enabled, description)| enabled.then_some(description))
+		.collect()
 	}
-	pub fn obj(&self, coords: IVec2) -> Option<&Obj> {
-		self.grid.get(&coords).and_then(|tile| tile.obj.as_ref())
+}
+
+/// Experience of the player, gained from kills, and what the level ups brought.
+#[derive(Clone, Hash, Serialize, Deserialize)]
+pub struct Progression {
+	pub level: i32,
+	/// Experience gained since the last level up.
+	pub xp: i32,
+	/// Level ups that were not yet exchanged for a perk.
+	pub pending_perk_choices: i32,
+	pub bonus_force: i32,
+	pub bonus_vision: i32,
+}
+
+impl Progression {
+	fn new() -> Progression {
+		Progression {
+			level: 1,
+			xp: 0,
+			pending_perk_choices: 0,
+			bonus_force: 0,
+			bonus_vision: 0,
+		}
 	}
 
-	fn player_coords(&self) -> Option<IVec2> {
-		self.grid.iter().find_map(|(&coords, tile)| {
-			tile.obj.as_ref().is_some_and(|obj| matches!(obj, Obj::Bunny { .. })).then_some(coords)
-		})
+	/// Experience needed to go from the current level to the next.
+	pub fn xp_for_next_level(&self) -> i32 {
+		self.level * 3
 	}
 
-	pub fn has_player(&self) -> bool {
-		self.player_coords().is_some()
+	fn gain_xp(&mut self, xp: i32) {
+		self.xp += xp;
+		while self.xp >= self.xp_for_next_level() {
+			self.xp -= self.xp_for_next_level();
+			self.level += 1;
+			self.pending_perk_choices += 1;
+		}
 	}
+}
 
-	/// Computes the visibility of the tiles.
-	fn updated_visibility(mut self) -> LogicalWorld {
-		// TODO: Make this whole function more readable.
-		let player_coords = self.player_coords();
+/// Statistics of the run, counted from the logical events of the transitions.
+#[derive(Clone, Default, Hash, Serialize, Deserialize)]
+pub struct RunStats {
+	/// Moves of the player, each followed by the game's turn.
+	pub turns: i32,
+	/// Tiles that the bunny went over on the player's moves, walking then sliding on ice.
+	/// Being teleported, or moved during the game's turn (by conveyors, magnets or enemies),
+	/// is not the player going anywhere.
+	pub tiles_moved: i32,
+	pub enemies_killed: i32,
+	/// Damages taken by enemies, whatever hit them.
+	pub damages_dealt: i32,
+	pub damages_taken: i32,
+	/// Walls mined or blown up.
+	pub walls_mined: i32,
+	/// Objects that moved because the player pushed them.
+	pub objs_pushed: i32,
+}
 
-		// Handle vision gem effect.
-		// If the player is adjacent to a vision gem then they get see-through vision.
-		if let Some(player_coords) = player_coords {
-			let adjacent_to_vision_gem = 'vision_gem: {
-				for to_adjecent in four_directions() {
-					let adjacent_coords = player_coords + to_adjecent;
-					if let Some(Obj::VisionGem) = self.obj(adjacent_coords) {
-						break 'vision_gem true;
-					}
-				}
-				false
-			};
-			if adjacent_to_vision_gem {
-				for (coords, tile) in self.grid.iter_mut() {
-					let dist = player_coords.as_vec2().distance(coords.as_vec2());
-					tile.visible = dist <= 6.5;
-				}
+impl RunStats {
+	/// The player's coords are where the bunny was before the player's move,
+	/// or `None` if the transition is part of the game's turn.
+	fn count(&mut self, transition: &LogicalTransition, player_coords: Option<IVec2>) {
+		let lw = &transition.resulting_lw;
+		// The bunny is followed from where it was through the events, as it may be somewhere
+		// else than where a move ends by the end of the transition (or not be anywhere).
+		let mut bunny_coords = player_coords;
+		for logical_event in transition.logical_events.iter() {
+			match logical_event {
+				LogicalEvent::Move { from, to } if bunny_coords == Some(*from) => {
+					let distance = (*to - *from).abs();
+					self.tiles_moved += distance.x + distance.y;
+					bunny_coords = Some(*to);
+				},
+				LogicalEvent::Teleported { from, to, .. } if bunny_coords == Some(*from) =>
+					bunny_coords = Some(*to),
+				LogicalEvent::Move { .. } if player_coords.is_some() => self.objs_pushed += 1,
+				LogicalEvent::Killed { obj: Obj::Bunny { .. }, damages, .. } =>
+					self.damages_taken += damages,
+				LogicalEvent::Killed { obj, damages, .. } if obj.is_enemy() => {
+					self.enemies_killed += 1;
+					self.damages_dealt += damages;
+				},
+				LogicalEvent::Hit { at, damages } => match lw.obj(*at) {
+					Some(Obj::Bunny { .. }) => self.damages_taken += damages,
+					Some(obj) if obj.is_enemy() => self.damages_dealt += damages,
+					_ => {},
+				},
+				LogicalEvent::Mined { .. } => self.walls_mined += 1,
+				_ => {},
+			}
+		}
+	}
+}
+
+impl LogicalWorld {
+	pub fn new_empty() -> LogicalWorld {
+		LogicalWorld {
+			grid: ChunkedGrid::new(),
+			redo_count: 3,
+			max_redo_count: 9,
+			depth: 1,
+			exited_objs: vec![],
+			mirror_curse_turns_left: 0,
+			progression: Progression::new(),
+			stats: RunStats::default(),
+			rewind_tokens: 0,
+			bombs: 0,
+			gold: 0,
+			scrap: 0,
+			key_shards: 0,
+			key_shards_needed: 0,
+			base_vision_radius: default_base_vision_radius(),
+			last_player_move: None,
+			agent_order_seed: 0,
+			director: EncounterDirector::new(),
+			assist: AssistOptions::default(),
+			revive_available: false,
+			activity_area: None,
+			scent_map: Arc::new(HashMap::new()),
+			footprints: HashMap::new(),
+			looks: HashMap::new(),
+			last_seen: ChunkedGrid::new(),
+		}
+	}
+
+	/// Places a multi-tile object with its anchor at the given coords, taking the tiles of its
+	/// footprint (which must exist and be free).
+	pub fn place_multi_tile_obj(&mut self, anchor_coords: IVec2, obj: Obj) {
+		let footprint = obj.footprint();
+		self.grid.get_mut(&anchor_coords).unwrap().obj = Some(obj);
+		self.take_footprint(anchor_coords, footprint);
+	}
+
+	fn take_footprint(&mut self, anchor_coords: IVec2, footprint: IVec2) {
+		for coords in filled_rect(anchor_coords, footprint) {
+			if coords != anchor_coords {
+				self.footprints.insert(coords, anchor_coords);
+			}
+		}
+	}
+
+	/// Frees the tiles taken by the multi-tile object anchored at the given coords.
+	fn free_footprint(&mut self, anchor_coords: IVec2) {
+		self.footprints.retain(|_coords, anchor| *anchor != anchor_coords);
+	}
+
+	/// The coords of the tile that holds the object that takes the tile at the given coords
+	/// (which are the given coords, unless it is taken by a multi-tile object).
+	fn anchor_of(&self, coords: IVec2) -> IVec2 {
+		self.footprints.get(&coords).copied().unwrap_or(coords)
+	}
+
+	/// The object that takes the tile at the given coords, be it there
+	/// or a multi-tile object anchored elsewhere.
+	pub fn occupant(&self, coords: IVec2) -> Option<&Obj> {
+		self.obj(self.anchor_of(coords))
+	}
+
+	/// Is there a tile at the given coords with nothing on it, not even a part of a multi-tile
+	/// object, so that an object can be put there?
+	pub fn is_free(&self, coords: IVec2) -> bool {
+		self.tile(coords).is_some() && self.occupant(coords).is_none()
+	}
+
+	pub fn place_tile(&mut self, coords: IVec2, tile: Tile) {
+		self.grid.insert(coords, tile);
+	}
+	pub fn remove_tile(&mut self, coords: IVec2) {
+		self.grid.remove(&coords);
+	}
+	pub fn place_tile_no_overwrite(&mut self, coords: IVec2, tile: Tile) {
+		if !self.grid.contains_key(&coords) {
+			self.grid.insert(coords, tile);
+		}
+	}
+
+	pub fn tiles(&self) -> impl Iterator<Item = (IVec2, &Tile)> {
+		self.grid.iter().map(|(&coords, tile)| (coords, tile))
+	}
+	/// The look given by the level to the object that takes the tile at the given coords, if any.
+	pub fn look(&self, coords: IVec2) -> Option<&Look> {
+		self.looks.get(&self.anchor_of(coords))
+	}
+
+	/// The coords of the tiles that pass the filter, row by row from the top-left.
+	/// The grid is iterated over in an order that differs from one launch to the next, so the
+	/// passes that handle tiles one after the other go over these instead, for a turn to play
+	/// out the same each time it is played from the same world.
+	pub fn coords_where(&self, filter: impl Fn(&Tile) -> bool) -> Vec<IVec2> {
+		let mut coords: Vec<_> = self
+			.grid
+			.iter()
+			.filter(|(_coords, tile)| filter(tile))
+			.map(|(coords, _tile)| *coords)
+			.collect();
+		coords.sort_unstable_by_key(|coords| (coords.y, coords.x));
+		coords
+	}
+	pub fn tile(&self, coords: IVec2) -> Option<&Tile> {
+		self.grid.get(&coords)
+	}
+	fn turn_obj_towards(&mut self, coords: IVec2, direction: IVec2) {
+		if let Some(obj) = self.grid.get_mut(&coords).and_then(|tile| tile.obj.as_mut()) {
+			obj.turn_towards(direction);
+		}
+	}
+
+	/// The tile as it was the last time the player saw it, if they ever did.
+	pub fn last_seen(&self, coords: IVec2) -> Option<&Tile> {
+		self.last_seen.get(&coords)
+	}
+	pub fn obj(&self, coords: IVec2) -> Option<&Obj> {
+		self.grid.get(&coords).and_then(|tile| tile.obj.as_ref())
+	}
+
+	pub fn seed_agent_order(&mut self, seed: u64) {
+		self.agent_order_seed = seed;
+	}
+
+	/// A random number generator that only depends on the world (through the seed of the order
+	/// of the agents) and on what it is for, so that what is left to chance plays out the same
+	/// each time the same moves are played from the same world, like in a replay or a redo,
+	/// and on every platform.
+	pub fn seeded_rng(&self, purpose: &str) -> StdRng {
+		let mut hasher = StableHasher::new();
+		self.agent_order_seed.hash(&mut hasher);
+		purpose.hash(&mut hasher);
+		StdRng::seed_from_u64(hasher.finish())
+	}
+
+	/// Changes the order in which the agents will move, like for the game to play its turn
+	/// again differently.
+	pub fn reroll_agent_order(&mut self) {
+		self.agent_order_seed = StdRng::seed_from_u64(self.agent_order_seed).gen();
+	}
+
+	/// Enables the given assist options, to be done once at the beginning of a floor.
+	pub fn set_assist(&mut self, assist: AssistOptions) {
+		self.assist = assist;
+		self.revive_available = assist.free_revive;
+		if assist.extra_starting_redos {
+			self.redo_count = (self.redo_count + 3).min(self.max_redo_count);
+		}
+	}
+
+	/// The damages that would be dealt by the hitter to the target, at the given coords.
+	fn damages_dealt(
+		&self,
+		hitter: &Obj,
+		hitter_coords: IVec2,
+		target: &Obj,
+		target_coords: IVec2,
+	) -> i32 {
+		let is_sword = matches!(hitter.wielded(), Obj::Sword { .. } | Obj::VisionSword);
+		if matches!(target, Obj::Ghost { .. }) && !is_sword {
+			return 0;
+		}
+		let damages = if matches!(hitter, Obj::Bunny { .. }) {
+			hitter.damages() + self.attack_bonus(hitter_coords)
+		} else {
+			hitter.damages()
+		};
+		self.damages_taken(damages, target, target_coords)
+	}
+
+	/// Is there an object right next to the given coords (not diagonally) that passes the filter?
+	fn is_next_to(&self, coords: IVec2, filter: impl Fn(&Obj) -> bool) -> bool {
+		four_directions()
+			.into_iter()
+			.any(|direction| self.obj(coords + direction).is_some_and(&filter))
+	}
+
+	/// Added to the damages of the hits of the bunny at the given coords,
+	/// 1 if a sword lies next to it.
+	pub fn attack_bonus(&self, coords: IVec2) -> i32 {
+		self.is_next_to(coords, |obj| {
+			matches!(obj, Obj::Sword { .. } | Obj::VisionSword)
+		}) as i32
+	}
+
+	/// Blocked from the damages of each hit taken by the bunny at the given coords,
+	/// 1 if it holds a shield or if a shield lies next to it.
+	pub fn defense(&self, coords: IVec2) -> i32 {
+		let shielded = matches!(self.obj(coords).and_then(Obj::held), Some(Obj::Shield))
+			|| self.is_next_to(coords, |obj| matches!(obj, Obj::Shield));
+		shielded as i32
+	}
+
+	/// The damages that the target at the given coords actually takes from a hit
+	/// of the given damages.
+	fn damages_taken(&self, damages: i32, target: &Obj, target_coords: IVec2) -> i32 {
+		let damages = if matches!(target, Obj::Bunny { .. }) {
+			(damages - self.defense(target_coords)).max(0)
+		} else {
+			damages
+		};
+		if self.assist.reduced_enemy_damage && matches!(target, Obj::Bunny { .. }) {
+			(damages + 1) / 2
+		} else {
+			damages
+		}
+	}
+
+	/// Would the given damages kill the given target?
+	/// Not if it is the player and the free revive is still available.
+	fn is_lethal(&self, damages: i32, target: &Obj) -> bool {
+		target.hp().is_some_and(|hp| hp <= damages)
+			&& !(self.revive_available && matches!(target, Obj::Bunny { .. }))
+	}
+
+	/// If the player at the given coords was brought to zero HP, the free revive saves them.
+	fn revive_if_needed(&mut self, coords: IVec2, logical_events: &mut Vec<LogicalEvent>) {
+		if let Some(Obj::Bunny { hp, max_hp, .. }) = self.grid.get_mut(&coords).unwrap().obj.as_mut()
+		{
+			if *hp <= 0 {
+				*hp = *max_hp;
+				self.revive_available = false;
+				logical_events.push(LogicalEvent::Revived { at: coords });
+			}
+		}
+	}
+
+	/// Makes the player tougher, and heals them by as much.
+	pub fn raise_player_max_hp(&mut self, amount: i32) {
+		let is_bunny = |_coords: &IVec2, tile: &Tile| matches!(tile.obj, Some(Obj::Bunny { .. }));
+		self.grid.modify_where(is_bunny, |_coords, tile| {
+			if let Some(Obj::Bunny { hp, max_hp, .. }) = tile.obj.as_mut() {
+				*hp += amount;
+				*max_hp += amount;
+			}
+		});
+	}
+
+	/// Only tiles at most this far from the player at the given coords may be visible.
+	/// A torch held by the player or next to them lights up farther.
+	fn vision_radius(&self, player_coords: IVec2) -> f32 {
+		let lit_by_torch = matches!(
+			self.obj(player_coords).and_then(Obj::held),
+			Some(Obj::Torch)
+		) || self.is_next_to(player_coords, |obj| matches!(obj, Obj::Torch));
+		let torch_bonus = if lit_by_torch {
+			TORCH_VISION_BONUS
+		} else {
+			0.0
+		};
+		self.base_vision_radius + self.progression.bonus_vision as f32 + torch_bonus
+	}
+
+	pub fn player_coords(&self) -> Option<IVec2> {
+		self.grid.iter().find_map(|(&coords, tile)| {
+			tile.obj.as_ref().is_some_and(|obj| matches!(obj, Obj::Bunny { .. })).then_some(coords)
+		})
+	}
+
+	/// Checks some invariants that should always hold, to catch a corrupted state right when
+	/// it happens instead of much later. During the player's turn, no agent should be waiting
+	/// to make a move.
+	pub fn validate(&self, players_turn: bool) -> Result<(), String> {
+		let player_count =
+			self.grid.values().filter(|tile| matches!(tile.obj, Some(Obj::Bunny { .. }))).count();
+		if player_count > 1 {
+			return Err(format!("there are {player_count} players"));
+		}
+		if !(0..=self.max_redo_count).contains(&self.redo_count) {
+			return Err(format!(
+				"redo count {} is out of bounds 0..={}",
+				self.redo_count, self.max_redo_count
+			));
+		}
+		for (&coords, tile) in self.grid.iter() {
+			let Some(obj) = tile.obj.as_ref() else {
+				continue;
+			};
+			if obj.hp().is_some_and(|hp| hp <= 0) {
+				return Err(format!(
+					"{} at {coords} is dead but still there",
+					obj.name()
+				));
+			}
+			if let Obj::Bunny { hp, max_hp, .. } = obj {
+				if hp > max_hp {
+					return Err(format!("player at {coords} has {hp} HP out of {max_hp}"));
+				}
+			}
+			if players_turn && obj.has_move_token() {
+				return Err(format!(
+					"{} at {coords} still has a move token during the player's turn",
+					obj.name()
+				));
+			}
+			if let Obj::Piston { facing, extended: true, .. } = obj {
+				if !matches!(self.obj(coords + *facing), Some(Obj::PistonHead)) {
+					return Err(format!("extended piston at {coords} has no head"));
+				}
+			}
+			if matches!(tile.ground, Ground::Hole) {
+				return Err(format!("{} at {coords} is over a hole", obj.name()));
+			}
+			if let Some(anchor_coords) = self.footprints.get(&coords) {
+				return Err(format!(
+					"{} at {coords} is inside the multi-tile object at {anchor_coords}",
+					obj.name()
+				));
+			}
+		}
+		for (&coords, tile) in self.grid.iter() {
+			if !matches!(tile.obj, Some(Obj::Wall)) {
+				let neighbors = filled_rect(coords - IVec2::new(1, 1), IVec2::new(3, 3));
+				if let Some(outside) =
+					neighbors.iter().find(|neighbor| !self.grid.contains_key(neighbor))
+				{
+					return Err(format!(
+						"{coords} is not walled in, there is nothing at {outside}"
+					));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Roughly how many bytes this takes in memory, not counting the allocator overhead.
+	pub fn estimated_memory_size(&self) -> usize {
+		std::mem::size_of::<LogicalWorld>()
+			+ (self.grid.len() + self.last_seen.len()) * std::mem::size_of::<(IVec2, Tile)>()
+			+ self.exited_objs.capacity() * std::mem::size_of::<Obj>()
+	}
+
+	/// Nothing goes through the exit while it is sealed, it is as good as a wall.
+	pub fn is_exit_sealed(&self) -> bool {
+		self.key_shards < self.key_shards_needed
+	}
+
+	pub fn has_player(&self) -> bool {
+		self.player_coords().is_some()
+	}
+
+	/// Did the player go through an exit door?
+	pub fn player_exited(&self) -> bool {
+		self.exited_objs.iter().any(|obj| matches!(obj, Obj::Bunny { .. }))
+	}
+
+	/// Gives the player what they had when they went through the exit of the previous level:
+	/// their HP, redos, experience and items, and the objects that went through the exit before
+	/// them are placed around them.
+	pub fn carry_over_from(&mut self, previous_lw: &LogicalWorld) {
+		let Some(player_coords) = self.player_coords() else {
+			return;
+		};
+		if let Some(bunny) =
+			previous_lw.exited_objs.iter().find(|obj| matches!(obj, Obj::Bunny { .. }))
+		{
+			self.grid.get_mut(&player_coords).unwrap().obj = Some(bunny.clone());
+		}
+		self.redo_count = previous_lw.redo_count;
+		self.max_redo_count = previous_lw.max_redo_count;
+		self.progression = previous_lw.progression.clone();
+		self.stats = previous_lw.stats.clone();
+		self.rewind_tokens = previous_lw.rewind_tokens;
+		self.bombs = previous_lw.bombs;
+		self.gold = previous_lw.gold;
+		self.scrap = previous_lw.scrap;
+		// The closest free tiles first, enemies that went through the exit are left behind.
+		let mut free_coords: Vec<_> = filled_rect(player_coords - IVec2::splat(3), IVec2::splat(7))
+			.into_iter()
+			.filter(|&coords| {
+				self.is_free(coords)
+					&& self.tile(coords).is_some_and(|tile| matches!(tile.ground, Ground::Floor))
+			})
+			.collect();
+		free_coords.sort_by_key(|&coords| (coords - player_coords).abs().max_element());
+		let carried_objs = previous_lw.exited_objs.iter().filter(|obj| !obj.is_agent());
+		for (obj, coords) in carried_objs.zip(free_coords) {
+			self.grid.get_mut(&coords).unwrap().obj = Some(obj.clone());
+		}
+	}
+
+	/// Computes the visibility of the tiles.
+	pub fn updated_visibility(mut self) -> LogicalWorld {
+		let _timer = metrics::time("visibility");
+		// TODO: Make this whole function more readable.
+		let player_coords = self.player_coords();
+
+		// Handle vision gem effect.
+		// If the player is adjacent to a vision gem then they get see-through vision.
+		if let Some(player_coords) = player_coords {
+			let vision_radius = self.vision_radius(player_coords);
+			let adjacent_to_vision_gem = 'vision_gem: {
+				for to_adjecent in four_directions() {
+					let adjacent_coords = player_coords + to_adjecent;
+					if let Some(Obj::VisionGem | Obj::VisionSword) = self.obj(adjacent_coords) {
+						break 'vision_gem true;
+					}
+				}
+				false
+			};
+			if adjacent_to_vision_gem {
+				self.set_visible_coords(|coords| {
+					player_coords.as_vec2().distance(coords.as_vec2()) <= vision_radius
+				});
+				let reach = vision_radius.ceil() as i32;
+				self.remember_visible_tiles(filled_rect(
+					player_coords - IVec2::splat(reach),
+					IVec2::splat(reach * 2 + 1),
+				));
 				return self;
 			}
 		}
 
-		// First pass, most of the vision is established here.
-		let lw_clone = self.clone();
-		for (coords, tile) in self.grid.iter_mut() {
-			tile.visible = if let Some(player_coords) = player_coords {
-				let dist = player_coords.as_vec2().distance(coords.as_vec2());
-				if dist == 0.0 {
-					true
-				} else {
-					// Only tiles in this radius may become visible.
-					dist <= 6.5 && {
-						let direction = (coords.as_vec2() - player_coords.as_vec2()).normalize();
-						let step = 0.1;
-						let mut point = player_coords.as_vec2();
-						loop {
-							if point.distance(coords.as_vec2()) < 3.0 * step {
-								// A line of sight was established, we got vision here.
-								break true;
-							}
-							let point_coords = point.round().as_ivec2();
-							if lw_clone.obj(point_coords).is_some_and(|obj| obj.blocks_vision()) {
-								// A vision-blocking object is blocking the line of sight.
-								break point_coords == *coords;
-							}
-							point += direction * step;
-						}
-					}
-				}
-			} else {
-				true
-			};
+		// The line of sight only depends on what is around the player, so it is cached.
+		// The key changes when vision blockers change, which invalidates the cached result.
+		let Some(player_coords) = player_coords else {
+			return self.with_everything_visible();
+		};
+		let cache_key = self.line_of_sight_cache_key(player_coords);
+		let cached_visible_coords =
+			LINE_OF_SIGHT_CACHE.with(|cache| cache.borrow().get(&cache_key).cloned());
+		let visible_coords = cached_visible_coords.unwrap_or_else(|| {
+			let visible_coords = Rc::new(self.line_of_sight_visible_coords(player_coords));
+			LINE_OF_SIGHT_CACHE.with(|cache| {
+				let mut cache = cache.borrow_mut();
+				if cache.len() >= LINE_OF_SIGHT_CACHE_CAPACITY {
+					cache.clear();
+				}
+				cache.insert(cache_key, Rc::clone(&visible_coords));
+				metrics::set_count("line of sight cache entries", cache.len());
+			});
+			visible_coords
+		});
+		self.set_visible_coords(|coords| visible_coords.contains(&coords));
+		self.remember_visible_tiles(visible_coords.iter().copied());
+		self
+	}
+
+	/// Updates the memory of the visible tiles among the given coords, only touching the tiles
+	/// that changed since they were last seen (for the memory to stay shared too).
+	fn remember_visible_tiles(&mut self, coords: impl IntoIterator<Item = IVec2>) {
+		for coords in coords {
+			let Some(tile) = self.grid.get(&coords).filter(|tile| tile.visible) else {
+				continue;
+			};
+			if self.last_seen.get(&coords) != Some(tile) {
+				self.last_seen.insert(coords, tile.clone());
+			}
+		}
+	}
+
+	/// Only touches the tiles whose visibility changes,
+	/// so that the others stay shared with the previous states of the world.
+	fn set_visible_coords(&mut self, is_visible: impl Fn(IVec2) -> bool) {
+		self.grid.modify_where(
+			|coords, tile| tile.visible != is_visible(*coords),
+			|_coords, tile| tile.visible = !tile.visible,
+		);
+	}
+
+	/// Hashes the whole state of the world, to tell cheaply whether two worlds that should be
+	/// the same really are. The tiles are combined in a way that doesn't depend on their order,
+	/// and the hash is stable, the same worlds have the same checksum on every platform.
+	pub fn checksum(&self) -> u64 {
+		let tiles_checksum = self
+			.grid
+			.iter()
+			.map(|(coords, tile)| {
+				let mut hasher = StableHasher::new();
+				coords.hash(&mut hasher);
+				tile.hash(&mut hasher);
+				hasher.finish()
+			})
+			.fold(0, u64::wrapping_add);
+		let mut hasher = StableHasher::new();
+		tiles_checksum.hash(&mut hasher);
+		self.redo_count.hash(&mut hasher);
+		self.max_redo_count.hash(&mut hasher);
+		self.depth.hash(&mut hasher);
+		self.exited_objs.hash(&mut hasher);
+		self.mirror_curse_turns_left.hash(&mut hasher);
+		self.progression.hash(&mut hasher);
+		self.rewind_tokens.hash(&mut hasher);
+		self.bombs.hash(&mut hasher);
+		self.gold.hash(&mut hasher);
+		self.scrap.hash(&mut hasher);
+		self.key_shards.hash(&mut hasher);
+		self.key_shards_needed.hash(&mut hasher);
+		self.base_vision_radius.to_bits().hash(&mut hasher);
+		self.last_player_move.hash(&mut hasher);
+		self.agent_order_seed.hash(&mut hasher);
+		self.assist.hash(&mut hasher);
+		self.revive_available.hash(&mut hasher);
+		self.activity_area.hash(&mut hasher);
+		self.director.hash(&mut hasher);
+		// The memory of the tiles and the looks are left out, they are only displayed
+		// and change nothing.
+		hasher.finish()
+	}
+
+	/// Are the two worlds the same, but for the order in which their agents would move and their
+	/// rewind tokens? That is what a rewound turn that played out like the first time ends in.
+	pub fn is_same_outcome_as(&self, other: &LogicalWorld) -> bool {
+		let mut lw = self.clone();
+		lw.agent_order_seed = other.agent_order_seed;
+		lw.rewind_tokens = other.rewind_tokens;
+		lw.checksum() == other.checksum()
+	}
+
+	/// Hashes everything that the line of sight from the given coords depends on,
+	/// which is which tiles exist and which of them block vision, near enough to matter.
+	fn line_of_sight_cache_key(&self, player_coords: IVec2) -> u64 {
+		let vision_radius = self.vision_radius(player_coords);
+		with_scratch_buffer(&NEARBY_TILES_BUFFER, |nearby_tiles| {
+			nearby_tiles.extend(
+				self
+					.grid
+					.iter()
+					.filter(|(coords, _tile)| {
+						player_coords.as_vec2().distance(coords.as_vec2()) <= vision_radius + 1.5
+					})
+					.map(|(coords, tile)| {
+						(
+							*coords,
+							tile.obj.as_ref().is_some_and(|obj| obj.blocks_vision()),
+						)
+					}),
+			);
+			nearby_tiles.sort_by_key(|(coords, _blocks_vision)| (coords.x, coords.y));
+			let mut hasher = DefaultHasher::new();
+			player_coords.hash(&mut hasher);
+			vision_radius.to_bits().hash(&mut hasher);
+			nearby_tiles.hash(&mut hasher);
+			hasher.finish()
+		})
+	}
+
+	/// The coords of the tiles seen from the given coords, with lines of sight.
+	fn line_of_sight_visible_coords(&self, player_coords: IVec2) -> HashSet<IVec2> {
+		let vision_radius = self.vision_radius(player_coords);
+		let in_vision_radius =
+			|coords: IVec2| player_coords.as_vec2().distance(coords.as_vec2()) <= vision_radius;
+		// Vision-blocking objects out of the radius can only cast shadows farther away,
+		// where nothing is seen anyway.
+		let obstructions: HashSet<IVec2> = self
+			.grid
+			.iter()
+			.filter(|(coords, tile)| {
+				in_vision_radius(**coords) && tile.obj.as_ref().is_some_and(|obj| obj.blocks_vision())
+			})
+			.map(|(coords, _tile)| *coords)
+			.collect();
+		let is_visible_floor = |visible: &HashSet<IVec2>, coords: IVec2| {
+			visible.contains(&coords) && !obstructions.contains(&coords)
+		};
+		let is_visible_obstruction = |visible: &HashSet<IVec2>, coords: IVec2| {
+			visible.contains(&coords) && obstructions.contains(&coords)
+		};
+
+		// Most of the vision is established here.
+		let mut visible: HashSet<IVec2> =
+			field_of_view::visible_coords(player_coords, vision_radius, &obstructions)
+				.into_iter()
+				.filter(|coords| self.grid.contains_key(coords))
+				.collect();
+
+		// Add vision to vision-blocking objects next to visible floor, mostly for aesthetic
+		// purposes. Each of these passes only reads the visibility from before it, so that the
+		// result does not depend on the order in which the tiles are processed.
+		let seen_sides: Vec<IVec2> = obstructions
+			.iter()
+			.filter(|coords| !visible.contains(coords))
+			.filter(|coords| {
+				four_directions()
+					.into_iter()
+					.any(|to_adjacent| is_visible_floor(&visible, **coords + to_adjacent))
+			})
+			.copied()
+			.collect();
+		visible.extend(seen_sides);
+
+		// Add vision to vision-blocking objects in corners of visible vision-blocking objects,
+		// entierly for aesthetic purposes. Such a corner gets visible only if it completes the
+		// corner of a piece of room in which the player is, which is when the floor tile
+		// diagonal to it is visible and closer to the player than the corner and both its sides.
+		let seen_corners: Vec<IVec2> = obstructions
+			.iter()
+			.filter(|coords| !visible.contains(coords))
+			.filter(|coords| {
+				let dist = |coords: IVec2| coords.as_vec2().distance(player_coords.as_vec2());
+				four_directions().into_iter().any(|to_adjacent| {
+					let adjacent_coords = **coords + to_adjacent;
+					let other_adjacent_coords = **coords + to_adjacent.perp();
+					let corner_coords = **coords + to_adjacent + to_adjacent.perp();
+					is_visible_obstruction(&visible, adjacent_coords)
+						&& is_visible_obstruction(&visible, other_adjacent_coords)
+						&& is_visible_floor(&visible, corner_coords)
+						&& dist(corner_coords)
+							< dist(**coords).min(dist(adjacent_coords)).min(dist(other_adjacent_coords))
+				})
+			})
+			.copied()
+			.collect();
+		visible.extend(seen_corners);
+
+		// Seeing any tile of a multi-tile object is seeing all of it.
+		let seen_anchors: Vec<_> = self
+			.footprints
+			.iter()
+			.filter(|(coords, anchor)| visible.contains(coords) || visible.contains(anchor))
+			.map(|(_coords, anchor)| *anchor)
+			.collect();
+		for (coords, anchor) in self.footprints.iter() {
+			if seen_anchors.contains(anchor) {
+				visible.insert(*coords);
+				visible.insert(*anchor);
+			}
+		}
+		visible
+	}
+
+	/// Makes all the tiles visible, regardless of the player's vision.
+	pub fn with_everything_visible(mut self) -> LogicalWorld {
+		self.set_visible_coords(|_coords| true);
+		self
+	}
+
+	/// There are walls everywhere, we apply that design choice here.
+	pub fn generated_walls_outside(mut self) -> LogicalWorld {
+		with_scratch_buffer(&COORDS_BUFFER, |keys| {
+			keys.extend(self.grid.keys().copied());
+			for coords in keys.iter().copied() {
+				if !matches!(self.obj(coords), Some(Obj::Wall)) {
+					for coords in filled_rect(coords - IVec2::new(1, 1), IVec2::new(3, 3)) {
+						self.place_tile_no_overwrite(coords, Tile::obj(Obj::Wall));
+					}
+				}
+			}
+		});
+		self
+	}
+
+	/// Returns the transition of the player doing the given action, `None` if it cannot be done
+	/// (and the player's turn is not spent).
+	pub fn player_act(&self, action: PlayerAction) -> Option<LogicalTransition> {
+		match action {
+			PlayerAction::Move(direction) => Some(self.player_move(direction)),
+			PlayerAction::PlaceBomb(direction) => self.player_place_bomb(direction),
+			PlayerAction::PickUpOrDrop(direction) => self.player_pick_up_or_drop(direction),
+		}
+	}
+
+	/// Returns the transition of the player trying to move in the given direction.
+	pub fn player_move(&self, direction: IVec2) -> LogicalTransition {
+		if let Some(coords) = self.player_coords() {
+			let Some(&Obj::Bunny { force, .. }) = self.obj(coords) else {
+				unreachable!("The player is a bunny")
+			};
+			let player_force = force + self.progression.bonus_force;
+			let mut lw = self.clone();
+			lw.mirror_curse_turns_left = (lw.mirror_curse_turns_left - 1).max(0);
+			lw.last_player_move = Some(direction);
+			lw.stats.turns += 1;
+			lw.turn_obj_towards(coords, direction);
+			lw.try_to_move(coords, direction, player_force)
+				.generated_walls_outside()
+				.credited_kill_experience()
+				.with_looks_carried()
+				.counted_in_stats(Some(coords))
+				.updated_visibility()
+		} else {
+			self.clone().into()
+		}
+	}
+
+	/// The player lights one of their bombs and places it next to them, in the given direction.
+	/// Returns `None` if they have no bomb or if there is no room for it there, like over a hole
+	/// or in water or lava, where a dropped tool would not stay either.
+	pub fn player_place_bomb(&self, direction: IVec2) -> Option<LogicalTransition> {
+		let coords = self.player_coords()? + direction;
+		if self.bombs < 1
+			|| !self.is_free(coords)
+			|| self
+				.tile(coords)
+				.is_some_and(|tile| matches!(tile.ground, Ground::Hole | Ground::Water | Ground::Lava))
+		{
+			return None;
+		}
+		let mut lw = self.clone();
+		lw.mirror_curse_turns_left = (lw.mirror_curse_turns_left - 1).max(0);
+		lw.bombs -= 1;
+		lw.last_player_move = None;
+		lw.stats.turns += 1;
+		lw.grid.get_mut(&coords).unwrap().obj = Some(Obj::Bomb { fuse: Some(3) });
+		Some(LogicalTransition {
+			logical_events: vec![LogicalEvent::BombPlaced { at: coords }],
+			resulting_lw: lw,
+		})
+	}
+
+	/// The player picks up the tool next to them in the given direction if they hold nothing,
+	/// or else drops the tool they hold there.
+	/// Returns `None` if there is no tool to pick up or if there is no room to drop it there.
+	pub fn player_pick_up_or_drop(&self, direction: IVec2) -> Option<LogicalTransition> {
+		let player_coords = self.player_coords()?;
+		let coords = player_coords + direction;
+		let tile = self.tile(coords)?;
+		let mut lw = self.clone();
+		let mut bunny = lw.grid.get_mut(&player_coords).unwrap().obj.take().unwrap();
+		let Obj::Bunny { held, .. } = &mut bunny else {
+			unreachable!("The player is a bunny")
+		};
+		let logical_event = if let Some(held_obj) = held.take() {
+			if !self.is_free(coords)
+				|| matches!(tile.ground, Ground::Hole | Ground::Water | Ground::Lava)
+			{
+				return None;
+			}
+			lw.grid.get_mut(&coords).unwrap().obj = Some(*held_obj);
+			LogicalEvent::Dropped { from: player_coords, to: coords }
+		} else {
+			if !tile.obj.as_ref().is_some_and(Obj::is_holdable) {
+				return None;
+			}
+			let obj = lw.grid.get_mut(&coords).unwrap().obj.take().unwrap();
+			*held = Some(Box::new(obj.clone()));
+			LogicalEvent::PickedUp { obj, from: coords, to: player_coords }
+		};
+		lw.grid.get_mut(&player_coords).unwrap().obj = Some(bunny);
+		lw.mirror_curse_turns_left = (lw.mirror_curse_turns_left - 1).max(0);
+		lw.last_player_move = None;
+		lw.stats.turns += 1;
+		lw.turn_obj_towards(player_coords, direction);
+		Some(LogicalTransition { logical_events: vec![logical_event], resulting_lw: lw })
+	}
+
+	/// When it is the game's turn to play, agents are given one move token
+	/// so that one agent doesn't get to move twice.
+	/// Only the agents in the activity area get one, if there is one.
+	pub fn give_move_token_to_agents(&mut self) {
+		let activity_area = self.activity_area;
+		let is_agent = |coords: &IVec2, tile: &Tile| {
+			tile.obj.as_ref().is_some_and(Obj::is_agent)
+				&& activity_area.is_none_or(|area| area.contains(*coords))
+		};
+		self.grid.modify_where(is_agent, |_coords, tile| {
+			if let Some(obj) = tile.obj.as_mut() {
+				obj.give_move_token();
+			}
+		});
+	}
+
+	/// Centers the activity area (where agents take turns) on the player, with the given radius,
+	/// or makes all the agents take turns if there is no radius.
+	/// Returns the events of the agents that the area left behind or reached,
+	/// there is none when there was no area before.
+	pub fn update_activity_area(&mut self, activity_radius: Option<i32>) -> Vec<LogicalEvent> {
+		let previous_area = self.activity_area;
+		self.activity_area = activity_radius
+			.zip(self.player_coords())
+			.map(|(radius, center)| ActivityArea { center, radius });
+		let Some(previous_area) = previous_area else {
+			return vec![];
+		};
+		let new_area = self.activity_area;
+		self
+			.coords_where(|tile| {
+				tile.obj.as_ref().is_some_and(|obj| obj.is_agent() && !matches!(obj, Obj::Bunny { .. }))
+			})
+			.into_iter()
+			.filter(|coords| {
+				previous_area.contains(*coords) != new_area.is_none_or(|area| area.contains(*coords))
+			})
+			.map(|at| {
+				if previous_area.contains(at) {
+					LogicalEvent::FellDormant { at }
+				} else {
+					LogicalEvent::WokeUp { at }
+				}
+			})
+			.collect()
+	}
+
+	/// How costly it is for an enemy to go through the tile, if it can at all.
+	fn scent_cost(tile: &Tile) -> Option<i32> {
+		let ground_cost = match tile.ground {
+			Ground::Floor => 1,
+			// Enemies are wary of traps.
+			Ground::PressurePlate { .. } => 3,
+			// It may close on it.
+			Ground::OpenGate { .. } => 2,
+			Ground::Hole => return None,
+			// It may slide too far.
+			Ground::Ice => 2,
+			// It would only hold once.
+			Ground::FragileFloor => 3,
+			Ground::Water => return None,
+			Ground::Raft => 1,
+			// It may carry it away.
+			Ground::Conveyor { .. } => 2,
+			Ground::Teleporter { .. } => 1,
+			Ground::Lava => return None,
+			// Enemies are wary of traps.
+			Ground::Spikes { .. } => 3,
+			Ground::Price { .. } | Ground::Rubble => 1,
+		};
+		let obj_cost = match tile.obj {
+			None | Some(Obj::Bunny { .. }) => 0,
+			Some(
+				Obj::Wall
+				| Obj::Door
+				| Obj::Piston { .. }
+				| Obj::PistonHead
+				| Obj::LaserEmitter { .. }
+				| Obj::Gate { .. }
+				| Obj::Exit
+				| Obj::GolemBoss { .. }
+				| Obj::Altar { .. },
+			) => return None,
+			// It may move out of the way, or be pushed.
+			Some(_) => 2,
+		};
+		Some(ground_cost + obj_cost)
+	}
+
+	/// Floods the paths to the player outward from them, so that the enemies can track them
+	/// by going down the scent map instead of each looking for its own path.
+	pub fn spread_scent(&mut self) {
+		let _timer = metrics::time("scent map");
+		let mut scent_map = HashMap::with_capacity(self.scent_map.len());
+		if let Some(player_coords) = self.player_coords() {
+			with_scratch_buffer(&SCENT_FRONTIER_BUFFER, |frontier| {
+				frontier.push(Reverse((0, player_coords.x, player_coords.y)));
+				while let Some(Reverse((cost, x, y))) = frontier.pop() {
+					let coords = IVec2::new(x, y);
+					if scent_map.contains_key(&coords) {
+						continue;
+					}
+					scent_map.insert(coords, cost);
+					for direction in four_directions() {
+						let next_coords = coords + direction;
+						// The scent does not go through the parts of multi-tile objects.
+						if scent_map.contains_key(&next_coords)
+							|| self.footprints.contains_key(&next_coords)
+						{
+							continue;
+						}
+						let Some(step_cost) =
+							self.grid.get(&next_coords).and_then(LogicalWorld::scent_cost)
+						else {
+							continue;
+						};
+						let next_cost = cost + step_cost;
+						if next_cost <= SCENT_RADIUS {
+							frontier.push(Reverse((next_cost, next_coords.x, next_coords.y)));
+						}
+					}
+				}
+			});
+		}
+		self.scent_map = Arc::new(scent_map);
+	}
+
+	/// The cost of the cheapest path from the given tile to the player, if they can be smelled
+	/// from there. Up to date as of the beginning of the game's turn.
+	pub fn scent(&self, coords: IVec2) -> Option<i32> {
+		self.scent_map.get(&coords).copied()
+	}
+
+	/// At the beginning of the game's turn, machines act before anything else.
+	/// Pistons that were extended retract, and pistons whose pressure plate is pressed extend.
+	/// Returns `None` if no machine did anything.
+	/// The conveyors carry what is on them one tile in their direction, as if it moved there
+	/// without pushing anything. What is carried onto an other conveyor is not carried again.
+	pub fn run_conveyors(&self) -> Option<LogicalTransition> {
+		let mut conveyors: Vec<_> = self
+			.coords_where(|tile| matches!(tile.ground, Ground::Conveyor { .. }) && tile.obj.is_some())
+			.into_iter()
+			.filter_map(|coords| match self.grid[&coords].ground {
+				Ground::Conveyor { direction } => Some((coords, direction)),
+				_ => None,
+			})
+			.collect();
+		// The front of a line of conveyors goes first, making room for what is behind it.
+		conveyors.sort_by_key(|(coords, direction)| -coords.dot(*direction));
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		let mut carried_to = vec![];
+		for (coords, direction) in conveyors {
+			// The head of an extended piston stays where its piston holds it.
+			if carried_to.contains(&coords)
+				|| matches!(res_lw.obj(coords), None | Some(Obj::PistonHead))
+				|| !res_lw.what_would_happen_if_try_to_move(coords, direction, 0).success
+			{
+				continue;
+			}
+			let transition = res_lw.try_to_move(coords, direction, 0);
+			res_lw = transition.resulting_lw;
+			for logical_event in transition.logical_events.iter() {
+				if let LogicalEvent::Move { to, .. } = logical_event {
+					carried_to.push(*to);
+				}
+			}
+			logical_events.extend(transition.logical_events);
+		}
+		(!logical_events.is_empty()).then(|| {
+			LogicalTransition { resulting_lw: res_lw, logical_events }
+				.generated_walls_outside()
+				.credited_kill_experience()
+				.updated_visibility()
+		})
+	}
+
+	/// The pressure plates with something on them activate the pistons and gates linked to them:
+	/// the pistons extend, and the gates open until nothing is on any of their plates anymore.
+	pub fn run_machines(&self) -> Option<LogicalTransition> {
+		let active_links: Vec<u32> = self
+			.grid
+			.values()
+			.filter_map(|tile| match tile.ground {
+				Ground::PressurePlate { link_id } if tile.obj.is_some() => Some(link_id),
+				_ => None,
+			})
+			.collect();
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		let mut pistons_to_extend = vec![];
+		let machines_coords = self.coords_where(|tile| {
+			matches!(tile.obj, Some(Obj::Piston { .. } | Obj::Gate { .. }))
+				|| matches!(tile.ground, Ground::OpenGate { .. })
+		});
+		for coords in machines_coords {
+			let tile = &self.grid[&coords];
+			match (&tile.ground, tile.obj.as_ref()) {
+				(_, Some(Obj::Piston { facing, extended: true, link_id })) => {
+					let head_coords = coords + *facing;
+					if matches!(res_lw.obj(head_coords), Some(Obj::PistonHead)) {
+						let head_obj = res_lw.grid.get_mut(&head_coords).unwrap().obj.take().unwrap();
+						logical_events.push(LogicalEvent::MoveInto {
+							obj: head_obj,
+							from: head_coords,
+							to: coords,
+						});
+					}
+					res_lw.grid.get_mut(&coords).unwrap().obj =
+						Some(Obj::Piston { facing: *facing, extended: false, link_id: *link_id });
+				},
+				// A piston that just retracted waits for the next turn to extend again.
+				(_, Some(Obj::Piston { extended: false, link_id, .. }))
+					if active_links.contains(link_id) =>
+				{
+					pistons_to_extend.push(coords);
+				},
+				(_, Some(Obj::Gate { link_id })) if active_links.contains(link_id) => {
+					let gate_tile = res_lw.grid.get_mut(&coords).unwrap();
+					gate_tile.obj = None;
+					gate_tile.ground = Ground::OpenGate { link_id: *link_id };
+					logical_events.push(LogicalEvent::GateOpened { at: coords });
+				},
+				// An open gate does not close on what is in it.
+				(Ground::OpenGate { link_id }, None) if !active_links.contains(link_id) => {
+					let gate_tile = res_lw.grid.get_mut(&coords).unwrap();
+					gate_tile.obj = Some(Obj::Gate { link_id: *link_id });
+					gate_tile.ground = Ground::Floor;
+					logical_events.push(LogicalEvent::GateClosed { at: coords });
+				},
+				_ => {},
+			}
+		}
+		for piston_coords in pistons_to_extend {
+			if let Some(&Obj::Piston { facing, extended: false, link_id }) = res_lw.obj(piston_coords)
+			{
+				let piston_force = 9;
+				// The piston pushes as if it moved forward,
+				// then it is put back in place with its head where it would have moved.
+				// It may be blocked by what is in front of it, even by an other piston.
+				if !res_lw.what_would_happen_if_try_to_move(piston_coords, facing, piston_force).success
+				{
+					continue;
+				}
+				let mut transition = res_lw.try_to_move(piston_coords, facing, piston_force);
+				if matches!(
+					transition.resulting_lw.obj(piston_coords + facing),
+					Some(Obj::Piston { .. })
+				) {
+					transition.resulting_lw.grid.get_mut(&(piston_coords + facing)).unwrap().obj =
+						Some(Obj::PistonHead);
+					transition.resulting_lw.grid.get_mut(&piston_coords).unwrap().obj =
+						Some(Obj::Piston { facing, extended: true, link_id });
+				}
+				res_lw = transition.resulting_lw;
+				logical_events.extend(transition.logical_events);
+			}
+		}
+		(!logical_events.is_empty()).then(|| {
+			LogicalTransition { resulting_lw: res_lw, logical_events }
+				.generated_walls_outside()
+				.credited_kill_experience()
+				.updated_visibility()
+		})
+	}
+
+	/// Does the statue at the given coords face the altar of its group,
+	/// with nothing in between?
+	fn statue_faces_its_altar(&self, statue_coords: IVec2) -> bool {
+		let Some(&Obj::Statue { facing, group }) = self.obj(statue_coords) else {
+			return false;
+		};
+		let mut coords = statue_coords + facing;
+		while let Some(tile) = self.tile(coords) {
+			match tile.obj {
+				None => coords += facing,
+				Some(Obj::Altar { group: altar_group }) => return altar_group == group,
+				Some(_) => return false,
+			}
+		}
+		false
+	}
+
+	/// At the beginning of the game's turn, the groups of statues are evaluated, and the altars
+	/// whose statues all face them awaken, each turning into a redo heart.
+	/// Returns `None` if no altar awakened.
+	pub fn awaken_altars(&self) -> Option<LogicalTransition> {
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		for coords in self.coords_where(|tile| matches!(tile.obj, Some(Obj::Altar { .. }))) {
+			let Some(Obj::Altar { group }) = self.grid[&coords].obj else {
+				unreachable!("It was just found to be an altar")
+			};
+			let mut statues_coords = self.grid.iter().filter_map(|(statue_coords, tile)| {
+				matches!(tile.obj, Some(Obj::Statue { group: statue_group, .. }) if statue_group == group)
+					.then_some(*statue_coords)
+			});
+			let all_statues_face_it =
+				statues_coords.next().is_some_and(|first| self.statue_faces_its_altar(first))
+					&& statues_coords.all(|statue_coords| self.statue_faces_its_altar(statue_coords));
+			if all_statues_face_it {
+				res_lw.grid.get_mut(&coords).unwrap().obj = Some(Obj::RedoHeart);
+				logical_events.push(LogicalEvent::AltarAwakened { at: coords });
+			}
+		}
+		(!logical_events.is_empty())
+			.then_some(LogicalTransition { resulting_lw: res_lw, logical_events })
+	}
+
+	/// At the beginning of the game's turn, the fuses of the lit bombs burn down,
+	/// and the bombs whose fuse runs out explode, hitting everything around them.
+	/// Returns `None` if there is no lit bomb.
+	pub fn tick_bombs(&self) -> Option<LogicalTransition> {
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		let mut exploding_bombs_coords = vec![];
+		for coords in self.coords_where(|tile| matches!(tile.obj, Some(Obj::Bomb { fuse: Some(_) })))
+		{
+			if let Some(Obj::Bomb { fuse: Some(fuse) }) =
+				res_lw.grid.get_mut(&coords).unwrap().obj.as_mut()
+			{
+				*fuse -= 1;
+				if *fuse <= 0 {
+					exploding_bombs_coords.push(coords);
+				} else {
+					logical_events.push(LogicalEvent::FuseBurned { at: coords });
+				}
+			}
+		}
+		for bomb_coords in exploding_bombs_coords {
+			res_lw.explode(bomb_coords, &mut logical_events);
+		}
+		(!logical_events.is_empty()).then(|| {
+			LogicalTransition { resulting_lw: res_lw, logical_events }
+				.generated_walls_outside()
+				.credited_kill_experience()
+				.updated_visibility()
+		})
+	}
+
+	/// The barrels that were set off all explode at once, setting off the barrels caught in
+	/// their blasts, that explode next. Returns `None` if no barrel explodes, so that calling
+	/// it until then plays out a whole chain reaction, one wave of explosions at a time.
+	pub fn explode_barrels(&self) -> Option<LogicalTransition> {
+		let barrels_coords =
+			self.coords_where(|tile| matches!(tile.obj, Some(Obj::Barrel { set_off: true })));
+		if barrels_coords.is_empty() {
+			return None;
 		}
-		// Second pass, add vision to some vision-blocking objects,
-		// mostly for aesthetic purposes.
-		let lw_clone = self.clone();
-		for (coords, tile) in self.grid.iter_mut() {
-			if let Some(player_coords) = player_coords {
-				let dist = player_coords.as_vec2().distance(coords.as_vec2());
-				if dist <= 6.5
-					&& lw_clone.grid.get(coords).is_some_and(|tile| {
-						!tile.visible && tile.obj.as_ref().is_some_and(|obj| obj.blocks_vision())
-					}) {
-					for to_adjecent in four_directions() {
-						let adjacent_coords = *coords + to_adjecent;
-						if lw_clone.grid.get(&adjacent_coords).is_some_and(|tile| {
-							tile.visible
-								&& (tile.obj.as_ref().is_some_and(|obj| !obj.blocks_vision())
-									|| tile.obj.is_none())
-						}) {
-							tile.visible = true;
-							break;
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		for barrel_coords in barrels_coords {
+			res_lw.explode(barrel_coords, &mut logical_events);
+		}
+		Some(
+			LogicalTransition { resulting_lw: res_lw, logical_events }
+				.generated_walls_outside()
+				.credited_kill_experience()
+				.updated_visibility(),
+		)
+	}
+
+	/// The bomb or barrel at the given coords explodes, hurting everything around it
+	/// (stunning the survivors), mining the walls and setting off the barrels.
+	fn explode(&mut self, center: IVec2, logical_events: &mut Vec<LogicalEvent>) {
+		self.grid.get_mut(&center).unwrap().obj = None;
+		logical_events.push(LogicalEvent::Exploded { at: center });
+		// A multi-tile object is hit once, however many of its tiles are caught in the blast.
+		let mut hit_anchors = vec![];
+		for coords in filled_rect(center - IVec2::new(1, 1), IVec2::new(3, 3)) {
+			let target_coords = self.anchor_of(coords);
+			let Some(target_obj) = self.obj(target_coords) else {
+				continue;
+			};
+			match target_obj {
+				// Only swords can hurt ghosts.
+				Obj::Ghost { .. } => {},
+				Obj::Barrel { set_off: false } => {
+					self.grid.get_mut(&coords).unwrap().obj = Some(Obj::Barrel { set_off: true });
+					logical_events.push(LogicalEvent::BarrelSetOff { at: coords });
+				},
+				Obj::Wall => {
+					self.grid.get_mut(&coords).unwrap().obj = None;
+					logical_events.push(LogicalEvent::Mined { at: coords });
+				},
+				target_obj if target_obj.hp().is_some() && !hit_anchors.contains(&target_coords) => {
+					hit_anchors.push(target_coords);
+					let damages = self.damages_taken(BOMB_DAMAGES, target_obj, target_coords);
+					if self.take_hit(target_coords, damages, logical_events) {
+						// Survivors are dazed by the blast.
+						let status = Status::Stunned { turns_left: 1 };
+						let target_obj = self.grid.get_mut(&target_coords).unwrap().obj.as_mut().unwrap();
+						if target_obj.inflict_status(status) {
+							logical_events.push(LogicalEvent::StatusInflicted { at: target_coords });
 						}
 					}
-				}
+				},
+				_ => {},
 			}
 		}
-		// Third pass, add vision to some vision-blocking objects in corners of visible
-		// vision-blocking objects, entierly for aesthetic purposes.
-		let lw_clone = self.clone();
-		for (coords, tile) in self.grid.iter_mut() {
-			if let Some(player_coords) = player_coords {
-				let dist = player_coords.as_vec2().distance(coords.as_vec2());
-				if dist <= 6.5
-					&& lw_clone.grid.get(coords).is_some_and(|tile| {
-						!tile.visible && tile.obj.as_ref().is_some_and(|obj| obj.blocks_vision())
-					}) {
-					for to_adjecent in four_directions() {
-						// Sorry for the very bad code here,
-						// it could do with lots of cleanup,
-						// for the story, it makes sure that the corner that we are about
-						// to make visible despite it being out of sight is a corner that
-						// would complete the corner of a piece of room in which the player is.
-						// TODO: Make this more readable.
-						let adjacent_coords = *coords + to_adjecent;
-						let other_adjacent_coords = *coords + to_adjecent.perp();
-						let corner_coords = *coords + to_adjecent + to_adjecent.perp();
-						let coords_dist = coords.as_vec2().distance(player_coords.as_vec2());
-						let adjacent_dist = adjacent_coords.as_vec2().distance(player_coords.as_vec2());
-						let other_adjacent_dist =
-							other_adjacent_coords.as_vec2().distance(player_coords.as_vec2());
-						let corner_dist = corner_coords.as_vec2().distance(player_coords.as_vec2());
-						let min_dist_is_corner =
-							corner_dist < coords_dist.min(adjacent_dist).min(other_adjacent_dist);
-						if lw_clone.grid.get(&adjacent_coords).is_some_and(|tile| {
-							tile.visible && tile.obj.as_ref().is_some_and(|obj| obj.blocks_vision())
-						}) && lw_clone.grid.get(&other_adjacent_coords).is_some_and(|tile| {
-							tile.visible && tile.obj.as_ref().is_some_and(|obj| obj.blocks_vision())
-						}) && min_dist_is_corner
-							&& lw_clone.grid.get(&corner_coords).is_some_and(|tile| {
-								tile.visible
-									&& (tile.obj.is_none()
-										|| tile.obj.as_ref().is_some_and(|obj| !obj.blocks_vision()))
-							}) {
-							// Corner that would look better if visible, granting visibility.
-							tile.visible = true;
-							break;
-						}
-					}
-				}
+	}
+
+	/// The coords of the metal object that the magnet at the given coords pulls,
+	/// the nearest one in a straight line from it with nothing in between, if any.
+	fn magnet_target(&self, magnet_coords: IVec2) -> Option<IVec2> {
+		let mut targets: Vec<_> = four_directions()
+			.into_iter()
+			.filter_map(|direction| {
+				// Next to the magnet is as close as it gets, there is nothing to pull.
+				(1..=MAGNET_RANGE)
+					.map(|distance| magnet_coords + direction * distance)
+					.take_while(|coords| self.tile(*coords).is_some())
+					.find(|coords| self.obj(*coords).is_some())
+					.filter(|coords| {
+						(*coords - magnet_coords).abs().max_element() >= 2
+							&& self.obj(*coords).unwrap().is_metallic()
+					})
+			})
+			.collect();
+		targets.sort_unstable_by_key(|coords| {
+			(
+				(*coords - magnet_coords).abs().max_element(),
+				coords.y,
+				coords.x,
+			)
+		});
+		targets.first().copied()
+	}
+
+	/// At the beginning of the game's turn, each magnet pulls the nearest metal object
+	/// in a straight line from it by one tile towards it.
+	pub fn run_magnets(&self) -> Option<LogicalTransition> {
+		let magnets_coords = self.coords_where(|tile| matches!(tile.obj, Some(Obj::Magnet)));
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		for magnet_coords in magnets_coords {
+			// An other magnet may have pulled this one away already.
+			if !matches!(res_lw.obj(magnet_coords), Some(Obj::Magnet)) {
+				continue;
 			}
+			let Some(target_coords) = res_lw.magnet_target(magnet_coords) else {
+				continue;
+			};
+			let direction = (magnet_coords - target_coords).signum();
+			if !res_lw.what_would_happen_if_try_to_move(target_coords, direction, 0).success {
+				continue;
+			}
+			let transition = res_lw.try_to_move(target_coords, direction, 0);
+			res_lw = transition.resulting_lw;
+			logical_events.extend(transition.logical_events);
 		}
-		self
+		(!logical_events.is_empty()).then(|| {
+			LogicalTransition { resulting_lw: res_lw, logical_events }
+				.generated_walls_outside()
+				.credited_kill_experience()
+				.updated_visibility()
+		})
 	}
 
-	/// There are walls everywhere, we apply that design choice here.
-	fn generated_walls_outside(mut self) -> LogicalWorld {
-		let keys: Vec<_> = self.grid.keys().copied().collect();
-		for coords in keys {
-			if !matches!(self.obj(coords), Some(Obj::Wall)) {
-				for coords in filled_rect(coords - IVec2::new(1, 1), IVec2::new(3, 3)) {
-					self.place_tile_no_overwrite(coords, Tile::obj(Obj::Wall));
-				}
+	/// The tiles that the beam of the laser emitter at the given coords goes through, in order,
+	/// from the tile in front of the emitter to the tile of what stops it (if anything does).
+	pub fn laser_beam(&self, emitter_coords: IVec2) -> Vec<IVec2> {
+		let Some(&Obj::LaserEmitter { facing }) = self.obj(emitter_coords) else {
+			return vec![];
+		};
+		let mut beam = vec![];
+		let mut coords = emitter_coords;
+		let mut direction = facing;
+		// Mirrors can send a beam back where it already went, going round in circles.
+		let mut visited = HashSet::new();
+		loop {
+			coords += direction;
+			if !visited.insert((coords, direction)) {
+				break;
+			}
+			if self.tile(coords).is_none() {
+				break;
+			}
+			beam.push(coords);
+			match self.occupant(coords) {
+				None => {},
+				Some(Obj::Mirror { slant }) => direction = slant.reflect(direction),
+				Some(_) => break,
 			}
 		}
-		self
+		beam
 	}
 
-	/// Returns the transition of the player trying to move in the given direction.
-	pub fn player_move(&self, direction: IVec2) -> LogicalTransition {
-		if let Some(coords) = self.player_coords() {
-			let player_force = 2;
-			self
-				.try_to_move(coords, direction, player_force)
+	/// At the beginning of the game's turn, the laser emitters fire, hurting what their beams
+	/// end on. Returns `None` if no beam hurts anything.
+	pub fn fire_lasers(&self) -> Option<LogicalTransition> {
+		let emitters_coords =
+			self.coords_where(|tile| matches!(tile.obj, Some(Obj::LaserEmitter { .. })));
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		for emitter_coords in emitters_coords {
+			let Some(&target_coords) = res_lw.laser_beam(emitter_coords).last() else {
+				continue;
+			};
+			let Some(target_obj) = res_lw.occupant(target_coords) else {
+				continue;
+			};
+			if matches!(target_obj, Obj::Barrel { set_off: false }) {
+				logical_events
+					.push(LogicalEvent::LaserFired { from: emitter_coords, at: target_coords });
+				res_lw.grid.get_mut(&target_coords).unwrap().obj = Some(Obj::Barrel { set_off: true });
+				logical_events.push(LogicalEvent::BarrelSetOff { at: target_coords });
+				continue;
+			}
+			// Only swords can hurt ghosts.
+			if target_obj.hp().is_none() || matches!(target_obj, Obj::Ghost { .. }) {
+				continue;
+			}
+			let damages = res_lw.damages_taken(LASER_DAMAGES, target_obj, target_coords);
+			logical_events.push(LogicalEvent::LaserFired { from: emitter_coords, at: target_coords });
+			res_lw.take_hit(target_coords, damages, &mut logical_events);
+		}
+		(!logical_events.is_empty()).then(|| {
+			LogicalTransition { resulting_lw: res_lw, logical_events }
 				.generated_walls_outside()
+				.credited_kill_experience()
 				.updated_visibility()
-		} else {
-			self.clone().into()
+		})
+	}
+
+	/// The spikes that are not kept down by a rock rise if they were retracted and retract if
+	/// they were risen, hurting what stands on them as they rise.
+	/// Returns `None` if there are no spikes to do so.
+	pub fn run_spikes(&self) -> Option<LogicalTransition> {
+		let spikes_coords = self.coords_where(|tile| {
+			matches!(tile.ground, Ground::Spikes { .. }) && !matches!(tile.obj, Some(Obj::Rock))
+		});
+		if spikes_coords.is_empty() {
+			return None;
+		}
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		for coords in spikes_coords {
+			let tile = res_lw.grid.get_mut(&coords).unwrap();
+			let Ground::Spikes { armed } = &mut tile.ground else {
+				unreachable!("These are spikes")
+			};
+			*armed = !*armed;
+			if *armed {
+				logical_events.push(LogicalEvent::SpikesRose { at: coords });
+				res_lw.impale(coords, &mut logical_events);
+			} else {
+				logical_events.push(LogicalEvent::SpikesRetracted { at: coords });
+			}
 		}
+		Some(
+			LogicalTransition { resulting_lw: res_lw, logical_events }
+				.credited_kill_experience()
+				.updated_visibility(),
+		)
 	}
 
-	/// When it is the game's turn to play, agents are given one move token
-	/// so that one agent doesn't get to move twice.
-	pub fn give_move_token_to_agents(&mut self) {
-		for tile in self.grid.values_mut() {
-			if let Some(obj) = tile.obj.as_mut() {
-				obj.give_move_token();
+	/// What stands on the risen spikes at the given coords is hurt by them, if it can be.
+	fn impale(&mut self, coords: IVec2, logical_events: &mut Vec<LogicalEvent>) {
+		let Some(target_obj) = self.occupant(coords) else {
+			return;
+		};
+		// Ghosts float over the spikes.
+		if target_obj.hp().is_none() || matches!(target_obj, Obj::Ghost { .. }) {
+			return;
+		}
+		let damages = self.damages_taken(SPIKES_DAMAGES, target_obj, coords);
+		if damages > 0 {
+			self.take_hit(coords, damages, logical_events);
+		}
+	}
+
+	/// At the beginning of the game's turn, the statuses take effect and one turn passes for
+	/// them, and what stands on lava burns. Returns `None` if nothing is afflicted.
+	pub fn apply_statuses(&self) -> Option<LogicalTransition> {
+		let afflicted_coords = self.coords_where(|tile| {
+			tile.obj.as_ref().is_some_and(|obj| {
+				obj.hp().is_some()
+					&& (!obj.statuses().is_empty()
+						|| (matches!(tile.ground, Ground::Lava) && !matches!(obj, Obj::Ghost { .. })))
+			})
+		});
+		if afflicted_coords.is_empty() {
+			return None;
+		}
+		let mut res_lw = self.clone();
+		let mut logical_events = vec![];
+		for coords in afflicted_coords {
+			let tile = res_lw.grid.get_mut(&coords).unwrap();
+			let obj = tile.obj.as_mut().unwrap();
+			// Ghosts float over the lava.
+			let on_lava = matches!(tile.ground, Ground::Lava) && !matches!(obj, Obj::Ghost { .. });
+			// Each damaging status adds up, and so does the lava.
+			let damages = obj.statuses().iter().map(|status| status.damages_per_turn()).sum::<i32>()
+				+ on_lava as i32;
+			obj.tick_statuses();
+			if damages > 0 {
+				res_lw.take_hit(coords, damages, &mut logical_events);
 			}
 		}
+		Some(LogicalTransition { resulting_lw: res_lw, logical_events }.credited_kill_experience())
+	}
+
+	/// Takes back the move token of the agent at the given coords, if any,
+	/// so that it skips its move this turn.
+	pub fn withhold_move_token(&mut self, coords: IVec2) {
+		if let Some(obj) = self.grid.get_mut(&coords).and_then(|tile| tile.obj.as_mut()) {
+			obj.take_move_token();
+		}
 	}
 
 	/// If there are still agents that can move,
-	/// then returns the transition of one trying to move, chosen randomly.
+	/// then returns the transition of one trying to move, chosen randomly (but reproducibly,
+	/// as seeded by the world).
 	pub fn handle_move_for_one_agent(&mut self) -> Option<LogicalTransition> {
-		let mut keys: Vec<_> = self.grid.keys().collect();
-		keys.shuffle(&mut rand::thread_rng());
-		for coords in keys.into_iter() {
-			let tile = self.grid.get(coords).unwrap();
-			if let Some(obj) = tile.obj.as_ref() {
-				if obj.has_move_token() {
-					let mut res_lw = self.clone();
-					res_lw.grid.get_mut(coords).unwrap().obj.as_mut().unwrap().take_move_token();
-					let is_shroom = matches!(res_lw.obj(*coords), Some(Obj::Shroom { .. }));
-					let is_shroomer = matches!(res_lw.obj(*coords), Some(Obj::Shroomer { .. }));
-					let is_fish = matches!(res_lw.obj(*coords), Some(Obj::Fish { .. }));
-					let direction = if is_shroom {
-						self.shroom_ai_decision(*coords)
-					} else if is_fish {
-						self.fish_ai_decision(*coords)
-					} else {
-						self.ai_decision(*coords)
-					};
-					return Some(if let Some(direction) = direction {
-						let target_coords = *coords + direction;
-						let target_is_bunny =
-							matches!(res_lw.obj(target_coords), Some(Obj::Bunny { .. }));
-						if is_shroom || (is_shroomer && target_is_bunny) {
-							res_lw.sacrifice_hit(*coords, direction).updated_visibility()
-						} else {
-							let argent_force = 2;
-							res_lw.try_to_move(*coords, direction, argent_force).updated_visibility()
-						}
-					} else {
-						res_lw.into()
-					});
-				}
-			}
+		let agents_coords =
+			self.coords_where(|tile| tile.obj.as_ref().is_some_and(|obj| obj.has_move_token()));
+		let mut rng = StdRng::seed_from_u64(self.agent_order_seed);
+		let coords = *agents_coords.choose(&mut rng)?;
+		let mut res_lw = self.clone();
+		res_lw.agent_order_seed = rng.gen();
+		res_lw.grid.get_mut(&coords).unwrap().obj.as_mut().unwrap().take_move_token();
+		let is_shroom = matches!(res_lw.obj(coords), Some(Obj::Shroom { .. }));
+		let is_shroomer = matches!(res_lw.obj(coords), Some(Obj::Shroomer { .. }));
+		let is_fish = matches!(res_lw.obj(coords), Some(Obj::Fish { .. }));
+		let is_golem = matches!(res_lw.obj(coords), Some(Obj::Golem { .. }));
+		let is_arrow = matches!(res_lw.obj(coords), Some(Obj::Arrow { .. }));
+		let is_archer = matches!(res_lw.obj(coords), Some(Obj::Archer { .. }));
+		let is_ghost = matches!(res_lw.obj(coords), Some(Obj::Ghost { .. }));
+		let is_golem_boss = matches!(res_lw.obj(coords), Some(Obj::GolemBoss { .. }));
+		if let Some(Obj::GolemBoss { resting: resting @ true, .. }) =
+			res_lw.grid.get_mut(&coords).unwrap().obj.as_mut()
+		{
+			*resting = false;
+			return Some(res_lw.into());
+		}
+		if let Some(direction) = is_archer.then(|| self.shot_decision(coords)).flatten() {
+			res_lw.turn_obj_towards(coords, direction);
+			return Some(
+				res_lw.archer_shot(coords, direction).credited_kill_experience().updated_visibility(),
+			);
 		}
-		None
+		let direction = if is_shroom {
+			self.shroom_ai_decision(coords)
+		} else if is_fish {
+			self.fish_ai_decision(coords)
+		} else if is_golem {
+			self.golem_ai_decision(coords)
+		} else if is_archer {
+			self.archer_ai_decision(coords)
+		} else if is_ghost {
+			self.ghost_ai_decision(coords)
+		} else if is_golem_boss {
+			self.golem_boss_ai_decision(coords)
+		} else if let Some(Obj::Arrow { direction, .. }) = self.obj(coords) {
+			Some(*direction)
+		} else {
+			self.ai_decision(coords)
+		};
+		Some(if let Some(direction) = direction {
+			res_lw.turn_obj_towards(coords, direction);
+			let target_coords = coords + direction;
+			let target_is_bunny = matches!(res_lw.obj(target_coords), Some(Obj::Bunny { .. }));
+			if is_shroom || (is_shroomer && target_is_bunny) {
+				res_lw.sacrifice_hit(coords, direction).credited_kill_experience().updated_visibility()
+			} else if is_arrow {
+				res_lw.arrow_flight(coords, direction).credited_kill_experience().updated_visibility()
+			} else if is_ghost {
+				res_lw.ghost_move(coords, direction).generated_walls_outside().updated_visibility()
+			} else if is_golem_boss {
+				res_lw.golem_boss_move(coords, direction).updated_visibility()
+			} else {
+				let argent_force = 2;
+				res_lw
+					.try_to_move(coords, direction, argent_force)
+					.generated_walls_outside()
+					.credited_kill_experience()
+					.updated_visibility()
+			}
+		} else {
+			res_lw.into()
+		})
 	}
 
 	/// Simple enemy AI.
 	fn ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
 		let target_coords = self.player_coords()?;
-		// Move towards the target if it is in a streaight line.
+		// Go back home if the player escaped far enough.
+		let agent_obj = self.obj(agent_coords)?;
+		if let (Some(home), Some(leash_radius)) = (agent_obj.home(), agent_obj.leash_radius()) {
+			let player_dist_to_home = (target_coords - home).abs();
+			if player_dist_to_home.x + player_dist_to_home.y > leash_radius {
+				return self.go_home_decision(agent_coords, home);
+			}
+		}
+		self
+			.charge_decision(agent_coords, target_coords)
+			.or_else(|| self.scent_decision(agent_coords))
+			.filter(|direction| self.is_safe_to_step_on(agent_coords + *direction))
+	}
+
+	/// Enemies won't step onto lava, even to charge.
+	fn is_safe_to_step_on(&self, coords: IVec2) -> bool {
+		self.tile(coords).is_some_and(|tile| !matches!(tile.ground, Ground::Lava))
+	}
+
+	/// Move towards the target if it is in a streaight line and in sight.
+	fn charge_decision(&self, agent_coords: IVec2, target_coords: IVec2) -> Option<IVec2> {
 		let direction = if agent_coords.x == target_coords.x {
 			if target_coords.y < agent_coords.y {
 				IVec2::new(0, -1)
@@ -451,72 +2689,579 @@ impl LogicalWorld {
 		{
 			return None;
 		}
-		// No vision through vision-blocking objects.
-		let vision_blocked = {
-			let mut coords = agent_coords;
-			loop {
-				coords += direction;
-				if coords == target_coords {
-					break false;
-				} else if self.obj(coords).is_some_and(|obj| obj.blocks_vision()) {
-					break true;
+		// No vision through vision-blocking objects.
+		let vision_blocked = {
+			let mut coords = agent_coords;
+			loop {
+				coords += direction;
+				if coords == target_coords {
+					break false;
+				} else if self.obj(coords).is_some_and(|obj| obj.blocks_vision()) {
+					break true;
+				}
+			}
+		};
+		if vision_blocked {
+			return None;
+		}
+		// All good, can move forward!
+		Some(direction)
+	}
+
+	/// Step down the scent map, towards the player.
+	/// Doesn't push anything on the way, just waits for the way to be clear.
+	fn scent_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let scent = self.scent(agent_coords)?;
+		four_directions()
+			.into_iter()
+			.filter(|direction| {
+				let dst = agent_coords + *direction;
+				self.grid.contains_key(&dst) && self.obj(dst).is_none()
+			})
+			.filter_map(|direction| {
+				let dst_scent = self.scent(agent_coords + direction)?;
+				(dst_scent < scent).then_some((dst_scent, direction))
+			})
+			.min_by_key(|(dst_scent, _direction)| *dst_scent)
+			.map(|(_dst_scent, direction)| direction)
+	}
+
+	/// Step towards home, along the axis on which home is the farthest.
+	/// Doesn't push anything on the way, just waits for the way to be clear.
+	fn go_home_decision(&self, agent_coords: IVec2, home: IVec2) -> Option<IVec2> {
+		let to_home = home - agent_coords;
+		let direction = if to_home == IVec2::ZERO {
+			return None;
+		} else if to_home.x.abs() >= to_home.y.abs() {
+			IVec2::new(to_home.x.signum(), 0)
+		} else {
+			IVec2::new(0, to_home.y.signum())
+		};
+		let dst = agent_coords + direction;
+		(self.is_safe_to_step_on(dst) && self.obj(dst).is_none()).then_some(direction)
+	}
+
+	/// Shroom AI.
+	fn shroom_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let target_coords = self.player_coords()?;
+		let direction = target_coords - agent_coords;
+		// Attack the player if adjacent.
+		(direction.x.abs() + direction.y.abs() == 1).then_some(direction)
+	}
+
+	/// Fish AI.
+	fn fish_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let direction = if let Some(Obj::Fish { direction, .. }) = self.obj(agent_coords) {
+			*direction
+		} else {
+			return None;
+		};
+		let dst_coords = agent_coords + direction;
+		let target_coords = self.player_coords()?;
+		let noting_ahead = self.grid.contains_key(&dst_coords) && self.obj(dst_coords).is_none();
+		if target_coords == dst_coords || noting_ahead {
+			Some(direction)
+		} else {
+			Some(-direction)
+		}
+	}
+
+	/// Golem AI, it copies the player's last move.
+	fn golem_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let Some(Obj::Golem { mirroring, .. }) = self.obj(agent_coords) else {
+			return None;
+		};
+		self.last_player_move.map(|direction| mirroring.apply(direction))
+	}
+
+	/// Archer AI, when it does not shoot. It backs away from the player if adjacent,
+	/// and else it follows the scent until it gets a clear shot.
+	fn archer_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let target_coords = self.player_coords()?;
+		let to_target = target_coords - agent_coords;
+		if to_target.x.abs() + to_target.y.abs() == 1 {
+			// Straight away from the player if possible, else to the side.
+			let mut directions = vec![-to_target, to_target.perp(), -to_target.perp()];
+			directions.retain(|direction| {
+				let dst = agent_coords + *direction;
+				self.is_safe_to_step_on(dst) && self.obj(dst).is_none()
+			});
+			directions.first().copied()
+		} else {
+			self
+				.scent_decision(agent_coords)
+				.filter(|direction| self.is_safe_to_step_on(agent_coords + *direction))
+		}
+	}
+
+	/// Ghost AI, straight towards the player along the axis on which they are the farthest,
+	/// whatever is in the way.
+	fn ghost_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let to_target = self.player_coords()? - agent_coords;
+		if to_target.x.abs() + to_target.y.abs() > GHOST_CHASE_RADIUS {
+			None
+		} else if to_target.x.abs() >= to_target.y.abs() {
+			Some(IVec2::new(to_target.x.signum(), 0))
+		} else {
+			Some(IVec2::new(0, to_target.y.signum()))
+		}
+	}
+
+	/// An alternative to `try_to_move` for ghosts, that go through objects instead of pushing
+	/// them to end their move on the first free tile in the way (if not too far).
+	/// They can't go through other agents, and they hit the player like any enemy.
+	fn ghost_move(&self, ghost_coords: IVec2, direction: IVec2) -> LogicalTransition {
+		if matches!(self.obj(ghost_coords + direction), Some(Obj::Bunny { .. })) {
+			// No force to push the player, so it is a hit.
+			return self.try_to_move(ghost_coords, direction, 0);
+		}
+		let dst_coords = (1..=GHOST_PHASE_DISTANCE)
+			.map(|distance| ghost_coords + direction * distance)
+			.take_while(|coords| {
+				self.tile(*coords).is_some() && !self.occupant(*coords).is_some_and(Obj::is_agent)
+			})
+			// Ghosts float through walls, not over holes, there is nothing to stop on there.
+			.find(|coords| {
+				self.occupant(*coords).is_none()
+					&& !matches!(self.tile(*coords).unwrap().ground, Ground::Hole)
+			});
+		let Some(dst_coords) = dst_coords else {
+			return self.clone().into();
+		};
+		let mut res_lw = self.clone();
+		let ghost_obj = res_lw.grid.get_mut(&ghost_coords).unwrap().obj.take();
+		res_lw.grid.get_mut(&dst_coords).unwrap().obj = ghost_obj;
+		let logical_events = vec![LogicalEvent::Move { from: ghost_coords, to: dst_coords }];
+		LogicalTransition { resulting_lw: res_lw, logical_events }
+	}
+
+	/// Golem boss AI, straight towards the player along the axis on which they are the farthest
+	/// from its footprint, once they are close enough to wake it up.
+	fn golem_boss_ai_decision(&self, anchor_coords: IVec2) -> Option<IVec2> {
+		let target_coords = self.player_coords()?;
+		let footprint = self.obj(anchor_coords)?.footprint();
+		let nearest_coords =
+			target_coords.clamp(anchor_coords, anchor_coords + footprint - IVec2::ONE);
+		let to_target = target_coords - nearest_coords;
+		if to_target.x.abs() + to_target.y.abs() > GOLEM_BOSS_AWAKE_RADIUS {
+			None
+		} else if to_target.x.abs() >= to_target.y.abs() {
+			Some(IVec2::new(to_target.x.signum(), 0))
+		} else {
+			Some(IVec2::new(0, to_target.y.signum()))
+		}
+	}
+
+	/// An alternative to `try_to_move` for the golem boss, that moves all of its footprint.
+	/// It does not push anything, it stomps the player if they are in the way,
+	/// and it fails to move if anything else is in the way. It rests after trying.
+	fn golem_boss_move(&self, anchor_coords: IVec2, direction: IVec2) -> LogicalTransition {
+		let mut res_lw = self.clone();
+		let boss_obj = self.obj(anchor_coords).unwrap();
+		let footprint = boss_obj.footprint();
+		let taken_coords = filled_rect(anchor_coords, footprint);
+		let leading_coords: Vec<_> = filled_rect(anchor_coords + direction, footprint)
+			.into_iter()
+			.filter(|coords| !taken_coords.contains(coords))
+			.collect();
+		let player_coords =
+			leading_coords.iter().find(|coords| matches!(self.obj(**coords), Some(Obj::Bunny { .. })));
+		let is_free = |coords: &IVec2| {
+			self.occupant(*coords).is_none()
+				&& self.tile(*coords).is_some_and(|tile| {
+					!matches!(tile.ground, Ground::Hole | Ground::Water | Ground::Lava)
+				})
+		};
+		let mut logical_events = vec![];
+		let mut resting_coords = anchor_coords;
+		if let Some(&player_coords) = player_coords {
+			let damages = self.damages_dealt(
+				boss_obj,
+				anchor_coords,
+				self.obj(player_coords).unwrap(),
+				player_coords,
+			);
+			logical_events
+				.push(LogicalEvent::FailToMove { from: anchor_coords, to: anchor_coords + direction });
+			res_lw.take_hit(player_coords, damages, &mut logical_events);
+		} else if leading_coords.iter().all(is_free) {
+			res_lw.free_footprint(anchor_coords);
+			let boss_obj = res_lw.grid.get_mut(&anchor_coords).unwrap().obj.take();
+			res_lw.grid.get_mut(&(anchor_coords + direction)).unwrap().obj = boss_obj;
+			res_lw.take_footprint(anchor_coords + direction, footprint);
+			logical_events
+				.push(LogicalEvent::Move { from: anchor_coords, to: anchor_coords + direction });
+			resting_coords = anchor_coords + direction;
+		} else {
+			logical_events
+				.push(LogicalEvent::FailToMove { from: anchor_coords, to: anchor_coords + direction });
+		}
+		if let Some(Obj::GolemBoss { resting, .. }) =
+			res_lw.grid.get_mut(&resting_coords).unwrap().obj.as_mut()
+		{
+			*resting = true;
+		}
+		LogicalTransition { resulting_lw: res_lw, logical_events }
+	}
+
+	/// The object that takes the tile at the given coords takes a hit that does not come from
+	/// a push, and it is killed (leaving its corpse if any) if it is lethal.
+	/// Returns whether it survived the hit.
+	fn take_hit(
+		&mut self,
+		coords: IVec2,
+		damages: i32,
+		logical_events: &mut Vec<LogicalEvent>,
+	) -> bool {
+		// The hit may land on any tile of a multi-tile target.
+		let target_coords = self.anchor_of(coords);
+		let is_lethal = self.is_lethal(damages, self.obj(target_coords).unwrap());
+		if is_lethal {
+			self.free_footprint(target_coords);
+		}
+		let target_tile = self.grid.get_mut(&target_coords).unwrap();
+		if is_lethal {
+			let killed_obj = target_tile.obj.take().unwrap();
+			target_tile.obj = killed_obj.corpse();
+			logical_events.push(LogicalEvent::Killed { obj: killed_obj, at: target_coords, damages });
+		} else {
+			target_tile.obj.as_mut().unwrap().take_damage(damages);
+			logical_events.push(LogicalEvent::Hit { at: target_coords, damages });
+			self.revive_if_needed(target_coords, logical_events);
+		}
+		!is_lethal
+	}
+
+	/// An archer shoots the player if they are in a straight line from it, not adjacent,
+	/// in range and with nothing in between (its projectiles fly over any ground).
+	fn shot_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
+		let target_coords = self.player_coords()?;
+		let to_target = target_coords - agent_coords;
+		let distance = to_target.x.abs() + to_target.y.abs();
+		let aligned = to_target.x == 0 || to_target.y == 0;
+		if !aligned || !(2..=ARCHER_RANGE).contains(&distance) {
+			return None;
+		}
+		let direction = to_target.signum();
+		let line_is_clear = (1..distance).all(|i| self.obj(agent_coords + direction * i).is_none());
+		line_is_clear.then_some(direction)
+	}
+
+	/// The archer shoots down its clear line at the player, hitting them from afar.
+	fn archer_shot(&self, archer_coords: IVec2, direction: IVec2) -> LogicalTransition {
+		let mut res_lw = self.clone();
+		let target_coords = self.player_coords().unwrap();
+		let damages = self.damages_dealt(
+			self.obj(archer_coords).unwrap(),
+			archer_coords,
+			self.obj(target_coords).unwrap(),
+			target_coords,
+		);
+		let mut logical_events =
+			vec![LogicalEvent::Shot { direction, from: archer_coords, to: target_coords }];
+		res_lw.take_hit(target_coords, damages, &mut logical_events);
+		LogicalTransition { resulting_lw: res_lw, logical_events }
+	}
+
+	/// If the object at the given coords is a bow, it shoots an arrow in the given direction,
+	/// that appears in front of it if there is room there.
+	fn shoot_from_bow(
+		&mut self,
+		bow_coords: IVec2,
+		direction: IVec2,
+		logical_events: &mut Vec<LogicalEvent>,
+	) {
+		if !matches!(self.obj(bow_coords), Some(Obj::Bow)) {
+			return;
+		}
+		let arrow_coords = bow_coords + direction;
+		if !self.is_free(arrow_coords) {
+			return;
+		}
+		let tile = self.grid.get_mut(&arrow_coords).unwrap();
+		if !matches!(tile.ground, Ground::Hole) {
+			tile.obj = Some(Obj::Arrow { direction, move_token: false });
+			logical_events.push(LogicalEvent::ArrowShot { from: bow_coords, to: arrow_coords });
+		}
+	}
+
+	/// The arrow flies one tile further, over whatever ground is there (but a hole, it falls in),
+	/// or it breaks on the object in front of it, hurting it if it can be hurt
+	/// or setting it off if it is a barrel.
+	fn arrow_flight(&self, arrow_coords: IVec2, direction: IVec2) -> LogicalTransition {
+		let target_coords = arrow_coords + direction;
+		let target_tile = self.tile(target_coords);
+		match self.occupant(target_coords) {
+			Some(target_obj) if target_obj.hp().is_some() =>
+				self.sacrifice_hit(arrow_coords, direction),
+			None if target_tile.is_some_and(|tile| matches!(tile.ground, Ground::Hole)) => {
+				let mut res_lw = self.clone();
+				let arrow_obj = res_lw.grid.get_mut(&arrow_coords).unwrap().obj.take().unwrap();
+				LogicalTransition {
+					resulting_lw: res_lw,
+					logical_events: vec![LogicalEvent::FellIntoHole {
+						obj: arrow_obj,
+						from: arrow_coords,
+						at: target_coords,
+					}],
+				}
+			},
+			None if target_tile.is_some() => {
+				let mut res_lw = self.clone();
+				let arrow_obj = res_lw.grid.get_mut(&arrow_coords).unwrap().obj.take();
+				res_lw.grid.get_mut(&target_coords).unwrap().obj = arrow_obj;
+				LogicalTransition {
+					resulting_lw: res_lw,
+					logical_events: vec![LogicalEvent::Move { from: arrow_coords, to: target_coords }],
+				}
+			},
+			Some(Obj::Barrel { set_off: false }) => {
+				let mut res_lw = self.clone();
+				let arrow_obj = res_lw.grid.get_mut(&arrow_coords).unwrap().obj.take().unwrap();
+				res_lw.grid.get_mut(&target_coords).unwrap().obj = Some(Obj::Barrel { set_off: true });
+				LogicalTransition {
+					resulting_lw: res_lw,
+					logical_events: vec![
+						LogicalEvent::MoveInto { obj: arrow_obj, from: arrow_coords, to: target_coords },
+						LogicalEvent::BarrelSetOff { at: target_coords },
+					],
+				}
+			},
+			_ => {
+				let mut res_lw = self.clone();
+				let arrow_obj = res_lw.grid.get_mut(&arrow_coords).unwrap().obj.take().unwrap();
+				LogicalTransition {
+					resulting_lw: res_lw,
+					logical_events: vec![LogicalEvent::MoveInto {
+						obj: arrow_obj,
+						from: arrow_coords,
+						to: target_coords,
+					}],
+				}
+			},
+		}
+	}
+
+	/// Each object that moved in a push in the given direction and got on ice slides further
+	/// one tile at a time, as long as the next tile is free and it is still on ice.
+	/// The front of the push line slides first, making room for what is behind it.
+	fn slide_on_ice(&mut self, direction: IVec2, logical_events: &mut Vec<LogicalEvent>) {
+		let mut moved_to: Vec<_> = logical_events
+			.iter()
+			.filter_map(|logical_event| match logical_event {
+				LogicalEvent::Move { to, .. } => Some(*to),
+				_ => None,
+			})
+			.collect();
+		moved_to.sort_by_key(|coords| -coords.dot(direction));
+		let mut slides = vec![];
+		for mut coords in moved_to {
+			while self
+				.tile(coords)
+				.is_some_and(|tile| matches!(tile.ground, Ground::Ice) && tile.obj.is_some())
+				&& self.is_free(coords + direction)
+			{
+				let sliding_obj = self.grid.get_mut(&coords).unwrap().obj.take();
+				self.grid.get_mut(&(coords + direction)).unwrap().obj = sliding_obj;
+				slides.push(LogicalEvent::Move { from: coords, to: coords + direction });
+				coords += direction;
+			}
+		}
+		logical_events.extend(slides);
+	}
+
+	/// The grounds act on everything that moved in a push, be it pushed, pulled or slid,
+	/// as told by the `Move` events.
+	fn apply_grounds(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		self.collapse_fragile_floors(logical_events);
+		self.sink_or_float(logical_events);
+		self.burn_in_lava(logical_events);
+		self.step_on_spikes(logical_events);
+		self.teleport(logical_events);
+		self.fall_into_holes(logical_events);
+	}
+
+	/// The fragile floors that objects moved off of collapse into holes.
+	fn collapse_fragile_floors(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		let mut collapsed = vec![];
+		for logical_event in logical_events.iter() {
+			if let LogicalEvent::Move { from, .. } = logical_event {
+				let tile = self.grid.get_mut(from).unwrap();
+				if matches!(tile.ground, Ground::FragileFloor) && tile.obj.is_none() {
+					tile.ground = Ground::Hole;
+					collapsed.push(LogicalEvent::FloorCollapsed { at: *from });
+				}
+			}
+		}
+		logical_events.extend(collapsed);
+	}
+
+	/// The objects that moved onto water sink if they are heavy,
+	/// and the rafts that moved onto water become floating grounds.
+	fn sink_or_float(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		let mut sunk = vec![];
+		for logical_event in logical_events.iter() {
+			if let LogicalEvent::Move { from, to } = logical_event {
+				let tile = self.grid.get_mut(to).unwrap();
+				if !matches!(tile.ground, Ground::Water) {
+					continue;
+				}
+				if matches!(tile.obj, Some(Obj::Raft)) {
+					tile.obj = None;
+					tile.ground = Ground::Raft;
+					sunk.push(LogicalEvent::RaftMoored { from: *from, at: *to });
+				} else if tile.obj.as_ref().is_some_and(Obj::sinks) {
+					let obj = tile.obj.take().unwrap();
+					sunk.push(LogicalEvent::Sank { obj, from: *from, at: *to });
+				}
+			}
+		}
+		logical_events.extend(sunk);
+	}
+
+	/// The consumables that moved onto lava burn up.
+	fn burn_in_lava(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		let mut burnt = vec![];
+		for logical_event in logical_events.iter() {
+			if let LogicalEvent::Move { from, to } = logical_event {
+				let tile = self.grid.get_mut(to).unwrap();
+				if matches!(tile.ground, Ground::Lava)
+					&& tile.obj.as_ref().is_some_and(Obj::is_consumable)
+				{
+					let obj = tile.obj.take().unwrap();
+					burnt.push(LogicalEvent::BurntUp { obj, from: *from, at: *to });
 				}
 			}
-		};
-		if vision_blocked {
-			return None;
 		}
-		// All good, can move forward!
-		Some(direction)
+		logical_events.extend(burnt);
 	}
 
-	/// Shroom AI.
-	fn shroom_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
-		let target_coords = self.player_coords()?;
-		let direction = target_coords - agent_coords;
-		// Attack the player if adjacent.
-		(direction.x.abs() + direction.y.abs() == 1).then_some(direction)
+	/// The objects that moved onto risen spikes are hurt by them, except for rocks that
+	/// push them down instead.
+	fn step_on_spikes(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		let mut impaled = vec![];
+		for logical_event in logical_events.iter() {
+			if let LogicalEvent::Move { to, .. } = logical_event {
+				let tile = self.grid.get_mut(to).unwrap();
+				match (&mut tile.ground, &tile.obj) {
+					(Ground::Spikes { armed: armed @ true }, Some(Obj::Rock)) => {
+						*armed = false;
+						impaled.push(LogicalEvent::SpikesRetracted { at: *to });
+					},
+					(Ground::Spikes { armed: true }, Some(_)) => self.impale(*to, &mut impaled),
+					_ => {},
+				}
+			}
+		}
+		logical_events.extend(impaled);
 	}
 
-	/// Fish AI.
-	fn fish_ai_decision(&self, agent_coords: IVec2) -> Option<IVec2> {
-		let direction = if let Some(Obj::Fish { direction, .. }) = self.obj(agent_coords) {
-			*direction
-		} else {
-			return None;
-		};
-		let dst_coords = agent_coords + direction;
-		let target_coords = self.player_coords()?;
-		let noting_ahead = self.grid.get(&dst_coords).is_some() && self.obj(dst_coords).is_none();
-		if target_coords == dst_coords || noting_ahead {
-			Some(direction)
-		} else {
-			Some(-direction)
+	/// The objects that moved onto a teleporter are moved to the other teleporter of the pair,
+	/// if there is nothing there. They don't teleport back, as they did not move onto it.
+	fn teleport(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		let mut teleported = vec![];
+		for logical_event in logical_events.iter() {
+			if let LogicalEvent::Move { to: pad_coords, .. } = logical_event {
+				let Ground::Teleporter { link_id } = self.grid[pad_coords].ground else {
+					continue;
+				};
+				if self.grid[pad_coords].obj.is_none() {
+					continue;
+				}
+				let paired_pad_coords = self
+					.coords_where(|tile| {
+						matches!(tile.ground, Ground::Teleporter { link_id: other_link_id }
+							if other_link_id == link_id)
+					})
+					.into_iter()
+					.find(|coords| coords != pad_coords && self.is_free(*coords));
+				if let Some(paired_pad_coords) = paired_pad_coords {
+					let obj = self.grid.get_mut(pad_coords).unwrap().obj.take().unwrap();
+					self.grid.get_mut(&paired_pad_coords).unwrap().obj = Some(obj.clone());
+					teleported.push(LogicalEvent::Teleported {
+						obj,
+						from: *pad_coords,
+						to: paired_pad_coords,
+					});
+				}
+			}
+		}
+		logical_events.extend(teleported);
+	}
+
+	/// The objects that moved onto holes fall in.
+	fn fall_into_holes(&mut self, logical_events: &mut Vec<LogicalEvent>) {
+		let mut fallen = vec![];
+		for logical_event in logical_events.iter() {
+			if let LogicalEvent::Move { from, to } = logical_event {
+				let tile = self.grid.get_mut(to).unwrap();
+				if matches!(tile.ground, Ground::Hole) {
+					if let Some(obj) = tile.obj.take() {
+						fallen.push(LogicalEvent::FellIntoHole { obj, from: *from, at: *to });
+					}
+				}
+			}
 		}
+		logical_events.extend(fallen);
 	}
 
 	/// If the source object was pushed into the destination object in a blocked push, then what?
 	fn what_would_happen_if_interact(
 		&self,
 		src_obj: &Obj,
+		src_coords: IVec2,
 		dst_obj: &Obj,
 		dst_coords: IVec2,
 	) -> Option<InteractionConsequences> {
-		if matches!(dst_obj, Obj::Exit) {
+		if matches!(dst_obj, Obj::Exit) && !self.is_exit_sealed() {
 			Some(InteractionConsequences::Exit { at: dst_coords })
-		} else if matches!((src_obj, dst_obj), (Obj::Pickaxe, Obj::Wall)) {
+		} else if let Some(result) = crafting_result(src_obj, dst_obj) {
+			Some(InteractionConsequences::Craft { result })
+		} else if matches!(
+			(src_obj.wielded(), dst_obj),
+			(Obj::Pickaxe { .. }, Obj::Wall)
+		) {
 			Some(InteractionConsequences::Mine)
-		} else if matches!((src_obj, dst_obj), (Obj::Key, Obj::Door)) {
+		} else if matches!(
+			(src_obj.wielded(), dst_obj),
+			(Obj::Sword { .. } | Obj::VisionSword, Obj::Crate { .. })
+		) {
+			Some(InteractionConsequences::BreakCrate)
+		} else if matches!(dst_obj, Obj::Barrel { set_off: false }) && src_obj.damages() > 0 {
+			Some(InteractionConsequences::SetOffBarrel)
+		} else if matches!((src_obj.wielded(), dst_obj), (Obj::Key, Obj::Door)) {
 			Some(InteractionConsequences::KeyOpenDoor)
 		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::Heart)) {
 			Some(InteractionConsequences::Heal)
 		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::RedoHeart)) {
 			Some(InteractionConsequences::GainARedo)
+		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::RewindToken)) {
+			Some(InteractionConsequences::GainARewind)
+		} else if matches!(
+			(src_obj, dst_obj),
+			(Obj::Bunny { .. }, Obj::Bomb { fuse: None })
+		) {
+			Some(InteractionConsequences::GainABomb)
+		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::Coin)) {
+			Some(InteractionConsequences::GainGold)
+		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::KeyShard)) {
+			Some(InteractionConsequences::GainAKeyShard)
+		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::StrengthPotion)) {
+			Some(InteractionConsequences::GainStrength)
+		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::Note)) {
+			Some(InteractionConsequences::ReadNote)
+		} else if matches!((src_obj, dst_obj), (Obj::Bunny { .. }, Obj::CursedMirror)) {
+			Some(InteractionConsequences::GetMirrorCursed)
 		} else if matches!(dst_obj, Obj::Shroom { .. }) {
 			Some(InteractionConsequences::StompShroom)
-		} else if let Some(target_hp) = dst_obj.hp() {
-			let damages = src_obj.damages();
-			if target_hp <= damages {
+		} else if dst_obj.hp().is_some() {
+			let damages = self.damages_dealt(src_obj, src_coords, dst_obj, dst_coords);
+			let is_lethal = self.is_lethal(damages, dst_obj);
+			if is_lethal && (dst_obj.corpse().is_some() || dst_obj.footprint() != IVec2::ONE) {
+				// HP would drop to zero or less, but the corpse would still be in the way.
+				// A multi-tile object is too big to have something take its place right away.
+				Some(InteractionConsequences::KillLeavingCorpse { damages })
+			} else if is_lethal {
 				// HP would drop to zero or less.
 				Some(InteractionConsequences::Kill { damages })
 			} else {
@@ -546,20 +3291,29 @@ impl LogicalWorld {
 		let success = 'success: loop {
 			coords += direction;
 			length += 1;
-			if let Some(dst_tile) = self.grid.get(&coords) {
-				if let Some(dst_obj) = dst_tile.obj.as_ref() {
+			if self.grid.contains_key(&coords) {
+				if let Some(dst_obj) = self.occupant(coords) {
+					// What is for sale only leaves the shop pushed by the bunny, that pays for it.
+					if let Ground::Price { price } = self.grid[&coords].ground {
+						let is_bought = length == 1
+							&& matches!(self.obj(mover_coords), Some(Obj::Bunny { .. }))
+							&& price <= self.gold;
+						if !is_bought {
+							break false;
+						}
+					}
 					remaining_force -= dst_obj.mass();
 					if remaining_force < 0 {
 						// All the force of the pusher was used up, nothing more can be pushed.
 						// Now we scan the pushed chain backwards for an interaction.
 						while length_removed_due_to_interaction < length {
 							let src_coords = coords - direction;
-							let src_obj = self.obj(src_coords).unwrap();
-							let dst_obj = self.obj(coords).unwrap();
+							let src_obj = self.occupant(src_coords).unwrap();
+							let dst_obj = self.occupant(coords).unwrap();
 							// The final object of the chain that would have been pushed but is blocked by
 							// the target now try to interact with the target.
 							final_interaction =
-								self.what_would_happen_if_interact(src_obj, dst_obj, coords);
+								self.what_would_happen_if_interact(src_obj, src_coords, dst_obj, coords);
 							if let Some(final_interaction) = final_interaction.as_ref() {
 								// Depending on the interaction, the move may succeed or not.
 								break 'success final_interaction.allows_move();
@@ -569,6 +3323,14 @@ impl LogicalWorld {
 						}
 						break false;
 					}
+				} else if matches!(self.grid[&coords].ground, Ground::Spikes { .. })
+					&& self
+						.occupant(coords - direction)
+						.is_some_and(|obj| matches!(obj.wielded(), Obj::Pickaxe { .. } | Obj::Key))
+				{
+					// A tool is not pushed onto spikes, it is used to disarm them.
+					final_interaction = Some(InteractionConsequences::Disarm);
+					break false;
 				} else {
 					break true;
 				}
@@ -579,30 +3341,42 @@ impl LogicalWorld {
 		if final_interaction.is_some() {
 			length -= length_removed_due_to_interaction;
 		}
+		// The bunny does not walk into water, even pushing something in front of it.
+		// It may still be pushed in by an other object, or get in to interact with what floats.
+		let success = success
+			&& !(final_interaction.is_none()
+				&& matches!(self.obj(mover_coords), Some(Obj::Bunny { .. }))
+				&& self
+					.tile(mover_coords + direction)
+					.is_some_and(|tile| matches!(tile.ground, Ground::Water)));
 		let non_pulled_length = length;
 		// Pull.
+		// Pistons push but they don't pull.
+		let can_pull = !matches!(self.obj(mover_coords), Some(Obj::Piston { .. }));
 		let mut coords = mover_coords;
 		let mut remaining_force = force;
 		let mut pulled_length = 0;
 		let mut can_pull_next = false;
-		loop {
-			coords -= direction;
-			if let Some(dst_obj) = self.obj(coords) {
-				if matches!(dst_obj, Obj::Rope) || can_pull_next {
-					can_pull_next = false;
-					remaining_force -= dst_obj.mass();
-					if remaining_force < 0 {
+		if can_pull {
+			loop {
+				coords -= direction;
+				if let Some(dst_obj) = self.obj(coords) {
+					if matches!(dst_obj, Obj::Rope) || can_pull_next {
+						can_pull_next = false;
+						remaining_force -= dst_obj.mass();
+						if remaining_force < 0 {
+							break;
+						}
+						pulled_length += 1;
+						if matches!(dst_obj, Obj::Rope) {
+							can_pull_next = true;
+						}
+					} else {
 						break;
 					}
-					pulled_length += 1;
-					if matches!(dst_obj, Obj::Rope) {
-						can_pull_next = true;
-					}
 				} else {
 					break;
 				}
-			} else {
-				break;
 			}
 		}
 		MoveAttemptConsequences { success, non_pulled_length, pulled_length, final_interaction }
@@ -617,6 +3391,7 @@ impl LogicalWorld {
 			self.what_would_happen_if_try_to_move(mover_coords, direction, force);
 		let mut coords = mover_coords;
 		let mut previous_obj = None;
+		let push_events_start = logical_events.len();
 		for _ in 0..non_pulled_length {
 			if success {
 				// The push is successful so each object in the chain is replaced
@@ -627,6 +3402,7 @@ impl LogicalWorld {
 				);
 				previous_obj = match previous_obj.take() {
 					Some(Obj::Fish { move_token, .. }) => Some(Obj::Fish { direction, move_token }),
+					Some(Obj::Statue { group, .. }) => Some(Obj::Statue { facing: direction, group }),
 					x => x,
 				};
 				let is_exiting = if let Some(InteractionConsequences::Exit { at }) = final_interaction {
@@ -646,8 +3422,25 @@ impl LogicalWorld {
 			}
 			coords += direction;
 		}
+		if success {
+			// The chain is gone over from the mover, but it is the front of it that moves first,
+			// making room for what is behind it. The events tell it in that order, so that what
+			// moves can be followed through them.
+			logical_events[push_events_start..].reverse();
+		}
 		// We are at the end of the push chain. There may be an interaction happening there,
 		// with the last object moving or failing to move interacting with what comes after.
+		let is_hit = matches!(
+			final_interaction,
+			Some(
+				InteractionConsequences::NonLethalHit { .. }
+					| InteractionConsequences::Kill { .. }
+					| InteractionConsequences::KillLeavingCorpse { .. }
+			)
+		);
+		if is_hit && matches!(self.obj(coords - direction), Some(Obj::SlimeCorpse)) {
+			logical_events.push(LogicalEvent::Squelched { at: coords });
+		}
 		if success {
 			std::mem::swap(
 				&mut previous_obj,
@@ -666,15 +3459,27 @@ impl LogicalWorld {
 						});
 					},
 					InteractionConsequences::StompShroom => {
-						let target_obj = previous_obj.take().unwrap();
-						logical_events.push(LogicalEvent::Stomped { obj: target_obj, at: coords });
+						previous_obj.take().unwrap();
+						logical_events.push(LogicalEvent::Stomped { at: coords });
 					},
 					InteractionConsequences::Mine => {
-						let target_obj = previous_obj.take().unwrap();
-						logical_events.push(LogicalEvent::Mined { obj: target_obj, at: coords });
+						previous_obj.take().unwrap();
+						logical_events.push(LogicalEvent::Mined { at: coords });
+					},
+					InteractionConsequences::Craft { result } => {
+						// The pushed component took the place of the other component,
+						// and the two are merged into the result.
+						previous_obj.take().unwrap();
+						res_lw.grid.get_mut(&coords).unwrap().obj = Some(result);
+						logical_events.push(LogicalEvent::Crafted { at: coords });
 					},
 					InteractionConsequences::KeyOpenDoor => {
-						let key_obj = res_lw.grid.get_mut(&coords).unwrap().obj.take().unwrap();
+						// A key held by the bunny is used up, the bunny going through the door.
+						let opener = &mut res_lw.grid.get_mut(&coords).unwrap().obj;
+						let key_obj = match opener {
+							Some(Obj::Bunny { held, .. }) => *held.take().unwrap(),
+							_ => opener.take().unwrap(),
+						};
 						let door_obj = previous_obj.take().unwrap();
 						logical_events.push(LogicalEvent::DoorOpenedWithKey {
 							key_obj,
@@ -689,6 +3494,7 @@ impl LogicalWorld {
 							&mut res_lw.grid.get_mut(&coords).unwrap().obj,
 						);
 						let exiting_obj = previous_obj.take().unwrap();
+						res_lw.exited_objs.push(exiting_obj.clone());
 						logical_events.push(LogicalEvent::Exit {
 							obj: exiting_obj,
 							from: coords - direction,
@@ -699,37 +3505,155 @@ impl LogicalWorld {
 						let _heart_obj = previous_obj.take().unwrap();
 						let healed_obj = &mut res_lw.grid.get_mut(&coords).unwrap().obj.as_mut().unwrap();
 						match healed_obj {
-							Obj::Bunny { hp, max_hp } => *hp = *max_hp,
+							Obj::Bunny { hp, max_hp, .. } => *hp = *max_hp,
 							_ => unreachable!("Only a bunny interacting with a heart can trigger a heal"),
 						}
-						logical_events.push(LogicalEvent::Healed { obj: healed_obj.clone(), at: coords });
+						logical_events.push(LogicalEvent::Healed { at: coords });
 					},
 					InteractionConsequences::GainARedo => {
-						let redo_heart_obj = previous_obj.take().unwrap();
+						previous_obj.take().unwrap();
 						res_lw.redo_count = (self.redo_count + 1).clamp(0, self.max_redo_count);
-						logical_events.push(LogicalEvent::RedoGained { obj: redo_heart_obj, at: coords });
+						logical_events.push(LogicalEvent::RedoGained { at: coords });
+					},
+					InteractionConsequences::GainARewind => {
+						previous_obj.take().unwrap();
+						res_lw.rewind_tokens += 1;
+						logical_events.push(LogicalEvent::RewindGained { at: coords });
+					},
+					InteractionConsequences::GainABomb => {
+						previous_obj.take().unwrap();
+						res_lw.bombs += 1;
+						logical_events.push(LogicalEvent::BombGained { at: coords });
+					},
+					InteractionConsequences::GainGold => {
+						previous_obj.take().unwrap();
+						res_lw.gold += 1;
+						logical_events.push(LogicalEvent::GoldGained { at: coords });
+					},
+					InteractionConsequences::GainStrength => {
+						previous_obj.take().unwrap();
+						match &mut res_lw.grid.get_mut(&coords).unwrap().obj {
+							Some(Obj::Bunny { force, .. }) => *force += 1,
+							_ => unreachable!("Only a bunny can drink a strength potion"),
+						}
+						logical_events.push(LogicalEvent::StrengthGained { at: coords });
+					},
+					InteractionConsequences::GainAKeyShard => {
+						previous_obj.take().unwrap();
+						res_lw.key_shards += 1;
+						logical_events.push(LogicalEvent::KeyShardGained { at: coords });
+					},
+					InteractionConsequences::ReadNote => {
+						previous_obj.take().unwrap();
+						logical_events.push(LogicalEvent::NoteRead { at: coords });
+					},
+					InteractionConsequences::GetMirrorCursed => {
+						previous_obj.take().unwrap();
+						res_lw.mirror_curse_turns_left = if self.assist.slower_hazards { 5 } else { 9 };
+						logical_events.push(LogicalEvent::MirrorCursed { at: coords });
 					},
-					InteractionConsequences::NonLethalHit { .. } => {
+					InteractionConsequences::NonLethalHit { .. }
+					| InteractionConsequences::KillLeavingCorpse { .. }
+					| InteractionConsequences::BreakCrate
+					| InteractionConsequences::SetOffBarrel
+					| InteractionConsequences::Disarm => {
 						unreachable!(
-							"If there is a non-killed target, then the push would have been a failure"
+							"If there is a non-killed target, its corpse, a broken crate's content \
+							or a barrel, then the push would have been a failure"
 						)
 					},
 				}
 			}
 			assert!(previous_obj.is_none());
+			// The pulling, before the grounds act on what moved, the pulled objects included.
+			let mut pulled_coords = mover_coords;
+			for _ in 0..pulled_length {
+				pulled_coords -= direction;
+				let obj = res_lw.grid.get_mut(&pulled_coords).unwrap().obj.take();
+				res_lw.grid.get_mut(&(pulled_coords + direction)).unwrap().obj = obj;
+				logical_events
+					.push(LogicalEvent::Move { from: pulled_coords, to: pulled_coords + direction });
+			}
+			// The bunny pays for what it pushed out of the shop (or used right away).
+			let shop_coords = mover_coords + direction;
+			if let (Some(Obj::Bunny { .. }), Some(_), Ground::Price { price }) = (
+				self.obj(mover_coords),
+				self.obj(shop_coords),
+				&self.grid[&shop_coords].ground,
+			) {
+				res_lw.gold -= price;
+				res_lw.grid.get_mut(&shop_coords).unwrap().ground = Ground::Floor;
+				logical_events.push(LogicalEvent::Bought { at: shop_coords });
+			}
+			res_lw.shoot_from_bow(coords, direction, &mut logical_events);
+			res_lw.slide_on_ice(direction, &mut logical_events);
+			res_lw.apply_grounds(&mut logical_events);
 		} else if let Some(final_interaction) = final_interaction {
 			match final_interaction {
 				InteractionConsequences::NonLethalHit { damages } => {
-					let target_obj = res_lw.grid.get_mut(&coords).unwrap().obj.as_mut().unwrap();
+					let hitter_obj = self.obj(coords - direction).unwrap();
+					let rider_status = hitter_obj
+						.wielded()
+						.enchantment()
+						.map(Enchantment::rider_status)
+						.or(hitter_obj.inflicted_status());
+					// The hit may land on any tile of a multi-tile target.
+					let target_coords = self.anchor_of(coords);
+					let target_obj = res_lw.grid.get_mut(&target_coords).unwrap().obj.as_mut().unwrap();
 					target_obj.take_damage(damages);
-					logical_events.push(LogicalEvent::Hit { at: coords, damages });
+					logical_events.push(LogicalEvent::Hit { at: target_coords, damages });
+					if let Some(status) = rider_status {
+						if target_obj.inflict_status(status) {
+							logical_events.push(LogicalEvent::StatusInflicted { at: target_coords });
+						}
+					}
+					res_lw.revive_if_needed(target_coords, &mut logical_events);
+				},
+				InteractionConsequences::BreakCrate => {
+					// What the crate held takes its place, still blocking the push.
+					let crate_tile = res_lw.grid.get_mut(&coords).unwrap();
+					if let Some(Obj::Crate { content }) = crate_tile.obj.take() {
+						crate_tile.obj = content.map(|content| *content);
+					}
+					logical_events.push(LogicalEvent::CrateBroken { at: coords });
+				},
+				InteractionConsequences::SetOffBarrel => {
+					res_lw.grid.get_mut(&coords).unwrap().obj = Some(Obj::Barrel { set_off: true });
+					logical_events.push(LogicalEvent::BarrelSetOff { at: coords });
+				},
+				InteractionConsequences::Disarm => {
+					// The tool stays where it was, next to what is left of the spikes.
+					res_lw.grid.get_mut(&coords).unwrap().ground = Ground::Rubble;
+					res_lw.scrap += 1;
+					logical_events.push(LogicalEvent::Disarmed { at: coords });
+				},
+				InteractionConsequences::KillLeavingCorpse { damages } => {
+					// The target dies but its corpse takes its place, still blocking the push.
+					let target_coords = self.anchor_of(coords);
+					res_lw.free_footprint(target_coords);
+					let target_tile = res_lw.grid.get_mut(&target_coords).unwrap();
+					let target_obj = target_tile.obj.take().unwrap();
+					target_tile.obj = target_obj.corpse();
+					logical_events.push(LogicalEvent::Killed {
+						obj: target_obj,
+						at: target_coords,
+						damages,
+					});
 				},
 				InteractionConsequences::Kill { .. }
 				| InteractionConsequences::Mine
+				| InteractionConsequences::Craft { .. }
 				| InteractionConsequences::StompShroom
 				| InteractionConsequences::KeyOpenDoor
 				| InteractionConsequences::Heal
 				| InteractionConsequences::GainARedo
+				| InteractionConsequences::GainARewind
+				| InteractionConsequences::GainABomb
+				| InteractionConsequences::GainGold
+				| InteractionConsequences::GainAKeyShard
+				| InteractionConsequences::GainStrength
+				| InteractionConsequences::ReadNote
+				| InteractionConsequences::GetMirrorCursed
 				| InteractionConsequences::Exit { .. } => {
 					unreachable!(
 						"If there is no or no more target, \
@@ -738,16 +3662,6 @@ impl LogicalWorld {
 				},
 			}
 		}
-		// The pulling.
-		if success {
-			let mut coords = mover_coords;
-			for _ in 0..pulled_length {
-				coords -= direction;
-				let obj = res_lw.grid.get_mut(&coords).unwrap().obj.take();
-				res_lw.grid.get_mut(&(coords + direction)).unwrap().obj = obj;
-				logical_events.push(LogicalEvent::Move { from: coords, to: coords + direction });
-			}
-		}
 		// Shroomer tries to shroom.
 		if matches!(self.obj(mover_coords), Some(Obj::Shroomer { .. }))
 			&& res_lw.obj(mover_coords).is_none()
@@ -761,9 +3675,10 @@ impl LogicalWorld {
 				}
 				false
 			};
-			if !adjacent_to_shroom {
-				res_lw.grid.get_mut(&mover_coords).unwrap().obj =
-					Some(Obj::Shroom { move_token: false });
+			// The fragile floor it left may have collapsed already.
+			let tile = res_lw.grid.get_mut(&mover_coords).unwrap();
+			if !adjacent_to_shroom && !matches!(tile.ground, Ground::Hole) {
+				tile.obj = Some(Obj::Shroom { move_token: false });
 			}
 		}
 		// Done ^^.
@@ -775,22 +3690,36 @@ impl LogicalWorld {
 		let mut res_lw = self.clone();
 		let mut logical_events = vec![];
 		let hitter_obj = res_lw.grid.get_mut(&hitter_coords).unwrap().obj.take().unwrap();
-		let target_coords = hitter_coords + direction;
-		let damages = hitter_obj.damages();
+		// The hit may land on any tile of a multi-tile target.
+		let target_coords = self.anchor_of(hitter_coords + direction);
+		let damages = self.damages_dealt(
+			&hitter_obj,
+			hitter_coords,
+			self.obj(target_coords).unwrap(),
+			target_coords,
+		);
 		logical_events.push(LogicalEvent::MoveInto {
 			obj: hitter_obj,
 			from: hitter_coords,
-			to: target_coords,
+			to: hitter_coords + direction,
+		});
+		let revived = self.obj(target_coords).is_some_and(|target| {
+			matches!(target, Obj::Bunny { .. }) && !self.is_lethal(damages, target)
 		});
 		let target_obj = res_lw.grid.get_mut(&target_coords).unwrap().obj.as_mut().unwrap();
 		target_obj.take_damage(damages);
-		if target_obj.hp().unwrap() <= 0 {
+		if revived {
+			logical_events.push(LogicalEvent::Hit { at: target_coords, damages });
+			res_lw.revive_if_needed(target_coords, &mut logical_events);
+		} else if target_obj.hp().unwrap() <= 0 {
+			let corpse = target_obj.corpse();
 			logical_events.push(LogicalEvent::Killed {
 				obj: target_obj.clone(),
 				at: target_coords,
 				damages,
 			});
-			res_lw.grid.get_mut(&target_coords).unwrap().obj = None;
+			res_lw.grid.get_mut(&target_coords).unwrap().obj = corpse;
+			res_lw.free_footprint(target_coords);
 		} else {
 			logical_events.push(LogicalEvent::Hit { at: target_coords, damages });
 		}
@@ -798,6 +3727,16 @@ impl LogicalWorld {
 	}
 }
 
+/// The recipe table of crafting, pushing a component into the other component of a recipe
+/// merges the two into the result.
+fn crafting_result(pushed_obj: &Obj, target_obj: &Obj) -> Option<Obj> {
+	match (pushed_obj, target_obj) {
+		(Obj::Sword { enchantment: None }, Obj::VisionGem)
+		| (Obj::VisionGem, Obj::Sword { enchantment: None }) => Some(Obj::VisionSword),
+		_ => None,
+	}
+}
+
 enum InteractionConsequences {
 	NonLethalHit {
 		damages: i32,
@@ -807,8 +3746,16 @@ enum InteractionConsequences {
 		/// even if higher than the remaining HP of the killed target.
 		damages: i32,
 	},
+	/// The target is killed but leaves a corpse behind, that blocks the push like the target did.
+	KillLeavingCorpse {
+		damages: i32,
+	},
 	/// Pickaxe mining a wall for example.
 	Mine,
+	/// Two components are merged into an upgraded object, following a crafting recipe.
+	Craft {
+		result: Obj,
+	},
 	/// A key is used to open a door, being consumed in the operation.
 	KeyOpenDoor,
 	/// Exit the level through an exit door.
@@ -820,21 +3767,53 @@ enum InteractionConsequences {
 	Heal,
 	/// Bunny ate a redo heart.
 	GainARedo,
+	/// Bunny picked up a rewind token.
+	GainARewind,
+	/// Bunny picked up an unlit bomb.
+	GainABomb,
+	/// Bunny picked up a coin.
+	GainGold,
+	/// Bunny picked up a key shard.
+	GainAKeyShard,
+	/// Bunny drank a strength potion.
+	GainStrength,
+	/// A sword broke a crate open.
+	BreakCrate,
+	/// Something that hurts hit a barrel, that will explode.
+	SetOffBarrel,
+	/// Bunny picked up a note to read it.
+	ReadNote,
+	/// Bunny touched a cursed mirror, the controls are mirrored for a while.
+	GetMirrorCursed,
 	/// Something stomps on a shroom, the poor thing.
 	StompShroom,
+	/// A pickaxe or a key was pushed at spikes, that are broken into rubble.
+	Disarm,
 }
 
 impl InteractionConsequences {
 	/// Does this intercation clears up a tile so that the move is allowed to succeed?
 	fn allows_move(&self) -> bool {
 		match self {
-			InteractionConsequences::NonLethalHit { .. } => false,
+			InteractionConsequences::NonLethalHit { .. }
+			| InteractionConsequences::KillLeavingCorpse { .. }
+			| InteractionConsequences::BreakCrate
+			| InteractionConsequences::SetOffBarrel
+			| InteractionConsequences::Disarm => false,
 			InteractionConsequences::Kill { .. }
 			| InteractionConsequences::Mine
+			| InteractionConsequences::Craft { .. }
 			| InteractionConsequences::StompShroom
 			| InteractionConsequences::KeyOpenDoor
 			| InteractionConsequences::Heal
 			| InteractionConsequences::GainARedo
+			| InteractionConsequences::GainARewind
+			| InteractionConsequences::GainABomb
+			| InteractionConsequences::GainGold
+			| InteractionConsequences::GainAKeyShard
+			| InteractionConsequences::GainStrength
+			| InteractionConsequences::ReadNote
+			| InteractionConsequences::GetMirrorCursed
 			| InteractionConsequences::Exit { .. } => true,
 		}
 	}
@@ -875,7 +3854,20 @@ pub enum LogicalEvent {
 		damages: i32,
 	},
 	Mined {
-		obj: Obj,
+		at: IVec2,
+	},
+	/// A corpse was pushed into something that it hit.
+	Squelched {
+		at: IVec2,
+	},
+	/// The player was saved from a killing blow by the free revive of the assist options.
+	Revived {
+		at: IVec2,
+	},
+	StatusInflicted {
+		at: IVec2,
+	},
+	Crafted {
 		at: IVec2,
 	},
 	DoorOpenedWithKey {
@@ -885,11 +3877,38 @@ pub enum LogicalEvent {
 		to: IVec2,
 	},
 	Healed {
-		obj: Obj,
 		at: IVec2,
 	},
 	RedoGained {
-		obj: Obj,
+		at: IVec2,
+	},
+	RewindGained {
+		at: IVec2,
+	},
+	BombGained {
+		at: IVec2,
+	},
+	GoldGained {
+		at: IVec2,
+	},
+	KeyShardGained {
+		at: IVec2,
+	},
+	StrengthGained {
+		at: IVec2,
+	},
+	/// The crate was broken open, what it held (if anything) is left in its place.
+	CrateBroken {
+		at: IVec2,
+	},
+	/// The bunny paid the price of the object for sale at `at`, pushing it out of the shop.
+	Bought {
+		at: IVec2,
+	},
+	NoteRead {
+		at: IVec2,
+	},
+	MirrorCursed {
 		at: IVec2,
 	},
 	Exit {
@@ -903,7 +3922,111 @@ pub enum LogicalEvent {
 		to: IVec2,
 	},
 	Stomped {
+		at: IVec2,
+	},
+	BombPlaced {
+		at: IVec2,
+	},
+	/// The bunny at `to` picked up the tool at `from`, to hold it.
+	PickedUp {
+		obj: Obj,
+		from: IVec2,
+		to: IVec2,
+	},
+	/// The bunny at `from` dropped the tool it held at `to`.
+	Dropped {
+		from: IVec2,
+		to: IVec2,
+	},
+	/// The fuse of a lit bomb burned down by one turn, without running out yet.
+	FuseBurned {
+		at: IVec2,
+	},
+	Exploded {
+		at: IVec2,
+	},
+	/// The spikes rose, they hurt what steps on them until they retract.
+	SpikesRose {
+		at: IVec2,
+	},
+	SpikesRetracted {
+		at: IVec2,
+	},
+	/// The spikes were disarmed with a tool, leaving rubble and some scrap for the player.
+	Disarmed {
+		at: IVec2,
+	},
+	/// The barrel was hit or caught in an explosion, it explodes next.
+	BarrelSetOff {
+		at: IVec2,
+	},
+	/// The archer at `from` shot the player at `to`, farther away in the given direction.
+	Shot {
+		direction: IVec2,
+		from: IVec2,
+		to: IVec2,
+	},
+	/// The bow at `from` was pushed, and it shot an arrow that appeared at `to`.
+	ArrowShot {
+		from: IVec2,
+		to: IVec2,
+	},
+	/// The beam of the laser emitter at `from` hurt what it ended on at `at`.
+	LaserFired {
+		from: IVec2,
+		at: IVec2,
+	},
+	/// The object moved onto a hole and fell in.
+	FellIntoHole {
+		obj: Obj,
+		from: IVec2,
+		at: IVec2,
+	},
+	/// The object moved onto water and sank.
+	Sank {
+		obj: Obj,
+		from: IVec2,
+		at: IVec2,
+	},
+	/// The raft moved onto water, it is a floating ground now.
+	RaftMoored {
+		from: IVec2,
+		at: IVec2,
+	},
+	/// The consumable moved onto lava and burnt up.
+	BurntUp {
+		obj: Obj,
+		from: IVec2,
+		at: IVec2,
+	},
+	/// The object moved onto the teleporter at `from`, and was moved to the one at `to`.
+	Teleported {
 		obj: Obj,
+		from: IVec2,
+		to: IVec2,
+	},
+	/// A pressure plate linked to the gate has something on it.
+	GateOpened {
+		at: IVec2,
+	},
+	/// Nothing is on the pressure plates linked to the gate anymore.
+	GateClosed {
+		at: IVec2,
+	},
+	/// The fragile floor was left by the object that cracked it, it is a hole now.
+	FloorCollapsed {
+		at: IVec2,
+	},
+	/// All the statues of the group of the altar faced it, it turned into the reward.
+	AltarAwakened {
+		at: IVec2,
+	},
+	/// The agent got in the activity area, it takes turns again.
+	WokeUp {
+		at: IVec2,
+	},
+	/// The agent was left out of the activity area, it stops taking turns until it is back in.
+	FellDormant {
 		at: IVec2,
 	},
 }
@@ -914,6 +4037,70 @@ pub enum LogicalEvent {
 /// This allows for animation to have access to all the events to animate,
 /// for the game to play all its moves and then the animations to play each of them
 /// taking some time, for the ai to play in its head and consider world states, etc.
+impl LogicalEvent {
+	/// The coords of the tiles that the event is about.
+	pub fn coords(&self) -> Vec<IVec2> {
+		match *self {
+			LogicalEvent::Move { from, to }
+			| LogicalEvent::FailToMove { from, to }
+			| LogicalEvent::DoorOpenedWithKey { from, to, .. }
+			| LogicalEvent::Exit { from, to, .. }
+			| LogicalEvent::MoveInto { from, to, .. }
+			| LogicalEvent::PickedUp { from, to, .. }
+			| LogicalEvent::Dropped { from, to, .. }
+			| LogicalEvent::Shot { from, to, .. }
+			| LogicalEvent::ArrowShot { from, to }
+			| LogicalEvent::Teleported { from, to, .. } => vec![from, to],
+			LogicalEvent::FellIntoHole { from, at, .. }
+			| LogicalEvent::LaserFired { from, at }
+			| LogicalEvent::Sank { from, at, .. }
+			| LogicalEvent::RaftMoored { from, at }
+			| LogicalEvent::BurntUp { from, at, .. } => vec![from, at],
+			LogicalEvent::Hit { at, .. }
+			| LogicalEvent::Killed { at, .. }
+			| LogicalEvent::Mined { at, .. }
+			| LogicalEvent::Squelched { at }
+			| LogicalEvent::Revived { at }
+			| LogicalEvent::StatusInflicted { at, .. }
+			| LogicalEvent::Crafted { at, .. }
+			| LogicalEvent::Healed { at, .. }
+			| LogicalEvent::RedoGained { at, .. }
+			| LogicalEvent::RewindGained { at, .. }
+			| LogicalEvent::BombGained { at, .. }
+			| LogicalEvent::GoldGained { at, .. }
+			| LogicalEvent::KeyShardGained { at, .. }
+			| LogicalEvent::StrengthGained { at, .. }
+			| LogicalEvent::CrateBroken { at, .. }
+			| LogicalEvent::Bought { at, .. }
+			| LogicalEvent::NoteRead { at, .. }
+			| LogicalEvent::MirrorCursed { at, .. }
+			| LogicalEvent::Stomped { at, .. }
+			| LogicalEvent::BombPlaced { at }
+			| LogicalEvent::FuseBurned { at }
+			| LogicalEvent::Exploded { at, .. }
+			| LogicalEvent::BarrelSetOff { at }
+			| LogicalEvent::SpikesRose { at }
+			| LogicalEvent::SpikesRetracted { at }
+			| LogicalEvent::Disarmed { at }
+			| LogicalEvent::GateOpened { at }
+			| LogicalEvent::GateClosed { at }
+			| LogicalEvent::FloorCollapsed { at }
+			| LogicalEvent::AltarAwakened { at, .. }
+			| LogicalEvent::WokeUp { at }
+			| LogicalEvent::FellDormant { at } => vec![at],
+		}
+	}
+}
+
+/// What the player can do on their turn, each in one of the four directions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerAction {
+	/// Walking, pushing what is in the way, or attacking it.
+	Move(IVec2),
+	PlaceBomb(IVec2),
+	PickUpOrDrop(IVec2),
+}
+
 #[derive(Clone)]
 pub struct LogicalTransition {
 	pub logical_events: Vec<LogicalEvent>,
@@ -927,6 +4114,21 @@ impl From<LogicalWorld> for LogicalTransition {
 }
 
 impl LogicalTransition {
+	/// The coords of the tiles that the events of the transition are about.
+	pub fn touched_coords(&self) -> HashSet<IVec2> {
+		self.logical_events.iter().flat_map(LogicalEvent::coords).collect()
+	}
+
+	/// The transition straight to the world of the given transition that follows this one,
+	/// with the events of both, as if they happened at the same time.
+	pub fn followed_by(mut self, next_transition: LogicalTransition) -> LogicalTransition {
+		self.logical_events.extend(next_transition.logical_events);
+		LogicalTransition {
+			logical_events: self.logical_events,
+			resulting_lw: next_transition.resulting_lw,
+		}
+	}
+
 	pub fn updated_visibility(self) -> LogicalTransition {
 		LogicalTransition {
 			resulting_lw: self.resulting_lw.updated_visibility(),
@@ -940,6 +4142,51 @@ impl LogicalTransition {
 			logical_events: self.logical_events,
 		}
 	}
+
+	/// The player gains the experience of the objects killed during the transition.
+	fn credited_kill_experience(mut self) -> LogicalTransition {
+		if self.resulting_lw.has_player() {
+			let xp = self
+				.logical_events
+				.iter()
+				.map(|logical_event| match logical_event {
+					LogicalEvent::Killed { obj, .. } => obj.experience_value(),
+					_ => 0,
+				})
+				.sum();
+			self.resulting_lw.progression.gain_xp(xp);
+		}
+		self
+	}
+
+	/// The looks follow the objects they were given to through their moves, and are lost when
+	/// these objects are killed or turn into something else.
+	pub fn with_looks_carried(mut self) -> LogicalTransition {
+		let looks = std::mem::take(&mut self.resulting_lw.looks);
+		'looks: for (mut coords, look) in looks {
+			for logical_event in self.logical_events.iter() {
+				match logical_event {
+					LogicalEvent::Move { from, to } | LogicalEvent::Teleported { from, to, .. }
+						if *from == coords =>
+						coords = *to,
+					LogicalEvent::Killed { at, .. } if *at == coords => continue 'looks,
+					_ => {},
+				}
+			}
+			if self.resulting_lw.obj(coords).is_some_and(|obj| look.is_for(obj)) {
+				self.resulting_lw.looks.insert(coords, look);
+			}
+		}
+		self
+	}
+
+	/// Counts what happened in the transition in the statistics of its resulting world.
+	pub fn counted_in_stats(mut self, player_coords: Option<IVec2>) -> LogicalTransition {
+		let mut stats = self.resulting_lw.stats.clone();
+		stats.count(&self, player_coords);
+		self.resulting_lw.stats = stats;
+		self
+	}
 }
 
 pub fn four_directions() -> [IVec2; 4] {
@@ -950,3 +4197,194 @@ pub fn four_directions() -> [IVec2; 4] {
 		IVec2::from((0, -1)),
 	]
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		generation::{generate_level, RunConfig, FINAL_DEPTH},
+		simulation::play_game_turn,
+	};
+
+	fn run_config(seed: u64, depth: i32) -> RunConfig {
+		RunConfig { seed, depth, assist: AssistOptions::default(), ..RunConfig::new() }
+	}
+
+	/// Plays the moves from the given world, the game's turn following each of them,
+	/// checking that the world is valid after each move and each game's turn.
+	/// Stops if the run is over.
+	fn play_and_validate(lw: LogicalWorld, moves: &[IVec2], run_config: &RunConfig) {
+		let mut lw = lw;
+		for (move_index, &direction) in moves.iter().enumerate() {
+			if !lw.has_player() || lw.player_exited() {
+				return;
+			}
+			let transition = lw.player_move(direction);
+			if let Err(error) = transition.resulting_lw.validate(false) {
+				panic!("Invalid world after the player's move {move_index}: {error}");
+			}
+			let transitions = play_game_turn(transition.clone(), run_config);
+			lw = transitions.last().map_or(transition.resulting_lw, |t| t.resulting_lw.clone());
+			if let Err(error) = lw.validate(true) {
+				panic!("Invalid world after the game's turn after move {move_index}: {error}");
+			}
+		}
+	}
+
+	#[test]
+	fn generated_levels_are_valid() {
+		for seed in 0..8 {
+			for depth in 1..=FINAL_DEPTH {
+				let lw = generate_level(&run_config(seed, depth)).updated_visibility();
+				if let Err(error) = lw.validate(true) {
+					panic!("Invalid level generated at depth {depth} with seed {seed}: {error}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn generated_levels_stay_valid_when_played() {
+		let directions = four_directions();
+		for seed in 0..2 {
+			for depth in 1..=FINAL_DEPTH {
+				let run_config = run_config(seed, depth);
+				let lw = generate_level(&run_config).updated_visibility();
+				// A walk that goes everywhere and pushes everything, without being any good.
+				let moves: Vec<_> = (0..25_usize)
+					.map(|move_index| directions[(move_index * 7 + move_index / 5) % 4])
+					.collect();
+				play_and_validate(lw, &moves, &run_config);
+			}
+		}
+	}
+
+	/// Handcrafted situations where a push sets off a lot at once, played move by move.
+	#[test]
+	fn scripted_pushes_stay_valid() {
+		let right = IVec2::new(1, 0);
+		let left = IVec2::new(-1, 0);
+		let down = IVec2::new(0, 1);
+		let levels: [(&str, &[IVec2]); 6] = [
+			// A rope pulled onto a hole.
+			(
+				"@ = floor bunny:5:5\nr = floor rope\n---\nr_@...\n",
+				&[right, right],
+			),
+			// A line of rocks pushed onto ice, the bunny sliding after them.
+			(
+				"@ = floor bunny:5:5\n---\n@oo===...\n",
+				&[right, right, right],
+			),
+			// Teleporters, conveyors and a slime that is carried around.
+			(
+				"@ = floor bunny:5:5\nT = teleporter:1\nc = conveyor:right\ns = floor slime:2\n---\n\
+				 @.oT....\n...cccs.\n......T.\n",
+				&[right, right, down, right, left, left],
+			),
+			// Spikes and lava next to a slime to push onto them.
+			(
+				"@ = floor bunny:5:5\n^ = spikes:armed\ns = floor slime:3\n---\n@s^%..\n",
+				&[right, right, right],
+			),
+			// A pickaxe pushed at spikes to disarm them, then pushed over the rubble.
+			(
+				"@ = floor bunny:5:5\np = floor pickaxe\n^ = spikes:armed\n---\n@p^..\n",
+				&[right, right],
+			),
+			// A golem boss caught in the blast of a barrel.
+			(
+				"@ = floor bunny:5:5\nG = floor golem_boss:1:9\nX = floor barrel:set_off\n---\n\
+				 ......\n.G....\n...X..\n......\n.....@\n",
+				&[left, left, left],
+			),
+		];
+		for (level, moves) in levels {
+			let lw = LogicalWorld::from_level_str(level)
+				.unwrap()
+				.generated_walls_outside()
+				.updated_visibility();
+			lw.validate(true).unwrap();
+			play_and_validate(lw, moves, &run_config(0, 1));
+		}
+	}
+
+	#[test]
+	fn ghosts_phase_over_holes_without_stopping_there() {
+		let lw = LogicalWorld::from_level_str(
+			"@ = floor bunny:5:5\ng = floor ghost:3:3\n---\ng#_.\n...@\n",
+		)
+		.unwrap()
+		.generated_walls_outside();
+		let transition = lw.ghost_move(IVec2::ZERO, IVec2::new(1, 0));
+		transition.resulting_lw.validate(true).unwrap();
+		assert!(matches!(
+			transition.resulting_lw.obj(IVec2::new(3, 0)),
+			Some(Obj::Ghost { .. })
+		));
+	}
+
+	#[test]
+	fn arrows_fall_in_holes() {
+		let lw = LogicalWorld::from_level_str(
+			"@ = floor bunny:5:5\na = floor arrow:right\n---\na_.\n..@\n",
+		)
+		.unwrap()
+		.generated_walls_outside();
+		let transition = lw.arrow_flight(IVec2::ZERO, IVec2::new(1, 0));
+		transition.resulting_lw.validate(true).unwrap();
+		assert!(transition.resulting_lw.obj(IVec2::new(1, 0)).is_none());
+		assert!(transition.resulting_lw.obj(IVec2::ZERO).is_none());
+	}
+
+	#[test]
+	fn bombs_are_not_placed_where_they_would_not_stay() {
+		let mut lw = LogicalWorld::from_level_str("@ = floor bunny:5:5\n---\n._.\n~@%\n...\n")
+			.unwrap()
+			.generated_walls_outside();
+		lw.bombs = 3;
+		for direction in [IVec2::new(0, -1), IVec2::new(-1, 0), IVec2::new(1, 0)] {
+			assert!(lw.player_place_bomb(direction).is_none());
+		}
+		assert!(lw.player_place_bomb(IVec2::new(0, 1)).is_some());
+	}
+
+	#[test]
+	fn arrows_set_off_barrels() {
+		let lw = LogicalWorld::from_level_str(
+			"@ = floor bunny:5:5\na = floor arrow:right\nx = floor barrel\n---\nax.\n..@\n",
+		)
+		.unwrap()
+		.generated_walls_outside();
+		let transition = lw.arrow_flight(IVec2::ZERO, IVec2::new(1, 0));
+		transition.resulting_lw.validate(true).unwrap();
+		assert!(transition.resulting_lw.obj(IVec2::ZERO).is_none());
+		assert!(matches!(
+			transition.resulting_lw.obj(IVec2::new(1, 0)),
+			Some(Obj::Barrel { set_off: true })
+		));
+	}
+
+	/// The tiles that a multi-tile object takes are not free, even with no object on them.
+	#[test]
+	fn nothing_goes_inside_a_multi_tile_object() {
+		let left = IVec2::new(-1, 0);
+		let mut lw = LogicalWorld::from_level_str(
+			"@ = floor bunny:5:5\nG = floor golem_boss:1:9\n---\n.G..\n...@\n",
+		)
+		.unwrap()
+		.generated_walls_outside()
+		.updated_visibility();
+		lw.validate(true).unwrap();
+		assert!(!lw.is_free(IVec2::new(2, 1)));
+		lw.bombs = 1;
+		assert!(lw.player_place_bomb(left).is_none());
+		let player_coords = lw.player_coords().unwrap();
+		if let Some(Obj::Bunny { held, .. }) = &mut lw.grid.get_mut(&player_coords).unwrap().obj {
+			*held = Some(Box::new(Obj::Shield));
+		}
+		assert!(lw.player_pick_up_or_drop(left).is_none());
+		lw.grid.get_mut(&IVec2::new(2, 1)).unwrap().obj = Some(Obj::Rock);
+		assert!(lw.validate(true).is_err());
+	}
+}
@@ -1,13 +1,21 @@
 //! Procedural generation of levels.
 
-use ggez::glam::IVec2;
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use glam::IVec2;
+use std::{
+	collections::HashMap,
+	hash::{Hash, Hasher},
+};
 
-use crate::gameplay::{four_directions, LogicalWorld, Obj, Tile};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-fn randint(inf: i32, sup_included: i32) -> i32 {
-	thread_rng().gen_range(inf..=sup_included)
-}
+use crate::{
+	gameplay::{
+		four_directions, AssistOptions, Enchantment, Facing, Ground, LogicalWorld, Mirroring, Obj,
+		Tile, DEFAULT_BASE_VISION_RADIUS, DEFAULT_BUNNY_FORCE,
+	},
+	stable_hash::StableHasher,
+};
 
 pub fn filled_rect(top_left: IVec2, dimensions: IVec2) -> Vec<IVec2> {
 	let mut vec = vec![];
@@ -30,13 +38,210 @@ fn line_rect(top_left: IVec2, dimensions: IVec2) -> Vec<IVec2> {
 	outer_vec
 }
 
-struct Generator {
+/// Handcrafted room interiors (written in the level format), that some rooms get
+/// instead of being filled at random.
+fn prefabs() -> [(&'static str, &'static str); 5] {
+	[
+		("vault", include_str!("../assets/prefabs/vault.txt")),
+		("pillars", include_str!("../assets/prefabs/pillars.txt")),
+		("island", include_str!("../assets/prefabs/island.txt")),
+		(
+			"gated_heart",
+			include_str!("../assets/prefabs/gated_heart.txt"),
+		),
+		(
+			"laser_vault",
+			include_str!("../assets/prefabs/laser_vault.txt"),
+		),
+	]
+}
+
+/// The run is won by going through the exit of the level at that depth.
+pub const FINAL_DEPTH: i32 = 3;
+
+/// The redos of a daily challenge, that is also as many as it can hold.
+const DAILY_REDO_COUNT: i32 = 5;
+
+/// A bit more than how far the scent of the player spreads,
+/// so that every enemy that could come for the player does take turns.
+const DEFAULT_ACTIVITY_RADIUS: i32 = 16;
+
+/// The key shards to collect in each level to unseal its exits, when they are sealed.
+const KEY_SHARDS_PER_LEVEL: i32 = 3;
+
+/// Something earned across runs, that the runs started from then on find in their starting room.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unlock {
+	Pickaxe,
+	Bomb,
+	RedoHeart,
+}
+
+impl Unlock {
+	pub fn all() -> [Unlock; 3] {
+		[Unlock::Pickaxe, Unlock::Bomb, Unlock::RedoHeart]
+	}
+
+	/// The item placed in the starting room once unlocked.
+	pub fn starting_obj(self) -> Obj {
+		match self {
+			Unlock::Pickaxe => Obj::Pickaxe { enchantment: None },
+			Unlock::Bomb => Obj::Bomb { fuse: None },
+			Unlock::RedoHeart => Obj::RedoHeart,
+		}
+	}
+}
+
+/// What a level is generated from (along with the rest of the run config),
+/// the same seed generates the same level.
+pub type Seed = u64;
+
+/// What differs from one run to an other, and that the generation takes into account.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunConfig {
+	/// New Game Plus tier, zero for a first run, it goes up by one with every victory.
+	/// Enemies get tougher with each tier.
+	pub difficulty_tier: i32,
+	/// An item that the player picked to carry into this run.
+	pub starting_item: Option<Obj>,
+	/// Should the encounter director nudge the enemies to keep the tension in check?
+	/// Modes where runs are to be compared fairly should turn it off.
+	pub encounter_director: bool,
+	pub assist: AssistOptions,
+	/// The generation of the level only depends on the seed (and the rest of the run config),
+	/// the same seed generates the same level.
+	pub seed: Seed,
+	/// The level of the run to generate, the first one is at depth 1 and each exit leads
+	/// one level deeper, with more enemies.
+	pub depth: i32,
+	/// What previous runs unlocked, to find in the starting room.
+	#[serde(default)]
+	pub unlocks: Vec<Unlock>,
+	/// The date of the daily challenge, if the run is one. It has a fixed number of redos.
+	#[serde(default)]
+	pub daily_date: Option<String>,
+	/// Only the agents at most that far (in steps) from the player take turns, if set,
+	/// so that the far-away parts of big levels stay frozen and cost nothing to simulate.
+	#[serde(default)]
+	pub activity_radius: Option<i32>,
+	/// Are the exits sealed until the key shards scattered in the level are collected?
+	#[serde(default)]
+	pub sealed_exits: bool,
+}
+
+impl Default for RunConfig {
+	fn default() -> RunConfig {
+		RunConfig::new()
+	}
+}
+
+impl RunConfig {
+	pub fn new() -> RunConfig {
+		RunConfig {
+			difficulty_tier: 0,
+			starting_item: None,
+			encounter_director: true,
+			assist: AssistOptions::from_env().unwrap_or_default(),
+			seed: rand::random(),
+			depth: 1,
+			unlocks: vec![],
+			daily_date: None,
+			activity_radius: Some(DEFAULT_ACTIVITY_RADIUS),
+			sealed_exits: false,
+		}
+	}
+
+	/// What identifies the run, like in a bug report or when sharing it: the seed, the version
+	/// of the game and the modifiers that change how it plays.
+	pub fn summary(&self) -> String {
+		let mut parts = vec![
+			format!("seed {}", self.seed),
+			format!("v{}", env!("CARGO_PKG_VERSION")),
+			format!("tier {}", self.difficulty_tier),
+		];
+		if let Some(item) = self.starting_item.as_ref() {
+			parts.push(format!("carrying {}", item.name()));
+		}
+		if !self.unlocks.is_empty() {
+			let names: Vec<_> =
+				self.unlocks.iter().map(|unlock| unlock.starting_obj().name()).collect();
+			parts.push(format!("unlocked: {}", names.join(", ")));
+		}
+		if let Some(date) = self.daily_date.as_ref() {
+			parts.push(format!("daily challenge of {date}"));
+		}
+		if self.sealed_exits {
+			parts.push("sealed exits".to_string());
+		}
+		if !self.encounter_director {
+			parts.push("no encounter director".to_string());
+		}
+		if self.activity_radius != Some(DEFAULT_ACTIVITY_RADIUS) {
+			parts.push(match self.activity_radius {
+				Some(radius) => format!("activity radius {radius}"),
+				None => "no activity radius".to_string(),
+			});
+		}
+		let assist_descriptions = self.assist.enabled_descriptions();
+		if !assist_descriptions.is_empty() {
+			parts.push(format!("assisted: {}", assist_descriptions.join(", ")));
+		}
+		parts.join(", ")
+	}
+
+	/// Each level has its own seed, derived from the seed of the run (with a stable hash, for a
+	/// seed to give the same levels on every platform). The first level uses the seed of the run
+	/// itself.
+	pub fn level_seed(&self) -> Seed {
+		if self.depth == 1 {
+			self.seed
+		} else {
+			let mut hasher = StableHasher::new();
+			(self.seed, self.depth).hash(&mut hasher);
+			hasher.finish()
+		}
+	}
+
+	/// Enemies get more HP in deeper New Game Plus tiers.
+	pub fn enemy_hp(&self) -> i32 {
+		5 + self.difficulty_tier * 2
+	}
+}
+
+/// The seed that a seed typed by the player stands for,
+/// it can be a number or any text (like a word to share easily) that is then hashed,
+/// the same way on every platform.
+pub fn seed_from_text(text: &str) -> Seed {
+	text.trim().parse().unwrap_or_else(|_| {
+		let mut hasher = StableHasher::new();
+		text.trim().hash(&mut hasher);
+		hasher.finish()
+	})
+}
+
+struct Generator<'a> {
 	lw: LogicalWorld,
+	run_config: &'a RunConfig,
+	rng: StdRng,
+	/// The group of the next altar, each altar and its statues get their own.
+	next_statue_group: u32,
+	/// The link of the next pressure plate, each plate and what it activates get their own.
+	next_link_id: u32,
 }
 
-impl Generator {
-	fn new() -> Generator {
-		Generator { lw: LogicalWorld::new_empty() }
+impl<'a> Generator<'a> {
+	fn new(run_config: &'a RunConfig) -> Generator<'a> {
+		Generator {
+			lw: LogicalWorld::new_empty(),
+			run_config,
+			rng: StdRng::seed_from_u64(run_config.level_seed()),
+			next_statue_group: 0,
+			next_link_id: 0,
+		}
+	}
+
+	fn randint(&mut self, inf: i32, sup_included: i32) -> i32 {
+		self.rng.gen_range(inf..=sup_included)
 	}
 
 	fn generate_empty_room(&mut self, top_left: IVec2, dimensions: IVec2) {
@@ -83,7 +288,14 @@ impl Generator {
 		if is_starting_room {
 			self.lw.place_tile(
 				top_left + dimensions / 2,
-				Tile::obj(Obj::Bunny { hp: 7, max_hp: 7 }),
+				Tile::obj(Obj::Bunny {
+					hp: 7,
+					max_hp: 7,
+					statuses: vec![],
+					facing: Facing::Left,
+					held: None,
+					force: DEFAULT_BUNNY_FORCE,
+				}),
 			);
 			self.lw.place_tile(
 				top_left + dimensions / 2 + IVec2::new(-2, 0),
@@ -91,34 +303,124 @@ impl Generator {
 			);
 			self.lw.place_tile(
 				top_left + dimensions / 2 + IVec2::new(2, 0),
-				Tile::obj(Obj::Sword),
+				Tile::obj(Obj::Sword { enchantment: None }),
 			);
+			// The starting item is only given at the start of the run.
+			if let Some(starting_item) =
+				self.run_config.starting_item.as_ref().filter(|_| self.run_config.depth == 1)
+			{
+				self.lw.place_tile(
+					top_left + dimensions / 2 + IVec2::new(0, 2),
+					Tile::obj(starting_item.clone()),
+				);
+			}
+			// So are the unlocks, in a row above the bunny.
+			if self.run_config.depth == 1 {
+				for (index, unlock) in self.run_config.unlocks.iter().enumerate() {
+					self.lw.place_tile(
+						top_left + dimensions / 2 + IVec2::new(index as i32 * 2 - 2, -2),
+						Tile::obj(unlock.starting_obj()),
+					);
+				}
+			}
+		} else if !is_exit_room && self.randint(0, 9) == 0 {
+			// A handcrafted room is already complete, it gets nothing more.
+			self.stamp_prefab(top_left + IVec2::new(1, 1));
+			return;
+		} else if !is_exit_room && self.randint(0, 11) == 0 {
+			// A shop is kept clear, nothing gets in the way of the wares.
+			self.generate_shop(top_left, dimensions);
+			return;
 		} else {
+			let enemy_hp = self.run_config.enemy_hp();
+			// Deeper levels have more enemies.
+			let deeper = self.run_config.depth - 1;
 			// Weighted table of object spawn.
 			let obj_table = [
 				(500, None),
 				(25, Some(Obj::Rock)),
-				(5, Some(Obj::Sword)),
+				(5, Some(Obj::Sword { enchantment: None })),
+				(
+					1,
+					Some(Obj::Sword { enchantment: Some(Enchantment::Flaming) }),
+				),
 				(4, Some(Obj::Shield)),
-				(2, Some(Obj::Pickaxe)),
+				(2, Some(Obj::Pickaxe { enchantment: None })),
+				(
+					1,
+					Some(Obj::Pickaxe { enchantment: Some(Enchantment::Frost) }),
+				),
 				(3, Some(Obj::VisionGem)),
 				(1, Some(Obj::Heart)),
 				(2, Some(Obj::RedoHeart)),
+				(1, Some(Obj::RewindToken)),
+				(2, Some(Obj::Bomb { fuse: None })),
+				(1, Some(Obj::Bow)),
+				(2, Some(Obj::Torch)),
+				(1, Some(Obj::Magnet)),
+				(4, Some(Obj::Crate { content: None })),
+				(1, Some(Obj::StrengthPotion)),
 				(3, Some(Obj::Key)),
 				(3, Some(Obj::Rope)),
 				(2, Some(Obj::Bush)),
-				(25, Some(Obj::Slime { hp: 5, move_token: false })),
-				(8, Some(Obj::Shroomer { hp: 5, move_token: false })),
+				(1, Some(Obj::Note)),
+				(1, Some(Obj::CursedMirror)),
+				(12, Some(Obj::Coin)),
+				(
+					25 + deeper * 10,
+					Some(Obj::Slime {
+						hp: enemy_hp,
+						max_hp: enemy_hp,
+						move_token: false,
+						home: IVec2::ZERO,
+						statuses: vec![],
+						facing: Facing::Left,
+					}),
+				),
+				(
+					8 + deeper * 3,
+					Some(Obj::Shroomer {
+						hp: enemy_hp,
+						max_hp: enemy_hp,
+						move_token: false,
+						home: IVec2::ZERO,
+						statuses: vec![],
+						facing: Facing::Left,
+					}),
+				),
+				(
+					4 + deeper * 2,
+					Some(Obj::Archer {
+						hp: enemy_hp,
+						max_hp: enemy_hp,
+						move_token: false,
+						statuses: vec![],
+						facing: Facing::Left,
+					}),
+				),
+				(
+					1 + deeper,
+					Some(Obj::Ghost {
+						hp: enemy_hp,
+						max_hp: enemy_hp,
+						move_token: false,
+						facing: Facing::Left,
+					}),
+				),
 				(6, Some(Obj::Shroom { move_token: false })),
 				(
-					8,
+					8 + deeper * 3,
 					Some(Obj::Fish { direction: IVec2::new(1, 0), move_token: false }),
 				),
+				(
+					2 + deeper,
+					Some(Obj::Golem { mirroring: Mirroring::Reversed, move_token: false }),
+				),
 			];
 			let total_weight: i32 = obj_table.iter().map(|(weight, _obj)| weight).sum();
 			// Fill the room.
 			for coords in filled_inner_rect(top_left, dimensions) {
-				let mut random_value = randint(0, total_weight - 1);
+				let mut random_value = self.randint(0, total_weight - 1);
 				let obj = 'obj: {
 					for weighted_obj in obj_table.iter() {
 						let (weight, obj) = weighted_obj;
@@ -131,19 +433,32 @@ impl Generator {
 				};
 				if let Some(mut obj) = obj.clone() {
 					if let Obj::Fish { ref mut direction, .. } = obj {
-						*direction = four_directions()[randint(0, 3) as usize];
+						*direction = four_directions()[self.randint(0, 3) as usize];
+					}
+					if let Obj::Golem { ref mut mirroring, .. } = obj {
+						if self.randint(0, 2) == 0 {
+							*mirroring = Mirroring::Rotated;
+						}
+					}
+					if let Obj::Slime { ref mut home, .. } | Obj::Shroomer { ref mut home, .. } = obj {
+						*home = coords;
+					}
+					if let Obj::Crate { ref mut content } = obj {
+						*content = self.crate_content();
 					}
 					self.lw.place_tile(coords, Tile::obj(obj.clone()));
 				}
 			}
 
-			if randint(0, 3) == 0 {
-				let v = randint(2, 4);
+			if self.randint(0, 3) == 0 {
+				let v = self.randint(2, 4);
 				for coords in filled_inner_rect(top_left, dimensions) {
-					if ((coords.x + coords.y) % v == 0 && coords.x % 2 == 0 && randint(0, 6 - 1) != 0)
-						|| ((coords.x + coords.y) % 2 != v && randint(0, 10 - 1) == 0)
+					if ((coords.x + coords.y) % v == 0
+						&& coords.x % 2 == 0
+						&& self.randint(0, 6 - 1) != 0)
+						|| ((coords.x + coords.y) % 2 != v && self.randint(0, 10 - 1) == 0)
 					{
-						let wall = if randint(0, 30) == 0 {
+						let wall = if self.randint(0, 30) == 0 {
 							Obj::Door
 						} else {
 							Obj::Wall
@@ -154,33 +469,390 @@ impl Generator {
 			}
 		}
 
+		if !is_starting_room && self.randint(0, 5) == 0 {
+			self.generate_piston_machine(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 6) == 0 {
+			self.generate_ice(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 6) == 0 {
+			self.generate_holes(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 6) == 0 {
+			self.generate_fragile_floors(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 7) == 0 {
+			self.generate_pond(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 7) == 0 {
+			self.generate_conveyor_line(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 7) == 0 {
+			self.generate_lava(top_left, dimensions);
+		}
+
+		if !is_starting_room && self.randint(0, 7) == 0 {
+			self.generate_spikes(top_left, dimensions);
+		}
+
+		if !is_starting_room && !is_exit_room && self.randint(0, 12) == 0 {
+			self.generate_statue_group(top_left, dimensions);
+		}
+
 		if is_exit_room {
 			// Exit.
-			let x = top_left.x + randint(0, dimensions.x - 1);
-			let y = top_left.y + randint(0, dimensions.y - 1);
+			let x = top_left.x + self.randint(0, dimensions.x - 1);
+			let y = top_left.y + self.randint(0, dimensions.y - 1);
 			let coords = IVec2::new(x, y);
 			self.lw.place_tile(coords, Tile::obj(Obj::Exit));
 		}
 	}
 
+	/// What a crate holds, to be found once it is broken open. Some of them are empty.
+	fn crate_content(&mut self) -> Option<Box<Obj>> {
+		let contents = [
+			None,
+			Some(Obj::Coin),
+			Some(Obj::Coin),
+			Some(Obj::Heart),
+			Some(Obj::RedoHeart),
+			Some(Obj::Bomb { fuse: None }),
+			Some(Obj::Key),
+			Some(Obj::StrengthPotion),
+		];
+		contents[self.randint(0, contents.len() as i32 - 1) as usize].clone().map(Box::new)
+	}
+
+	/// Copies a random prefab with its top-left corner at the given coords. Its links and
+	/// groups are replaced by new ones, not to be shared with the rest of the level.
+	fn stamp_prefab(&mut self, top_left: IVec2) {
+		let (name, level_str) = prefabs()[self.randint(0, prefabs().len() as i32 - 1) as usize];
+		let prefab = LogicalWorld::from_level_str(level_str)
+			.unwrap_or_else(|error| panic!("The prefab \"{name}\" is invalid: {error}"));
+		// The new links are given in a set order, for the level to only depend on the seed.
+		let tiles: Vec<_> = prefab
+			.coords_where(|_tile| true)
+			.into_iter()
+			.map(|coords| (coords, prefab.tile(coords).unwrap().clone()))
+			.collect();
+		let mut link_ids = HashMap::new();
+		let mut groups = HashMap::new();
+		let mut multi_tile_objs = vec![];
+		for (prefab_coords, mut tile) in tiles {
+			let coords = top_left + prefab_coords;
+			match &mut tile.ground {
+				Ground::PressurePlate { link_id }
+				| Ground::OpenGate { link_id }
+				| Ground::Teleporter { link_id } =>
+					*link_id = *link_ids.entry(*link_id).or_insert_with(|| {
+						self.next_link_id += 1;
+						self.next_link_id - 1
+					}),
+				_ => {},
+			}
+			match tile.obj.as_mut() {
+				Some(Obj::Piston { link_id, .. } | Obj::Gate { link_id }) =>
+					*link_id = *link_ids.entry(*link_id).or_insert_with(|| {
+						self.next_link_id += 1;
+						self.next_link_id - 1
+					}),
+				Some(Obj::Statue { group, .. } | Obj::Altar { group }) =>
+					*group = *groups.entry(*group).or_insert_with(|| {
+						self.next_statue_group += 1;
+						self.next_statue_group - 1
+					}),
+				Some(Obj::Slime { hp, max_hp, home, .. } | Obj::Shroomer { hp, max_hp, home, .. }) => {
+					*hp = self.run_config.enemy_hp();
+					*max_hp = self.run_config.enemy_hp();
+					*home = coords;
+				},
+				Some(
+					Obj::Archer { hp, max_hp, .. }
+					| Obj::Ghost { hp, max_hp, .. }
+					| Obj::GolemBoss { hp, max_hp, .. },
+				) => {
+					*hp = self.run_config.enemy_hp();
+					*max_hp = self.run_config.enemy_hp();
+				},
+				_ => {},
+			}
+			if tile.obj.as_ref().is_some_and(|obj| obj.footprint() != IVec2::ONE) {
+				multi_tile_objs.push((coords, tile.obj.take().unwrap()));
+			}
+			self.lw.place_tile(coords, tile);
+		}
+		for (coords, obj) in multi_tile_objs {
+			self.lw.place_multi_tile_obj(coords, obj);
+		}
+		for (prefab_coords, look) in prefab.looks {
+			self.lw.looks.insert(top_left + prefab_coords, look);
+		}
+	}
+
+	/// Three wares for sale in a row in the middle of the room, each on its price tile.
+	fn generate_shop(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let wares = [
+			(Obj::Heart, 3),
+			(Obj::RedoHeart, 4),
+			(Obj::RewindToken, 3),
+			(Obj::Bomb { fuse: None }, 2),
+			(Obj::Key, 2),
+			(Obj::Shield, 3),
+			(Obj::Pickaxe { enchantment: None }, 4),
+			(Obj::Sword { enchantment: Some(Enchantment::Frost) }, 6),
+		];
+		let center = top_left + dimensions / 2;
+		for index in -1..=1 {
+			let (obj, price) = wares.choose(&mut self.rng).unwrap().clone();
+			self.lw.place_tile(
+				center + IVec2::new(index * 2, 0),
+				Tile { ground: Ground::Price { price }, obj: Some(obj), visible: false },
+			);
+		}
+	}
+
+	/// A pressure plate and the piston it activates, somewhere in the room.
+	fn generate_piston_machine(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let inner_coords = filled_inner_rect(top_left, dimensions);
+		let mut chosen = inner_coords.choose_multiple(&mut self.rng, 2);
+		let (Some(&plate_coords), Some(&piston_coords)) = (chosen.next(), chosen.next()) else {
+			return;
+		};
+		let facing = four_directions()[self.randint(0, 3) as usize];
+		let link_id = self.next_link_id;
+		self.next_link_id += 1;
+		self.lw.place_tile(
+			plate_coords,
+			Tile {
+				ground: Ground::PressurePlate { link_id },
+				obj: None,
+				visible: false,
+			},
+		);
+		self.lw.place_tile(
+			piston_coords,
+			Tile::obj(Obj::Piston { facing, extended: false, link_id }),
+		);
+	}
+
+	/// A gate at the given coords, and its pressure plate in the given room
+	/// with a rock next to it, to push on the plate to keep the gate open.
+	/// There is no gate if there is no room for them on the floor.
+	fn generate_gate(&mut self, gate_coords: IVec2, top_left: IVec2, dimensions: IVec2) {
+		// Away from the walls, so that the rock can be pushed onto the plate from any side.
+		let plate_coords = top_left
+			+ IVec2::new(
+				self.randint(2, dimensions.x - 3),
+				self.randint(2, dimensions.y - 3),
+			);
+		let rock_coords = plate_coords + four_directions()[self.randint(0, 3) as usize];
+		let is_free_floor = |coords| {
+			self
+				.lw
+				.tile(coords)
+				.is_some_and(|tile| matches!(tile.ground, Ground::Floor) && tile.obj.is_none())
+		};
+		if !is_free_floor(plate_coords) || !is_free_floor(rock_coords) {
+			return;
+		}
+		let link_id = self.next_link_id;
+		self.next_link_id += 1;
+		self.lw.place_tile(gate_coords, Tile::obj(Obj::Gate { link_id }));
+		self.lw.place_tile(
+			plate_coords,
+			Tile {
+				ground: Ground::PressurePlate { link_id },
+				obj: None,
+				visible: false,
+			},
+		);
+		self.lw.place_tile(rock_coords, Tile::obj(Obj::Rock));
+	}
+
+	/// A patch of ice somewhere in the room, under what was there.
+	fn generate_ice(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let ice_dimensions = IVec2::new(self.randint(2, 5), self.randint(2, 5));
+		let ice_top_left = top_left
+			+ IVec2::new(
+				self.randint(1, dimensions.x - 1 - ice_dimensions.x),
+				self.randint(1, dimensions.y - 1 - ice_dimensions.y),
+			);
+		for coords in filled_rect(ice_top_left, ice_dimensions) {
+			let obj = self.lw.tile(coords).and_then(|tile| tile.obj.clone());
+			self.lw.place_tile(coords, Tile { ground: Ground::Ice, obj, visible: false });
+		}
+	}
+
+	/// A few holes here and there in the room, swallowing what was there.
+	fn generate_holes(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let hole_count = self.randint(1, 4) as usize;
+		let inner_coords = filled_inner_rect(top_left, dimensions);
+		for &coords in inner_coords.choose_multiple(&mut self.rng, hole_count) {
+			self.lw.place_tile(
+				coords,
+				Tile { ground: Ground::Hole, obj: None, visible: false },
+			);
+		}
+	}
+
+	/// A pond somewhere in the room, swallowing what would sink in it,
+	/// and a raft elsewhere in the room to cross it.
+	fn generate_pond(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let pond_dimensions = IVec2::new(self.randint(2, 4), self.randint(2, 3));
+		let pond_top_left = top_left
+			+ IVec2::new(
+				self.randint(1, dimensions.x - 1 - pond_dimensions.x),
+				self.randint(1, dimensions.y - 1 - pond_dimensions.y),
+			);
+		let pond_coords = filled_rect(pond_top_left, pond_dimensions);
+		for &coords in pond_coords.iter() {
+			let obj =
+				self.lw.tile(coords).and_then(|tile| tile.obj.clone()).filter(|obj| !obj.sinks());
+			self.lw.place_tile(coords, Tile { ground: Ground::Water, obj, visible: false });
+		}
+		let shore_coords: Vec<_> = filled_inner_rect(top_left, dimensions)
+			.into_iter()
+			.filter(|coords| !pond_coords.contains(coords))
+			.collect();
+		if let Some(&raft_coords) = shore_coords.choose(&mut self.rng) {
+			self.lw.place_tile(raft_coords, Tile::obj(Obj::Raft));
+		}
+	}
+
+	/// A straight line of conveyors across the room, all going the same way, under what was there.
+	fn generate_conveyor_line(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let direction = four_directions()[self.randint(0, 3) as usize];
+		let along = direction.abs();
+		let across = IVec2::new(along.y, along.x);
+		let room_length = (dimensions * along).max_element();
+		let room_width = (dimensions * across).max_element();
+		let length = self.randint(3, room_length - 2);
+		let start = top_left
+			+ along * self.randint(1, room_length - 1 - length)
+			+ across * self.randint(1, room_width - 2);
+		for i in 0..length {
+			let coords = start + along * i;
+			let obj = self.lw.tile(coords).and_then(|tile| tile.obj.clone());
+			self.lw.place_tile(
+				coords,
+				Tile { ground: Ground::Conveyor { direction }, obj, visible: false },
+			);
+		}
+	}
+
+	/// A few fragile floors here and there in the room, that can be walked over only once.
+	fn generate_fragile_floors(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let fragile_floor_count = self.randint(2, 6) as usize;
+		let inner_coords = filled_inner_rect(top_left, dimensions);
+		for &coords in inner_coords.choose_multiple(&mut self.rng, fragile_floor_count) {
+			let obj = self.lw.tile(coords).and_then(|tile| tile.obj.clone());
+			self.lw.place_tile(
+				coords,
+				Tile { ground: Ground::FragileFloor, obj, visible: false },
+			);
+		}
+	}
+
+	/// A few lava tiles here and there in the room, on the empty floors so that nothing
+	/// starts burning or burns up before the player gets there.
+	fn generate_lava(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let lava_count = self.randint(2, 5) as usize;
+		let empty_coords: Vec<_> = filled_inner_rect(top_left, dimensions)
+			.into_iter()
+			.filter(|&coords| {
+				self
+					.lw
+					.tile(coords)
+					.is_some_and(|tile| tile.obj.is_none() && matches!(tile.ground, Ground::Floor))
+			})
+			.collect();
+		for &coords in empty_coords.choose_multiple(&mut self.rng, lava_count) {
+			self.lw.place_tile(
+				coords,
+				Tile { ground: Ground::Lava, obj: None, visible: false },
+			);
+		}
+	}
+
+	/// A few spikes here and there in the room, on the empty floors, some of them risen and some
+	/// retracted, and a rock on an other empty floor to keep one of them down.
+	fn generate_spikes(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let spikes_count = self.randint(3, 6) as usize;
+		let empty_coords: Vec<_> = filled_inner_rect(top_left, dimensions)
+			.into_iter()
+			.filter(|&coords| {
+				self
+					.lw
+					.tile(coords)
+					.is_some_and(|tile| tile.obj.is_none() && matches!(tile.ground, Ground::Floor))
+			})
+			.collect();
+		let chosen_coords: Vec<_> =
+			empty_coords.choose_multiple(&mut self.rng, spikes_count + 1).copied().collect();
+		let Some((&rock_coords, spikes_coords)) = chosen_coords.split_first() else {
+			return;
+		};
+		self.lw.place_tile(rock_coords, Tile::obj(Obj::Rock));
+		for &coords in spikes_coords {
+			let armed = self.randint(0, 1) == 0;
+			self.lw.place_tile(
+				coords,
+				Tile { ground: Ground::Spikes { armed }, obj: None, visible: false },
+			);
+		}
+	}
+
+	/// An altar in the middle of the room, and a group of statues around it to turn towards it.
+	fn generate_statue_group(&mut self, top_left: IVec2, dimensions: IVec2) {
+		let group = self.next_statue_group;
+		self.next_statue_group += 1;
+		let altar_coords = top_left + dimensions / 2;
+		self.lw.place_tile(altar_coords, Tile::obj(Obj::Altar { group }));
+		let inner_coords: Vec<_> = filled_inner_rect(top_left, dimensions)
+			.into_iter()
+			.filter(|coords| (*coords - altar_coords).abs().max_element() >= 2)
+			.collect();
+		for statue_coords in
+			inner_coords.choose_multiple(&mut self.rng, 3).copied().collect::<Vec<_>>()
+		{
+			// They start facing away from the altar, so that there is something to do.
+			let facing = (statue_coords - altar_coords).signum();
+			let facing = if facing.x != 0 {
+				IVec2::new(facing.x, 0)
+			} else {
+				facing
+			};
+			self.lw.place_tile(statue_coords, Tile::obj(Obj::Statue { facing, group }));
+		}
+	}
+
 	fn generate_grid_corridor(&mut self, room_grid_coords: IVec2, direction: IVec2) {
 		let dimensions = IVec2::new(9, 9);
 		let space = IVec2::new(1, 1);
 		let top_left = room_grid_coords * (dimensions + space);
 		let center = top_left + dimensions / 2;
-		let number_of_corridors = if randint(0, 4) == 0 {
+		let number_of_corridors = if self.randint(0, 4) == 0 {
 			0
-		} else if randint(0, 3) == 0 {
-			randint(2, 6)
+		} else if self.randint(0, 3) == 0 {
+			self.randint(2, 6)
 		} else {
 			1
 		};
 		for _ in 0..number_of_corridors {
-			let start = center + direction.perp() * randint(-dimensions.x / 2, dimensions.x / 2);
+			let start = center + direction.perp() * self.randint(-dimensions.x / 2, dimensions.x / 2);
 			self.generate_corridor(start, direction, (dimensions + space).x, 1);
-			if number_of_corridors == 1 && randint(0, 3) == 0 {
+			if number_of_corridors == 1 && self.randint(0, 3) == 0 {
 				let coords = start + direction * ((dimensions + space).x / 2);
 				self.lw.place_tile(coords, Tile::obj(Obj::Door));
+			} else if number_of_corridors == 1 && self.randint(0, 5) == 0 {
+				let coords = start + direction * ((dimensions + space).x / 2);
+				self.generate_gate(coords, top_left, dimensions);
 			}
 		}
 	}
@@ -199,13 +871,17 @@ impl Generator {
 			IVec2::new(grid_x_inf, grid_y_inf),
 			IVec2::new(grid_w, grid_h),
 		)
-		.choose_multiple(&mut thread_rng(), 3)
+		.choose_multiple(&mut self.rng, 3)
 		.copied()
 		.collect();
+		// On the last level, the only exit is in the boss room, next to one of the exit rooms.
+		let boss_room_neighbor = (self.run_config.depth == FINAL_DEPTH).then_some(exit_rooms[0]);
 		for grid_y in grid_y_inf..=grid_y_sup {
 			for grid_x in grid_x_inf..=grid_x_sup {
 				let room_grid_coords = IVec2::new(grid_x, grid_y);
-				self.generate_grid_room(room_grid_coords, exit_rooms.contains(&room_grid_coords));
+				let is_exit_room =
+					boss_room_neighbor.is_none() && exit_rooms.contains(&room_grid_coords);
+				self.generate_grid_room(room_grid_coords, is_exit_room);
 			}
 		}
 		for grid_y in grid_y_inf..=grid_y_sup {
@@ -219,11 +895,167 @@ impl Generator {
 				}
 			}
 		}
+		let rooms = filled_rect(
+			IVec2::new(grid_x_inf, grid_y_inf),
+			IVec2::new(grid_w, grid_h),
+		);
+		for _ in 0..self.randint(1, 2) {
+			self.generate_teleporter_pair(&rooms);
+		}
+		for _ in 0..self.randint(1, 2) {
+			self.generate_barrel_cluster(&rooms);
+		}
+		if self.run_config.sealed_exits {
+			self.generate_key_shards(&rooms);
+		}
+		if let Some(boss_room_neighbor) = boss_room_neighbor {
+			self.generate_boss_room(boss_room_neighbor);
+		}
+	}
+
+	/// A large room out of the grid, next to the given room on the edge of the grid and linked
+	/// to it by a corridor, where the golem boss guards the exit.
+	fn generate_boss_room(&mut self, neighbor_room_grid_coords: IVec2) {
+		let room = neighbor_room_grid_coords;
+		let direction = if room.x.abs() >= room.y.abs() {
+			IVec2::new(room.x.signum(), 0)
+		} else {
+			IVec2::new(0, room.y.signum())
+		};
+		let along = direction.abs();
+		let across = IVec2::new(along.y, along.x);
+		let dimensions = IVec2::new(9, 9);
+		let space = IVec2::new(1, 1);
+		let boss_room_dimensions = IVec2::new(13, 13);
+		// It takes the place of the room that would be next on the grid, and more,
+		// away from the grid and evenly on both sides.
+		let extra_dimensions = boss_room_dimensions - dimensions;
+		let mut top_left = (room + direction) * (dimensions + space) - across * extra_dimensions / 2;
+		if direction.x + direction.y < 0 {
+			top_left -= along * extra_dimensions;
+		}
+		self.generate_empty_room(top_left, boss_room_dimensions);
+		let neighbor_center = room * (dimensions + space) + dimensions / 2;
+		self.generate_corridor(neighbor_center, direction, (dimensions + space).x, 1);
+
+		let center = top_left + boss_room_dimensions / 2;
+		let boss_hp = self.run_config.enemy_hp() * 2;
+		self.lw.place_multi_tile_obj(
+			center - IVec2::ONE,
+			Obj::GolemBoss {
+				hp: boss_hp,
+				max_hp: boss_hp,
+				move_token: false,
+				resting: false,
+				statuses: vec![],
+			},
+		);
+		// On the far side, the boss is in the way.
+		let far_side = boss_room_dimensions / 2 - IVec2::ONE;
+		self.lw.place_tile(center + direction * far_side, Tile::obj(Obj::Exit));
+	}
+
+	/// Some random empty floor tile inside the given room of the grid, if there is any.
+	fn free_floor_in_room(&mut self, room_grid_coords: IVec2) -> Option<IVec2> {
+		let dimensions = IVec2::new(9, 9);
+		let space = IVec2::new(1, 1);
+		let free_coords: Vec<_> =
+			filled_inner_rect(room_grid_coords * (dimensions + space), dimensions)
+				.into_iter()
+				.filter(|coords| {
+					self
+						.lw
+						.tile(*coords)
+						.is_some_and(|tile| matches!(tile.ground, Ground::Floor) && tile.obj.is_none())
+				})
+				.collect();
+		free_coords.choose(&mut self.rng).copied()
+	}
+
+	/// The shards that unseal the exit, each in a different room, none in the starting room.
+	fn generate_key_shards(&mut self, rooms: &[IVec2]) {
+		let other_rooms: Vec<_> =
+			rooms.iter().copied().filter(|room| *room != IVec2::new(0, 0)).collect();
+		let shard_rooms: Vec<_> = other_rooms
+			.choose_multiple(&mut self.rng, KEY_SHARDS_PER_LEVEL as usize)
+			.copied()
+			.collect();
+		for room in shard_rooms {
+			if let Some(coords) = self.free_floor_in_room(room) {
+				self.lw.place_tile(coords, Tile::obj(Obj::KeyShard));
+				self.lw.key_shards_needed += 1;
+			}
+		}
+	}
+
+	/// A few barrels next to each other in a room other than the starting room,
+	/// for one of them going off to set off the others.
+	fn generate_barrel_cluster(&mut self, rooms: &[IVec2]) {
+		let other_rooms: Vec<_> =
+			rooms.iter().copied().filter(|room| *room != IVec2::new(0, 0)).collect();
+		let Some(&room) = other_rooms.choose(&mut self.rng) else {
+			return;
+		};
+		let Some(center) = self.free_floor_in_room(room) else {
+			return;
+		};
+		self.lw.place_tile(center, Tile::obj(Obj::Barrel { set_off: false }));
+		let barrel_count = self.randint(1, 3);
+		let free_neighbors: Vec<_> = four_directions()
+			.into_iter()
+			.map(|direction| center + direction)
+			.filter(|coords| {
+				self
+					.lw
+					.tile(*coords)
+					.is_some_and(|tile| matches!(tile.ground, Ground::Floor) && tile.obj.is_none())
+			})
+			.collect();
+		for coords in free_neighbors.choose_multiple(&mut self.rng, barrel_count as usize) {
+			self.lw.place_tile(*coords, Tile::obj(Obj::Barrel { set_off: false }));
+		}
+	}
+
+	/// Two teleporters linked to each other, in two rooms far apart.
+	fn generate_teleporter_pair(&mut self, rooms: &[IVec2]) {
+		let room_a = *rooms.choose(&mut self.rng).unwrap();
+		let far_rooms: Vec<_> =
+			rooms.iter().filter(|room| (**room - room_a).abs().max_element() >= 3).collect();
+		let Some(&&room_b) = far_rooms.choose(&mut self.rng) else {
+			return;
+		};
+		let pad_coords: Vec<_> =
+			[room_a, room_b].into_iter().filter_map(|room| self.free_floor_in_room(room)).collect();
+		if pad_coords.len() < 2 {
+			return;
+		}
+		let link_id = self.next_link_id;
+		self.next_link_id += 1;
+		for coords in pad_coords {
+			self.lw.place_tile(
+				coords,
+				Tile { ground: Ground::Teleporter { link_id }, obj: None, visible: false },
+			);
+		}
 	}
 }
 
-pub fn generate_level() -> LogicalWorld {
-	let mut generator = Generator::new();
+/// The deeper levels are darker, the player seeing less far there.
+fn base_vision_radius(depth: i32) -> f32 {
+	DEFAULT_BASE_VISION_RADIUS - (depth - 1) as f32 * 0.5
+}
+
+pub fn generate_level(run_config: &RunConfig) -> LogicalWorld {
+	let mut generator = Generator::new(run_config);
 	generator.generate_level();
-	generator.lw
+	// The redos of the next levels are carried over from the first one.
+	if run_config.daily_date.is_some() && run_config.depth == 1 {
+		generator.lw.redo_count = DAILY_REDO_COUNT;
+		generator.lw.max_redo_count = DAILY_REDO_COUNT;
+	}
+	generator.lw.set_assist(run_config.assist);
+	generator.lw.depth = run_config.depth;
+	generator.lw.base_vision_radius = base_vision_radius(run_config.depth);
+	generator.lw.seed_agent_order(run_config.level_seed());
+	generator.lw.generated_walls_outside()
 }
@@ -0,0 +1,89 @@
+//! The ghost of the best won run on the same seed, displayed alongside the current run.
+//!
+//! The position of the bunny after each of its moves is recorded during the run. When the run
+//! is won in fewer moves than the best run recorded on its seed (if any), the positions are saved
+//! in the `ghosts` directory of the user data directory, in one file per seed with one `x,y` line
+//! per move. The same seed generates the same level, so on a later run on that seed, a ghost
+//! bunny is displayed where the bunny of the best run was after as many moves.
+//!
+//! Each level of a run has its own seed, so each level has its own best run, saved when the
+//! bunny goes through its exit.
+
+use std::path::PathBuf;
+
+use ggez::{glam::IVec2, Context};
+
+use crate::gameplay::LogicalWorld;
+
+pub struct Ghost {
+	/// Where the best runs are saved, one file per seed.
+	dir: PathBuf,
+	seed: u64,
+	/// The positions of the best run on the seed, empty if there is none.
+	best_run: Vec<IVec2>,
+	/// The positions of the current run so far, starting with where the bunny started.
+	current_run: Vec<IVec2>,
+}
+
+impl Ghost {
+	pub fn new(ctx: &Context) -> Ghost {
+		Ghost {
+			dir: ctx.fs.user_data_dir().join("ghosts"),
+			seed: 0,
+			best_run: vec![],
+			current_run: vec![],
+		}
+	}
+
+	fn file_path(&self) -> PathBuf {
+		self.dir.join(format!("{}.txt", self.seed))
+	}
+
+	/// Starts recording a new run, and loads the best run on its seed to follow it.
+	pub fn start(&mut self, seed: u64, lw: &LogicalWorld) {
+		self.seed = seed;
+		let content = std::fs::read_to_string(self.file_path()).unwrap_or_default();
+		// A corrupted file is as good as no file, the ghost would go through walls.
+		self.best_run = content
+			.lines()
+			.map(|line| {
+				let (x, y) = line.split_once(',')?;
+				Some(IVec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+			})
+			.collect::<Option<_>>()
+			.unwrap_or_default();
+		self.current_run = lw.player_coords().into_iter().collect();
+	}
+
+	/// Records the position of the bunny after its move.
+	pub fn record(&mut self, lw: &LogicalWorld) {
+		if let Some(player_coords) = lw.player_coords() {
+			self.current_run.push(player_coords);
+		}
+	}
+
+	/// Forgets the last move, that was redone.
+	pub fn undo(&mut self) {
+		if self.current_run.len() > 1 {
+			self.current_run.pop();
+		}
+	}
+
+	/// Where the bunny of the best run was after as many moves as in the current run,
+	/// if there is a best run that lasted that long.
+	pub fn position(&self) -> Option<IVec2> {
+		self.best_run.get(self.current_run.len().checked_sub(1)?).copied()
+	}
+
+	/// The run was won, it becomes the best run on its seed if it took fewer moves.
+	pub fn save_if_best(&self) {
+		if !self.best_run.is_empty() && self.best_run.len() <= self.current_run.len() {
+			return;
+		}
+		let content: String =
+			self.current_run.iter().map(|coords| format!("{},{}\n", coords.x, coords.y)).collect();
+		// Failing to persist is not a big deal, there will just be no ghost.
+		let _ = std::fs::create_dir_all(&self.dir);
+		let _ = std::fs::write(self.file_path(), content);
+	}
+}
@@ -6,36 +6,47 @@
 //! These are like two levels of rendering, the first creates sprites and defines animations,
 //! and the second draws the sprites and plays the animations.
 
-use std::time::{Duration, Instant};
+use std::{cell::Cell, time::Duration};
 
 use ggez::{
-	glam::Vec2,
-	graphics::{Canvas, Color, DrawParam},
+	glam::{IVec2, Vec2},
+	graphics::{Canvas, Color, DrawParam, Image, Quad, Rect, Sampler, Text, TextLayout},
 	Context, GameResult,
 };
 
 use crate::{
-	gameplay::{Ground, LogicalEvent, LogicalTransition, LogicalWorld, Obj},
+	gameplay::{
+		Enchantment, Facing, Ground, LogicalEvent, LogicalTransition, LogicalWorld, Look,
+		MirrorSlant, Obj, Status, Tile,
+	},
+	generation::filled_rect,
+	metrics,
 	spritesheet::{SpriteFromSheet, SpritesheetStuff},
 };
 
-enum DepthLayer {
+pub enum DepthLayer {
 	Floor,
+	Aura,
 	Obj,
 	AnimatedObj,
+	Weather,
 	TemporaryText,
 	Interface,
+	Popup,
 }
 
 impl DepthLayer {
-	fn to_z_value(&self) -> i32 {
+	pub fn to_z_value(&self) -> i32 {
 		// Higer is closer to foreground, lower is closer to background.
 		match self {
 			DepthLayer::Floor => 1,
-			DepthLayer::Obj => 2,
-			DepthLayer::AnimatedObj => 3,
-			DepthLayer::TemporaryText => 4,
-			DepthLayer::Interface => 5,
+			DepthLayer::Aura => 2,
+			DepthLayer::Obj => 3,
+			DepthLayer::AnimatedObj => 4,
+			DepthLayer::Weather => 5,
+			DepthLayer::TemporaryText => 6,
+			DepthLayer::Interface => 7,
+			DepthLayer::Popup => 8,
 		}
 	}
 }
@@ -53,8 +64,90 @@ struct DisplayedSprite {
 	fail_to_move_animation: Option<FailToMoveAnimation>,
 	hit_animation: Option<HitAnimation>,
 	temporary_text_animation: Option<TemporaryTextAnimation>,
+	fall_animation: Option<FallAnimation>,
+	fade_animation: Option<FadeAnimation>,
+	frame_cycle: Option<FrameCycle>,
+	/// Frames played over the move animation, in place of the other frames, while it moves.
+	walk_frames: Option<&'static [SpriteFromSheet]>,
+	/// How many tiles high it is, when the height for scale is not given.
+	size_in_tiles: f32,
+	/// Is it a memory of something out of sight? Then it is drawn dimmed and greyed out.
+	remembered: bool,
+	/// Is it mirrored horizontally?
+	flipped: bool,
 }
 
+/// Over an enemy that lost HP, how much of its max HP it has left.
+struct HealthBar {
+	center: Vec2,
+	/// In tiles.
+	width: f32,
+	/// From 0 (no HP left) to 1 (all of its max HP).
+	fraction: f32,
+	/// It follows the enemy around.
+	move_animation: Option<MoveAnimation>,
+}
+
+impl HealthBar {
+	fn current_position(&self) -> Vec2 {
+		self.move_animation.as_ref().map_or(self.center, MoveAnimation::current_position)
+	}
+}
+
+/// A straight part of the beam of a laser emitter, one tile long at most.
+struct LaserSegment {
+	from: Vec2,
+	to: Vec2,
+}
+
+/// Frames that a sprite cycles through (like the waves of water), in place of its sprite.
+/// All the cycles are in sync, they follow the time since the game started.
+struct FrameCycle {
+	frames: &'static [SpriteFromSheet],
+	frame_duration: Duration,
+}
+
+impl FrameCycle {
+	fn current_frame(&self, time_since_start: Duration) -> &SpriteFromSheet {
+		let frame_index = (time_since_start.as_secs_f32() / self.frame_duration.as_secs_f32())
+			as usize
+			% self.frames.len();
+		&self.frames[frame_index]
+	}
+}
+
+const WATER_FRAMES: [SpriteFromSheet; 3] = [
+	SpriteFromSheet::Water(0),
+	SpriteFromSheet::Water(1),
+	SpriteFromSheet::Water(2),
+];
+
+const LAVA_FRAMES: [SpriteFromSheet; 2] = [SpriteFromSheet::Lava(0), SpriteFromSheet::Lava(1)];
+
+/// Still most of the time, with a quick double twitch of the ear now and then.
+const BUNNY_IDLE_FRAMES: [SpriteFromSheet; 10] = [
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(1),
+	SpriteFromSheet::BunnyIdle(0),
+	SpriteFromSheet::BunnyIdle(1),
+];
+
+/// Up in the air for the first half of the move, landing for the second half.
+const BUNNY_WALK_FRAMES: [SpriteFromSheet; 2] =
+	[SpriteFromSheet::BunnyWalk(0), SpriteFromSheet::BunnyWalk(1)];
+
+const SLIME_SQUISH_FRAMES: [SpriteFromSheet; 3] = [
+	SpriteFromSheet::SlimeSquish(0),
+	SpriteFromSheet::SlimeSquish(0),
+	SpriteFromSheet::SlimeSquish(1),
+];
+
 impl DisplayedSprite {
 	fn new(
 		sprite_from_sheet: SpriteFromSheet,
@@ -70,6 +163,8 @@ impl DisplayedSprite {
 			fail_to_move_animation,
 			hit_animation,
 			temporary_text_animation,
+			fall_animation,
+			fade_animation,
 		} = animations;
 		DisplayedSprite {
 			sprite_from_sheet,
@@ -82,6 +177,56 @@ impl DisplayedSprite {
 			fail_to_move_animation,
 			hit_animation,
 			temporary_text_animation,
+			fall_animation,
+			fade_animation,
+			frame_cycle: None,
+			walk_frames: None,
+			size_in_tiles: 1.0,
+			remembered: false,
+			flipped: false,
+		}
+	}
+
+	/// The sprites look left, so the sprite of an object that looks right is flipped.
+	fn with_facing_of(self, obj: &Obj) -> DisplayedSprite {
+		DisplayedSprite { flipped: obj.facing() == Some(Facing::Right), ..self }
+	}
+
+	fn remembered(self) -> DisplayedSprite {
+		DisplayedSprite { remembered: true, ..self }
+	}
+
+	/// The sprite spans several tiles, like the objects that take more than one tile.
+	fn with_size_in_tiles(self, size_in_tiles: f32) -> DisplayedSprite {
+		DisplayedSprite { size_in_tiles, ..self }
+	}
+
+	/// The sprite cycles through the given frames instead of displaying its sprite.
+	fn with_frame_cycle(
+		self,
+		frames: &'static [SpriteFromSheet],
+		frame_duration: Duration,
+	) -> DisplayedSprite {
+		DisplayedSprite { frame_cycle: Some(FrameCycle { frames, frame_duration }), ..self }
+	}
+
+	fn with_walk_frames(self, walk_frames: &'static [SpriteFromSheet]) -> DisplayedSprite {
+		DisplayedSprite { walk_frames: Some(walk_frames), ..self }
+	}
+
+	fn current_sprite(&self, time_since_start: Duration) -> &SpriteFromSheet {
+		let walk_progress = self
+			.move_animation
+			.as_ref()
+			.map(|anim| anim.time_interval.progress())
+			.filter(|&progress| progress < 1.0);
+		if let (Some(walk_frames), Some(progress)) = (self.walk_frames, walk_progress) {
+			let frame_index = (progress * walk_frames.len() as f32) as usize;
+			return &walk_frames[frame_index.min(walk_frames.len() - 1)];
+		}
+		match self.frame_cycle.as_ref() {
+			Some(frame_cycle) => frame_cycle.current_frame(time_since_start),
+			None => &self.sprite_from_sheet,
 		}
 	}
 
@@ -96,10 +241,17 @@ impl DisplayedSprite {
 				.temporary_text_animation
 				.as_ref()
 				.is_some_and(|anim| anim.time_interval.progress() < 1.0)
+			|| self.fall_animation.as_ref().is_some_and(|anim| anim.time_interval.progress() < 1.0)
+			|| self.fade_animation.as_ref().is_some_and(|anim| anim.time_interval.progress() < 1.0)
 	}
 
 	fn visible(&self) -> bool {
-		if let Some(move_animation) = self.move_animation.as_ref() {
+		if let Some(fall_animation) = self.fall_animation.as_ref() {
+			fall_animation.time_interval.progress() < 1.0
+		} else if let Some(fade_animation) = self.fade_animation.as_ref().filter(|anim| !anim.fade_in)
+		{
+			fade_animation.time_interval.progress() < 1.0
+		} else if let Some(move_animation) = self.move_animation.as_ref() {
 			move_animation.currently_visible()
 		} else if let Some(temporary_text_animation) = self.temporary_text_animation.as_ref() {
 			temporary_text_animation.currently_visible()
@@ -109,7 +261,9 @@ impl DisplayedSprite {
 	}
 
 	fn center(&self) -> Vec2 {
-		if let Some(move_animation) = self.move_animation.as_ref() {
+		if let Some(fall_animation) = self.fall_animation.as_ref() {
+			fall_animation.current_position()
+		} else if let Some(move_animation) = self.move_animation.as_ref() {
 			move_animation.current_position()
 		} else if let Some(fail_to_move_animation) = self.fail_to_move_animation.as_ref() {
 			fail_to_move_animation.current_position()
@@ -130,42 +284,150 @@ impl DisplayedSprite {
 		}
 		.or(self.plain_color)
 	}
+
+	/// How big the sprite is, relative to its normal size.
+	fn scale(&self) -> f32 {
+		self.fall_animation.as_ref().map_or(1.0, |anim| anim.current_scale())
+	}
+
+	/// How opaque the sprite is, from 0 (invisible) to 1.
+	fn alpha(&self) -> f32 {
+		self.fade_animation.as_ref().map_or(1.0, |anim| anim.current_alpha())
+	}
 }
 
-fn obj_to_sprite(obj: &Obj) -> SpriteFromSheet {
+pub fn obj_to_sprite(obj: &Obj) -> SpriteFromSheet {
 	match obj {
 		Obj::Wall => SpriteFromSheet::Wall,
-		Obj::Sword => SpriteFromSheet::Sword,
+		Obj::Sword { .. } => SpriteFromSheet::Sword,
 		Obj::Shield => SpriteFromSheet::Shield,
-		Obj::Pickaxe => SpriteFromSheet::Pickaxe,
+		Obj::Pickaxe { .. } => SpriteFromSheet::Pickaxe,
 		Obj::Rock => SpriteFromSheet::Rock,
 		Obj::Door => SpriteFromSheet::Door,
 		Obj::Key => SpriteFromSheet::Key,
 		Obj::Rope => SpriteFromSheet::Rope,
 		Obj::Bush => SpriteFromSheet::Bush,
+		Obj::Note => SpriteFromSheet::Note,
+		Obj::Piston { .. } => SpriteFromSheet::Piston,
+		Obj::PistonHead => SpriteFromSheet::PistonHead,
+		Obj::Gate { .. } => SpriteFromSheet::Gate,
+		Obj::Statue { facing, .. } => SpriteFromSheet::Statue(*facing),
+		Obj::Altar { .. } => SpriteFromSheet::Altar,
+		Obj::Bomb { .. } => SpriteFromSheet::Bomb,
+		Obj::Barrel { set_off: false } => SpriteFromSheet::Barrel,
+		Obj::Barrel { set_off: true } => SpriteFromSheet::BarrelSetOff,
+		Obj::Bow => SpriteFromSheet::Bow,
+		Obj::Torch => SpriteFromSheet::Torch,
+		Obj::Magnet => SpriteFromSheet::Magnet,
+		Obj::Crate { .. } => SpriteFromSheet::Crate,
+		Obj::StrengthPotion => SpriteFromSheet::StrengthPotion,
+		Obj::Raft => SpriteFromSheet::Raft,
+		Obj::LaserEmitter { facing } => SpriteFromSheet::LaserEmitter(*facing),
+		Obj::Mirror { slant: MirrorSlant::Rising } => SpriteFromSheet::MirrorRising,
+		Obj::Mirror { slant: MirrorSlant::Falling } => SpriteFromSheet::MirrorFalling,
+		Obj::Arrow { direction, .. } => SpriteFromSheet::Arrow(*direction),
+		Obj::CursedMirror => SpriteFromSheet::CursedMirror,
+		Obj::Coin => SpriteFromSheet::Coin,
+		Obj::KeyShard => SpriteFromSheet::KeyShard,
+		Obj::SlimeCorpse => SpriteFromSheet::SlimeCorpse,
 		Obj::Exit => SpriteFromSheet::Exit,
 		Obj::VisionGem => SpriteFromSheet::VisionGem,
+		Obj::VisionSword => SpriteFromSheet::VisionSword,
 		Obj::Heart => SpriteFromSheet::Heart,
 		Obj::RedoHeart => SpriteFromSheet::RedoHeart,
+		Obj::RewindToken => SpriteFromSheet::RewindToken,
 		Obj::Bunny { .. } => SpriteFromSheet::Bunny,
 		Obj::Slime { .. } => SpriteFromSheet::Slime,
 		Obj::Shroomer { .. } => SpriteFromSheet::Shroomer,
+		Obj::Archer { .. } => SpriteFromSheet::Archer,
+		Obj::Ghost { .. } => SpriteFromSheet::Ghost,
+		Obj::GolemBoss { .. } => SpriteFromSheet::GolemBoss,
 		Obj::Shroom { .. } => SpriteFromSheet::Shroom,
 		Obj::Fish { direction, .. } => SpriteFromSheet::Fish(*direction),
+		Obj::Golem { .. } => SpriteFromSheet::Golem,
 	}
 }
 
+/// The sprite of the object, unless the level gave it a look of its own.
+pub fn looked_obj_to_sprite(obj: &Obj, look: Option<&Look>) -> SpriteFromSheet {
+	match look.filter(|look| look.is_for(obj)).and_then(|look| look.drawn_as.as_ref()) {
+		Some(drawn_as) => obj_to_sprite(drawn_as),
+		None => obj_to_sprite(obj),
+	}
+}
+
+pub fn ground_to_sprite(ground: &Ground) -> SpriteFromSheet {
+	match ground {
+		Ground::Floor => SpriteFromSheet::Floor,
+		Ground::PressurePlate { .. } => SpriteFromSheet::PressurePlate,
+		Ground::Hole => SpriteFromSheet::Hole,
+		Ground::Ice => SpriteFromSheet::Ice,
+		Ground::FragileFloor => SpriteFromSheet::FragileFloor,
+		// The raft floats on water.
+		Ground::Water | Ground::Raft => SpriteFromSheet::Water(0),
+		Ground::Conveyor { direction } => SpriteFromSheet::Conveyor(*direction),
+		Ground::OpenGate { .. } => SpriteFromSheet::OpenGate,
+		Ground::Teleporter { .. } => SpriteFromSheet::Teleporter,
+		Ground::Lava => SpriteFromSheet::Lava(0),
+		Ground::Spikes { armed: false } => SpriteFromSheet::Spikes,
+		Ground::Spikes { armed: true } => SpriteFromSheet::SpikesArmed,
+		Ground::Rubble => SpriteFromSheet::Rubble,
+		Ground::Price { .. } => SpriteFromSheet::Price,
+	}
+}
+
+/// Enchanted weapons and afflicted agents have an aura displayed around them.
+fn obj_to_aura_sprite(obj: &Obj) -> Option<SpriteFromSheet> {
+	let statuses = obj.statuses();
+	if matches!(obj.enchantment(), Some(Enchantment::Flaming))
+		|| statuses.iter().any(|status| matches!(status, Status::Burning { .. }))
+	{
+		Some(SpriteFromSheet::FlameAura)
+	} else if matches!(obj.enchantment(), Some(Enchantment::Frost))
+		|| statuses.iter().any(|status| matches!(status, Status::Frozen { .. }))
+	{
+		Some(SpriteFromSheet::FrostAura)
+	} else {
+		None
+	}
+}
+
+fn status_to_icon_sprite(status: Status) -> SpriteFromSheet {
+	match status {
+		Status::Burning { .. } => SpriteFromSheet::StatusBurning,
+		Status::Poisoned { .. } => SpriteFromSheet::StatusPoisoned,
+		Status::Stunned { .. } => SpriteFromSheet::StatusStunned,
+		Status::Frozen { .. } => SpriteFromSheet::StatusFrozen,
+	}
+}
+
+/// How big the status icons are, in tiles.
+const STATUS_ICON_SIZE: f32 = 0.4;
+
+/// How big the digits of the prices in shops are, in tiles.
+const PRICE_DIGIT_SIZE: f32 = 0.3;
+
+/// How big the tool held by the bunny is, in tiles.
+const HELD_ITEM_SIZE: f32 = 0.5;
+
 /// The world, as a set of animated sprites, to be displayed.
 /// It represents a logical world or even a transition to a logical world,
 /// but the logical nature of things is lost to sprites, it is a render in a sense.
 pub struct GraphicalWorld {
 	sprites: Vec<DisplayedSprite>,
+	health_bars: Vec<HealthBar>,
+	laser_segments: Vec<LaserSegment>,
 	pub info_for_camera: InfoForCamera,
 }
 
 impl GraphicalWorld {
 	pub fn new() -> GraphicalWorld {
-		GraphicalWorld { sprites: vec![], info_for_camera: InfoForCamera::new() }
+		GraphicalWorld {
+			sprites: vec![],
+			health_bars: vec![],
+			laser_segments: vec![],
+			info_for_camera: InfoForCamera::new(),
+		}
 	}
 
 	pub fn from_logical_world(lw: &LogicalWorld) -> GraphicalWorld {
@@ -183,46 +445,103 @@ impl GraphicalWorld {
 	/// using animations to convey the transition, and making sure that as animations end
 	/// the remaining representation depicts the logical world that results from the transition.
 	pub fn from_logical_world_transition(transition: &LogicalTransition) -> GraphicalWorld {
+		let _timer = metrics::time("graphical world build");
 		let mut gw = GraphicalWorld::new();
-		let mut bunny_copy = None;
 		// We iterate over all the tiles, creating sprites to represent their content.
 		for (coords, tile) in transition.resulting_lw.tiles() {
 			if !tile.visible {
+				if let Some(remembered_tile) = transition.resulting_lw.last_seen(coords) {
+					let look = transition.resulting_lw.look(coords);
+					for sprite in remembered_tile_sprites(coords, remembered_tile, look) {
+						gw.add_sprite(sprite);
+					}
+				}
 				continue;
 			}
 			// Ground.
-			if matches!(tile.ground, Ground::Floor) {
+			let ground_sprite = match tile.ground {
+				// The cracks tell that it will collapse once the object leaves.
+				Ground::FragileFloor if tile.obj.is_some() => SpriteFromSheet::FragileFloorCracked,
+				_ => ground_to_sprite(&tile.ground),
+			};
+			let ground_sprite = DisplayedSprite::new(
+				ground_sprite,
+				coords.as_vec2(),
+				DepthLayer::Floor,
+				true,
+				None,
+				None,
+				Animations::new(None, None, None, None),
+			);
+			gw.add_sprite(match tile.ground {
+				Ground::Water | Ground::Raft =>
+					ground_sprite.with_frame_cycle(&WATER_FRAMES, Duration::from_secs_f32(0.5)),
+				Ground::Lava =>
+					ground_sprite.with_frame_cycle(&LAVA_FRAMES, Duration::from_secs_f32(0.7)),
+				_ => ground_sprite,
+			});
+			if let Ground::Price { price } = tile.ground {
+				// The price is written under what is for sale.
+				let digits = number_sprites(price);
+				let row_start = -((digits.len() - 1) as f32) * PRICE_DIGIT_SIZE / 2.0;
+				for (index, digit) in digits.into_iter().enumerate() {
+					gw.add_sprite(
+						DisplayedSprite::new(
+							digit,
+							coords.as_vec2() + Vec2::new(row_start + index as f32 * PRICE_DIGIT_SIZE, 0.3),
+							DepthLayer::TemporaryText,
+							true,
+							Some(Color::YELLOW),
+							None,
+							Animations::new(None, None, None, None),
+						)
+						.with_size_in_tiles(PRICE_DIGIT_SIZE),
+					);
+				}
+			}
+			if matches!(tile.ground, Ground::Raft) {
+				// It may just have been pushed there.
+				let moored_from =
+					transition.logical_events.iter().find_map(|logical_event| match logical_event {
+						LogicalEvent::RaftMoored { from, at } if *at == coords => Some(*from),
+						_ => None,
+					});
 				gw.add_sprite(DisplayedSprite::new(
-					SpriteFromSheet::Floor,
+					SpriteFromSheet::Raft,
 					coords.as_vec2(),
-					DepthLayer::Floor,
+					DepthLayer::Aura,
 					true,
 					None,
 					None,
-					Animations::new(None, None, None, None),
+					Animations::new(
+						moored_from.map(|from| MoveAnimation::new(from.as_vec2(), coords.as_vec2())),
+						None,
+						None,
+						None,
+					),
 				));
 			}
 			// Object.
 			if let Some(obj) = tile.obj.as_ref() {
-				let sprite_from_sheet = obj_to_sprite(obj);
+				let sprite_from_sheet = match obj {
+					Obj::Exit if transition.resulting_lw.is_exit_sealed() => SpriteFromSheet::ExitSealed,
+					_ => looked_obj_to_sprite(obj, transition.resulting_lw.look(coords)),
+				};
 				if matches!(obj, Obj::Bunny { .. }) {
-					bunny_copy = Some(obj);
 					gw.info_for_camera.player_position = Some(coords.as_vec2());
 				}
+				// A multi-tile object is displayed over all of its footprint, from its anchor tile.
+				let footprint = obj.footprint();
+				let offset = (footprint - IVec2::ONE).as_vec2() / 2.0;
 				// If the object is mentioned by a logical event of the transition,
 				// then it may be animated to represent that event happening.
-				let move_animation =
-					transition.logical_events.iter().find_map(|logical_event| match logical_event {
-						LogicalEvent::Move { from, to, .. } if *to == coords => {
-							Some(MoveAnimation::new(from.as_vec2(), to.as_vec2()))
-						},
-						_ => None,
-					});
+				let move_animation = moved_from(transition, coords)
+					.map(|from| MoveAnimation::new(from.as_vec2() + offset, coords.as_vec2() + offset));
 				let fail_to_move_animation =
 					transition.logical_events.iter().find_map(|logical_event| match logical_event {
-						LogicalEvent::FailToMove { from, to, .. } if *from == coords => {
-							Some(FailToMoveAnimation::new(from.as_vec2(), to.as_vec2()))
-						},
+						LogicalEvent::FailToMove { from, to, .. } if *from == coords => Some(
+							FailToMoveAnimation::new(from.as_vec2() + offset, to.as_vec2() + offset),
+						),
 						_ => None,
 					});
 				let hit_animation = {
@@ -237,47 +556,175 @@ impl GraphicalWorld {
 				} else {
 					DepthLayer::Obj
 				};
-				gw.add_sprite(DisplayedSprite::new(
+				if let Some(aura_sprite) = obj_to_aura_sprite(obj) {
+					// The aura follows the object around.
+					gw.add_sprite(DisplayedSprite::new(
+						aura_sprite,
+						coords.as_vec2(),
+						DepthLayer::Aura,
+						true,
+						None,
+						None,
+						Animations::new(
+							move_animation.clone(),
+							fail_to_move_animation.clone(),
+							None,
+							None,
+						),
+					));
+				}
+				if let Obj::Bomb { fuse: Some(fuse) } = obj {
+					// The countdown is displayed over the bomb, and it follows the bomb around.
+					let offset = Vec2::new(0.0, -0.75);
+					let countdown_move_animation = moved_from(transition, coords).map(|from| {
+						MoveAnimation::new(from.as_vec2() + offset, coords.as_vec2() + offset)
+					});
+					gw.add_sprite(DisplayedSprite::new(
+						SpriteFromSheet::Digit(*fuse as u8),
+						coords.as_vec2() + offset,
+						DepthLayer::TemporaryText,
+						true,
+						Some(Color::WHITE),
+						None,
+						Animations::new(countdown_move_animation, None, None, None),
+					));
+				}
+				// The icons of the statuses are displayed in a row over the object,
+				// and they follow the object around.
+				let statuses = obj.statuses();
+				for (index, status) in statuses.iter().enumerate() {
+					let row_start = -((statuses.len() - 1) as f32) * STATUS_ICON_SIZE / 2.0;
+					let icon_offset = offset
+						+ Vec2::new(
+							row_start + index as f32 * STATUS_ICON_SIZE,
+							-(footprint.y as f32) / 2.0 - 0.1,
+						);
+					let icon_move_animation = moved_from(transition, coords).map(|from| {
+						MoveAnimation::new(from.as_vec2() + icon_offset, coords.as_vec2() + icon_offset)
+					});
+					gw.add_sprite(
+						DisplayedSprite::new(
+							status_to_icon_sprite(*status),
+							coords.as_vec2() + icon_offset,
+							DepthLayer::TemporaryText,
+							true,
+							None,
+							None,
+							Animations::new(icon_move_animation, None, None, None),
+						)
+						.with_size_in_tiles(STATUS_ICON_SIZE),
+					);
+				}
+				if let (Some(hp), Some(max_hp)) = (obj.hp(), obj.max_hp()) {
+					if !matches!(obj, Obj::Bunny { .. }) && 0 < max_hp && hp < max_hp {
+						// Above the status icons if there are any.
+						let bar_y = if statuses.is_empty() {
+							-(footprint.y as f32) / 2.0 - 0.05
+						} else {
+							-(footprint.y as f32) / 2.0 - 0.35
+						};
+						let bar_offset = offset + Vec2::new(0.0, bar_y);
+						gw.health_bars.push(HealthBar {
+							center: coords.as_vec2() + bar_offset,
+							width: footprint.x as f32 * 0.8,
+							fraction: (hp.max(0) as f32 / max_hp as f32).min(1.0),
+							move_animation: moved_from(transition, coords).map(|from| {
+								MoveAnimation::new(
+									from.as_vec2() + bar_offset,
+									coords.as_vec2() + bar_offset,
+								)
+							}),
+						});
+					}
+				}
+				let teleported = transition.logical_events.iter().any(
+					|logical_event| matches!(logical_event, LogicalEvent::Teleported { to, .. } if *to == coords),
+				);
+				let obj_sprite = DisplayedSprite::new(
 					sprite_from_sheet,
-					coords.as_vec2(),
+					coords.as_vec2() + offset,
 					depth_layer,
 					true,
 					None,
 					None,
-					Animations::new(move_animation, fail_to_move_animation, hit_animation, None),
-				));
+					if teleported {
+						// It appears once it has vanished from the other teleporter.
+						Animations::fade(None, FadeAnimation::new(true))
+					} else {
+						Animations::new(move_animation, fail_to_move_animation, hit_animation, None)
+					},
+				)
+				.with_size_in_tiles(footprint.y as f32)
+				.with_facing_of(obj);
+				if let Some(held) = obj.held() {
+					// Carried in its paws, on the side it looks towards.
+					let held_offset = offset
+						+ match obj.facing() {
+							Some(Facing::Right) => Vec2::new(0.3, 0.2),
+							_ => Vec2::new(-0.3, 0.2),
+						};
+					let held_move_animation = moved_from(transition, coords).map(|from| {
+						MoveAnimation::new(from.as_vec2() + held_offset, coords.as_vec2() + held_offset)
+					});
+					gw.add_sprite(
+						DisplayedSprite::new(
+							obj_to_sprite(held),
+							coords.as_vec2() + held_offset,
+							DepthLayer::AnimatedObj,
+							true,
+							None,
+							None,
+							Animations::new(held_move_animation, None, None, None),
+						)
+						.with_size_in_tiles(HELD_ITEM_SIZE),
+					);
+				}
+				gw.add_sprite(match obj {
+					Obj::Bunny { .. } => obj_sprite
+						.with_frame_cycle(&BUNNY_IDLE_FRAMES, Duration::from_secs_f32(0.2))
+						.with_walk_frames(&BUNNY_WALK_FRAMES),
+					Obj::Slime { .. } =>
+						obj_sprite.with_frame_cycle(&SLIME_SQUISH_FRAMES, Duration::from_secs_f32(0.3)),
+					_ => obj_sprite,
+				});
 			}
 		}
+		gw.add_laser_beams(&transition.resulting_lw);
+		gw.info_for_camera.heavy_hit_direction = heavy_hit_direction(transition);
+		gw.info_for_camera.rumble = rumbles(transition);
+		gw.info_for_camera.player_hurt = player_hurt(transition);
 		// Some sprites represent events which are not exactly representations of tiles.
 		for logical_event in transition.logical_events.iter() {
 			match logical_event {
-				LogicalEvent::Killed { at, damages, .. } | LogicalEvent::Hit { at, damages, .. } => {
+				LogicalEvent::Killed { at, damages, .. } | LogicalEvent::Hit { at, damages, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
 					// When damages are dealt, a damage number shall appear and float away.
-					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) {
-						gw.add_sprite(DisplayedSprite::new(
-							SpriteFromSheet::Digit(*damages as u8),
-							at.as_vec2(),
-							DepthLayer::TemporaryText,
-							true,
+					gw.add_sprite(DisplayedSprite::new(
+						SpriteFromSheet::Digit(*damages as u8),
+						at.as_vec2(),
+						DepthLayer::TemporaryText,
+						true,
+						None,
+						None,
+						Animations::new(
 							None,
 							None,
-							Animations::new(
-								None,
-								None,
-								None,
-								Some(TemporaryTextAnimation::new(
-									at.as_vec2() + Vec2::new(0.0, -0.5),
-									at.as_vec2() + Vec2::new(0.0, -1.5),
-									Color::RED,
-								)),
-							),
-						));
-					}
+							None,
+							Some(TemporaryTextAnimation::new(
+								at.as_vec2() + Vec2::new(0.0, -0.5),
+								at.as_vec2() + Vec2::new(0.0, -1.5),
+								Color::RED,
+							)),
+						),
+					));
 				},
-				LogicalEvent::Exit { obj, from, to } | LogicalEvent::MoveInto { obj, from, to } => {
-					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) {
-						let sprite_from_sheet = obj_to_sprite(obj);
-						gw.add_sprite(DisplayedSprite::new(
+				LogicalEvent::Exit { obj, from, to } | LogicalEvent::MoveInto { obj, from, to }
+					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) =>
+				{
+					let sprite_from_sheet = obj_to_sprite(obj);
+					gw.add_sprite(
+						DisplayedSprite::new(
 							sprite_from_sheet,
 							to.as_vec2(),
 							DepthLayer::AnimatedObj,
@@ -293,52 +740,267 @@ impl GraphicalWorld {
 								None,
 								None,
 							),
-						));
-					}
+						)
+						.with_facing_of(obj),
+					);
 				},
-				LogicalEvent::DoorOpenedWithKey { key_obj, door_obj, from, to } => {
-					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) {
-						gw.add_sprite(DisplayedSprite::new(
-							obj_to_sprite(key_obj),
-							to.as_vec2(),
+				LogicalEvent::Shot { direction, from, to }
+					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) =>
+				{
+					// The projectile flies over all the tiles to the player, and vanishes there.
+					gw.add_sprite(DisplayedSprite::new(
+						SpriteFromSheet::Arrow(*direction),
+						to.as_vec2(),
+						DepthLayer::AnimatedObj,
+						true,
+						None,
+						None,
+						Animations::new(
+							Some(MoveAnimation::new_disappear_after(
+								from.as_vec2(),
+								to.as_vec2(),
+							)),
+							None,
+							None,
+							None,
+						),
+					));
+				},
+				LogicalEvent::FellIntoHole { obj, from, at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sprite(
+						DisplayedSprite::new(
+							obj_to_sprite(obj),
+							at.as_vec2(),
 							DepthLayer::AnimatedObj,
 							true,
 							None,
 							None,
-							Animations::new(
-								Some(MoveAnimation::new_disappear_after(
-									from.as_vec2(),
-									to.as_vec2(),
-								)),
-								None,
-								None,
-								None,
-							),
-						));
-						gw.add_sprite(DisplayedSprite::new(
-							obj_to_sprite(door_obj),
-							to.as_vec2(),
+							Animations::fall(FallAnimation::new(from.as_vec2(), at.as_vec2())),
+						)
+						.with_facing_of(obj),
+					);
+				},
+				LogicalEvent::Sank { obj, from, at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					// Like falling into a hole, but tinted by the water it sinks in.
+					gw.add_sprite(
+						DisplayedSprite::new(
+							obj_to_sprite(obj),
+							at.as_vec2(),
 							DepthLayer::AnimatedObj,
 							true,
+							Some(Color::new(0.3, 0.5, 0.9, 1.0)),
 							None,
+							Animations::fall(FallAnimation::new(from.as_vec2(), at.as_vec2())),
+						)
+						.with_facing_of(obj),
+					);
+				},
+				LogicalEvent::BurntUp { obj, from, at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					// Like sinking, but glowing with the heat of the lava.
+					gw.add_sprite(
+						DisplayedSprite::new(
+							obj_to_sprite(obj),
+							at.as_vec2(),
+							DepthLayer::AnimatedObj,
+							true,
+							Some(Color::new(1.0, 0.5, 0.1, 1.0)),
 							None,
-							Animations::new(
-								Some(MoveAnimation::new_disappear_after(
-									to.as_vec2(),
-									to.as_vec2(),
-								)),
-								None,
-								None,
-								None,
+							Animations::fall(FallAnimation::new(from.as_vec2(), at.as_vec2())),
+						)
+						.with_facing_of(obj),
+					);
+				},
+				LogicalEvent::Teleported { obj, from, .. }
+					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) =>
+				{
+					// It gets onto the teleporter, and vanishes.
+					gw.add_sprite(
+						DisplayedSprite::new(
+							obj_to_sprite(obj),
+							from.as_vec2(),
+							DepthLayer::AnimatedObj,
+							true,
+							None,
+							None,
+							Animations::fade(
+								moved_from(transition, *from)
+									.map(|before| MoveAnimation::new(before.as_vec2(), from.as_vec2())),
+								FadeAnimation::new(false),
 							),
+						)
+						.with_facing_of(obj),
+					);
+				},
+				LogicalEvent::GateOpened { at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					// The bars shrink into the ground.
+					gw.add_sprite(DisplayedSprite::new(
+						SpriteFromSheet::Gate,
+						at.as_vec2(),
+						DepthLayer::AnimatedObj,
+						true,
+						None,
+						None,
+						Animations::fall(FallAnimation::new(at.as_vec2(), at.as_vec2())),
+					));
+				},
+				LogicalEvent::FloorCollapsed { at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sprite(DisplayedSprite::new(
+						SpriteFromSheet::FragileFloorCracked,
+						at.as_vec2(),
+						DepthLayer::Aura,
+						true,
+						None,
+						None,
+						Animations::fall(FallAnimation::new(at.as_vec2(), at.as_vec2())),
+					));
+				},
+				LogicalEvent::DoorOpenedWithKey { key_obj, door_obj, from, to }
+					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sprite(DisplayedSprite::new(
+						obj_to_sprite(key_obj),
+						to.as_vec2(),
+						DepthLayer::AnimatedObj,
+						true,
+						None,
+						None,
+						Animations::new(
+							Some(MoveAnimation::new_disappear_after(
+								from.as_vec2(),
+								to.as_vec2(),
+							)),
+							None,
+							None,
+							None,
+						),
+					));
+					gw.add_sprite(DisplayedSprite::new(
+						obj_to_sprite(door_obj),
+						to.as_vec2(),
+						DepthLayer::AnimatedObj,
+						true,
+						None,
+						None,
+						Animations::new(
+							Some(MoveAnimation::new_disappear_after(
+								to.as_vec2(),
+								to.as_vec2(),
+							)),
+							None,
+							None,
+							None,
+						),
+					));
+				},
+				LogicalEvent::PickedUp { obj, from, to }
+					if transition.resulting_lw.tile(*from).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sprite(DisplayedSprite::new(
+						obj_to_sprite(obj),
+						to.as_vec2(),
+						DepthLayer::AnimatedObj,
+						true,
+						None,
+						None,
+						Animations::new(
+							Some(MoveAnimation::new_disappear_after(
+								from.as_vec2(),
+								to.as_vec2(),
+							)),
+							None,
+							None,
+							None,
+						),
+					));
+				},
+				LogicalEvent::Crafted { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					// Crafting makes some sparkles burst out of the result.
+					gw.add_sparkle_burst(*at, Color::YELLOW);
+				},
+				LogicalEvent::Bought { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::YELLOW);
+				},
+				LogicalEvent::KeyShardGained { .. } if !transition.resulting_lw.is_exit_sealed() => {
+					// The last shard unseals the exits, that let out some sparkles.
+					for (coords, tile) in transition.resulting_lw.tiles() {
+						if tile.visible && matches!(tile.obj, Some(Obj::Exit)) {
+							gw.add_sparkle_burst(coords, Color::GREEN);
+						}
+					}
+				},
+				LogicalEvent::CrateBroken { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::new(0.6, 0.4, 0.2, 1.0));
+				},
+				LogicalEvent::StrengthGained { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::new(1.0, 0.5, 0.0, 1.0));
+				},
+				LogicalEvent::BarrelSetOff { at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::YELLOW);
+				},
+				LogicalEvent::LaserFired { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::RED);
+				},
+				LogicalEvent::Squelched { at }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::GREEN);
+				},
+				LogicalEvent::Revived { at } => {
+					gw.add_sparkle_burst(*at, Color::WHITE);
+				},
+				LogicalEvent::Exploded { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::new(1.0, 0.5, 0.0, 1.0));
+					// A flash over the whole area of the explosion, that quickly fades away.
+					for coords in filled_rect(*at - IVec2::new(1, 1), IVec2::new(3, 3)) {
+						gw.add_sprite(DisplayedSprite::new(
+							SpriteFromSheet::Floor,
+							coords.as_vec2(),
+							DepthLayer::AnimatedObj,
+							true,
+							Some(Color::new(1.0, 0.8, 0.3, 0.8)),
+							None,
+							Animations::fall(FallAnimation::new(coords.as_vec2(), coords.as_vec2())),
 						));
 					}
 				},
+				LogicalEvent::AltarAwakened { at, .. }
+					if transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible) =>
+				{
+					gw.add_sparkle_burst(*at, Color::MAGENTA);
+				},
 				_ => {},
 			}
 		}
+		gw
+	}
 
-		// Interface.
+	/// The interface, as a graphical world of sprites that are not in the world.
+	fn from_hud_values(values: &HudValues) -> GraphicalWorld {
+		let mut gw = GraphicalWorld::new();
 		let interface_scale = 5.0;
 		let char_height = 5.0 * interface_scale;
 		let char_width = 3.0 * interface_scale;
@@ -346,7 +1008,7 @@ impl GraphicalWorld {
 		let heart_width = 7.0 * interface_scale;
 		let heart_height = 8.0 * interface_scale;
 		let heart_rescale = 5.0 / 6.0;
-		let heart_y_offset = -1.0 * interface_scale;
+		let heart_y_offset = -interface_scale;
 		let mut add_char_sprite =
 			|sprite_from_sheet: SpriteFromSheet, center: Vec2, height: f32, white: bool| {
 				gw.add_sprite(DisplayedSprite::new(
@@ -360,75 +1022,134 @@ impl GraphicalWorld {
 				));
 			};
 		let ui_x = 15.0;
+		let mut add_icon = |sprite_from_sheet: SpriteFromSheet, base_y: f32| {
+			add_char_sprite(
+				sprite_from_sheet,
+				Vec2::new(ui_x, base_y + heart_y_offset)
+					+ Vec2::new(heart_width, heart_height) * heart_rescale / 2.0,
+				heart_height * heart_rescale,
+				false,
+			);
+		};
+		let mut chars = vec![];
+		// Characters after the icon of a line, counting columns from the first character.
+		let mut add_chars = |sprites: Vec<SpriteFromSheet>, base_y: f32, first_column: usize| {
+			for (index, sprite_from_sheet) in sprites.into_iter().enumerate() {
+				let column = (first_column + index) as f32;
+				chars.push((
+					sprite_from_sheet,
+					Vec2::new(ui_x, base_y)
+						+ Vec2::new(char_width, char_height) / 2.0
+						+ Vec2::new(
+							heart_width + char_width * column + space_width * (column + 1.0),
+							0.0,
+						),
+				));
+			}
+		};
 
 		// Redo count.
 		let base_y = 20.0;
-		add_char_sprite(
-			SpriteFromSheet::RedoHeart,
-			Vec2::new(ui_x, base_y + heart_y_offset)
-				+ Vec2::new(heart_width, heart_height) * heart_rescale / 2.0,
-			heart_height * heart_rescale,
-			false,
-		);
-		add_char_sprite(
-			SpriteFromSheet::Digit(transition.resulting_lw.redo_count as u8),
-			Vec2::new(ui_x, base_y)
-				+ Vec2::new(char_width, char_height) / 2.0
-				+ Vec2::new(heart_width + space_width, 0.0),
-			char_height,
-			true,
-		);
-		add_char_sprite(
-			SpriteFromSheet::Slash,
-			Vec2::new(ui_x, base_y)
-				+ Vec2::new(char_width, char_height) / 2.0
-				+ Vec2::new(heart_width + char_width + space_width * 2.0, 0.0),
-			char_height,
-			true,
-		);
-		add_char_sprite(
-			SpriteFromSheet::Digit(transition.resulting_lw.max_redo_count as u8),
-			Vec2::new(ui_x, base_y)
-				+ Vec2::new(char_width, char_height) / 2.0
-				+ Vec2::new(heart_width + char_width * 2.0 + space_width * 3.0, 0.0),
-			char_height,
-			true,
+		add_icon(SpriteFromSheet::RedoHeart, base_y);
+		add_chars(
+			number_out_of_sprites(values.redo_count, values.max_redo_count),
+			base_y,
+			0,
 		);
 
 		// HP count.
-		if let Some(Obj::Bunny { hp, max_hp }) = bunny_copy {
+		if let Some((hp, max_hp)) = values.hp {
 			let base_y = 60.0;
-			add_char_sprite(
-				SpriteFromSheet::Heart,
-				Vec2::new(ui_x, base_y + heart_y_offset)
-					+ Vec2::new(heart_width, heart_height) * heart_rescale / 2.0,
-				heart_height * heart_rescale,
-				false,
-			);
-			add_char_sprite(
-				SpriteFromSheet::Digit(*hp as u8),
-				Vec2::new(ui_x, base_y)
-					+ Vec2::new(char_width, char_height) / 2.0
-					+ Vec2::new(heart_width + space_width, 0.0),
-				char_height,
-				true,
-			);
-			add_char_sprite(
-				SpriteFromSheet::Slash,
-				Vec2::new(ui_x, base_y)
-					+ Vec2::new(char_width, char_height) / 2.0
-					+ Vec2::new(heart_width + char_width + space_width * 2.0, 0.0),
-				char_height,
-				true,
-			);
-			add_char_sprite(
-				SpriteFromSheet::Digit(*max_hp as u8),
-				Vec2::new(ui_x, base_y)
-					+ Vec2::new(char_width, char_height) / 2.0
-					+ Vec2::new(heart_width + char_width * 2.0 + space_width * 3.0, 0.0),
-				char_height,
-				true,
+			add_icon(SpriteFromSheet::Heart, base_y);
+			add_chars(number_out_of_sprites(hp, max_hp), base_y, 0);
+		}
+
+		// Level, and experience towards the next level.
+		let base_y = 100.0;
+		let (level, xp, xp_for_next_level) = values.level_and_xp;
+		add_icon(SpriteFromSheet::Star, base_y);
+		let level_sprites = number_sprites(level);
+		let xp_first_column = level_sprites.len() + 1;
+		add_chars(level_sprites, base_y, 0);
+		add_chars(
+			number_out_of_sprites(xp, xp_for_next_level),
+			base_y,
+			xp_first_column,
+		);
+
+		// Depth of the level in the dungeon.
+		let base_y = 140.0;
+		add_icon(SpriteFromSheet::Exit, base_y);
+		add_chars(number_sprites(values.depth), base_y, 0);
+
+		// Damages of the bunny's hits, and damages blocked from the hits it takes.
+		let base_y = 180.0;
+		if let Some((attack, defense)) = values.attack_and_defense {
+			add_icon(SpriteFromSheet::Sword, base_y);
+			add_chars(number_sprites(attack), base_y, 0);
+			let base_y = 220.0;
+			add_icon(SpriteFromSheet::Shield, base_y);
+			add_chars(number_sprites(defense), base_y, 0);
+		}
+
+		// The lines that are only displayed when relevant are stacked below.
+		let mut base_y = 260.0;
+
+		// Rewind tokens.
+		if values.rewind_tokens > 0 {
+			add_icon(SpriteFromSheet::RewindToken, base_y);
+			add_chars(number_sprites(values.rewind_tokens), base_y, 0);
+			base_y += 40.0;
+		}
+
+		// Bombs.
+		if values.bombs > 0 {
+			add_icon(SpriteFromSheet::Bomb, base_y);
+			add_chars(number_sprites(values.bombs), base_y, 0);
+			base_y += 40.0;
+		}
+
+		// Gold.
+		if values.gold > 0 {
+			add_icon(SpriteFromSheet::Coin, base_y);
+			add_chars(number_sprites(values.gold), base_y, 0);
+			base_y += 40.0;
+		}
+
+		// Scrap.
+		if values.scrap > 0 {
+			add_icon(SpriteFromSheet::Scrap, base_y);
+			add_chars(number_sprites(values.scrap), base_y, 0);
+			base_y += 40.0;
+		}
+
+		// Key shards, out of the ones needed to unseal the exit.
+		let (key_shards, key_shards_needed) = values.key_shards;
+		if key_shards_needed > 0 {
+			add_icon(SpriteFromSheet::KeyShard, base_y);
+			add_chars(
+				number_out_of_sprites(key_shards, key_shards_needed),
+				base_y,
+				0,
 			);
+			base_y += 40.0;
+		}
+
+		// The tool held by the bunny.
+		if let Some(held) = values.held.as_ref() {
+			add_icon(obj_to_sprite(held), base_y);
+			base_y += 40.0;
+		}
+
+		// Mirror curse, could be very confusing if not displayed.
+		let mirror_curse_turns_left = values.mirror_curse_turns_left;
+		if mirror_curse_turns_left > 0 {
+			add_icon(SpriteFromSheet::CursedMirror, base_y);
+			add_chars(number_sprites(mirror_curse_turns_left), base_y, 0);
+		}
+
+		for (sprite_from_sheet, center) in chars {
+			add_char_sprite(sprite_from_sheet, center, char_height, true);
 		}
 		gw
 	}
@@ -437,31 +1158,86 @@ impl GraphicalWorld {
 		self.sprites.push(displayed_sprite);
 	}
 
+	/// Sparkles that burst out of the given tile in the four diagonal directions.
+	fn add_sparkle_burst(&mut self, at: IVec2, color: Color) {
+		for direction in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+			let direction = Vec2::from(direction);
+			self.add_sprite(DisplayedSprite::new(
+				SpriteFromSheet::Sparkle,
+				at.as_vec2(),
+				DepthLayer::TemporaryText,
+				true,
+				None,
+				None,
+				Animations::new(
+					None,
+					None,
+					None,
+					Some(TemporaryTextAnimation::new(
+						at.as_vec2() + direction * 0.2,
+						at.as_vec2() + direction * 0.7,
+						color,
+					)),
+				),
+			));
+		}
+	}
+
+	/// The beams of the laser emitters, over the tiles in sight. A beam ends at the edge of the
+	/// tile of what stops it, or at the center of the last mirror if it goes round in circles.
+	fn add_laser_beams(&mut self, lw: &LogicalWorld) {
+		let is_visible = |coords: IVec2| lw.tile(coords).is_some_and(|tile| tile.visible);
+		for (emitter_coords, tile) in lw.tiles() {
+			if !matches!(tile.obj, Some(Obj::LaserEmitter { .. })) {
+				continue;
+			}
+			let mut from = emitter_coords;
+			for to in lw.laser_beam(emitter_coords) {
+				let stops_there = lw.obj(to).is_some_and(|obj| !matches!(obj, Obj::Mirror { .. }));
+				if is_visible(from) && is_visible(to) {
+					let end = if stops_there {
+						(from.as_vec2() + to.as_vec2()) / 2.0
+					} else {
+						to.as_vec2()
+					};
+					self.laser_segments.push(LaserSegment { from: from.as_vec2(), to: end });
+				}
+				from = to;
+			}
+		}
+	}
+
 	/// Render the rendering!
 	pub fn draw(
 		&self,
-		_ctx: &mut Context,
+		ctx: &mut Context,
 		canvas: &mut Canvas,
 		spritesheet_stuff: &SpritesheetStuff,
 		camera: &Camera,
+		options: &DrawOptions,
 	) -> GameResult {
 		let tile_size_px = camera.tile_size_px();
-		let camera_pos = (camera.current_position * tile_size_px).as_ivec2().as_vec2() / tile_size_px;
+		let camera_pos = (camera.current_position * tile_size_px).as_ivec2().as_vec2() / tile_size_px
+			+ camera.shake_offset();
+		let color_split_px = camera.color_split() * tile_size_px;
+		let screen_center = options.screen_size / 2.0;
+		let time_since_start = ctx.time.time_since_start();
+		let mut draw_calls = 0;
 		for sprite in self.sprites.iter() {
 			if !sprite.visible() {
 				continue;
 			}
 			let center = sprite.center();
 			let dest = if sprite.in_world {
-				(center - camera_pos) * tile_size_px + Vec2::new(400.0, 400.0)
+				(center - camera_pos) * tile_size_px + screen_center
 			} else {
 				center
 			};
 			let margin = 50.0;
 			if dest.x < -margin
-				|| dest.x > 800.0 + margin
+				|| dest.x > options.screen_size.x + margin
 				|| dest.y < -margin
-				|| dest.y > 800.0 + margin
+				|| dest.y > options.screen_size.y + margin
 			{
 				continue;
 			}
@@ -471,63 +1247,487 @@ impl GraphicalWorld {
 				// to be exactly of that *plain* color, so we choose a variant of the sprite that
 				// is all white. We find it in the spritesheet that was painted in white.
 				(&spritesheet_stuff.spritesheet_white, color)
+			} else if sprite.remembered {
+				(&spritesheet_stuff.spritesheet_grey, REMEMBERED_TINT)
 			} else {
 				(&spritesheet_stuff.spritesheet, Color::WHITE)
 			};
-			let rect_in_spritesheet = {
-				let mut rect = sprite.sprite_from_sheet.rect_in_spritesheet();
-				// Acceptable hack imho: Reduce a tiny bit the rect in the spritesheet,
-				// less than what would be necessary to see a difference,
-				// but enough so that edges of the rect are not ambiguously touching adjacent sprites.
-				// Not doing so leads to edges of adjacent sprites being sometime visible for a frame
-				// where they are not wanted, which is bad.
-				let margin = 0.03 / 128.0;
-				rect.x += margin;
-				rect.y += margin;
-				rect.w -= margin * 2.0;
-				rect.h -= margin * 2.0;
-				rect
+			let color = if sprite.in_world {
+				multiply_colors(color, options.world_filter)
+			} else {
+				color
 			};
-			let height_for_scale = sprite.height_for_scale.unwrap_or(tile_size_px);
+			let rect_in_spritesheet =
+				rect_in_spritesheet_with_margin(sprite.current_sprite(time_since_start));
+			// Shrinking sprites fade at the same time.
+			let color = Color { a: color.a * sprite.scale() * sprite.alpha(), ..color };
+			let height_for_scale =
+				sprite.height_for_scale.unwrap_or(tile_size_px * sprite.size_in_tiles) * sprite.scale();
+			let draw_param = DrawParam::default()
+				.offset(Vec2::new(0.5, 0.5))
+				.scale(
+					Vec2::new(if sprite.flipped { -1.0 } else { 1.0 }, 1.0) * height_for_scale
+						/ (rect_in_spritesheet.h * 128.0),
+				)
+				.src(rect_in_spritesheet)
+				.z(sprite.depth_layer.to_z_value());
+			if sprite.in_world && color_split_px > 0.0 {
+				// Colored copies of the sprite, drawn first so that they only show on its edges.
+				for (split, split_color) in [
+					(-color_split_px, Color::new(1.0, 0.0, 0.0, 0.6)),
+					(color_split_px, Color::new(0.0, 0.8, 1.0, 0.6)),
+				] {
+					canvas.draw(
+						&spritesheet_stuff.spritesheet_white,
+						draw_param.dest(dest + Vec2::new(split, 0.0)).color(split_color),
+					);
+					draw_calls += 1;
+				}
+			}
+			canvas.draw(spritesheet, draw_param.dest(dest).color(color));
+			draw_calls += 1;
+		}
+		for segment in self.laser_segments.iter() {
+			let from = (segment.from - camera_pos) * tile_size_px + screen_center;
+			let to = (segment.to - camera_pos) * tile_size_px + screen_center;
+			let half_thickness = Vec2::splat(tile_size_px / 16.0);
+			let top_left = from.min(to) - half_thickness;
+			let size = (from.max(to) + half_thickness) - top_left;
 			canvas.draw(
-				spritesheet,
+				&Quad,
 				DrawParam::default()
-					.dest(dest)
-					.offset(Vec2::new(0.5, 0.5))
-					.scale(Vec2::new(1.0, 1.0) * height_for_scale / (rect_in_spritesheet.h * 128.0))
-					.src(rect_in_spritesheet)
-					.z(sprite.depth_layer.to_z_value())
-					.color(color),
+					.dest_rect(Rect::new(top_left.x, top_left.y, size.x, size.y))
+					.color(multiply_colors(
+						Color::new(0.9, 0.1, 0.3, 0.8),
+						options.world_filter,
+					))
+					.z(DepthLayer::Aura.to_z_value()),
 			);
+			draw_calls += 1;
+		}
+		for health_bar in self.health_bars.iter() {
+			let center = (health_bar.current_position() - camera_pos) * tile_size_px + screen_center;
+			let sprite_px = tile_size_px / 8.0;
+			let size = Vec2::new(health_bar.width * tile_size_px, sprite_px);
+			let top_left = center - size / 2.0;
+			let z = DepthLayer::TemporaryText.to_z_value();
+			for (width, color) in [
+				(size.x, Color::new(0.3, 0.0, 0.0, 1.0)),
+				(size.x * health_bar.fraction, Color::new(0.9, 0.1, 0.1, 1.0)),
+			] {
+				canvas.draw(
+					&Quad,
+					DrawParam::default()
+						.dest_rect(Rect::new(top_left.x, top_left.y, width, size.y))
+						.color(multiply_colors(color, options.world_filter))
+						.z(z),
+				);
+				draw_calls += 1;
+			}
 		}
+		metrics::set_count("sprites", self.sprites.len());
+		metrics::set_count("draw calls", draw_calls);
 		Ok(())
 	}
 }
 
+/// Debug view of the scent map that the enemies follow, as path costs written on the tiles.
+pub fn draw_scent_map(canvas: &mut Canvas, lw: &LogicalWorld, camera: &Camera, screen_size: Vec2) {
+	let tile_size_px = camera.tile_size_px();
+	for (coords, tile) in lw.tiles() {
+		let Some(scent) = lw.scent(coords).filter(|_scent| tile.visible) else {
+			continue;
+		};
+		let dest = (coords.as_vec2() - camera.current_position) * tile_size_px + screen_size / 2.0;
+		let mut text = Text::new(scent.to_string());
+		text.set_scale(16.0);
+		canvas.draw(
+			&text,
+			DrawParam::default()
+				.dest(dest)
+				.offset(Vec2::new(0.5, 0.5))
+				.color(Color::new(1.0, 0.5, 1.0, 1.0))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+	}
+}
+
+/// A translucent bunny at the given coords, the ghost of an other run.
+pub fn draw_ghost(
+	canvas: &mut Canvas,
+	spritesheet_stuff: &SpritesheetStuff,
+	coords: IVec2,
+	camera: &Camera,
+	screen_size: Vec2,
+) {
+	draw_translucent_sprite(
+		canvas,
+		spritesheet_stuff,
+		&SpriteFromSheet::Bunny,
+		coords,
+		Color::new(0.7, 0.8, 1.0, 0.4),
+		camera,
+		screen_size,
+	);
+}
+
+/// Draws the silhouette of the sprite over the tile at the given coords, in the given color.
+pub fn draw_translucent_sprite(
+	canvas: &mut Canvas,
+	spritesheet_stuff: &SpritesheetStuff,
+	sprite_from_sheet: &SpriteFromSheet,
+	coords: IVec2,
+	color: Color,
+	camera: &Camera,
+	screen_size: Vec2,
+) {
+	let tile_size_px = camera.tile_size_px();
+	let dest = (coords.as_vec2() - camera.current_position) * tile_size_px + screen_size / 2.0;
+	let rect_in_spritesheet = rect_in_spritesheet_with_margin(sprite_from_sheet);
+	canvas.draw(
+		&spritesheet_stuff.spritesheet_white,
+		DrawParam::default()
+			.dest(dest)
+			.offset(Vec2::new(0.5, 0.5))
+			.scale(Vec2::new(1.0, 1.0) * tile_size_px / (rect_in_spritesheet.h * 128.0))
+			.src(rect_in_spritesheet)
+			.color(color)
+			.z(DepthLayer::AnimatedObj.to_z_value()),
+	);
+}
+
+/// While going back and forth in the history, tells which turn is displayed.
+pub fn draw_history_overlay(
+	canvas: &mut Canvas,
+	turn: usize,
+	latest_turn: usize,
+	screen_size: Vec2,
+) {
+	let mut text = Text::new(format!(
+		"Turn {turn}/{latest_turn}, Backspace to go back, Y to go forward"
+	));
+	text.set_scale(18.0);
+	text.set_layout(TextLayout::center());
+	canvas.draw(
+		&text,
+		DrawParam::default()
+			.dest(Vec2::new(screen_size.x / 2.0, screen_size.y - 30.0))
+			.z(DepthLayer::Interface.to_z_value()),
+	);
+}
+
+/// The digits of a number, as sprites.
+fn number_sprites(number: i32) -> Vec<SpriteFromSheet> {
+	number.max(0).to_string().bytes().map(|digit| SpriteFromSheet::Digit(digit - b'0')).collect()
+}
+
+/// Like `3/9`.
+fn number_out_of_sprites(number: i32, out_of: i32) -> Vec<SpriteFromSheet> {
+	let mut sprites = number_sprites(number);
+	sprites.push(SpriteFromSheet::Slash);
+	sprites.extend(number_sprites(out_of));
+	sprites
+}
+
+/// What the interface displays.
+#[derive(Clone, PartialEq)]
+struct HudValues {
+	redo_count: i32,
+	max_redo_count: i32,
+	/// HP and max HP of the player, if there is a player.
+	hp: Option<(i32, i32)>,
+	/// Attack and defense of the player, if there is a player.
+	attack_and_defense: Option<(i32, i32)>,
+	mirror_curse_turns_left: i32,
+	/// Level, experience, and experience needed for the next level.
+	level_and_xp: (i32, i32, i32),
+	rewind_tokens: i32,
+	bombs: i32,
+	gold: i32,
+	scrap: i32,
+	/// Key shards collected, and key shards needed to unseal the exit.
+	key_shards: (i32, i32),
+	held: Option<Obj>,
+	depth: i32,
+}
+
+impl HudValues {
+	fn of(lw: &LogicalWorld) -> HudValues {
+		let hp = lw.player_coords().and_then(|coords| match lw.obj(coords) {
+			Some(Obj::Bunny { hp, max_hp, .. }) => Some((*hp, *max_hp)),
+			_ => None,
+		});
+		let attack_and_defense = lw.player_coords().map(|coords| {
+			let attack = lw.obj(coords).unwrap().damages() + lw.attack_bonus(coords);
+			(attack, lw.defense(coords))
+		});
+		HudValues {
+			redo_count: lw.redo_count,
+			max_redo_count: lw.max_redo_count,
+			hp,
+			attack_and_defense,
+			mirror_curse_turns_left: lw.mirror_curse_turns_left,
+			level_and_xp: (
+				lw.progression.level,
+				lw.progression.xp,
+				lw.progression.xp_for_next_level(),
+			),
+			rewind_tokens: lw.rewind_tokens,
+			bombs: lw.bombs,
+			gold: lw.gold,
+			scrap: lw.scrap,
+			key_shards: (lw.key_shards, lw.key_shards_needed),
+			held: lw.player_coords().and_then(|coords| lw.obj(coords)?.held().cloned()),
+			depth: lw.depth,
+		}
+	}
+}
+
+/// The interface is rendered once into an offscreen image that is drawn over the world
+/// every frame, and it is rendered again only when what it displays changes.
+pub struct CachedHud {
+	values: Option<HudValues>,
+	image: Option<Image>,
+}
+
+impl CachedHud {
+	pub fn new() -> CachedHud {
+		CachedHud { values: None, image: None }
+	}
+
+	/// Draws the interface that displays info about the given logical world.
+	pub fn draw(
+		&mut self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		lw: &LogicalWorld,
+		screen_size: Vec2,
+	) -> GameResult {
+		let values = HudValues::of(lw);
+		let image = match self.image.as_ref() {
+			Some(image)
+				if self.values.as_ref() == Some(&values)
+					&& Vec2::new(image.width() as f32, image.height() as f32) == screen_size =>
+				image,
+			_ => {
+				let _timer = metrics::time("hud render");
+				let image = Image::new_canvas_image(
+					ctx,
+					ctx.gfx.surface_format(),
+					screen_size.x as u32,
+					screen_size.y as u32,
+					1,
+				);
+				let mut hud_canvas =
+					Canvas::from_image(ctx, image.clone(), Color::new(0.0, 0.0, 0.0, 0.0));
+				hud_canvas.set_sampler(Sampler::nearest_clamp());
+				GraphicalWorld::from_hud_values(&values).draw(
+					ctx,
+					&mut hud_canvas,
+					spritesheet_stuff,
+					&Camera::new(),
+					&DrawOptions::new(screen_size),
+				)?;
+				hud_canvas.finish(ctx)?;
+				self.values = Some(values);
+				self.image.insert(image)
+			},
+		};
+		canvas.draw(
+			image,
+			DrawParam::default().z(DepthLayer::Interface.to_z_value()),
+		);
+		Ok(())
+	}
+}
+
+/// Draws the world at one pixel per sprite pixel in an offscreen image that is then upscaled
+/// to the screen by the zoom level, an integer factor, so that every sprite pixel covers
+/// exactly as many screen pixels as the others wherever the camera is.
+pub struct PixelPerfectRenderer {
+	image: Option<Image>,
+}
+
+impl PixelPerfectRenderer {
+	pub fn new() -> PixelPerfectRenderer {
+		PixelPerfectRenderer { image: None }
+	}
+
+	pub fn draw(
+		&mut self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		gw: &GraphicalWorld,
+		spritesheet_stuff: &SpritesheetStuff,
+		camera: &Camera,
+		options: &DrawOptions,
+	) -> GameResult {
+		// The upscaling by a whole factor is the point, so there is no smooth zoom here.
+		let factor = camera.zoom_level;
+		// Even sizes put the center of the image (where the camera looks) on a pixel corner,
+		// where sprites can be aligned to the pixel grid.
+		let even_ceil = |length: f32| (length / factor as f32 / 2.0).ceil() as u32 * 2;
+		let image_width = even_ceil(options.screen_size.x);
+		let image_height = even_ceil(options.screen_size.y);
+		let image = match self.image.as_ref() {
+			Some(image) if image.width() == image_width && image.height() == image_height =>
+				image.clone(),
+			_ => self
+				.image
+				.insert(Image::new_canvas_image(
+					ctx,
+					ctx.gfx.surface_format(),
+					image_width,
+					image_height,
+					1,
+				))
+				.clone(),
+		};
+		let image_size = Vec2::new(image_width as f32, image_height as f32);
+		let mut image_canvas = Canvas::from_image(ctx, image.clone(), Color::BLACK);
+		image_canvas.set_sampler(Sampler::nearest_clamp());
+		let unzoomed_camera = camera.with_zoom_level(1);
+		gw.draw(
+			ctx,
+			&mut image_canvas,
+			spritesheet_stuff,
+			&unzoomed_camera,
+			&DrawOptions { screen_size: image_size, world_filter: options.world_filter },
+		)?;
+		image_canvas.finish(ctx)?;
+		// The image may be a bit bigger than the screen, its center stays at the screen's center.
+		let dest = (options.screen_size - image_size * factor as f32) / 2.0;
+		canvas.draw(
+			&image,
+			DrawParam::default()
+				.dest(dest)
+				.scale(Vec2::new(1.0, 1.0) * factor as f32)
+				.z(DepthLayer::Floor.to_z_value()),
+		);
+		Ok(())
+	}
+}
+
+/// How a graphical world should be drawn, other than what the camera decides.
+pub struct DrawOptions {
+	/// The size in pixels of what is drawn on, the center of which is what the camera targets.
+	pub screen_size: Vec2,
+	/// Draw only the world, without the interface.
+	/// Multiplied to the colors of everything in the world.
+	pub world_filter: Color,
+}
+
+impl DrawOptions {
+	pub fn new(screen_size: Vec2) -> DrawOptions {
+		DrawOptions { screen_size, world_filter: Color::WHITE }
+	}
+}
+
+fn multiply_colors(a: Color, b: Color) -> Color {
+	Color::new(a.r * b.r, a.g * b.g, a.b * b.b, a.a * b.a)
+}
+
+fn rect_in_spritesheet_with_margin(sprite_from_sheet: &SpriteFromSheet) -> Rect {
+	let mut rect = sprite_from_sheet.rect_in_spritesheet();
+	// Acceptable hack imho: Reduce a tiny bit the rect in the spritesheet,
+	// less than what would be necessary to see a difference,
+	// but enough so that edges of the rect are not ambiguously touching adjacent sprites.
+	// Not doing so leads to edges of adjacent sprites being sometime visible for a frame
+	// where they are not wanted, which is bad.
+	let margin = 0.03 / 128.0;
+	rect.x += margin;
+	rect.y += margin;
+	rect.w -= margin * 2.0;
+	rect.h -= margin * 2.0;
+	rect
+}
+
+/// Draws a sprite directly on the screen (not in the world) over everything else,
+/// for interface pieces that are not part of a graphical world like popups.
+pub fn draw_popup_sprite(
+	canvas: &mut Canvas,
+	spritesheet_stuff: &SpritesheetStuff,
+	sprite_from_sheet: &SpriteFromSheet,
+	center: Vec2,
+	height: f32,
+) {
+	let rect_in_spritesheet = rect_in_spritesheet_with_margin(sprite_from_sheet);
+	canvas.draw(
+		&spritesheet_stuff.spritesheet,
+		DrawParam::default()
+			.dest(center)
+			.offset(Vec2::new(0.5, 0.5))
+			.scale(Vec2::new(1.0, 1.0) * height / (rect_in_spritesheet.h * 128.0))
+			.src(rect_in_spritesheet)
+			.z(DepthLayer::Popup.to_z_value()),
+	);
+}
+
+thread_local! {
+	/// How many times faster than normal the animations play, zero making them instant.
+	static ANIMATION_SPEED: Cell<f32> = const { Cell::new(1.0) };
+	/// The time that the animations follow, it stands still during hit-stops.
+	static ANIMATION_CLOCK: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+	/// How long the animation clock still has to stand still.
+	static HIT_STOP_LEFT: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Expected to be called once per frame, before anything is animated or drawn.
+pub fn advance_animation_clock(frame_dt: Duration) {
+	let hit_stop_left = HIT_STOP_LEFT.get();
+	HIT_STOP_LEFT.set(hit_stop_left.saturating_sub(frame_dt));
+	ANIMATION_CLOCK.set(ANIMATION_CLOCK.get() + frame_dt.saturating_sub(hit_stop_left));
+}
+
+/// Freezes all the animations for a brief moment, to make a blow feel heavy.
+fn start_hit_stop() {
+	HIT_STOP_LEFT.set(Duration::from_secs_f32(0.08));
+}
+
+/// The animations that start from now on play that many times faster than normal,
+/// or are skipped if it is zero.
+pub fn set_animation_speed(speed: f32) {
+	ANIMATION_SPEED.set(speed);
+}
+
 /// An animation plays during some time interval, and progresses during said interval.
+#[derive(Clone)]
 struct TimeInterval {
-	start_time: Instant,
+	/// On the animation clock.
+	start_time: Duration,
 	duration: Duration,
 }
 
 impl TimeInterval {
-	/// Starts now.
+	/// Starts now, the duration being scaled by the animation speed.
 	fn with_duration(duration: Duration) -> TimeInterval {
 		assert!(!duration.is_zero());
-		TimeInterval { start_time: Instant::now(), duration }
+		let speed = ANIMATION_SPEED.get();
+		let duration = if speed > 0.0 {
+			duration.div_f32(speed)
+		} else {
+			Duration::ZERO
+		};
+		TimeInterval { start_time: ANIMATION_CLOCK.get(), duration }
 	}
 
 	/// Zero before and at staring time,
 	/// progresses from zero to one linearly during the time interval
 	/// and stays at one at and after the end.
 	fn progress(&self) -> f32 {
-		(self.start_time.elapsed().as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+		if self.duration.is_zero() {
+			return 1.0;
+		}
+		let elapsed = ANIMATION_CLOCK.get().saturating_sub(self.start_time);
+		(elapsed.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
 	}
 }
 
 /// A sprites move linearly and then remain at its target position.
 ///
 /// Can be used on the sprites of objects that move and are pushed.
+#[derive(Clone)]
 struct MoveAnimation {
 	from: Vec2,
 	to: Vec2,
@@ -536,22 +1736,19 @@ struct MoveAnimation {
 }
 
 impl MoveAnimation {
+	/// Moves over several tiles (like when sliding on ice) take longer.
 	fn new(from: Vec2, to: Vec2) -> MoveAnimation {
+		let duration = 0.05 * from.distance(to).max(1.0);
 		MoveAnimation {
 			from,
 			to,
-			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(0.05)),
+			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(duration)),
 			disappear_after: false,
 		}
 	}
 
 	fn new_disappear_after(from: Vec2, to: Vec2) -> MoveAnimation {
-		MoveAnimation {
-			from,
-			to,
-			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(0.05)),
-			disappear_after: true,
-		}
+		MoveAnimation { disappear_after: true, ..MoveAnimation::new(from, to) }
 	}
 
 	fn currently_visible(&self) -> bool {
@@ -567,6 +1764,7 @@ impl MoveAnimation {
 /// to go back to its starting position, and remains there.
 ///
 /// Can be used on the sprites of objects that fail to push.
+#[derive(Clone)]
 struct FailToMoveAnimation {
 	from: Vec2,
 	to: Vec2,
@@ -623,6 +1821,65 @@ impl HitAnimation {
 	}
 }
 
+/// The sprite moves onto a hole, and then it shrinks and fades away as it falls in.
+struct FallAnimation {
+	from: Vec2,
+	to: Vec2,
+	time_interval: TimeInterval,
+}
+
+impl FallAnimation {
+	/// The part of the animation that is spent moving, before falling.
+	const MOVE_PART: f32 = 0.2;
+
+	fn new(from: Vec2, to: Vec2) -> FallAnimation {
+		FallAnimation {
+			from,
+			to,
+			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(0.25)),
+		}
+	}
+
+	fn current_position(&self) -> Vec2 {
+		let move_progress = (self.time_interval.progress() / FallAnimation::MOVE_PART).min(1.0);
+		self.from + move_progress * (self.to - self.from)
+	}
+
+	fn current_scale(&self) -> f32 {
+		let fall_progress = (self.time_interval.progress() - FallAnimation::MOVE_PART)
+			/ (1.0 - FallAnimation::MOVE_PART);
+		1.0 - fall_progress.max(0.0)
+	}
+}
+
+/// The sprite fades out during the first half of the animation, then is gone,
+/// or it is invisible during the first half and then fades in.
+///
+/// This is used on the sprites of objects that teleport, to vanish from a teleporter before
+/// appearing on the other one.
+struct FadeAnimation {
+	fade_in: bool,
+	time_interval: TimeInterval,
+}
+
+impl FadeAnimation {
+	fn new(fade_in: bool) -> FadeAnimation {
+		FadeAnimation {
+			fade_in,
+			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(0.3)),
+		}
+	}
+
+	fn current_alpha(&self) -> f32 {
+		let progress = self.time_interval.progress();
+		if self.fade_in {
+			(progress * 2.0 - 1.0).max(0.0)
+		} else {
+			(1.0 - progress * 2.0).max(0.0)
+		}
+	}
+}
+
 /// The sprite moves from and to the specified positions,
 /// appearing the specified plain color, and then vanishes at the end.
 ///
@@ -663,6 +1920,8 @@ struct Animations {
 	fail_to_move_animation: Option<FailToMoveAnimation>,
 	hit_animation: Option<HitAnimation>,
 	temporary_text_animation: Option<TemporaryTextAnimation>,
+	fall_animation: Option<FallAnimation>,
+	fade_animation: Option<FadeAnimation>,
 }
 
 impl Animations {
@@ -677,31 +1936,237 @@ impl Animations {
 			fail_to_move_animation,
 			hit_animation,
 			temporary_text_animation,
+			fall_animation: None,
+			fade_animation: None,
+		}
+	}
+
+	fn fall(fall_animation: FallAnimation) -> Animations {
+		Animations {
+			fall_animation: Some(fall_animation),
+			..Animations::new(None, None, None, None)
 		}
 	}
+
+	fn fade(move_animation: Option<MoveAnimation>, fade_animation: FadeAnimation) -> Animations {
+		Animations {
+			fade_animation: Some(fade_animation),
+			..Animations::new(move_animation, None, None, None)
+		}
+	}
+}
+
+/// Multiplied to the greyed out sprites of the remembered tiles, to dim them.
+const REMEMBERED_TINT: Color = Color::new(0.5, 0.5, 0.55, 1.0);
+
+/// A tile out of sight as the player remembers it, still and greyed out.
+fn remembered_tile_sprites(
+	coords: IVec2,
+	tile: &Tile,
+	look: Option<&Look>,
+) -> Vec<DisplayedSprite> {
+	let still_sprite =
+		|sprite_from_sheet: SpriteFromSheet, center: Vec2, depth_layer: DepthLayer| {
+			DisplayedSprite::new(
+				sprite_from_sheet,
+				center,
+				depth_layer,
+				true,
+				None,
+				None,
+				Animations::new(None, None, None, None),
+			)
+			.remembered()
+		};
+	let mut sprites = vec![still_sprite(
+		ground_to_sprite(&tile.ground),
+		coords.as_vec2(),
+		DepthLayer::Floor,
+	)];
+	if matches!(tile.ground, Ground::Raft) {
+		sprites.push(still_sprite(
+			SpriteFromSheet::Raft,
+			coords.as_vec2(),
+			DepthLayer::Aura,
+		));
+	}
+	// The bunny is where the player is, not where it was remembered (if it teleported away).
+	if let Some(obj) = tile.obj.as_ref().filter(|obj| !matches!(obj, Obj::Bunny { .. })) {
+		let footprint = obj.footprint();
+		let offset = (footprint - IVec2::ONE).as_vec2() / 2.0;
+		sprites.push(
+			still_sprite(
+				looked_obj_to_sprite(obj, look),
+				coords.as_vec2() + offset,
+				DepthLayer::Obj,
+			)
+			.with_size_in_tiles(footprint.y as f32)
+			.with_facing_of(obj),
+		);
+	}
+	sprites
+}
+
+/// Where the object at the given coords was before the transition, if it moved.
+/// An object that slid on ice made one move per tile, and left empty the tiles it went through,
+/// so its moves are followed back through these empty tiles.
+fn moved_from(transition: &LogicalTransition, coords: IVec2) -> Option<IVec2> {
+	let one_move_from = |coords: IVec2| {
+		transition.logical_events.iter().find_map(|logical_event| match logical_event {
+			LogicalEvent::Move { from, to } if *to == coords => Some(*from),
+			_ => None,
+		})
+	};
+	let mut from = one_move_from(coords)?;
+	while transition.resulting_lw.tile(from).is_some_and(|tile| tile.obj.is_none()) {
+		let Some(earlier_from) = one_move_from(from) else {
+			break;
+		};
+		from = earlier_from;
+	}
+	Some(from)
 }
 
 /// Info about the logical or graphical world that can help the camera set its target.
 pub struct InfoForCamera {
 	player_position: Option<Vec2>,
+	/// If the player took a heavy hit, the direction the blow came from.
+	heavy_hit_direction: Option<Vec2>,
+	/// Did something get killed or explode in sight?
+	rumble: bool,
+	player_hurt: bool,
 }
 
 impl InfoForCamera {
 	fn new() -> InfoForCamera {
-		InfoForCamera { player_position: None }
+		InfoForCamera {
+			player_position: None,
+			heavy_hit_direction: None,
+			rumble: false,
+			player_hurt: false,
+		}
 	}
 }
 
+/// Does something get killed or explode in sight during the transition?
+fn rumbles(transition: &LogicalTransition) -> bool {
+	transition.logical_events.iter().any(|logical_event| match logical_event {
+		LogicalEvent::Killed { at, .. } | LogicalEvent::Exploded { at, .. } =>
+			transition.resulting_lw.tile(*at).is_some_and(|tile| tile.visible),
+		_ => false,
+	})
+}
+
+/// Does the player take damages during the transition?
+fn player_hurt(transition: &LogicalTransition) -> bool {
+	let lw = &transition.resulting_lw;
+	transition.logical_events.iter().any(|logical_event| match logical_event {
+		LogicalEvent::Hit { at, damages } =>
+			*damages > 0 && matches!(lw.obj(*at), Some(Obj::Bunny { .. })),
+		LogicalEvent::Killed { obj, .. } => matches!(obj, Obj::Bunny { .. }),
+		_ => false,
+	})
+}
+
+/// Hits of at least that many damages to the player shake the screen.
+const HEAVY_HIT_DAMAGES: i32 = 3;
+
+/// If the player takes a heavy hit in the transition, the direction of the blow
+/// (going from the hitter to the player).
+fn heavy_hit_direction(transition: &LogicalTransition) -> Option<Vec2> {
+	let lw = &transition.resulting_lw;
+	let at = transition.logical_events.iter().find_map(|logical_event| match logical_event {
+		LogicalEvent::Hit { at, damages }
+			if *damages >= HEAVY_HIT_DAMAGES && matches!(lw.obj(*at), Some(Obj::Bunny { .. })) =>
+			Some(*at),
+		LogicalEvent::Killed { obj: Obj::Bunny { .. }, at, damages }
+			if *damages >= HEAVY_HIT_DAMAGES =>
+			Some(*at),
+		_ => None,
+	})?;
+	let direction = transition
+		.logical_events
+		.iter()
+		.find_map(|logical_event| match logical_event {
+			LogicalEvent::FailToMove { from, to } if *to == at => Some((at - *from).as_vec2()),
+			_ => None,
+		})
+		.unwrap_or(Vec2::new(0.0, 1.0));
+	Some(direction)
+}
+
+/// The screen shaking, either after a heavy hit to the player (the camera is punched in the
+/// direction of the blow and bounces back, while the colors of the world split apart for a
+/// moment) or after a kill or an explosion (the camera trembles a little).
+#[derive(Clone)]
+struct ScreenShake {
+	/// The direction of the blow, there is none for a tremble.
+	direction: Option<Vec2>,
+	time_interval: TimeInterval,
+}
+
+impl ScreenShake {
+	fn new(direction: Vec2) -> ScreenShake {
+		ScreenShake {
+			direction: Some(direction),
+			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(0.35)),
+		}
+	}
+
+	fn tremble() -> ScreenShake {
+		ScreenShake {
+			direction: None,
+			time_interval: TimeInterval::with_duration(Duration::from_secs_f32(0.2)),
+		}
+	}
+
+	/// How much the camera is displaced, in tiles.
+	fn offset(&self) -> Vec2 {
+		let progress = self.time_interval.progress();
+		match self.direction {
+			Some(direction) => {
+				let bounce = (progress * std::f32::consts::PI * 3.0).sin();
+				-direction * 0.15 * bounce * (1.0 - progress).powi(2)
+			},
+			None => {
+				// Fast enough that it does not look like going anywhere.
+				let jitter = Vec2::new((progress * 50.0).sin(), (progress * 37.0).cos());
+				jitter * 0.05 * (1.0 - progress).powi(2)
+			},
+		}
+	}
+
+	/// How far apart the colors are, in tiles.
+	fn color_split(&self) -> f32 {
+		if self.direction.is_none() {
+			return 0.0;
+		}
+		let progress = self.time_interval.progress();
+		0.04 * (1.0 - progress)
+	}
+}
+
+/// Some number that represents how fast the scale follows the zoom level.
+const ZOOM_SPEED: f32 = 12.0;
+
 /// Points to a position in the world that ends up displayed at the center of the window.
 /// When the target moves (even abruptly), the camera follows smoothly.
 /// Also hold the zoom level.
+#[derive(Clone)]
 pub struct Camera {
 	target_position: Vec2,
 	current_position: Vec2,
 	/// Some number that represents how fast the camera moves to follow the target.
 	speed: f32,
-	/// A pixel in the spritesheet will be scaled up by this factor.
-	sprite_px_scaled_to_how_many_screen_px: i32,
+	/// A pixel in the spritesheet will be scaled up by this factor,
+	/// it follows the zoom level smoothly.
+	sprite_px_scaled_to_how_many_screen_px: f32,
+	/// What the scale factor goes towards, some whole number.
+	zoom_level: i32,
+	/// When set, nothing shakes the screen, and the player getting hurt does not freeze it.
+	/// There is no weather either (see the `weather` module).
+	pub reduced_motion: bool,
+	shake: Option<ScreenShake>,
 }
 
 impl Camera {
@@ -710,17 +2175,62 @@ impl Camera {
 			target_position: Vec2::new(0.0, 0.0),
 			current_position: Vec2::new(0.0, 0.0),
 			speed: 3.0,
-			sprite_px_scaled_to_how_many_screen_px: 7,
+			sprite_px_scaled_to_how_many_screen_px: 7.0,
+			zoom_level: 7,
+			reduced_motion: false,
+			shake: None,
 		}
 	}
 
+	/// Zooms in (positive delta) or out (negative delta), staying in the given bounds.
+	/// The scale goes there smoothly.
+	pub fn zoom(&mut self, delta: i32, min: i32, max: i32) {
+		self.zoom_level = (self.zoom_level + delta).clamp(min, max);
+	}
+
+	pub fn zoom_level(&self) -> i32 {
+		self.zoom_level
+	}
+
+	/// The camera at the given zoom level, already reached.
+	fn with_zoom_level(&self, zoom_level: i32) -> Camera {
+		let mut camera = self.clone();
+		camera.zoom_level = zoom_level;
+		camera.sprite_px_scaled_to_how_many_screen_px = zoom_level as f32;
+		camera
+	}
+
+	/// Multiplies the zoom level, like to draw the same view on a bigger screen.
+	pub fn scaled_zoom(&self, factor: i32) -> Camera {
+		let mut camera = self.clone();
+		camera.zoom_level *= factor;
+		camera.sprite_px_scaled_to_how_many_screen_px *= factor as f32;
+		camera
+	}
+
+	/// Moves the target by the given amount of tiles, the camera follows smoothly.
+	pub fn move_target(&mut self, delta: Vec2) {
+		self.target_position += delta;
+	}
+
 	/// How long an edge of a tile should appear on the screen, measured in screen pixels.
 	fn tile_size_px(&self) -> f32 {
-		self.sprite_px_scaled_to_how_many_screen_px as f32 * 8.0
+		self.sprite_px_scaled_to_how_many_screen_px * 8.0
+	}
+
+	/// The coords of the tile that is displayed at the given position on the screen.
+	pub fn screen_to_world(&self, screen_position: Vec2, screen_size: Vec2) -> IVec2 {
+		((screen_position - screen_size / 2.0) / self.tile_size_px() + self.current_position)
+			.round()
+			.as_ivec2()
 	}
 
 	/// Make the camera move towards the target, smoothly. Expected to be called once per frame.
 	pub fn animate(&mut self, frame_dt: Duration) {
+		if self.shake.as_ref().is_some_and(|shake| shake.time_interval.progress() >= 1.0) {
+			self.shake = None;
+		}
+		self.animate_zoom(frame_dt);
 		// What portion of the remaining vector should we travel?
 		let update_factor = (self.speed * frame_dt.as_secs_f32()).min(1.0);
 		let next_position =
@@ -748,11 +2258,50 @@ impl Camera {
 		self.current_position += delta;
 	}
 
+	/// Makes the scale factor go towards the zoom level, smoothly.
+	fn animate_zoom(&mut self, frame_dt: Duration) {
+		let target = self.zoom_level as f32;
+		let update_factor = (ZOOM_SPEED * frame_dt.as_secs_f32()).min(1.0);
+		self.sprite_px_scaled_to_how_many_screen_px +=
+			(target - self.sprite_px_scaled_to_how_many_screen_px) * update_factor;
+		// Like for the position, it must eventually get exactly to the target.
+		if (target - self.sprite_px_scaled_to_how_many_screen_px).abs() < 0.01 {
+			self.sprite_px_scaled_to_how_many_screen_px = target;
+		}
+	}
+
+	/// Is the camera done following its target?
+	pub fn is_still(&self) -> bool {
+		self.current_position == self.target_position
+			&& self.sprite_px_scaled_to_how_many_screen_px == self.zoom_level as f32
+			&& self.shake.is_none()
+	}
+
+	fn shake_offset(&self) -> Vec2 {
+		self.shake.as_ref().map_or(Vec2::ZERO, ScreenShake::offset)
+	}
+
+	fn color_split(&self) -> f32 {
+		self.shake.as_ref().map_or(0.0, ScreenShake::color_split)
+	}
+
 	/// Sets the target on some new world state via some info about that state.
 	pub fn set_target(&mut self, info: &InfoForCamera) {
 		if let Some(player_position) = info.player_position {
 			self.target_position = player_position;
 		}
+		if self.reduced_motion {
+			return;
+		}
+		if let Some(direction) = info.heavy_hit_direction {
+			self.shake = Some(ScreenShake::new(direction));
+		} else if info.rumble && self.shake.as_ref().is_none_or(|shake| shake.direction.is_none()) {
+			// A tremble does not interrupt the bounce of a heavy hit.
+			self.shake = Some(ScreenShake::tremble());
+		}
+		if info.player_hurt {
+			start_hit_stop();
+		}
 	}
 
 	/// Sets the target on some initial world state via some info about that state.
@@ -0,0 +1,86 @@
+//! Hot-reloading of the assets, in debug builds, to iterate on art and data without restarting.
+//!
+//! The asset files are polled for modification from time to time. A sprite that changed gets
+//! the spritesheet reassembled and reloaded, and a change in the lore data file gets
+//! the lore fragments reloaded, with a message on screen telling whether it worked.
+//! Adding, removing or resizing a sprite still requires a rebuild, as the rects of the sprites
+//! in the sheet are compiled in.
+
+use std::{
+	path::{Path, PathBuf},
+	time::{Duration, Instant, SystemTime},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What changed since the last poll.
+#[derive(Default)]
+pub struct AssetChanges {
+	pub sprites: bool,
+	pub lore: bool,
+}
+
+pub struct HotReloader {
+	last_poll: Instant,
+	/// The last modification times of the sprite files, sorted by path.
+	sprites_modified: Vec<(PathBuf, SystemTime)>,
+	lore_modified: Option<SystemTime>,
+}
+
+pub fn sprites_dir() -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/sprites")
+}
+
+pub fn lore_path() -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/lore.txt")
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+	std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn sprites_modification_times() -> Vec<(PathBuf, SystemTime)> {
+	let mut times: Vec<_> = std::fs::read_dir(sprites_dir())
+		.into_iter()
+		.flatten()
+		.flatten()
+		.filter_map(|entry| {
+			let path = entry.path();
+			let time = modification_time(&path)?;
+			Some((path, time))
+		})
+		.collect();
+	times.sort();
+	times
+}
+
+impl HotReloader {
+	pub fn new() -> HotReloader {
+		HotReloader {
+			last_poll: Instant::now(),
+			sprites_modified: sprites_modification_times(),
+			lore_modified: modification_time(&lore_path()),
+		}
+	}
+
+	/// Cheap to call every frame, the files are only actually polled every `POLL_INTERVAL`.
+	pub fn poll(&mut self) -> AssetChanges {
+		let mut changes = AssetChanges::default();
+		if self.last_poll.elapsed() < POLL_INTERVAL {
+			return changes;
+		}
+		self.last_poll = Instant::now();
+
+		let sprites_modified = sprites_modification_times();
+		if sprites_modified != self.sprites_modified {
+			self.sprites_modified = sprites_modified;
+			changes.sprites = true;
+		}
+		let lore_modified = modification_time(&lore_path());
+		if lore_modified != self.lore_modified {
+			self.lore_modified = lore_modified;
+			changes.lore = true;
+		}
+		changes
+	}
+}
@@ -0,0 +1,256 @@
+//! Level browser, to play the levels found in the `levels/` directory of the user data directory,
+//! like levels made in the editor or shared by other players.
+//!
+//! The levels are the `.txt` files in the level format (see the `level_format` module), and the
+//! sub-directories are collections of levels, opened like folders. Each level is listed with its
+//! metadata, and whether it was solved with its best score. A level is played like a level of the
+//! campaign: solving it is remembered in the player profile (by its path) and gets back to the
+//! browser, and `F2` leaves it without solving it.
+
+use std::path::{Path, PathBuf};
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+	Context,
+};
+
+use crate::{
+	campaign::medal_to_sprite,
+	gameplay::{LogicalWorld, Obj},
+	graphics::{obj_to_sprite, DepthLayer},
+	level_format::LevelMetadata,
+	profile::Profile,
+	spritesheet::{SpriteFromSheet, SpritesheetStuff},
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+/// A level file of the browser, that can be played.
+#[derive(Clone)]
+pub struct BrowsedLevel {
+	/// What the profile remembers the level by, its path in the `levels/` directory.
+	pub id: String,
+	/// The directory of the browser, and the collection of the level in it.
+	levels_dir: PathBuf,
+	collection_dir: PathBuf,
+	pub metadata: LevelMetadata,
+	/// The level, with the walls around it.
+	pub lw: LogicalWorld,
+}
+
+enum Entry {
+	Collection(PathBuf),
+	Level(Box<BrowsedLevel>),
+	/// A file that is not a valid level, listed anyway to tell what is wrong with it.
+	Invalid,
+}
+
+/// What the game should do following a key press in the level browser.
+pub enum LevelBrowserAction {
+	Back,
+	Play(Box<BrowsedLevel>),
+}
+
+/// Lists the collections and the levels of one directory of the `levels/` directory.
+pub struct LevelBrowser {
+	levels_dir: PathBuf,
+	/// The directory being listed, the `levels/` directory itself or one of its collections.
+	collection_dir: PathBuf,
+	/// One per widget of the menu but the last one (that goes back).
+	entries: Vec<Entry>,
+	menu: Menu,
+}
+
+impl LevelBrowser {
+	pub fn levels_dir(ctx: &Context) -> PathBuf {
+		ctx.fs.user_data_dir().join("levels")
+	}
+
+	/// Lists the whole `levels/` directory.
+	pub fn new(levels_dir: PathBuf, profile: Option<&Profile>) -> LevelBrowser {
+		LevelBrowser::listing(levels_dir.clone(), levels_dir, profile)
+	}
+
+	/// Lists the collection of the given level again, once it was played.
+	pub fn back_from(level: &BrowsedLevel, profile: Option<&Profile>) -> LevelBrowser {
+		LevelBrowser::listing(
+			level.levels_dir.clone(),
+			level.collection_dir.clone(),
+			profile,
+		)
+	}
+
+	fn listing(
+		levels_dir: PathBuf,
+		collection_dir: PathBuf,
+		profile: Option<&Profile>,
+	) -> LevelBrowser {
+		// A missing directory is just an empty one, there are no levels until some are added.
+		let mut paths: Vec<PathBuf> = std::fs::read_dir(&collection_dir)
+			.map(|dir| dir.filter_map(|entry| Some(entry.ok()?.path())).collect())
+			.unwrap_or_default();
+		// The collections first, then the levels, each in alphabetical order.
+		paths.retain(|path| path.is_dir() || path.extension().is_some_and(|ext| ext == "txt"));
+		paths.sort_by_key(|path| (!path.is_dir(), path.clone()));
+		let mut entries = vec![];
+		let mut widgets = vec![];
+		for path in paths {
+			let file_name = path.file_name().map_or(String::new(), |name| {
+				name.to_string_lossy().trim_end_matches(".txt").to_string()
+			});
+			if path.is_dir() {
+				widgets.push(Widget::Button {
+					label: format!("{file_name}/"),
+					description: Some("A collection of levels.".to_string()),
+					icon: Some(SpriteFromSheet::Door),
+				});
+				entries.push(Entry::Collection(path));
+				continue;
+			}
+			match load_level(&levels_dir, &collection_dir, &path) {
+				Ok(level) => {
+					widgets.push(level_widget(&level, &file_name, profile));
+					entries.push(Entry::Level(Box::new(level)));
+				},
+				Err(error) => {
+					widgets.push(Widget::Button {
+						label: file_name,
+						description: Some(format!("Not a valid level: {error}")),
+						icon: None,
+					});
+					entries.push(Entry::Invalid);
+				},
+			}
+		}
+		if entries.is_empty() {
+			widgets.push(Widget::Button {
+				label: "No levels".to_string(),
+				description: Some(format!(
+					"Levels made in the editor can be put in {}",
+					levels_dir.display()
+				)),
+				icon: None,
+			});
+			entries.push(Entry::Invalid);
+		}
+		widgets.push(Widget::button("Back"));
+		LevelBrowser { levels_dir, collection_dir, entries, menu: Menu::new(widgets) }
+	}
+
+	pub fn key_down(
+		&mut self,
+		keycode: VirtualKeyCode,
+		profile: Option<&Profile>,
+	) -> Option<LevelBrowserAction> {
+		if keycode == VirtualKeyCode::Escape {
+			return self.back(profile);
+		}
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(index) if index == self.entries.len() => self.back(profile),
+			MenuEvent::Activated(index) => match &self.entries[index] {
+				Entry::Collection(dir) => {
+					*self = LevelBrowser::listing(self.levels_dir.clone(), dir.clone(), profile);
+					None
+				},
+				Entry::Level(level) => Some(LevelBrowserAction::Play(level.clone())),
+				Entry::Invalid => None,
+			},
+			MenuEvent::Changed(_) => None,
+		}
+	}
+
+	/// Going back from a collection lists the directory it is in.
+	fn back(&mut self, profile: Option<&Profile>) -> Option<LevelBrowserAction> {
+		if self.collection_dir == self.levels_dir {
+			return Some(LevelBrowserAction::Back);
+		}
+		let parent_dir =
+			self.collection_dir.parent().map_or(self.levels_dir.clone(), Path::to_path_buf);
+		*self = LevelBrowser::listing(self.levels_dir.clone(), parent_dir, profile);
+		None
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 600.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let title = match self.collection_dir.strip_prefix(&self.levels_dir) {
+			Ok(collection) if !collection.as_os_str().is_empty() =>
+				format!("Levels/{}", collection.display()),
+			_ => "Levels".to_string(),
+		};
+		let mut title_text = Text::new(title);
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 70.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
+
+fn load_level(
+	levels_dir: &Path,
+	collection_dir: &Path,
+	path: &Path,
+) -> Result<BrowsedLevel, String> {
+	let level_str = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+	let lw = LogicalWorld::from_level_str(&level_str)?.generated_walls_outside();
+	if !lw.has_player() {
+		return Err("there is no bunny".to_string());
+	}
+	let relative_path = path.strip_prefix(levels_dir).unwrap_or(path);
+	Ok(BrowsedLevel {
+		// The same on every platform, and without the commas that separate ids in the profile.
+		id: format!("levels/{}", relative_path.display()).replace('\\', "/").replace(',', "_"),
+		levels_dir: levels_dir.to_path_buf(),
+		collection_dir: collection_dir.to_path_buf(),
+		metadata: LevelMetadata::from_level_str(&level_str)?,
+		lw,
+	})
+}
+
+/// The level's title (or file name) and author, with its best result if it was solved.
+fn level_widget(level: &BrowsedLevel, file_name: &str, profile: Option<&Profile>) -> Widget {
+	let metadata = &level.metadata;
+	let label = match (&metadata.title, &metadata.author) {
+		(Some(title), Some(author)) => format!("{title}, by {author}"),
+		(Some(title), None) => title.clone(),
+		(None, _) => file_name.to_string(),
+	};
+	let is_solved = profile.is_some_and(|profile| profile.solved_levels.contains(&level.id));
+	let best_turns = profile.and_then(|profile| profile.best_turns(&level.id));
+	let mut description = match best_turns {
+		Some(turns) => format!("{}.", metadata.score(turns)),
+		None if is_solved => "Solved.".to_string(),
+		None => match metadata.par_turns {
+			Some(par_turns) => format!("Not solved yet, par {par_turns}."),
+			None => "Not solved yet.".to_string(),
+		},
+	};
+	if let Some(level_description) = &metadata.description {
+		description = format!("{description} {level_description}");
+	}
+	Widget::Button {
+		label,
+		description: Some(description),
+		icon: is_solved.then(|| {
+			best_turns
+				.and_then(|turns| metadata.medal(turns))
+				.map_or(obj_to_sprite(&Obj::Exit), medal_to_sprite)
+		}),
+	}
+}
@@ -0,0 +1,720 @@
+//! Text format of handcrafted levels, to be written and read by humans.
+//!
+//! A level is a grid of characters, one per tile, with a legend telling what each character
+//! stands for. The legend comes first, one `c = ground obj` line per character (the object
+//! being optional), then a `---` line, then the rows of the grid from top to bottom. The
+//! top-left character of the grid is at coords (0, 0). A space is a tile that is not there,
+//! where the game puts walls. Lines that start with `;` are comments.
+//!
+//! Grounds and objects are written as a name followed by their parameters separated by `:`,
+//! like `plate:2` for a pressure plate of link 2 or `piston:up:2` for a piston facing up.
+//! Enemies have their HP then their max HP, like `slime:2:5`, the max HP being their HP when
+//! it is left out.
+//! Directions are `up`, `down`, `left` and `right`. Some characters don't have to be in the
+//! legend, they have a default meaning (that the legend can override):
+//!
+//! ```text
+//! .  floor
+//! #  wall
+//! @  bunny
+//! E  exit
+//! o  rock
+//! ~  water
+//! _  hole
+//! =  ice
+//! %  lava
+//! ```
+//!
+//! An object can be given a look of its own after it: a `like:obj` token for the object it is
+//! drawn as, and a name in quotes, like `o = floor rock like:golem "Boulder"` for a rock that
+//! looks like a golem and is called a boulder. It still plays like a rock.
+//!
+//! The legend may also hold `key: value` lines that tell about the level, see `LevelMetadata`.
+//!
+//! The format only holds what a level is made of, not how a game in it is going: the state
+//! of the player (like their redos), statuses and move tokens are not part of it.
+
+use std::collections::HashMap;
+
+use glam::IVec2;
+
+use crate::{
+	gameplay::{
+		Enchantment, Facing, Ground, LogicalWorld, Look, MirrorSlant, Mirroring, Obj, Tile,
+		DEFAULT_BUNNY_FORCE,
+	},
+	generation::filled_rect,
+};
+
+const DEFAULT_LEGEND: [(char, &str); 9] = [
+	('.', "floor"),
+	('#', "floor wall"),
+	('@', "floor bunny:7:7"),
+	('E', "floor exit"),
+	('o', "floor rock"),
+	('~', "water"),
+	('_', "hole"),
+	('=', "ice"),
+	('%', "lava"),
+];
+
+/// The characters given to the tiles that have no default character, in that order.
+fn free_chars() -> impl Iterator<Item = char> {
+	let default_chars: Vec<_> = DEFAULT_LEGEND.iter().map(|(c, _)| *c).collect();
+	('a'..='z')
+		.chain('A'..='Z')
+		.chain('0'..='9')
+		// When that is not enough, there are plenty of other letters.
+		.chain((0xc0..0x2af).filter_map(char::from_u32))
+		.filter(move |c| !default_chars.contains(c))
+}
+
+fn direction_name(direction: IVec2) -> &'static str {
+	match (direction.x, direction.y) {
+		(0, -1) => "up",
+		(0, 1) => "down",
+		(-1, 0) => "left",
+		_ => "right",
+	}
+}
+
+fn parse_direction(name: &str) -> Result<IVec2, String> {
+	match name {
+		"up" => Ok(IVec2::new(0, -1)),
+		"down" => Ok(IVec2::new(0, 1)),
+		"left" => Ok(IVec2::new(-1, 0)),
+		"right" => Ok(IVec2::new(1, 0)),
+		_ => Err(format!("\"{name}\" is not a direction")),
+	}
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+	text.parse().map_err(|_| format!("\"{text}\" is not a number"))
+}
+
+/// An enemy's max HP is only written when it is not its HP.
+fn hp_params(hp: i32, max_hp: i32) -> String {
+	if hp == max_hp {
+		format!("{hp}")
+	} else {
+		format!("{hp}:{max_hp}")
+	}
+}
+
+fn parse_hp_params(params: &[&str]) -> Result<(i32, i32), String> {
+	match params {
+		[hp] => {
+			let hp = parse_number(hp)?;
+			Ok((hp, hp))
+		},
+		[hp, max_hp] => Ok((parse_number(hp)?, parse_number(max_hp)?)),
+		_ => Err(format!("\"{}\" is not an amount of HP", params.join(":"))),
+	}
+}
+
+fn enchantment_suffix(enchantment: Option<Enchantment>) -> &'static str {
+	match enchantment {
+		None => "",
+		Some(Enchantment::Flaming) => ":flaming",
+		Some(Enchantment::Frost) => ":frost",
+	}
+}
+
+fn parse_enchantment(params: &[&str]) -> Result<Option<Enchantment>, String> {
+	match params {
+		[] => Ok(None),
+		["flaming"] => Ok(Some(Enchantment::Flaming)),
+		["frost"] => Ok(Some(Enchantment::Frost)),
+		_ => Err(format!("\"{}\" is not an enchantment", params.join(":"))),
+	}
+}
+
+fn ground_token(ground: &Ground) -> String {
+	match ground {
+		Ground::Floor => "floor".to_string(),
+		Ground::PressurePlate { link_id } => format!("plate:{link_id}"),
+		Ground::OpenGate { link_id } => format!("open_gate:{link_id}"),
+		Ground::Hole => "hole".to_string(),
+		Ground::Ice => "ice".to_string(),
+		Ground::FragileFloor => "fragile".to_string(),
+		Ground::Water => "water".to_string(),
+		Ground::Raft => "raft".to_string(),
+		Ground::Conveyor { direction } => format!("conveyor:{}", direction_name(*direction)),
+		Ground::Lava => "lava".to_string(),
+		Ground::Spikes { armed: false } => "spikes".to_string(),
+		Ground::Spikes { armed: true } => "spikes:armed".to_string(),
+		Ground::Rubble => "rubble".to_string(),
+		Ground::Teleporter { link_id } => format!("teleporter:{link_id}"),
+		Ground::Price { price } => format!("price:{price}"),
+	}
+}
+
+fn parse_ground(token: &str) -> Result<Ground, String> {
+	let (name, params) = split_token(token);
+	Ok(match (name, params.as_slice()) {
+		("floor", []) => Ground::Floor,
+		("plate", [link_id]) => Ground::PressurePlate { link_id: parse_number(link_id)? },
+		("open_gate", [link_id]) => Ground::OpenGate { link_id: parse_number(link_id)? },
+		("hole", []) => Ground::Hole,
+		("ice", []) => Ground::Ice,
+		("fragile", []) => Ground::FragileFloor,
+		("water", []) => Ground::Water,
+		("raft", []) => Ground::Raft,
+		("conveyor", [direction]) => Ground::Conveyor { direction: parse_direction(direction)? },
+		("lava", []) => Ground::Lava,
+		("spikes", []) => Ground::Spikes { armed: false },
+		("spikes", ["armed"]) => Ground::Spikes { armed: true },
+		("rubble", []) => Ground::Rubble,
+		("teleporter", [link_id]) => Ground::Teleporter { link_id: parse_number(link_id)? },
+		("price", [price]) => Ground::Price { price: parse_number(price)? },
+		_ => return Err(format!("\"{token}\" is not a ground")),
+	})
+}
+
+fn obj_token(obj: &Obj) -> String {
+	match obj {
+		Obj::Wall => "wall".to_string(),
+		Obj::Sword { enchantment } => format!("sword{}", enchantment_suffix(*enchantment)),
+		Obj::Shield => "shield".to_string(),
+		Obj::Pickaxe { enchantment } => format!("pickaxe{}", enchantment_suffix(*enchantment)),
+		Obj::Rock => "rock".to_string(),
+		Obj::Exit => "exit".to_string(),
+		Obj::VisionGem => "vision_gem".to_string(),
+		Obj::VisionSword => "vision_sword".to_string(),
+		Obj::Heart => "heart".to_string(),
+		Obj::RedoHeart => "redo_heart".to_string(),
+		Obj::RewindToken => "rewind_token".to_string(),
+		Obj::Door => "door".to_string(),
+		Obj::Key => "key".to_string(),
+		Obj::Rope => "rope".to_string(),
+		Obj::Bush => "bush".to_string(),
+		Obj::Note => "note".to_string(),
+		Obj::Piston { facing, extended, link_id } => format!(
+			"piston:{}:{link_id}{}",
+			direction_name(*facing),
+			if *extended { ":extended" } else { "" }
+		),
+		Obj::Gate { link_id } => format!("gate:{link_id}"),
+		Obj::PistonHead => "piston_head".to_string(),
+		Obj::Statue { facing, group } => format!("statue:{}:{group}", direction_name(*facing)),
+		Obj::Altar { group } => format!("altar:{group}"),
+		Obj::Bow => "bow".to_string(),
+		Obj::Torch => "torch".to_string(),
+		Obj::Magnet => "magnet".to_string(),
+		Obj::Crate { content: None } => "crate".to_string(),
+		Obj::Crate { content: Some(content) } => format!("crate:{}", obj_token(content)),
+		Obj::StrengthPotion => "strength_potion".to_string(),
+		Obj::Raft => "raft".to_string(),
+		Obj::LaserEmitter { facing } => format!("laser:{}", direction_name(*facing)),
+		Obj::Mirror { slant: MirrorSlant::Rising } => "mirror:rising".to_string(),
+		Obj::Mirror { slant: MirrorSlant::Falling } => "mirror:falling".to_string(),
+		Obj::Arrow { direction, .. } => format!("arrow:{}", direction_name(*direction)),
+		Obj::Bomb { fuse: None } => "bomb".to_string(),
+		Obj::Bomb { fuse: Some(fuse) } => format!("bomb:{fuse}"),
+		Obj::Barrel { set_off: false } => "barrel".to_string(),
+		Obj::Barrel { set_off: true } => "barrel:set_off".to_string(),
+		Obj::CursedMirror => "cursed_mirror".to_string(),
+		Obj::Coin => "coin".to_string(),
+		Obj::KeyShard => "key_shard".to_string(),
+		Obj::SlimeCorpse => "slime_corpse".to_string(),
+		Obj::Bunny { hp, max_hp, .. } => format!("bunny:{hp}:{max_hp}"),
+		Obj::Slime { hp, max_hp, .. } => format!("slime:{}", hp_params(*hp, *max_hp)),
+		Obj::Shroomer { hp, max_hp, .. } => format!("shroomer:{}", hp_params(*hp, *max_hp)),
+		Obj::Archer { hp, max_hp, .. } => format!("archer:{}", hp_params(*hp, *max_hp)),
+		Obj::Ghost { hp, max_hp, .. } => format!("ghost:{}", hp_params(*hp, *max_hp)),
+		Obj::GolemBoss { hp, max_hp, .. } => format!("golem_boss:{}", hp_params(*hp, *max_hp)),
+		Obj::Shroom { .. } => "shroom".to_string(),
+		Obj::Fish { direction, .. } => format!("fish:{}", direction_name(*direction)),
+		Obj::Golem { mirroring: Mirroring::Reversed, .. } => "golem:reversed".to_string(),
+		Obj::Golem { mirroring: Mirroring::Rotated, .. } => "golem:rotated".to_string(),
+	}
+}
+
+/// Enemies that come back home are given the coords they are at as their home.
+fn parse_obj(token: &str, coords: IVec2) -> Result<Obj, String> {
+	let (name, params) = split_token(token);
+	Ok(match (name, params.as_slice()) {
+		("wall", []) => Obj::Wall,
+		("sword", params) => Obj::Sword { enchantment: parse_enchantment(params)? },
+		("shield", []) => Obj::Shield,
+		("pickaxe", params) => Obj::Pickaxe { enchantment: parse_enchantment(params)? },
+		("rock", []) => Obj::Rock,
+		("exit", []) => Obj::Exit,
+		("vision_gem", []) => Obj::VisionGem,
+		("vision_sword", []) => Obj::VisionSword,
+		("heart", []) => Obj::Heart,
+		("redo_heart", []) => Obj::RedoHeart,
+		("rewind_token", []) => Obj::RewindToken,
+		("door", []) => Obj::Door,
+		("key", []) => Obj::Key,
+		("rope", []) => Obj::Rope,
+		("bush", []) => Obj::Bush,
+		("note", []) => Obj::Note,
+		("piston", [facing, link_id]) => Obj::Piston {
+			facing: parse_direction(facing)?,
+			extended: false,
+			link_id: parse_number(link_id)?,
+		},
+		("piston", [facing, link_id, "extended"]) => Obj::Piston {
+			facing: parse_direction(facing)?,
+			extended: true,
+			link_id: parse_number(link_id)?,
+		},
+		("gate", [link_id]) => Obj::Gate { link_id: parse_number(link_id)? },
+		("piston_head", []) => Obj::PistonHead,
+		("statue", [facing, group]) =>
+			Obj::Statue { facing: parse_direction(facing)?, group: parse_number(group)? },
+		("altar", [group]) => Obj::Altar { group: parse_number(group)? },
+		("bow", []) => Obj::Bow,
+		("torch", []) => Obj::Torch,
+		("magnet", []) => Obj::Magnet,
+		("crate", []) => Obj::Crate { content: None },
+		("crate", content) =>
+			Obj::Crate { content: Some(Box::new(parse_obj(&content.join(":"), coords)?)) },
+		("strength_potion", []) => Obj::StrengthPotion,
+		("raft", []) => Obj::Raft,
+		("laser", [facing]) => Obj::LaserEmitter { facing: parse_direction(facing)? },
+		("mirror", ["rising"]) => Obj::Mirror { slant: MirrorSlant::Rising },
+		("mirror", ["falling"]) => Obj::Mirror { slant: MirrorSlant::Falling },
+		("arrow", [direction]) =>
+			Obj::Arrow { direction: parse_direction(direction)?, move_token: false },
+		("bomb", []) => Obj::Bomb { fuse: None },
+		("bomb", [fuse]) => Obj::Bomb { fuse: Some(parse_number(fuse)?) },
+		("barrel", []) => Obj::Barrel { set_off: false },
+		("barrel", ["set_off"]) => Obj::Barrel { set_off: true },
+		("cursed_mirror", []) => Obj::CursedMirror,
+		("coin", []) => Obj::Coin,
+		("key_shard", []) => Obj::KeyShard,
+		("slime_corpse", []) => Obj::SlimeCorpse,
+		("bunny", [hp, max_hp]) => Obj::Bunny {
+			hp: parse_number(hp)?,
+			max_hp: parse_number(max_hp)?,
+			statuses: vec![],
+			facing: Facing::Left,
+			held: None,
+			force: DEFAULT_BUNNY_FORCE,
+		},
+		("slime", params) => {
+			let (hp, max_hp) = parse_hp_params(params)?;
+			Obj::Slime {
+				hp,
+				max_hp,
+				move_token: false,
+				home: coords,
+				statuses: vec![],
+				facing: Facing::Left,
+			}
+		},
+		("shroomer", params) => {
+			let (hp, max_hp) = parse_hp_params(params)?;
+			Obj::Shroomer {
+				hp,
+				max_hp,
+				move_token: false,
+				home: coords,
+				statuses: vec![],
+				facing: Facing::Left,
+			}
+		},
+		("archer", params) => {
+			let (hp, max_hp) = parse_hp_params(params)?;
+			Obj::Archer {
+				hp,
+				max_hp,
+				move_token: false,
+				statuses: vec![],
+				facing: Facing::Left,
+			}
+		},
+		("ghost", params) => {
+			let (hp, max_hp) = parse_hp_params(params)?;
+			Obj::Ghost { hp, max_hp, move_token: false, facing: Facing::Left }
+		},
+		("golem_boss", params) => {
+			let (hp, max_hp) = parse_hp_params(params)?;
+			Obj::GolemBoss { hp, max_hp, move_token: false, resting: false, statuses: vec![] }
+		},
+		("shroom", []) => Obj::Shroom { move_token: false },
+		("fish", [direction]) =>
+			Obj::Fish { direction: parse_direction(direction)?, move_token: false },
+		("golem", ["reversed"]) => Obj::Golem { mirroring: Mirroring::Reversed, move_token: false },
+		("golem", ["rotated"]) => Obj::Golem { mirroring: Mirroring::Rotated, move_token: false },
+		_ => return Err(format!("\"{token}\" is not an object")),
+	})
+}
+
+/// What a handcrafted level tells about itself, with `key: value` lines among its legend,
+/// all optional: `title`, `author`, `description`, `par` (in how many turns it can be solved)
+/// and `redos` (how many redos the player has in it).
+#[derive(Clone, Default)]
+pub struct LevelMetadata {
+	pub title: Option<String>,
+	pub author: Option<String>,
+	pub description: Option<String>,
+	pub par_turns: Option<i32>,
+	pub redos: Option<i32>,
+}
+
+/// How well a level was solved, compared to its par.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Medal {
+	Bronze,
+	/// At most half the par more than the par.
+	Silver,
+	/// At par or better.
+	Gold,
+}
+
+impl Medal {
+	/// The medal for solving a level of the given par in the given number of turns.
+	pub fn for_turns(turns: i32, par_turns: i32) -> Medal {
+		if turns <= par_turns {
+			Medal::Gold
+		} else if turns <= par_turns + par_turns / 2 {
+			Medal::Silver
+		} else {
+			Medal::Bronze
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Medal::Bronze => "bronze",
+			Medal::Silver => "silver",
+			Medal::Gold => "gold",
+		}
+	}
+}
+
+/// The key and value of a line of the legend that is metadata, if it is.
+fn metadata_line(line: &str) -> Option<(&str, &str)> {
+	let (key, value) = line.split_once(':')?;
+	let key = key.trim();
+	["title", "author", "description", "par", "redos"].contains(&key).then_some((key, value.trim()))
+}
+
+impl LevelMetadata {
+	/// Reads the metadata of a level written in the level format.
+	pub fn from_level_str(level_str: &str) -> Result<LevelMetadata, String> {
+		let mut metadata = LevelMetadata::default();
+		for line in level_str.lines().take_while(|line| line.trim_end() != "---") {
+			match metadata_line(line) {
+				Some(("title", title)) => metadata.title = Some(title.to_string()),
+				Some(("author", author)) => metadata.author = Some(author.to_string()),
+				Some(("description", description)) =>
+					metadata.description = Some(description.to_string()),
+				Some(("par", par_turns)) => metadata.par_turns = Some(parse_number(par_turns)?),
+				Some(("redos", redos)) => metadata.redos = Some(parse_number(redos)?),
+				_ => {},
+			}
+		}
+		Ok(metadata)
+	}
+
+	/// The metadata lines, to be written at the top of a level in the level format.
+	pub fn to_level_str(&self) -> String {
+		let mut lines = String::new();
+		let mut add_line = |key: &str, value: Option<String>| {
+			if let Some(value) = value {
+				lines.push_str(&format!("{key}: {value}\n"));
+			}
+		};
+		add_line("title", self.title.clone());
+		add_line("author", self.author.clone());
+		add_line("description", self.description.clone());
+		add_line("par", self.par_turns.map(|par_turns| par_turns.to_string()));
+		add_line("redos", self.redos.map(|redos| redos.to_string()));
+		lines
+	}
+
+	/// The medal for solving the level in the given number of turns, if it has a par.
+	pub fn medal(&self, turns: i32) -> Option<Medal> {
+		self.par_turns.map(|par_turns| Medal::for_turns(turns, par_turns))
+	}
+
+	/// What to tell the player who solved the level in the given number of turns.
+	pub fn score(&self, turns: i32) -> String {
+		match (self.par_turns, self.medal(turns)) {
+			(Some(par_turns), Some(medal)) => format!(
+				"Solved in {turns} turns (par {par_turns}), {} medal",
+				medal.name()
+			),
+			_ => format!("Solved in {turns} turns"),
+		}
+	}
+}
+
+fn split_token(token: &str) -> (&str, Vec<&str>) {
+	let mut parts = token.split(':');
+	let name = parts.next().unwrap_or_default();
+	(name, parts.collect())
+}
+
+/// What a character of the legend stands for, the ground then the object if any,
+/// and the look of the object if it has one.
+fn tile_entry(tile: &Tile, look: Option<&Look>) -> String {
+	let mut entry = ground_token(&tile.ground);
+	if let Some(obj) = tile.obj.as_ref() {
+		entry.push_str(&format!(" {}", obj_token(obj)));
+		if let Some(drawn_as) = look.and_then(|look| look.drawn_as.as_ref()) {
+			entry.push_str(&format!(" like:{}", obj_token(drawn_as)));
+		}
+		if let Some(name) = look.and_then(|look| look.name.as_ref()) {
+			entry.push_str(&format!(" \"{name}\""));
+		}
+	}
+	entry
+}
+
+/// Takes the name in quotes out of an entry of the legend, if there is one.
+fn take_quoted_name(entry: &str) -> Result<(String, Option<String>), String> {
+	let Some((before, after)) = entry.split_once('"') else {
+		return Ok((entry.to_string(), None));
+	};
+	let Some((name, after)) = after.split_once('"') else {
+		return Err(format!("the name \"{after} has no closing quote"));
+	};
+	Ok((format!("{before} {after}"), Some(name.to_string())))
+}
+
+impl LogicalWorld {
+	/// Reads a level written in the level format, see the `level_format` module.
+	/// The walls that surround the level in game are not generated.
+	pub fn from_level_str(level_str: &str) -> Result<LogicalWorld, String> {
+		let lines: Vec<_> = level_str.lines().filter(|line| !line.starts_with(';')).collect();
+		let (legend_lines, grid_lines) = match lines.iter().position(|line| line.trim_end() == "---")
+		{
+			Some(separator) => (&lines[..separator], &lines[separator + 1..]),
+			None => (&lines[..0], &lines[..]),
+		};
+		let mut legend: HashMap<char, String> =
+			DEFAULT_LEGEND.iter().map(|(c, entry)| (*c, entry.to_string())).collect();
+		for (line_index, line) in legend_lines.iter().enumerate() {
+			if line.trim().is_empty() || metadata_line(line).is_some() {
+				continue;
+			}
+			let mut chars = line.chars();
+			let (Some(c), Some(entry)) = (chars.next(), chars.as_str().trim().strip_prefix('='))
+			else {
+				return Err(format!(
+					"line {} of the legend is not like \"c = ground obj\"",
+					line_index + 1
+				));
+			};
+			legend.insert(c, entry.trim().to_string());
+		}
+
+		let mut lw = LogicalWorld::new_empty();
+		let mut multi_tile_objs = vec![];
+		for (y, line) in grid_lines.iter().enumerate() {
+			for (x, c) in line.chars().enumerate() {
+				if c == ' ' {
+					continue;
+				}
+				let coords = IVec2::new(x as i32, y as i32);
+				let entry =
+					legend.get(&c).ok_or_else(|| format!("'{c}' at {coords} is not in the legend"))?;
+				let (entry, name) =
+					take_quoted_name(entry).map_err(|error| format!("'{c}': {error}"))?;
+				let mut tokens = entry.split_whitespace();
+				let ground = parse_ground(tokens.next().unwrap_or_default())
+					.map_err(|error| format!("'{c}': {error}"))?;
+				let obj = tokens
+					.next()
+					.map(|token| parse_obj(token, coords))
+					.transpose()
+					.map_err(|error| format!("'{c}': {error}"))?;
+				let drawn_as = tokens
+					.next()
+					.map(|token| match token.strip_prefix("like:") {
+						Some(drawn_as) => parse_obj(drawn_as, coords),
+						None => Err(format!("\"{token}\" is not like \"like:obj\"")),
+					})
+					.transpose()
+					.map_err(|error| format!("'{c}': {error}"))?;
+				if tokens.next().is_some() {
+					return Err(format!(
+						"'{c}' stands for more than a ground, an object and its look"
+					));
+				}
+				if name.is_some() || drawn_as.is_some() {
+					let Some(obj) = obj.as_ref() else {
+						return Err(format!("'{c}' gives a look to no object"));
+					};
+					lw.looks.insert(coords, Look::new(obj, name, drawn_as));
+				}
+				match obj {
+					Some(obj) if obj.footprint() != IVec2::ONE => {
+						lw.place_tile(coords, Tile { ground, obj: None, visible: false });
+						multi_tile_objs.push((coords, obj));
+					},
+					obj => lw.place_tile(coords, Tile { ground, obj, visible: false }),
+				}
+			}
+		}
+		// Once all the tiles are there, the multi-tile objects can take their footprints.
+		for (coords, obj) in multi_tile_objs {
+			let footprint_is_free = filled_rect(coords, obj.footprint())
+				.into_iter()
+				.all(|coords| lw.tile(coords).is_some_and(|tile| tile.obj.is_none()));
+			if !footprint_is_free {
+				return Err(format!(
+					"{} at {coords} doesn't have the room to fit",
+					obj.name()
+				));
+			}
+			lw.place_multi_tile_obj(coords, obj);
+		}
+		Ok(lw)
+	}
+
+	/// Writes the level in the level format, see the `level_format` module.
+	/// The top-left corner of the level ends up at coords (0, 0).
+	pub fn to_level_str(&self) -> String {
+		let Some(first_coords) = self.tiles().next().map(|(coords, _tile)| coords) else {
+			return "---\n".to_string();
+		};
+		let (min, max) =
+			self.tiles().fold((first_coords, first_coords), |(min, max), (coords, _)| {
+				(min.min(coords), max.max(coords))
+			});
+		let default_chars: HashMap<_, _> =
+			DEFAULT_LEGEND.iter().map(|(c, entry)| (entry.to_string(), *c)).collect();
+		let mut legend: Vec<(char, String)> = vec![];
+		let mut free_chars = free_chars();
+		let mut rows = vec![];
+		for y in min.y..=max.y {
+			let mut row = String::new();
+			for x in min.x..=max.x {
+				let Some(tile) = self.tile(IVec2::new(x, y)) else {
+					row.push(' ');
+					continue;
+				};
+				let entry = tile_entry(tile, self.looks.get(&IVec2::new(x, y)));
+				let c = if let Some(&c) = default_chars.get(&entry) {
+					c
+				} else if let Some((c, _)) = legend.iter().find(|(_, other)| *other == entry) {
+					*c
+				} else {
+					let c = free_chars.next().unwrap_or('?');
+					legend.push((c, entry));
+					c
+				};
+				row.push(c);
+			}
+			rows.push(row.trim_end().to_string());
+		}
+		let mut level_str = String::new();
+		for (c, entry) in legend {
+			level_str.push_str(&format!("{c} = {entry}\n"));
+		}
+		level_str.push_str("---\n");
+		for row in rows {
+			level_str.push_str(&row);
+			level_str.push('\n');
+		}
+		level_str
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn levels_are_written_as_they_are_read() {
+		let level_str = "\
+			title: The boulder\n\
+			author: Someone\n\
+			par: 12\n\
+			a = floor golem_boss:8:10\n\
+			b = floor rock like:slime:3 \"Boulder\"\n\
+			c = plate:1\n\
+			d = floor gate:1\n\
+			---\n\
+			#####\n\
+			#@a.b\n\
+			#c..d\n\
+			#  ~\n";
+		let lw = LogicalWorld::from_level_str(level_str).unwrap();
+		let look = lw.looks.get(&IVec2::new(4, 1)).unwrap();
+		assert_eq!(look.name.as_deref(), Some("Boulder"));
+		assert!(matches!(look.drawn_as, Some(Obj::Slime { hp: 3, .. })));
+		assert!(matches!(
+			lw.occupant(IVec2::new(3, 2)),
+			Some(Obj::GolemBoss { hp: 8, .. })
+		));
+		assert!(lw.tile(IVec2::new(1, 3)).is_none());
+		let written = lw.to_level_str();
+		assert_eq!(
+			written,
+			LogicalWorld::from_level_str(&written).unwrap().to_level_str()
+		);
+		assert_eq!(
+			written,
+			level_str.lines().skip(3).collect::<Vec<_>>().join("\n") + "\n"
+		);
+
+		let metadata = LevelMetadata::from_level_str(level_str).unwrap();
+		assert_eq!(metadata.title.as_deref(), Some("The boulder"));
+		assert_eq!(metadata.author.as_deref(), Some("Someone"));
+		assert_eq!(metadata.par_turns, Some(12));
+		assert_eq!(metadata.description, None);
+		assert_eq!(
+			metadata.to_level_str(),
+			level_str.lines().take(3).collect::<Vec<_>>().join("\n") + "\n"
+		);
+	}
+
+	#[test]
+	fn malformed_legend_lines_are_told_apart() {
+		let error = |legend_line: &str| {
+			let level_str = format!("{legend_line}\n---\n.a.\n");
+			LogicalWorld::from_level_str(&level_str).err().unwrap()
+		};
+		assert_eq!(
+			error("a floor"),
+			"line 1 of the legend is not like \"c = ground obj\""
+		);
+		assert_eq!(error("b = floor"), "'a' at [1, 0] is not in the legend");
+		assert_eq!(error("a = carpet"), "'a': \"carpet\" is not a ground");
+		assert_eq!(
+			error("a = floor teapot"),
+			"'a': \"teapot\" is not an object"
+		);
+		assert_eq!(
+			error("a = floor slime:1:2:3"),
+			"'a': \"1:2:3\" is not an amount of HP"
+		);
+		assert_eq!(error("a = floor slime:x"), "'a': \"x\" is not a number");
+		assert_eq!(
+			error("a = floor rock golem"),
+			"'a': \"golem\" is not like \"like:obj\""
+		);
+		assert_eq!(
+			error("a = floor rock like:slime:2 \"Boulder\" rock"),
+			"'a' stands for more than a ground, an object and its look"
+		);
+		assert_eq!(
+			error("a = floor rock \"Boulder"),
+			"'a': the name \"Boulder has no closing quote"
+		);
+		assert_eq!(
+			error("a = floor \"Boulder\""),
+			"'a' gives a look to no object"
+		);
+	}
+
+	#[test]
+	fn multi_tile_objects_need_the_room_to_fit() {
+		let error = |grid: &str| {
+			let level_str = format!("g = floor golem_boss:10\n---\n{grid}");
+			LogicalWorld::from_level_str(&level_str).err()
+		};
+		assert_eq!(error("g.\n..\n"), None);
+		let no_room = Some("Golem boss at [0, 0] doesn't have the room to fit".to_string());
+		assert_eq!(error("g.\n.o\n"), no_room);
+		assert_eq!(error("g.\n. \n"), no_room);
+		assert_eq!(error("g\n"), no_room);
+	}
+}
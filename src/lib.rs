@@ -0,0 +1,41 @@
+//! The simulation of PushDg, the logic of the game without its window, graphics or sounds.
+//!
+//! The game itself is the `pushdg` binary (`src/main.rs`), built with the default `game`
+//! feature. Without that feature only this library is built, with no dependency on ggez, to
+//! drive runs headlessly (see `simulation`).
+//!
+//! # Stability
+//!
+//! The items at the root of the crate are what solvers, bots and other tools can depend on:
+//! a `World` is changed by the player's `Action`s into `Transition`s, and levels are generated
+//! by a `Generator` from a `Seed`, with a `Simulation` to play whole turns. They follow semantic
+//! versioning, a breaking change to them (or to the public methods and fields of their types)
+//! only comes with a new major version, or a new minor version while it is `0.x` as Cargo
+//! has it. The modules are public too, for the game and for tools that need more, but what is
+//! in them may change in any version. How the world is stored, like its grid of tiles, is
+//! private.
+
+mod chunked_grid;
+pub mod director;
+pub mod field_of_view;
+pub mod gameplay;
+pub mod generation;
+pub mod level_format;
+pub mod metrics;
+pub mod simulation;
+mod stable_hash;
+
+/// The state of a level being played: its tiles, the bunny and what it carries.
+pub type World = gameplay::LogicalWorld;
+
+/// What the player can do on their turn, see `World::player_act`.
+pub type Action = gameplay::PlayerAction;
+
+/// A world changed by an action or by a turn of the game, with the events of the change.
+pub type Transition = gameplay::LogicalTransition;
+
+/// What the levels of a run are generated from, given to `generation::generate_level`.
+pub type Generator = generation::RunConfig;
+
+pub use generation::Seed;
+pub use simulation::Simulation;
@@ -0,0 +1,155 @@
+//! Lore notes, story fragments that the player can find in the dungeon and read.
+//!
+//! The fragments are written in a data file, separated by `---` lines, the first line of each
+//! fragment being its title. Reading a note reveals the next fragment not read yet,
+//! and all the fragments read so far are kept in a journal that can be opened.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Quad, Text},
+};
+
+use crate::{graphics::DepthLayer, widgets::centered_rect};
+
+struct LoreFragment {
+	title: String,
+	text: String,
+}
+
+fn load_fragments(source: &str) -> Vec<LoreFragment> {
+	source
+		.split("\n---\n")
+		.filter_map(|fragment| {
+			let (title, text) = fragment.trim().split_once('\n')?;
+			Some(LoreFragment { title: title.trim().to_string(), text: text.trim().to_string() })
+		})
+		.collect()
+}
+
+pub struct Journal {
+	fragments: Vec<LoreFragment>,
+	/// Indices of the fragments read so far, in the order in which they were read.
+	read: Vec<usize>,
+	/// The fragment that was just read and that is displayed until dismissed.
+	reading: Option<usize>,
+	/// Is the journal screen (listing the fragments read so far) displayed?
+	journal_screen_open: bool,
+}
+
+impl Journal {
+	pub fn new() -> Journal {
+		Journal {
+			fragments: load_fragments(include_str!("../assets/lore.txt")),
+			read: vec![],
+			reading: None,
+			journal_screen_open: false,
+		}
+	}
+
+	/// Replaces the fragments by the ones of the given data, keeping track of what was read
+	/// as long as the fragments read are still there.
+	#[cfg(debug_assertions)]
+	pub fn reload_fragments(&mut self, source: &str) {
+		self.fragments = load_fragments(source);
+		let fragment_count = self.fragments.len();
+		self.read.retain(|index| *index < fragment_count);
+		self.reading = self.reading.filter(|index| *index < fragment_count);
+	}
+
+	/// A note was read, it reveals the next fragment that was not read yet.
+	/// When all fragments were read, the notes are just the last fragment all over again.
+	pub fn read_a_note(&mut self) {
+		let unread = (0..self.fragments.len()).find(|index| !self.read.contains(index));
+		if let Some(index) = unread {
+			self.read.push(index);
+		}
+		self.reading = unread.or(self.read.last().copied());
+	}
+
+	/// While something is displayed, the game is expected to ignore the player's moves.
+	pub fn is_showing_something(&self) -> bool {
+		self.reading.is_some() || self.journal_screen_open
+	}
+
+	pub fn dismiss(&mut self) {
+		if self.reading.is_some() {
+			self.reading = None;
+		} else {
+			self.journal_screen_open = false;
+		}
+	}
+
+	pub fn toggle_journal_screen(&mut self) {
+		self.reading = None;
+		self.journal_screen_open = !self.journal_screen_open;
+	}
+
+	pub fn draw(&self, canvas: &mut Canvas, screen_size: Vec2) {
+		if let Some(index) = self.reading {
+			let fragment = &self.fragments[index];
+			self.draw_panel(canvas, &fragment.title, &fragment.text, screen_size);
+		} else if self.journal_screen_open {
+			let content = if self.read.is_empty() {
+				"No notes were read yet.".to_string()
+			} else {
+				self
+					.read
+					.iter()
+					.map(|&index| {
+						let fragment = &self.fragments[index];
+						format!("{}\n{}", fragment.title, fragment.text)
+					})
+					.collect::<Vec<_>>()
+					.join("\n\n")
+			};
+			let title = format!("Journal ({}/{})", self.read.len(), self.fragments.len());
+			self.draw_panel(canvas, &title, &content, screen_size);
+		}
+	}
+
+	fn draw_panel(&self, canvas: &mut Canvas, title: &str, content: &str, screen_size: Vec2) {
+		let panel_rect = centered_rect(640.0, 640.0, screen_size);
+		let z = DepthLayer::Popup.to_z_value();
+		canvas.draw(
+			&Quad,
+			DrawParam::default().dest_rect(panel_rect).color(Color::new(0.16, 0.13, 0.1, 0.95)).z(z),
+		);
+		let margin = 30.0;
+		let mut title_text = Text::new(title);
+		title_text.set_scale(32.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin))
+				.color(Color::new(0.95, 0.89, 0.77, 1.0))
+				.z(z),
+		);
+		let mut content_text = Text::new(content);
+		content_text.set_scale(18.0).set_bounds(Vec2::new(
+			panel_rect.w - margin * 2.0,
+			panel_rect.h - margin * 4.0,
+		));
+		canvas.draw(
+			&content_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 50.0,
+				))
+				.color(Color::new(0.95, 0.89, 0.77, 1.0))
+				.z(z),
+		);
+		let mut dismiss_text = Text::new("Press Enter to close");
+		dismiss_text.set_scale(14.0);
+		canvas.draw(
+			&dismiss_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.bottom() - margin,
+				))
+				.color(Color::new(0.6, 0.6, 0.6, 1.0))
+				.z(z),
+		);
+	}
+}
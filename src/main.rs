@@ -1,21 +1,153 @@
-mod gameplay;
-mod generation;
+mod audio;
+mod campaign;
+mod daily;
+mod editor;
+mod favorites;
+mod ghost;
 mod graphics;
+#[cfg(debug_assertions)]
+mod hot_reload;
+mod level_browser;
+mod lore;
+mod metrics_overlay;
+mod new_game_plus;
+#[cfg(feature = "observer")]
+mod observer;
+mod onboarding;
+mod options;
+mod pause;
+mod perks;
+mod photo;
+mod profile;
+mod run_stats;
+mod run_styles;
+mod save;
+#[cfg(debug_assertions)]
+mod sprite_packing;
 mod spritesheet;
+mod title;
+mod tutorial;
+mod unlocks;
+mod weather;
+mod widgets;
 
-use gameplay::{LogicalTransition, LogicalWorld};
-use generation::generate_level;
+use std::{
+	collections::{HashSet, VecDeque},
+	time::{Duration, Instant},
+};
+
+use audio::Audio;
+use campaign::{campaign_levels, CampaignAction, CampaignScreen, LevelIntro};
+use daily::{daily_run_config, today, DailyResult};
+use editor::{EditorAction, LevelEditor};
+use favorites::{Favorites, FavoritesAction, FavoritesScreen};
 use ggez::{
-	conf::{WindowMode, WindowSetup},
+	conf::{FullscreenType, WindowMode, WindowSetup},
 	event::{run, EventHandler},
-	glam::IVec2,
+	glam::{IVec2, Vec2},
 	graphics::{Canvas, Color, Sampler},
-	input::keyboard::KeyInput,
+	input::{keyboard::KeyInput, mouse::MouseButton},
 	winit::event::VirtualKeyCode,
 	Context, ContextBuilder, GameResult,
 };
-use graphics::{Camera, GraphicalWorld};
+use ghost::Ghost;
+use graphics::{
+	draw_ghost, draw_history_overlay, draw_scent_map, CachedHud, Camera, DrawOptions,
+	GraphicalWorld, PixelPerfectRenderer,
+};
+use level_browser::{BrowsedLevel, LevelBrowser, LevelBrowserAction};
+use lore::Journal;
+use metrics_overlay::draw_metrics_overlay;
+use new_game_plus::VictoryScreen;
+use onboarding::Onboarding;
+use options::{
+	animation_speed_factor, OptionsAction, OptionsScreen, Settings, MAX_ZOOM, MIN_ZOOM,
+	NORMAL_ANIMATION_SPEED,
+};
+use pause::{PauseAction, PauseScreen};
+use perks::PerkChoice;
+use photo::PhotoMode;
+use profile::Profile;
+use pushdg::{
+	gameplay::{self, AssistOptions, LogicalEvent, LogicalTransition, LogicalWorld, PlayerAction},
+	generation::{self, generate_level, RunConfig, FINAL_DEPTH},
+	level_format::{self, LevelMetadata},
+	metrics,
+	simulation::{next_level, play_game_turn},
+};
+use run_stats::draw_stats_panel;
+use run_styles::{RunHistory, RunStyleTracker};
+use save::SavedRun;
 use spritesheet::SpritesheetStuff;
+use title::{TitleAction, TitleScreen};
+use tutorial::{obj_inspection, Tutorial};
+use unlocks::{Unlocks, UnlocksScreen};
+use weather::Weather;
+use widgets::{draw_tooltip, Toast};
+
+#[global_allocator]
+static ALLOCATOR: metrics::CountingAllocator = metrics::CountingAllocator;
+
+/// The size of the window when the game starts, in pixels (it can then be resized).
+const SCREEN_SIZE: Vec2 = Vec2::new(800.0, 800.0);
+
+/// Smaller than that, the menu panels would not fit in the window.
+const MIN_SCREEN_SIZE: Vec2 = Vec2::new(700.0, 700.0);
+
+/// How many previous states of the world are kept to be able to redo moves,
+/// unless overridden by the `PUSHDG_HISTORY_CAP` environment variable.
+const DEFAULT_HISTORY_CAP: usize = 500;
+
+/// How many orders of the agents a rewind tries before giving up on the turn playing out
+/// any differently.
+const REWIND_REROLLS: usize = 8;
+
+/// The keys that move the bunny, holding one of them skips the animations of the game's turns.
+const MOVEMENT_KEYS: [VirtualKeyCode; 10] = [
+	VirtualKeyCode::Z,
+	VirtualKeyCode::W,
+	VirtualKeyCode::Up,
+	VirtualKeyCode::Q,
+	VirtualKeyCode::A,
+	VirtualKeyCode::Left,
+	VirtualKeyCode::S,
+	VirtualKeyCode::Down,
+	VirtualKeyCode::D,
+	VirtualKeyCode::Right,
+];
+
+/// When nothing moves on the screen and the player is not playing,
+/// there is no need to draw identical frames at full framerate.
+const IDLE_FPS: f32 = 20.0;
+/// How long after the last input the game may become idle.
+const IDLE_DELAY: Duration = Duration::from_secs(1);
+
+/// Takes the transitions at the front of the queue that may as well be animated at the same
+/// time, merged into one, so that crowds of enemies move together instead of one at a time.
+/// A transition joins the batch if it touches none of the tiles that the batch touches, and the
+/// ones that touch the player are animated on their own, for the player to see what happened.
+fn next_transition_batch(
+	queue: &mut Vec<LogicalTransition>,
+	player_coords: Option<IVec2>,
+) -> LogicalTransition {
+	let touches_player = |touched_coords: &HashSet<IVec2>| {
+		player_coords.is_some_and(|player_coords| touched_coords.contains(&player_coords))
+	};
+	let mut batch = queue.remove(0);
+	let mut batch_coords = batch.touched_coords();
+	if touches_player(&batch_coords) {
+		return batch;
+	}
+	while let Some(next_transition) = queue.first() {
+		let next_coords = next_transition.touched_coords();
+		if touches_player(&next_coords) || !batch_coords.is_disjoint(&next_coords) {
+			break;
+		}
+		batch = batch.followed_by(queue.remove(0));
+		batch_coords.extend(next_coords);
+	}
+	batch
+}
 
 enum Phase {
 	/// The player may take their time then make a move.
@@ -30,62 +162,736 @@ enum Phase {
 struct Game {
 	/// The current logical state of the world.
 	logical_world: LogicalWorld,
-	/// All previous states of the world, from oldest to most recent.
-	previous_logical_worlds: Vec<LogicalWorld>,
+	/// Previous states of the world, from oldest to most recent.
+	/// Only the most recent ones are kept, up to `history_cap`.
+	previous_logical_worlds: VecDeque<LogicalWorld>,
+	history_cap: usize,
+	/// States of the world that redos went back from, the most recent redo last,
+	/// to go forward to again. Making a move forgets them.
+	undone_logical_worlds: Vec<LogicalWorld>,
+	/// Checksums of the world at the start of each of the player's turns of the level,
+	/// all of them, even for the turns that are too old to be in the history.
+	turn_checksums: Vec<u64>,
+	/// The world right after the player's last move, before the game played its turn,
+	/// from where the game's turn can be played again by using a rewind token.
+	world_before_game_turn: Option<LogicalWorld>,
 	phase: Phase,
 	graphical_world: GraphicalWorld,
 	camera: Camera,
+	/// When set, the world is drawn in pixel perfect mode.
+	pixel_perfect_renderer: Option<PixelPerfectRenderer>,
 	spritesheet_stuff: SpritesheetStuff,
+	tutorial: Tutorial,
+	journal: Journal,
+	/// When in photo mode, the game is paused and the world is displayed for screenshots.
+	photo_mode: Option<PhotoMode>,
+	/// Opened from the title screen or with `--editor`, to craft a level and playtest it.
+	editor: Option<LevelEditor>,
+	run_config: RunConfig,
+	/// There is no profile until the first launch's questions are answered.
+	profile: Option<Profile>,
+	/// Asks the questions of the first launch.
+	onboarding: Option<Onboarding>,
+	/// Displayed at launch, until the player starts a run from it.
+	title_screen: Option<TitleScreen>,
+	/// Opened from the title screen, to pick a level of the campaign.
+	/// It is also where solving a level leads back to.
+	campaign_screen: Option<CampaignScreen>,
+	/// The index of the campaign level being played, if it is one.
+	campaign_level: Option<usize>,
+	/// Opened from the title screen, to pick a level of the `levels/` directory.
+	level_browser: Option<LevelBrowser>,
+	/// The level of the level browser being played, if it is one.
+	browsed_level: Option<BrowsedLevel>,
+	/// What just happened that the player should know about, like the run being saved.
+	toast: Option<Toast>,
+	weather: Weather,
+	/// Shown when a handcrafted level with a title starts, until dismissed.
+	level_intro: Option<LevelIntro>,
+	/// Opened from the title screen, to play a bookmarked seed.
+	favorites_screen: Option<FavoritesScreen>,
+	favorites: Favorites,
+	/// Opened from the title screen, to see the progress towards the unlocks.
+	unlocks_screen: Option<UnlocksScreen>,
+	unlocks: Unlocks,
+	/// Opened from the title screen, to change the settings out of a run.
+	options_screen: Option<OptionsScreen>,
+	/// The best won run on the seed of the current run, to race against.
+	ghost: Ghost,
+	/// When the run is won, this screen offers to start a New Game Plus.
+	victory_screen: Option<VictoryScreen>,
+	/// When the player leveled up, this screen offers a choice of perks.
+	perk_choice: Option<PerkChoice>,
+	/// When paused, the game waits for the player to resume, and offers some settings.
+	pause_screen: Option<PauseScreen>,
+	/// The key that uses a rewind token, it can be rebound in the pause screen.
+	rewind_key: VirtualKeyCode,
+	/// The next direction pressed places a bomb there instead of moving.
+	placing_bomb: bool,
+	/// The next direction pressed picks up or drops a tool there instead of moving.
+	grabbing: bool,
+	/// See `Settings`.
+	animation_speed: i32,
+	instant_moves: bool,
+	/// A movement key is held down, so the game's turns are not animated for the player to be
+	/// able to go fast.
+	fast_forwarding: bool,
+	run_styles: RunStyleTracker,
+	run_history: RunHistory,
+	/// The error if it failed to start, displayed in the profiling overlay like its address.
+	#[cfg(feature = "observer")]
+	observer: Result<observer::ObserverServer, String>,
+	last_input_time: Instant,
+	/// Where the mouse cursor is in the window, if it is in the window.
+	mouse_position: Option<Vec2>,
+	/// Is the profiling overlay displayed?
+	metrics_overlay: bool,
+	/// Are the statistics of the run displayed?
+	stats_panel: bool,
+	hud: CachedHud,
+	/// The current size of the window, in pixels.
+	screen_size: Vec2,
+	fullscreen: bool,
+	audio: Audio,
+	#[cfg(debug_assertions)]
+	hot_reloader: hot_reload::HotReloader,
 }
 
 impl Game {
 	fn new(ctx: &mut Context) -> GameResult<Game> {
-		let lw = generate_level();
+		let run_config = RunConfig::new();
+		let lw = generate_level(&run_config).updated_visibility();
 		let gw = GraphicalWorld::from_logical_world(&lw);
 		let spritesheet_stuff = SpritesheetStuff::new(ctx)?;
 		let phase = Phase::WaitingForPlayerToMakeAMove;
 		let mut camera = Camera::new();
 		camera.set_initial_target(&gw.info_for_camera);
+		// The objects get discovered when the run starts, after the title screen.
+		let tutorial = Tutorial::load(ctx);
+		let profile = Profile::load(ctx);
+		let title_screen = TitleScreen::new(
+			SavedRun::exists(ctx),
+			profile.as_ref().and_then(|profile| profile.daily_result(&today())),
+		);
+		let rewind_key = profile.as_ref().map_or(VirtualKeyCode::T, |profile| {
+			profile.control_scheme.rewind_key()
+		});
+		let (animation_speed, instant_moves) =
+			profile.as_ref().map_or((NORMAL_ANIMATION_SPEED, false), |profile| {
+				(profile.animation_speed, profile.instant_moves)
+			});
+		graphics::set_animation_speed(animation_speed_factor(animation_speed, instant_moves));
 		Ok(Game {
 			logical_world: lw,
-			previous_logical_worlds: vec![],
+			previous_logical_worlds: VecDeque::new(),
+			history_cap: std::env::var("PUSHDG_HISTORY_CAP")
+				.ok()
+				.and_then(|cap| cap.parse().ok())
+				.unwrap_or(DEFAULT_HISTORY_CAP),
+			undone_logical_worlds: vec![],
+			turn_checksums: vec![],
+			world_before_game_turn: None,
 			phase,
 			graphical_world: gw,
 			camera,
+			pixel_perfect_renderer: None,
 			spritesheet_stuff,
+			tutorial,
+			journal: Journal::new(),
+			photo_mode: None,
+			editor: std::env::args().any(|arg| arg == "--editor").then(|| LevelEditor::new(ctx)),
+			run_config,
+			onboarding: profile.is_none().then(Onboarding::new),
+			profile,
+			title_screen: Some(title_screen),
+			campaign_screen: None,
+			campaign_level: None,
+			level_browser: None,
+			browsed_level: None,
+			level_intro: None,
+			toast: None,
+			weather: Weather::new(),
+			favorites_screen: None,
+			favorites: Favorites::load(ctx),
+			unlocks_screen: None,
+			unlocks: Unlocks::load(ctx),
+			options_screen: None,
+			ghost: Ghost::new(ctx),
+			victory_screen: None,
+			perk_choice: None,
+			pause_screen: None,
+			rewind_key,
+			placing_bomb: false,
+			grabbing: false,
+			animation_speed,
+			instant_moves,
+			fast_forwarding: false,
+			run_styles: RunStyleTracker::new(),
+			run_history: RunHistory::load(ctx),
+			#[cfg(feature = "observer")]
+			observer: {
+				let address = std::env::var("PUSHDG_OBSERVER_ADDRESS")
+					.unwrap_or_else(|_| "127.0.0.1:8000".to_string());
+				observer::ObserverServer::start(&address).map_err(|error| error.to_string())
+			},
+			last_input_time: Instant::now(),
+			mouse_position: None,
+			metrics_overlay: false,
+			stats_panel: false,
+			hud: CachedHud::new(),
+			screen_size: SCREEN_SIZE,
+			fullscreen: false,
+			audio: Audio::new(),
+			#[cfg(debug_assertions)]
+			hot_reloader: hot_reload::HotReloader::new(),
 		})
 	}
 
+	/// Applies the changes made to the asset files since the last time, if any.
+	#[cfg(debug_assertions)]
+	fn hot_reload_assets(&mut self, ctx: &mut Context) {
+		let changes = self.hot_reloader.poll();
+		if changes.sprites {
+			match SpritesheetStuff::reloaded(ctx) {
+				Ok(spritesheet_stuff) => {
+					self.spritesheet_stuff = spritesheet_stuff;
+					// The cached HUD image was drawn with the old sprites.
+					self.hud = CachedHud::new();
+					self.toast = Some(Toast::new("Spritesheet reloaded"));
+				},
+				Err(error) => {
+					self.toast = Some(Toast::new(format!("Spritesheet not reloaded: {error}")));
+				},
+			}
+		}
+		if changes.lore {
+			match std::fs::read_to_string(hot_reload::lore_path()) {
+				Ok(source) => {
+					self.journal.reload_fragments(&source);
+					self.toast = Some(Toast::new("Lore reloaded"));
+				},
+				Err(error) => self.toast = Some(Toast::new(format!("Lore not reloaded: {error}"))),
+			}
+		}
+	}
+
+	/// Lets the observers (if any) see the current logical world.
+	fn publish_to_observer(&self) {
+		#[cfg(feature = "observer")]
+		if let Ok(observer) = self.observer.as_ref() {
+			observer.publish(&self.logical_world, self.turn_checksums.len());
+		}
+	}
+
+	/// Starts a whole new run, forgetting everything about the current run.
+	fn start_run(&mut self, run_config: RunConfig) {
+		let lw = generate_level(&run_config).updated_visibility();
+		self.start_run_in_level(run_config, lw);
+	}
+
+	/// Starts a whole new run in the given level, like a level made in the editor.
+	fn start_run_in_level(&mut self, run_config: RunConfig, lw: LogicalWorld) {
+		self.graphical_world = GraphicalWorld::from_logical_world(&lw);
+		self.camera.set_initial_target(&self.graphical_world.info_for_camera);
+		self.tutorial.discover_visible_objs(&lw);
+		self.ghost.start(run_config.level_seed(), &lw);
+		self.logical_world = lw;
+		self.previous_logical_worlds.clear();
+		self.undone_logical_worlds.clear();
+		self.turn_checksums.clear();
+		self.world_before_game_turn = None;
+		self.phase = Phase::WaitingForPlayerToMakeAMove;
+		self.run_config = run_config;
+		self.campaign_level = None;
+		self.browsed_level = None;
+		self.victory_screen = None;
+		self.perk_choice = None;
+		self.run_styles = RunStyleTracker::new();
+		self.publish_to_observer();
+		self.validate_logical_world(true);
+	}
+
+	/// The player went through the exit, the run goes on in a new level one deeper.
+	/// The moves made in the previous level cannot be redone anymore.
+	fn descend(&mut self) {
+		self.run_config.depth += 1;
+		let lw = next_level(&self.run_config, &self.logical_world);
+		self.graphical_world = GraphicalWorld::from_logical_world(&lw);
+		self.camera.set_initial_target(&self.graphical_world.info_for_camera);
+		self.tutorial.discover_visible_objs(&lw);
+		self.ghost.start(self.run_config.level_seed(), &lw);
+		self.logical_world = lw;
+		self.previous_logical_worlds.clear();
+		self.undone_logical_worlds.clear();
+		self.turn_checksums.clear();
+		self.world_before_game_turn = None;
+		self.phase = Phase::WaitingForPlayerToMakeAMove;
+		self.perk_choice = PerkChoice::if_pending(&self.logical_world);
+		self.publish_to_observer();
+		self.validate_logical_world(true);
+	}
+
+	/// Leaves the title screen for the run, with the assist options of the player profile.
+	fn start_run_from_title(&mut self, mut run_config: RunConfig) {
+		self.title_screen = None;
+		let profile_assist = self.profile.as_ref().map(|profile| profile.assist);
+		run_config.assist = AssistOptions::from_env().or(profile_assist).unwrap_or_default();
+		run_config.unlocks = self.unlocks.unlocked();
+		self.start_run(run_config);
+	}
+
+	fn new_title_screen(&self, ctx: &Context) -> TitleScreen {
+		let daily_result = self.profile.as_ref().and_then(|profile| profile.daily_result(&today()));
+		TitleScreen::new(SavedRun::exists(ctx), daily_result)
+	}
+
+	/// Starts today's daily challenge, that counts as lost until it is won.
+	fn start_daily(&mut self) {
+		let date = today();
+		if let Some(profile) = self.profile.as_mut() {
+			profile.record_daily_result(DailyResult { date: date.clone(), won: false, turns: 0 });
+		}
+		self.title_screen = None;
+		self.start_run(daily_run_config(&date));
+	}
+
+	/// Records how the daily challenge went, once it is won or lost for good.
+	fn record_daily_result(&mut self) {
+		let Some(date) = self.run_config.daily_date.clone() else {
+			return;
+		};
+		let won = self.victory_screen.is_some();
+		let lost = !self.logical_world.has_player()
+			&& !self.logical_world.player_exited()
+			&& self.logical_world.redo_count < 1;
+		if let Some(profile) = self.profile.as_mut().filter(|_| won || lost) {
+			profile.record_daily_result(DailyResult {
+				date,
+				won,
+				turns: self.logical_world.stats.turns,
+			});
+		}
+	}
+
+	/// Saves the current run, overwriting the previous save.
+	fn quick_save(&mut self, ctx: &Context) {
+		if !matches!(self.phase, Phase::WaitingForPlayerToMakeAMove) {
+			return;
+		}
+		let saved_run = SavedRun {
+			run_config: self.run_config.clone(),
+			logical_world: self.logical_world.clone(),
+			previous_logical_worlds: self.previous_logical_worlds.clone(),
+			turn_checksums: self.turn_checksums.clone(),
+			world_before_game_turn: self.world_before_game_turn.clone(),
+			run_styles: self.run_styles.clone(),
+		};
+		self.toast = Some(Toast::new(match saved_run.save(ctx) {
+			Ok(()) => "Run saved".to_string(),
+			Err(error) => format!("Run not saved: {error}"),
+		}));
+	}
+
+	/// Picks the saved run up where it was left, forgetting everything about the current run.
+	fn quick_load(&mut self, ctx: &Context) {
+		let saved_run = match SavedRun::load(ctx) {
+			Ok(saved_run) => saved_run,
+			Err(error) => {
+				self.toast = Some(Toast::new(format!("Run not loaded: {error}")));
+				return;
+			},
+		};
+		let lw = saved_run.logical_world;
+		self.graphical_world = GraphicalWorld::from_logical_world(&lw);
+		self.camera.set_initial_target(&self.graphical_world.info_for_camera);
+		self.tutorial.discover_visible_objs(&lw);
+		self.ghost.start(saved_run.run_config.level_seed(), &lw);
+		self.logical_world = lw;
+		self.previous_logical_worlds = saved_run.previous_logical_worlds;
+		self.undone_logical_worlds.clear();
+		while self.previous_logical_worlds.len() > self.history_cap {
+			self.previous_logical_worlds.pop_front();
+		}
+		self.turn_checksums = saved_run.turn_checksums;
+		self.world_before_game_turn = saved_run.world_before_game_turn;
+		self.phase = Phase::WaitingForPlayerToMakeAMove;
+		self.run_config = saved_run.run_config;
+		self.campaign_level = None;
+		self.browsed_level = None;
+		self.title_screen = None;
+		self.campaign_screen = None;
+		self.level_browser = None;
+		self.favorites_screen = None;
+		self.victory_screen = None;
+		self.pause_screen = None;
+		self.placing_bomb = false;
+		self.grabbing = false;
+		self.run_styles = saved_run.run_styles;
+		self.perk_choice = PerkChoice::if_pending(&self.logical_world);
+		self.publish_to_observer();
+		self.validate_logical_world(true);
+		self.toast = Some(Toast::new("Run loaded"));
+	}
+
+	/// Starts a run in a handcrafted level, with the player's assist options and the redos
+	/// that the level comes with, introduced by its card if it has a title.
+	fn start_run_in_handcrafted_level(
+		&mut self,
+		mut lw: LogicalWorld,
+		metadata: &LevelMetadata,
+		best_turns: Option<i32>,
+	) {
+		let profile_assist = self.profile.as_ref().map(|profile| profile.assist);
+		let run_config = RunConfig {
+			assist: AssistOptions::from_env().or(profile_assist).unwrap_or_default(),
+			..RunConfig::new()
+		};
+		lw.set_assist(run_config.assist);
+		if let Some(redos) = metadata.redos {
+			lw.redo_count = redos;
+			lw.max_redo_count = lw.max_redo_count.max(redos);
+		}
+		self.title_screen = None;
+		self.start_run_in_level(run_config, lw.updated_visibility());
+		self.level_intro = LevelIntro::if_titled(metadata, best_turns);
+	}
+
+	/// Plays the level of the campaign at the given index, from its start.
+	fn play_campaign_level(&mut self, index: usize) {
+		self.campaign_screen = None;
+		let level = &campaign_levels()[index];
+		let best_turns = self.profile.as_ref().and_then(|profile| profile.best_turns(level.id));
+		self.start_run_in_handcrafted_level(level.logical_world(), &level.metadata(), best_turns);
+		self.campaign_level = Some(index);
+	}
+
+	fn open_campaign_screen(&mut self) {
+		self.campaign_screen = Some(CampaignScreen::new(self.profile.as_ref()));
+	}
+
+	/// Leaves the campaign level for the campaign screen, where an other one can be picked.
+	fn leave_campaign_level(&mut self) {
+		self.campaign_level = None;
+		self.level_intro = None;
+		self.open_campaign_screen();
+		self.pause_screen = None;
+		self.perk_choice = None;
+		self.placing_bomb = false;
+		self.grabbing = false;
+	}
+
+	/// Plays a level of the level browser, from its start.
+	fn play_browsed_level(&mut self, level: BrowsedLevel) {
+		self.level_browser = None;
+		let best_turns = self.profile.as_ref().and_then(|profile| profile.best_turns(&level.id));
+		self.start_run_in_handcrafted_level(level.lw.clone(), &level.metadata, best_turns);
+		self.browsed_level = Some(level);
+	}
+
+	/// Leaves the level of the level browser for the browser, where an other one can be picked.
+	fn leave_browsed_level(&mut self) {
+		if let Some(level) = self.browsed_level.take() {
+			self.level_browser = Some(LevelBrowser::back_from(&level, self.profile.as_ref()));
+		}
+		self.level_intro = None;
+		self.pause_screen = None;
+		self.perk_choice = None;
+		self.placing_bomb = false;
+		self.grabbing = false;
+	}
+
+	/// Plays the level made in the editor, from the start and with the player's assist options.
+	fn playtest(&mut self, lw: LogicalWorld, metadata: LevelMetadata) {
+		self.start_run_in_handcrafted_level(lw, &metadata, None);
+		if let Some(editor) = self.editor.as_mut() {
+			editor.playtesting = true;
+		}
+	}
+
+	fn stop_playtest(&mut self) {
+		if let Some(editor) = self.editor.as_mut() {
+			editor.playtesting = false;
+		}
+		self.level_intro = None;
+		self.pause_screen = None;
+		self.victory_screen = None;
+		self.perk_choice = None;
+		self.placing_bomb = false;
+		self.grabbing = false;
+	}
+
+	/// Is the editor displayed, and not waiting for a playtest to end?
+	fn is_editing(&self) -> bool {
+		self.editor.as_ref().is_some_and(|editor| !editor.playtesting)
+	}
+
+	/// Continues with the world in which the player got the perk they chose.
+	fn choose_perk(&mut self, lw: LogicalWorld) {
+		self.logical_world = lw;
+		self.graphical_world = GraphicalWorld::from_logical_world(&self.logical_world);
+		self.tutorial.discover_visible_objs(&self.logical_world);
+		self.publish_to_observer();
+		self.validate_logical_world(true);
+		// Leveling up more than once at a time gives more than one choice.
+		self.perk_choice = PerkChoice::if_pending(&self.logical_world);
+	}
+
+	fn settings(&self) -> Settings {
+		Settings {
+			zoom: self.camera.zoom_level(),
+			pixel_perfect: self.pixel_perfect_renderer.is_some(),
+			metrics_overlay: self.metrics_overlay,
+			reduced_motion: self.camera.reduced_motion,
+			rewind_key: self.rewind_key,
+			volume: self.audio.volume,
+			animation_speed: self.animation_speed,
+			instant_moves: self.instant_moves,
+		}
+	}
+
+	fn apply_settings(&mut self, settings: Settings) {
+		self.camera.zoom(settings.zoom - self.camera.zoom_level(), 1, 100);
+		if settings.pixel_perfect != self.pixel_perfect_renderer.is_some() {
+			self.pixel_perfect_renderer = settings.pixel_perfect.then(PixelPerfectRenderer::new);
+		}
+		self.metrics_overlay = settings.metrics_overlay;
+		self.camera.reduced_motion = settings.reduced_motion;
+		self.rewind_key = settings.rewind_key;
+		self.audio.volume = settings.volume;
+		graphics::set_animation_speed(animation_speed_factor(
+			settings.animation_speed,
+			settings.instant_moves,
+		));
+		self.animation_speed = settings.animation_speed;
+		self.instant_moves = settings.instant_moves;
+		if let Some(profile) = self.profile.as_mut() {
+			profile.set_animations(settings.animation_speed, settings.instant_moves);
+		}
+	}
+
 	fn player_move(&mut self, direction: IVec2) {
 		if matches!(self.phase, Phase::WaitingForPlayerToMakeAMove) && self.logical_world.has_player()
 		{
-			let mut transition = self.logical_world.player_move(direction);
-			self.previous_logical_worlds.push(self.logical_world.clone());
+			let transition_build_timer = metrics::time("transition build");
+			let action = if std::mem::take(&mut self.placing_bomb) {
+				PlayerAction::PlaceBomb(direction)
+			} else if std::mem::take(&mut self.grabbing) {
+				PlayerAction::PickUpOrDrop(direction)
+			} else {
+				PlayerAction::Move(direction)
+			};
+			let Some(transition) = self.logical_world.player_act(action) else {
+				return;
+			};
+			self.push_to_history(self.logical_world.clone());
+			self.undone_logical_worlds.clear();
 			self.logical_world = transition.resulting_lw.clone();
 			self.graphical_world = GraphicalWorld::from_logical_world_transition(&transition);
+			self.audio.queue_transition(&transition);
 			self.camera.set_target(&self.graphical_world.info_for_camera);
+			self.tutorial.discover_visible_objs(&self.logical_world);
+			self.ghost.record(&self.logical_world);
+			if transition
+				.logical_events
+				.iter()
+				.any(|logical_event| matches!(logical_event, LogicalEvent::NoteRead { .. }))
+			{
+				self.journal.read_a_note();
+			}
+			self.run_styles.observe(&transition);
+			self.unlocks.observe(&transition);
+			if let Some(index) = self.campaign_level.filter(|_| self.logical_world.player_exited()) {
+				// Solved, back to the campaign screen for the next one.
+				if let Some(profile) = self.profile.as_mut() {
+					profile.mark_solved(campaign_levels()[index].id, self.logical_world.stats.turns);
+				}
+				self.leave_campaign_level();
+				return;
+			}
+			if let Some(level) =
+				self.browsed_level.as_ref().filter(|_| self.logical_world.player_exited())
+			{
+				// Solved, back to the level browser for an other one.
+				if let Some(profile) = self.profile.as_mut() {
+					profile.mark_solved(&level.id, self.logical_world.stats.turns);
+				}
+				self.leave_browsed_level();
+				return;
+			}
+			if self.editor.is_some() && self.logical_world.player_exited() {
+				// The playtest is over, back to editing, with how well it went.
+				if let Some(editor) = self.editor.as_mut() {
+					editor.tell_score(self.logical_world.stats.turns);
+				}
+				self.stop_playtest();
+				return;
+			}
+			if self.logical_world.player_exited() && self.run_config.depth < FINAL_DEPTH {
+				self.ghost.save_if_best();
+				self.descend();
+				return;
+			}
+			self.victory_screen = VictoryScreen::if_victory(
+				&self.logical_world,
+				&self.run_config,
+				self.run_styles.unbroken_styles(),
+			);
+			if self.victory_screen.is_some() {
+				self.record_daily_result();
+				self.run_history.record_victory(&self.run_config, &self.run_styles.unbroken_styles());
+				self.ghost.save_if_best();
+			}
+			self.publish_to_observer();
+			self.validate_logical_world(false);
+			self.world_before_game_turn = Some(transition.resulting_lw.clone());
+			drop(transition_build_timer);
+			self.play_game_turn(transition);
+		}
+	}
+
+	/// Remembers the given world as the world of the last turn, to be able to go back to it.
+	fn push_to_history(&mut self, lw: LogicalWorld) {
+		self.turn_checksums.push(lw.checksum());
+		self.previous_logical_worlds.push_back(lw);
+		if self.previous_logical_worlds.len() > self.history_cap {
+			// The oldest states are forgotten, it is not possible to go back that far anymore.
+			self.previous_logical_worlds.pop_front();
+		}
+	}
+
+	/// Plays all the moves of everything that is not a player up until the player's next turn.
+	fn play_game_turn(&mut self, transition: LogicalTransition) {
+		let transitions = play_game_turn(transition, &self.run_config);
+		self.phase = Phase::WaitingForAnimationsToFinish(transitions);
+	}
 
-			// Play all the moves of everything that is not a player up until the player's next turn.
-			transition.resulting_lw.give_move_token_to_agents();
-			let mut transitions = vec![];
-			while let Some(next_transition) = transition.resulting_lw.handle_move_for_one_agent() {
-				transitions.push(next_transition.clone());
-				transition = next_transition;
+	/// Uses a rewind token to play the game's last turn again, the player's move still standing.
+	/// The enemies may then move differently, as the order in which they move is rerolled.
+	/// If the turn plays out the same whatever the order (like when only one enemy moves),
+	/// the token is not spent.
+	fn rewind(&mut self) {
+		if !matches!(self.phase, Phase::WaitingForPlayerToMakeAMove)
+			|| self.logical_world.rewind_tokens < 1
+		{
+			return;
+		}
+		let Some(mut lw) = self.world_before_game_turn.clone() else {
+			return;
+		};
+		lw.rewind_tokens = self.logical_world.rewind_tokens - 1;
+		for _ in 0..REWIND_REROLLS {
+			lw.reroll_agent_order();
+			let transitions = play_game_turn(lw.clone().into(), &self.run_config);
+			let replayed_lw = transitions.last().map_or(&lw, |transition| &transition.resulting_lw);
+			if replayed_lw.is_same_outcome_as(&self.logical_world) {
+				continue;
 			}
+			// The snapshot stays so that an other token can rewind the same turn again.
+			self.world_before_game_turn = Some(lw.clone());
+			self.logical_world = lw.clone();
+			self.graphical_world = GraphicalWorld::from_logical_world(&lw);
+			self.camera.set_target(&self.graphical_world.info_for_camera);
+			self.publish_to_observer();
+			self.validate_logical_world(false);
 			self.phase = Phase::WaitingForAnimationsToFinish(transitions);
+			return;
+		}
+	}
+
+	/// Maps the direction of a movement input to the direction of the player's move,
+	/// which may differ if the controls are messed up by a curse.
+	fn input_move(&mut self, input_direction: IVec2) {
+		let direction = if self.logical_world.mirror_curse_turns_left > 0 {
+			IVec2::new(-input_direction.x, input_direction.y)
+		} else {
+			input_direction
+		};
+		self.player_move(direction);
+	}
+
+	/// Is the player looking at the level, with no screen or popup in the way?
+	fn is_playing(&self) -> bool {
+		self.onboarding.is_none()
+			&& self.campaign_screen.is_none()
+			&& self.level_browser.is_none()
+			&& self.favorites_screen.is_none()
+			&& self.unlocks_screen.is_none()
+			&& self.options_screen.is_none()
+			&& self.title_screen.is_none()
+			&& !self.tutorial.is_showing_card()
+			&& self.level_intro.is_none()
+			&& self.victory_screen.is_none()
+			&& self.perk_choice.is_none()
+			&& self.pause_screen.is_none()
+			&& !self.journal.is_showing_something()
+			&& self.photo_mode.is_none()
+	}
+
+	/// The coords of the tile under the mouse cursor, if it is in the window.
+	fn hovered_coords(&self) -> Option<IVec2> {
+		Some(self.camera.screen_to_world(self.mouse_position?, self.screen_size))
+	}
+
+	/// Is the game just displaying the same frame over and over?
+	fn is_idle(&self) -> bool {
+		matches!(self.phase, Phase::WaitingForPlayerToMakeAMove)
+			&& self.photo_mode.is_none()
+			&& self.title_screen.is_none()
+			&& self.onboarding.is_none()
+			&& !self.graphical_world.has_animation()
+			&& self.camera.is_still()
+			&& self.weather.is_clear()
+			&& self.last_input_time.elapsed() >= IDLE_DELAY
+	}
+
+	/// In debug builds, makes sure that the current logical world is not corrupted.
+	fn validate_logical_world(&self, players_turn: bool) {
+		if cfg!(debug_assertions) {
+			if let Err(error) = self.logical_world.validate(players_turn) {
+				panic!("Invalid logical world: {error}");
+			}
 		}
 	}
 
 	fn redo(&mut self) {
-		if matches!(self.phase, Phase::WaitingForPlayerToMakeAMove) {
-			if let Some(previous_lw) = self.previous_logical_worlds.pop() {
-				let redo_count = self.logical_world.redo_count;
-				if redo_count >= 1 {
-					self.logical_world = previous_lw;
-					self.logical_world.redo_count = redo_count - 1;
-					self.graphical_world = GraphicalWorld::from_logical_world(&self.logical_world);
-					self.camera.set_target(&self.graphical_world.info_for_camera);
+		// Without a redo to use, the history is left as it is.
+		let redo_count = self.logical_world.redo_count;
+		if matches!(self.phase, Phase::WaitingForPlayerToMakeAMove) && redo_count >= 1 {
+			if let Some(previous_lw) = self.previous_logical_worlds.pop_back() {
+				let expected_checksum = self.turn_checksums.pop();
+				if cfg!(debug_assertions) && expected_checksum != Some(previous_lw.checksum()) {
+					panic!(
+						"Desync: the world of turn {} in the history is not what it was",
+						self.turn_checksums.len()
+					);
 				}
+				let undone_lw = std::mem::replace(&mut self.logical_world, previous_lw);
+				self.undone_logical_worlds.push(undone_lw);
+				self.logical_world.redo_count = redo_count - 1;
+				self.run_styles.observe_redo();
+				self.ghost.undo();
+				// The turn to rewind would be one that did not happen anymore.
+				self.world_before_game_turn = None;
+				self.graphical_world = GraphicalWorld::from_logical_world(&self.logical_world);
+				self.camera.set_target(&self.graphical_world.info_for_camera);
+				self.publish_to_observer();
+			}
+		}
+	}
+
+	/// Goes forward to the state that the last redo went back from, which cancels that redo
+	/// (and gives back the redo it used).
+	fn go_forward(&mut self) {
+		if matches!(self.phase, Phase::WaitingForPlayerToMakeAMove) {
+			if let Some(undone_lw) = self.undone_logical_worlds.pop() {
+				let lw = std::mem::replace(&mut self.logical_world, undone_lw);
+				self.push_to_history(lw);
+				self.ghost.record(&self.logical_world);
+				self.world_before_game_turn = None;
+				self.graphical_world = GraphicalWorld::from_logical_world(&self.logical_world);
+				self.camera.set_target(&self.graphical_world.info_for_camera);
+				self.publish_to_observer();
+				self.validate_logical_world(true);
 			}
 		}
 	}
@@ -93,17 +899,49 @@ impl Game {
 
 impl EventHandler for Game {
 	fn update(&mut self, ctx: &mut Context) -> GameResult {
+		let update_timer = metrics::time("update");
+		graphics::advance_animation_clock(ctx.time.delta());
+		#[cfg(debug_assertions)]
+		self.hot_reload_assets(ctx);
+		if self.toast.as_ref().is_some_and(Toast::is_over) {
+			self.toast = None;
+		}
+		if let Some(photo_mode) = self.photo_mode.as_mut() {
+			photo_mode.animate(ctx.time.delta());
+			return Ok(());
+		}
+		if let Some(editor) = self.editor.as_mut().filter(|editor| !editor.playtesting) {
+			editor.animate(ctx.time.delta());
+			return Ok(());
+		}
+
+		if self.fast_forwarding && !MOVEMENT_KEYS.iter().any(|key| ctx.keyboard.is_key_pressed(*key))
+		{
+			self.fast_forwarding = false;
+		}
 		loop {
-			let no_more_animations = !self.graphical_world.has_animation();
+			let fast_forward =
+				self.fast_forwarding && matches!(self.phase, Phase::WaitingForAnimationsToFinish(_));
+			let no_more_animations = !self.graphical_world.has_animation() || fast_forward;
 			if no_more_animations {
 				if let Phase::WaitingForAnimationsToFinish(next_tranitions) = &mut self.phase {
 					if !next_tranitions.is_empty() {
-						let transition = next_tranitions.remove(0);
+						let transition =
+							next_transition_batch(next_tranitions, self.logical_world.player_coords());
 						self.logical_world = transition.resulting_lw.clone();
 						self.graphical_world = GraphicalWorld::from_logical_world_transition(&transition);
+						self.audio.queue_transition(&transition);
 						self.camera.set_target(&self.graphical_world.info_for_camera);
+						self.tutorial.discover_visible_objs(&self.logical_world);
+						self.run_styles.observe(&transition);
+						self.unlocks.observe(&transition);
+						self.publish_to_observer();
+						self.validate_logical_world(false);
 					} else {
 						self.phase = Phase::WaitingForPlayerToMakeAMove;
+						self.validate_logical_world(true);
+						self.perk_choice = PerkChoice::if_pending(&self.logical_world);
+						self.record_daily_result();
 					}
 				} else {
 					break;
@@ -114,20 +952,334 @@ impl EventHandler for Game {
 		}
 
 		self.camera.animate(ctx.time.delta());
+		self.weather.animate(
+			&self.logical_world,
+			self.camera.reduced_motion,
+			ctx.time.delta(),
+			self.screen_size,
+		);
+
+		metrics::set_count("grid size", self.logical_world.tiles().count());
+		let queued_transitions = match &self.phase {
+			Phase::WaitingForAnimationsToFinish(next_tranitions) => next_tranitions.len(),
+			Phase::WaitingForPlayerToMakeAMove => 0,
+		};
+		metrics::set_count("queued transitions", queued_transitions);
+		metrics::set_count("history length", self.previous_logical_worlds.len());
+		metrics::set_count(
+			"history KiB",
+			self
+				.previous_logical_worlds
+				.iter()
+				.map(LogicalWorld::estimated_memory_size)
+				.sum::<usize>()
+				/ 1024,
+		);
+		self.audio.play_queued(ctx);
+		self.audio.update_music(ctx, self.logical_world.depth, ctx.time.delta());
+		drop(update_timer);
+
+		if self.is_idle() {
+			// Slow down the frame rate, which also slows down the drawing.
+			std::thread::sleep(Duration::from_secs_f32(1.0 / IDLE_FPS));
+		}
 
 		Ok(())
 	}
 
-	fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeated: bool) -> GameResult {
+	fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+		if let Some(title_screen) = self.title_screen.as_mut() {
+			title_screen.text_input(character);
+		} else if let Some(victory_screen) = self.victory_screen.as_mut() {
+			victory_screen.text_input(character);
+		} else if let Some(pause_screen) = self.pause_screen.as_mut() {
+			pause_screen.text_input(character);
+		}
+		Ok(())
+	}
+
+	fn mouse_motion_event(
+		&mut self,
+		_ctx: &mut Context,
+		x: f32,
+		y: f32,
+		_dx: f32,
+		_dy: f32,
+	) -> GameResult {
+		self.last_input_time = Instant::now();
+		self.mouse_position = Some(Vec2::new(x, y));
+		Ok(())
+	}
+
+	fn resize_event(&mut self, ctx: &mut Context, _width: f32, _height: f32) -> GameResult {
+		let (width, height) = ctx.gfx.drawable_size();
+		self.screen_size = Vec2::new(width, height);
+		Ok(())
+	}
+
+	/// Scrolling up zooms in, like the `+` key.
+	fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+		self.last_input_time = Instant::now();
+		if self.is_playing() && self.photo_mode.is_none() && y != 0.0 {
+			self.camera.zoom(y.signum() as i32, MIN_ZOOM, MAX_ZOOM);
+		}
+		Ok(())
+	}
+
+	fn mouse_enter_or_leave(&mut self, _ctx: &mut Context, entered: bool) -> GameResult {
+		if !entered {
+			self.mouse_position = None;
+		}
+		Ok(())
+	}
+
+	/// Clicking on a tile next to the bunny moves it there, like the matching movement key.
+	fn mouse_button_down_event(
+		&mut self,
+		_ctx: &mut Context,
+		button: MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult {
+		self.last_input_time = Instant::now();
+		self.mouse_position = Some(Vec2::new(x, y));
+		if button != MouseButton::Left || !self.is_playing() {
+			return Ok(());
+		}
+		if let (Some(clicked_coords), Some(player_coords)) =
+			(self.hovered_coords(), self.logical_world.player_coords())
+		{
+			let direction = clicked_coords - player_coords;
+			if direction.x.abs() + direction.y.abs() == 1 {
+				self.input_move(direction);
+			}
+		}
+		Ok(())
+	}
+
+	fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeated: bool) -> GameResult {
 		use VirtualKeyCode as K;
+		self.last_input_time = Instant::now();
 		if let Some(keycode) = input.keycode {
+			if keycode == K::F11 {
+				self.fullscreen = !self.fullscreen;
+				ctx.gfx.set_fullscreen(if self.fullscreen {
+					FullscreenType::Desktop
+				} else {
+					FullscreenType::Windowed
+				})?;
+				return Ok(());
+			}
+			if let Some(onboarding) = self.onboarding.as_mut() {
+				if keycode == K::Escape {
+					ctx.request_quit();
+				} else if let Some((assist, control_scheme)) = onboarding.key_down(keycode) {
+					self.onboarding = None;
+					self.rewind_key = control_scheme.rewind_key();
+					self.profile = Some(Profile::new(ctx, assist, control_scheme));
+				}
+				return Ok(());
+			}
+			if let Some(editor) = self.editor.as_mut().filter(|editor| !editor.playtesting) {
+				match editor.key_down(keycode) {
+					Some(EditorAction::Close) => {
+						// The editor is opened from the title screen, or instead of it at launch.
+						self.editor = None;
+						self.title_screen = Some(self.new_title_screen(ctx));
+					},
+					Some(EditorAction::Playtest(lw, metadata)) => self.playtest(*lw, metadata),
+					None => {},
+				}
+				return Ok(());
+			}
+			if let Some(campaign_screen) = self.campaign_screen.as_mut() {
+				match campaign_screen.key_down(keycode) {
+					Some(CampaignAction::Back) => {
+						self.campaign_screen = None;
+						// Solving a level leads back to the campaign screen, out of the title screen.
+						if self.title_screen.is_none() {
+							self.title_screen = Some(self.new_title_screen(ctx));
+						}
+					},
+					Some(CampaignAction::Play(index)) => self.play_campaign_level(index),
+					None => {},
+				}
+				return Ok(());
+			}
+			if let Some(level_browser) = self.level_browser.as_mut() {
+				match level_browser.key_down(keycode, self.profile.as_ref()) {
+					Some(LevelBrowserAction::Back) => {
+						self.level_browser = None;
+						// Solving a level leads back to the level browser, out of the title screen.
+						if self.title_screen.is_none() {
+							self.title_screen = Some(self.new_title_screen(ctx));
+						}
+					},
+					Some(LevelBrowserAction::Play(level)) => self.play_browsed_level(*level),
+					None => {},
+				}
+				return Ok(());
+			}
+			if let Some(favorites_screen) = self.favorites_screen.as_mut() {
+				match favorites_screen.key_down(keycode) {
+					Some(FavoritesAction::Back) => self.favorites_screen = None,
+					Some(FavoritesAction::Play(run_config)) => {
+						self.favorites_screen = None;
+						self.start_run_from_title(run_config);
+					},
+					None => {},
+				}
+				return Ok(());
+			}
+			if let Some(unlocks_screen) = self.unlocks_screen.as_mut() {
+				if unlocks_screen.key_down(keycode) {
+					self.unlocks_screen = None;
+				}
+				return Ok(());
+			}
+			if let Some(options_screen) = self.options_screen.as_mut() {
+				match options_screen.key_down(keycode) {
+					Some(OptionsAction::Back) => self.options_screen = None,
+					Some(OptionsAction::ApplySettings(settings)) => self.apply_settings(settings),
+					None => {},
+				}
+				return Ok(());
+			}
+			if let Some(title_screen) = self.title_screen.as_mut() {
+				if keycode == K::Escape {
+					ctx.request_quit();
+				} else if keycode == K::F9 {
+					self.quick_load(ctx);
+				} else {
+					match title_screen.key_down(keycode) {
+						Some(TitleAction::Play(run_config)) => self.start_run_from_title(run_config),
+						Some(TitleAction::PlayDaily) => self.start_daily(),
+						Some(TitleAction::Continue) => self.quick_load(ctx),
+						Some(TitleAction::OpenCampaign) => self.open_campaign_screen(),
+						Some(TitleAction::OpenLevelBrowser) =>
+							self.level_browser = Some(LevelBrowser::new(
+								LevelBrowser::levels_dir(ctx),
+								self.profile.as_ref(),
+							)),
+						Some(TitleAction::OpenFavorites) =>
+							self.favorites_screen = Some(FavoritesScreen::new(&self.favorites)),
+						Some(TitleAction::OpenUnlocks) =>
+							self.unlocks_screen = Some(UnlocksScreen::new(&self.unlocks)),
+						Some(TitleAction::OpenEditor) => self.editor = Some(LevelEditor::new(ctx)),
+						Some(TitleAction::OpenOptions) =>
+							self.options_screen = Some(OptionsScreen::new(&self.settings())),
+						None => {},
+					}
+				}
+				return Ok(());
+			}
+			if self.level_intro.is_some() {
+				// Input is paused until the card is dismissed, like the tutorial cards.
+				match keycode {
+					K::Escape => ctx.request_quit(),
+					K::Return | K::Space => self.level_intro = None,
+					_ => {},
+				}
+				return Ok(());
+			}
+			if self.tutorial.is_showing_card() {
+				// Input is paused until the card is dismissed.
+				match keycode {
+					K::Escape => ctx.request_quit(),
+					K::Return | K::Space => self.tutorial.dismiss_card(),
+					_ => {},
+				}
+				return Ok(());
+			}
+			if let Some(victory_screen) = self.victory_screen.as_mut() {
+				if keycode == K::Escape {
+					ctx.request_quit();
+				} else if let Some(run_config) = victory_screen.key_down(keycode, &mut self.favorites) {
+					self.start_run(run_config);
+				}
+				return Ok(());
+			}
+			if let Some(perk_choice) = self.perk_choice.as_mut() {
+				if keycode == K::Escape {
+					ctx.request_quit();
+				} else if let Some(lw) = perk_choice.key_down(keycode, &self.logical_world) {
+					self.choose_perk(lw);
+				}
+				return Ok(());
+			}
+			if let Some(pause_screen) = self.pause_screen.as_mut() {
+				match pause_screen.key_down(keycode) {
+					Some(PauseAction::Resume) => self.pause_screen = None,
+					Some(PauseAction::Quit) => ctx.request_quit(),
+					Some(PauseAction::ApplySettings(settings)) => self.apply_settings(settings),
+					Some(PauseAction::Bookmark(note)) => self.favorites.add(self.run_config.seed, &note),
+					None => {},
+				}
+				return Ok(());
+			}
+			if self.journal.is_showing_something() {
+				match keycode {
+					K::Escape => ctx.request_quit(),
+					K::Return | K::Space => self.journal.dismiss(),
+					K::J => self.journal.toggle_journal_screen(),
+					_ => {},
+				}
+				return Ok(());
+			}
+			if let Some(photo_mode) = self.photo_mode.as_mut() {
+				match keycode {
+					K::Escape | K::P => self.photo_mode = None,
+					K::Z | K::W | K::Up => photo_mode.move_camera(Vec2::new(0.0, -1.0)),
+					K::Q | K::A | K::Left => photo_mode.move_camera(Vec2::new(-1.0, 0.0)),
+					K::S | K::Down => photo_mode.move_camera(Vec2::new(0.0, 1.0)),
+					K::D | K::Right => photo_mode.move_camera(Vec2::new(1.0, 0.0)),
+					K::Equals | K::Plus | K::NumpadAdd => photo_mode.zoom(1),
+					K::Minus | K::NumpadSubtract => photo_mode.zoom(-1),
+					K::L => photo_mode.toggle_lighting(),
+					K::F => photo_mode.cycle_filter(),
+					K::Key1 => photo_mode.set_supersample_factor(1),
+					K::Key2 => photo_mode.set_supersample_factor(2),
+					K::Key3 => photo_mode.set_supersample_factor(3),
+					K::Key4 => photo_mode.set_supersample_factor(4),
+					K::Return | K::F12 => photo_mode.take_screenshot(
+						ctx,
+						&self.spritesheet_stuff,
+						&self.graphical_world,
+						self.screen_size,
+					)?,
+					_ => {},
+				}
+				return Ok(());
+			}
+			if repeated && MOVEMENT_KEYS.contains(&keycode) {
+				self.fast_forwarding = true;
+			}
 			match keycode {
-				K::Escape => ctx.request_quit(),
-				K::Z | K::W | K::Up => self.player_move(IVec2::new(0, -1)),
-				K::Q | K::A | K::Left => self.player_move(IVec2::new(-1, 0)),
-				K::S | K::Down => self.player_move(IVec2::new(0, 1)),
-				K::D | K::Right => self.player_move(IVec2::new(1, 0)),
+				K::Escape =>
+					self.pause_screen = Some(PauseScreen::new(&self.settings(), self.run_config.seed)),
+				K::Z | K::W | K::Up => self.input_move(IVec2::new(0, -1)),
+				K::Q | K::A | K::Left => self.input_move(IVec2::new(-1, 0)),
+				K::S | K::Down => self.input_move(IVec2::new(0, 1)),
+				K::D | K::Right => self.input_move(IVec2::new(1, 0)),
 				K::R | K::Back => self.redo(),
+				K::Y => self.go_forward(),
+				K::B => self.placing_bomb = !self.placing_bomb && self.logical_world.bombs > 0,
+				K::G => self.grabbing = !self.grabbing,
+				keycode if keycode == self.rewind_key => self.rewind(),
+				K::Equals | K::Plus | K::NumpadAdd => self.camera.zoom(1, MIN_ZOOM, MAX_ZOOM),
+				K::Minus | K::NumpadSubtract => self.camera.zoom(-1, MIN_ZOOM, MAX_ZOOM),
+				K::F3 => self.metrics_overlay = !self.metrics_overlay,
+				K::F4 => self
+					.apply_settings(Settings { instant_moves: !self.instant_moves, ..self.settings() }),
+				K::Tab => self.stats_panel = !self.stats_panel,
+				K::F2 if self.editor.is_some() => self.stop_playtest(),
+				K::F2 if self.campaign_level.is_some() => self.leave_campaign_level(),
+				K::F2 if self.browsed_level.is_some() => self.leave_browsed_level(),
+				K::M => self.audio.music_muted = !self.audio.music_muted,
+				K::F5 => self.quick_save(ctx),
+				K::F9 => self.quick_load(ctx),
+				K::J => self.journal.toggle_journal_screen(),
+				K::P => self.photo_mode = Some(PhotoMode::new(&self.logical_world, &self.camera)),
 				_ => {},
 			}
 		}
@@ -135,10 +1287,184 @@ impl EventHandler for Game {
 	}
 
 	fn draw(&mut self, ctx: &mut Context) -> GameResult {
+		let draw_timer = metrics::time("draw");
 		let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
 		canvas.set_sampler(Sampler::nearest_clamp());
-		self.graphical_world.draw(ctx, &mut canvas, &self.spritesheet_stuff, &self.camera)?;
+		if let Some(photo_mode) = self.photo_mode.as_ref() {
+			photo_mode.draw(
+				ctx,
+				&mut canvas,
+				&self.spritesheet_stuff,
+				&self.graphical_world,
+				self.screen_size,
+			)?;
+		} else if let Some(editor) = self.editor.as_ref().filter(|_| self.is_editing()) {
+			editor.draw(ctx, &mut canvas, &self.spritesheet_stuff, self.screen_size)?;
+		} else {
+			if let Some(pixel_perfect_renderer) = self.pixel_perfect_renderer.as_mut() {
+				pixel_perfect_renderer.draw(
+					ctx,
+					&mut canvas,
+					&self.graphical_world,
+					&self.spritesheet_stuff,
+					&self.camera,
+					&DrawOptions::new(self.screen_size),
+				)?;
+			} else {
+				self.graphical_world.draw(
+					ctx,
+					&mut canvas,
+					&self.spritesheet_stuff,
+					&self.camera,
+					&DrawOptions::new(self.screen_size),
+				)?;
+			}
+			self.weather.draw(&mut canvas);
+			if let Some(ghost_coords) = self.ghost.position() {
+				draw_ghost(
+					&mut canvas,
+					&self.spritesheet_stuff,
+					ghost_coords,
+					&self.camera,
+					self.screen_size,
+				);
+			}
+			if let Some(latest_lw) = self.undone_logical_worlds.first() {
+				// Where the bunny was before the redos, to see how far back it went.
+				if let Some(latest_player_coords) = latest_lw.player_coords() {
+					draw_ghost(
+						&mut canvas,
+						&self.spritesheet_stuff,
+						latest_player_coords,
+						&self.camera,
+						self.screen_size,
+					);
+				}
+				let turn = self.turn_checksums.len();
+				draw_history_overlay(
+					&mut canvas,
+					turn,
+					turn + self.undone_logical_worlds.len(),
+					self.screen_size,
+				);
+			}
+			self.hud.draw(
+				ctx,
+				&mut canvas,
+				&self.spritesheet_stuff,
+				&self.logical_world,
+				self.screen_size,
+			)?;
+			self.journal.draw(&mut canvas, self.screen_size);
+			if self.is_playing() {
+				// Inspect the object under the mouse cursor.
+				let hovered_coords = self.hovered_coords();
+				let hovered_obj = hovered_coords
+					.and_then(|coords| self.logical_world.tile(coords))
+					.and_then(|tile| tile.obj.as_ref());
+				if let (Some(obj), Some(mouse_position)) = (hovered_obj, self.mouse_position) {
+					let look = hovered_coords.and_then(|coords| self.logical_world.look(coords));
+					draw_tooltip(
+						ctx,
+						&mut canvas,
+						&obj_inspection(obj, look),
+						mouse_position,
+						self.screen_size,
+					)?;
+				}
+			}
+			let game_over = !self.logical_world.has_player()
+				&& !self.logical_world.player_exited()
+				&& matches!(self.phase, Phase::WaitingForPlayerToMakeAMove);
+			if game_over {
+				let hint = if self.logical_world.redo_count >= 1 {
+					"Press R to redo the last move."
+				} else if self.campaign_level.is_some() || self.browsed_level.is_some() {
+					"No redo left, press F2 to pick a level again."
+				} else {
+					"No redo left, press Escape to pause."
+				};
+				draw_stats_panel(
+					&mut canvas,
+					&self.logical_world.stats,
+					"Game over",
+					hint,
+					self.screen_size,
+				);
+			} else if self.stats_panel && self.is_playing() {
+				draw_stats_panel(
+					&mut canvas,
+					&self.logical_world.stats,
+					"Statistics",
+					"Press Tab to hide.",
+					self.screen_size,
+				);
+			}
+			if let Some(victory_screen) = self.victory_screen.as_ref() {
+				victory_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(perk_choice) = self.perk_choice.as_ref() {
+				perk_choice.draw(
+					&mut canvas,
+					&self.spritesheet_stuff,
+					self.logical_world.progression.level,
+					self.screen_size,
+				);
+			}
+			if let Some(pause_screen) = self.pause_screen.as_ref() {
+				pause_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			self.tutorial.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			if let Some(level_intro) = self.level_intro.as_ref() {
+				level_intro.draw(&mut canvas, self.screen_size);
+			}
+			if let Some(title_screen) = self.title_screen.as_ref() {
+				title_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(campaign_screen) = self.campaign_screen.as_ref() {
+				campaign_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(level_browser) = self.level_browser.as_ref() {
+				level_browser.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(favorites_screen) = self.favorites_screen.as_ref() {
+				favorites_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(unlocks_screen) = self.unlocks_screen.as_ref() {
+				unlocks_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(options_screen) = self.options_screen.as_ref() {
+				options_screen.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+			if let Some(onboarding) = self.onboarding.as_ref() {
+				onboarding.draw(&mut canvas, &self.spritesheet_stuff, self.screen_size);
+			}
+		}
+		// Over everything, even the editor, where assets may be hot-reloaded too.
+		if let Some(toast) = self.toast.as_ref() {
+			toast.draw(&mut canvas, self.screen_size);
+		}
+		if self.metrics_overlay {
+			if self.photo_mode.is_none() {
+				draw_scent_map(
+					&mut canvas,
+					&self.logical_world,
+					&self.camera,
+					self.screen_size,
+				);
+			}
+			#[cfg(feature = "observer")]
+			let status_lines = vec![match self.observer.as_ref() {
+				Ok(observer) => format!("observer: {}", observer.url()),
+				Err(error) => format!("observer failed to start: {error}"),
+			}];
+			#[cfg(not(feature = "observer"))]
+			let status_lines = vec![];
+			draw_metrics_overlay(&mut canvas, &status_lines, self.screen_size);
+		}
 		canvas.finish(ctx)?;
+		drop(draw_timer);
+		metrics::end_frame();
 		Ok(())
 	}
 }
@@ -146,7 +1472,12 @@ impl EventHandler for Game {
 fn main() -> GameResult {
 	let (mut ctx, event_loop) = ContextBuilder::new("PushDg", "Anima :3")
 		.window_setup(WindowSetup::default().title("PushDg").vsync(true).srgb(false))
-		.window_mode(WindowMode::default().dimensions(800.0, 800.0))
+		.window_mode(
+			WindowMode::default()
+				.dimensions(SCREEN_SIZE.x, SCREEN_SIZE.y)
+				.min_dimensions(MIN_SCREEN_SIZE.x, MIN_SCREEN_SIZE.y)
+				.resizable(true),
+		)
 		.build()
 		.unwrap();
 	let game = Game::new(&mut ctx)?;
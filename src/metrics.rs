@@ -0,0 +1,111 @@
+//! Profiling instrumentation, sampled across subsystems and displayed in an overlay.
+//!
+//! Subsystems time their work with `time` and report live counts with `set_count`,
+//! without having to carry anything around. Every frame, `end_frame` makes the samples of the
+//! frame the ones displayed. Allocations are counted by a global allocator wrapper.
+//! The overlay itself is drawn by the game, see `metrics_overlay`.
+
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	cell::RefCell,
+	collections::BTreeMap,
+	sync::atomic::{AtomicUsize, Ordering},
+	time::{Duration, Instant},
+};
+
+/// The system allocator, but it keeps count of the allocations.
+pub struct CountingAllocator;
+
+static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+		ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+		System.dealloc(ptr, layout)
+	}
+}
+
+#[derive(Default)]
+struct Metrics {
+	/// Time spent in each subsystem during the current frame so far.
+	current_frame_timings: BTreeMap<&'static str, Duration>,
+	/// Time spent in each subsystem during the last frame in which it did something.
+	timings: BTreeMap<&'static str, Duration>,
+	counts: BTreeMap<&'static str, usize>,
+	/// Total allocation count at the end of the previous frame.
+	allocation_count_at_frame_start: usize,
+	allocations_last_frame: usize,
+}
+
+thread_local! {
+	static METRICS: RefCell<Metrics> = RefCell::new(Metrics::default());
+}
+
+/// Measures the time spent from its creation to its drop.
+pub struct Timer {
+	name: &'static str,
+	start: Instant,
+}
+
+impl Drop for Timer {
+	fn drop(&mut self) {
+		let elapsed = self.start.elapsed();
+		METRICS.with(|metrics| {
+			*metrics.borrow_mut().current_frame_timings.entry(self.name).or_default() += elapsed;
+		});
+	}
+}
+
+/// Times the work done until the returned timer is dropped.
+/// Work done multiple times during a frame is summed up.
+pub fn time(name: &'static str) -> Timer {
+	Timer { name, start: Instant::now() }
+}
+
+pub fn set_count(name: &'static str, value: usize) {
+	METRICS.with(|metrics| {
+		metrics.borrow_mut().counts.insert(name, value);
+	});
+}
+
+/// To be called once per frame, after everything else.
+pub fn end_frame() {
+	METRICS.with(|metrics| {
+		let mut metrics = metrics.borrow_mut();
+		let current_frame_timings = std::mem::take(&mut metrics.current_frame_timings);
+		metrics.timings.extend(current_frame_timings);
+		let allocation_count = ALLOCATION_COUNT.load(Ordering::Relaxed);
+		metrics.allocations_last_frame = allocation_count - metrics.allocation_count_at_frame_start;
+		metrics.allocation_count_at_frame_start = allocation_count;
+	});
+}
+
+/// The lines of the F3 overlay, listing all the metrics.
+pub fn overlay_lines() -> Vec<String> {
+	let mut lines = vec![];
+	METRICS.with(|metrics| {
+		let metrics = metrics.borrow();
+		for (name, duration) in metrics.timings.iter() {
+			lines.push(format!("{name}: {:.3} ms", duration.as_secs_f64() * 1000.0));
+		}
+		for (name, count) in metrics.counts.iter() {
+			lines.push(format!("{name}: {count}"));
+		}
+		lines.push(format!(
+			"allocations last frame: {}",
+			metrics.allocations_last_frame
+		));
+	});
+	lines.push(format!(
+		"allocated: {} KiB",
+		ALLOCATED_BYTES.load(Ordering::Relaxed) / 1024
+	));
+	lines
+}
@@ -0,0 +1,33 @@
+//! The profiling overlay, toggled with `F3`, that displays the metrics (see `metrics`).
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Quad, Rect, Text},
+};
+
+use crate::{graphics::DepthLayer, metrics};
+
+/// The F3 overlay, listing all the metrics after the given lines about the state of the game.
+pub fn draw_metrics_overlay(canvas: &mut Canvas, status_lines: &[String], screen_size: Vec2) {
+	let mut lines = status_lines.to_vec();
+	lines.extend(metrics::overlay_lines());
+	let mut text = Text::new(lines.join("\n"));
+	text.set_scale(16.0);
+	let width = 280.0;
+	let margin = 10.0;
+	let panel_rect = Rect::new(
+		screen_size.x - width - margin,
+		margin,
+		width,
+		lines.len() as f32 * 16.0 + margin * 2.0,
+	);
+	let z = DepthLayer::Popup.to_z_value();
+	canvas.draw(
+		&Quad,
+		DrawParam::default().dest_rect(panel_rect).color(Color::new(0.0, 0.0, 0.0, 0.7)).z(z),
+	);
+	canvas.draw(
+		&text,
+		DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+	);
+}
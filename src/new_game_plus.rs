@@ -0,0 +1,175 @@
+//! Victory screen offering a New Game Plus.
+//!
+//! When the player exits the deepest level, they win the run. They are then offered to start a new run
+//! in a tougher New Game Plus tier, carrying one item of their choice among the items that
+//! went through the exit before them. The badges of the run styles that the run followed
+//! are displayed too, and so is a summary of the run (seed, version, assist options...).
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+};
+
+use crate::{
+	favorites::Favorites,
+	gameplay::{LogicalWorld, Obj},
+	generation::{RunConfig, FINAL_DEPTH},
+	graphics::{obj_to_sprite, DepthLayer},
+	run_styles::RunStyle,
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+pub struct VictoryScreen {
+	/// The items that the player can pick from, the last choice being to carry nothing.
+	choices: Vec<Option<Obj>>,
+	/// One button per choice.
+	menu: Menu,
+	/// The config of the run that was just won.
+	won_run_config: RunConfig,
+	badges: Vec<RunStyle>,
+}
+
+impl VictoryScreen {
+	/// The run is won if the player went through the exit of the deepest level.
+	pub fn if_victory(
+		lw: &LogicalWorld,
+		run_config: &RunConfig,
+		badges: Vec<RunStyle>,
+	) -> Option<VictoryScreen> {
+		let victory = run_config.depth == FINAL_DEPTH && lw.player_exited();
+		victory.then(|| {
+			let mut choices: Vec<_> = lw
+				.exited_objs
+				.iter()
+				.filter(|obj| !obj.is_agent())
+				.map(|obj| Some(obj.clone()))
+				.collect();
+			choices.push(None);
+			let mut widgets: Vec<_> = choices
+				.iter()
+				.map(|choice| Widget::Button {
+					label: choice.as_ref().map_or("Nothing", |obj| obj.name()).to_string(),
+					description: None,
+					icon: choice.as_ref().map(obj_to_sprite),
+				})
+				.collect();
+			// After the choices, the seed of the won run can be bookmarked.
+			widgets.push(Widget::TextEntry {
+				label: "Bookmark seed, note".to_string(),
+				text: String::new(),
+				max_length: 24,
+			});
+			let menu = Menu::new(widgets);
+			VictoryScreen { choices, menu, won_run_config: run_config.clone(), badges }
+		})
+	}
+
+	/// Returns the config of the New Game Plus run to start when a choice is confirmed.
+	pub fn key_down(
+		&mut self,
+		keycode: VirtualKeyCode,
+		favorites: &mut Favorites,
+	) -> Option<RunConfig> {
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(index) if index == self.choices.len() => {
+				let Widget::TextEntry { label, text, .. } = &mut self.menu.widgets[index] else {
+					unreachable!()
+				};
+				favorites.add(self.won_run_config.seed, text);
+				text.clear();
+				*label = "Bookmarked! Again, note".to_string();
+				None
+			},
+			MenuEvent::Activated(index) => Some(RunConfig {
+				difficulty_tier: self.won_run_config.difficulty_tier + 1,
+				starting_item: self.choices[index].clone(),
+				seed: rand::random(),
+				depth: 1,
+				daily_date: None,
+				..self.won_run_config.clone()
+			}),
+			MenuEvent::Changed(_) => None,
+		}
+	}
+
+	pub fn text_input(&mut self, character: char) {
+		self.menu.text_input(character);
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 500.0, screen_size);
+		let z = DepthLayer::Popup.to_z_value();
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let mut title_text = Text::new("Victory!");
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+		);
+		let badges_description = if self.badges.is_empty() {
+			"No style badge this time.".to_string()
+		} else {
+			let badges: Vec<_> = self
+				.badges
+				.iter()
+				.map(|style| format!("{} ({})", style.name(), style.description()))
+				.collect();
+			format!("Badges: {}", badges.join(", "))
+		};
+		let mut badges_text = Text::new(badges_description);
+		badges_text.set_scale(16.0);
+		badges_text.set_bounds(Vec2::new(panel_rect.w - margin * 2.0, f32::INFINITY));
+		canvas.draw(
+			&badges_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 55.0,
+				))
+				.color(Color::YELLOW)
+				.z(z),
+		);
+		// The seed and version are there for the run to be shared or reported.
+		let mut summary_text = Text::new(format!("Run: {}", self.won_run_config.summary()));
+		summary_text.set_scale(16.0);
+		summary_text.set_bounds(Vec2::new(panel_rect.w - margin * 2.0, f32::INFINITY));
+		canvas.draw(
+			&summary_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 95.0,
+				))
+				.color(Color::new(0.7, 0.7, 0.7, 1.0))
+				.z(z),
+		);
+		let mut subtitle_text = Text::new(format!(
+			"Pick an item to carry into New Game Plus {}:",
+			self.won_run_config.difficulty_tier + 1
+		));
+		subtitle_text.set_scale(18.0);
+		canvas.draw(
+			&subtitle_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 140.0,
+				))
+				.z(z),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 180.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
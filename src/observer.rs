@@ -0,0 +1,127 @@
+//! Observer web view, to use a second device as a companion map display.
+//!
+//! When the `observer` feature is enabled, a small HTTP server serves a JSON snapshot of what
+//! the player currently sees at `/world.json`, and a simple HTML viewer of it at `/`
+//! that polls the snapshot. The game publishes a new snapshot every time the world changes.
+
+use std::{
+	io::{BufRead, BufReader, Write},
+	net::{TcpListener, TcpStream},
+	sync::{Arc, Mutex},
+	thread,
+};
+
+use crate::gameplay::LogicalWorld;
+
+const VIEWER_HTML: &str = r##"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>PushDg observer</title></head>
+<body style="margin: 0; background: #222034; color: #cbdbfc; font-family: sans-serif;">
+<p id="info" style="margin: 8px;"></p>
+<canvas id="map"></canvas>
+<script>
+const colors = {
+	"Wall": "#595652", "Door": "#8f563b", "Bunny": "#ffffff", "Slime": "#ac3232",
+	"Shroomer": "#ac3232", "Archer": "#ac3232",
+	"Ghost": "#cbdbfc", "Golem boss": "#847e87", "Shroom": "#d77bba", "Slime corpse": "#4b692f", "Exit": "#6abe30",
+};
+async function refresh() {
+	const world = await (await fetch("/world.json")).json();
+	document.getElementById("info").textContent =
+		`Turn ${world.turn} (${world.checksum}), redos: ${world.redo_count}/${world.max_redo_count}`;
+	const canvas = document.getElementById("map");
+	const xs = world.tiles.map(tile => tile.x), ys = world.tiles.map(tile => tile.y);
+	const minX = Math.min(...xs), minY = Math.min(...ys);
+	const size = 16;
+	canvas.width = (Math.max(...xs) - minX + 1) * size;
+	canvas.height = (Math.max(...ys) - minY + 1) * size;
+	const ctx = canvas.getContext("2d");
+	for (const tile of world.tiles) {
+		ctx.fillStyle = tile.obj === null ? "#45283c" : (colors[tile.obj] ?? "#fbf236");
+		ctx.fillRect((tile.x - minX) * size, (tile.y - minY) * size, size - 1, size - 1);
+	}
+}
+setInterval(refresh, 500);
+refresh();
+</script>
+</body>
+</html>
+"##;
+
+/// Serves the last published snapshot from a background thread.
+pub struct ObserverServer {
+	address: String,
+	snapshot: Arc<Mutex<String>>,
+}
+
+impl ObserverServer {
+	pub fn start(address: &str) -> std::io::Result<ObserverServer> {
+		let listener = TcpListener::bind(address)?;
+		let snapshot = Arc::new(Mutex::new("{\"tiles\": []}".to_string()));
+		let served_snapshot = Arc::clone(&snapshot);
+		thread::spawn(move || {
+			for stream in listener.incoming().flatten() {
+				// A misbehaving client is not worth stopping the server for.
+				let _ = serve(stream, &served_snapshot);
+			}
+		});
+		Ok(ObserverServer { address: address.to_string(), snapshot })
+	}
+
+	/// Where the viewer is served.
+	pub fn url(&self) -> String {
+		format!("http://{}/", self.address)
+	}
+
+	/// Replaces the snapshot served to observers with one of the given world.
+	/// The turn and the checksum of the world are included, so that the observers can tell
+	/// whether they display the same world as the game.
+	pub fn publish(&self, lw: &LogicalWorld, turn: usize) {
+		*self.snapshot.lock().unwrap() = world_snapshot_json(lw, turn);
+	}
+}
+
+fn serve(mut stream: TcpStream, snapshot: &Mutex<String>) -> std::io::Result<()> {
+	let mut request_line = String::new();
+	BufReader::new(&stream).read_line(&mut request_line)?;
+	let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+	let (status, content_type, body) = match path {
+		"/" => ("200 OK", "text/html", VIEWER_HTML.to_string()),
+		"/world.json" => (
+			"200 OK",
+			"application/json",
+			snapshot.lock().unwrap().clone(),
+		),
+		_ => ("404 Not Found", "text/plain", "Not found".to_string()),
+	};
+	write!(
+		stream,
+		"HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+		Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{body}",
+		body.len()
+	)
+}
+
+/// Only the visible tiles are included, the observer sees what the player sees.
+fn world_snapshot_json(lw: &LogicalWorld, turn: usize) -> String {
+	let tiles: Vec<String> = lw
+		.tiles()
+		.filter(|(_coords, tile)| tile.visible)
+		.map(|(coords, tile)| {
+			let obj =
+				tile.obj.as_ref().map_or("null".to_string(), |obj| format!("\"{}\"", obj.name()));
+			format!(
+				"{{\"x\": {}, \"y\": {}, \"obj\": {obj}}}",
+				coords.x, coords.y
+			)
+		})
+		.collect();
+	format!(
+		"{{\"turn\": {turn}, \"checksum\": \"{:016x}\", \"redo_count\": {}, \"max_redo_count\": {}, \
+		\"tiles\": [{}]}}",
+		lw.checksum(),
+		lw.redo_count,
+		lw.max_redo_count,
+		tiles.join(", ")
+	)
+}
@@ -0,0 +1,130 @@
+//! A few questions asked on the first launch, to pick the preferences of the player profile.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+};
+
+use crate::{
+	gameplay::AssistOptions,
+	graphics::DepthLayer,
+	profile::ControlScheme,
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+struct Question {
+	text: &'static str,
+	answers: &'static [&'static str],
+}
+
+const QUESTIONS: [Question; 3] = [
+	Question {
+		text: "Have you played roguelikes before?",
+		answers: &["Yes", "No"],
+	},
+	Question {
+		text: "Do you prefer puzzles or combat?",
+		answers: &["Puzzles", "Combat"],
+	},
+	Question {
+		text: "What do you move with?",
+		answers: &["WASD or ZQSD", "The arrows"],
+	},
+];
+
+pub struct Onboarding {
+	/// The index of the answer to each question answered so far.
+	answers: Vec<usize>,
+	/// The answers to the current question.
+	menu: Menu,
+}
+
+impl Onboarding {
+	pub fn new() -> Onboarding {
+		Onboarding { answers: vec![], menu: Onboarding::answers_menu(&QUESTIONS[0]) }
+	}
+
+	fn answers_menu(question: &Question) -> Menu {
+		Menu::new(question.answers.iter().map(|answer| Widget::button(*answer)).collect())
+	}
+
+	/// Returns the picked preferences once the last question is answered.
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<(AssistOptions, ControlScheme)> {
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(index) => self.answers.push(index),
+			MenuEvent::Changed(_) => return None,
+		}
+		if let Some(question) = QUESTIONS.get(self.answers.len()) {
+			self.menu = Onboarding::answers_menu(question);
+			return None;
+		}
+		let new_to_roguelikes = self.answers[0] == 1;
+		let prefers_puzzles = self.answers[1] == 0;
+		let assist = AssistOptions {
+			reduced_enemy_damage: prefers_puzzles,
+			extra_starting_redos: new_to_roguelikes,
+			slower_hazards: prefers_puzzles,
+			free_revive: new_to_roguelikes,
+		};
+		let control_scheme = if self.answers[2] == 0 {
+			ControlScheme::Letters
+		} else {
+			ControlScheme::Arrows
+		};
+		Some((assist, control_scheme))
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 360.0, screen_size);
+		let z = DepthLayer::Popup.to_z_value();
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let mut title_text = Text::new("Welcome!");
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+		);
+		let question = &QUESTIONS[self.answers.len()];
+		let mut question_text = Text::new(format!(
+			"{} ({}/{})",
+			question.text,
+			self.answers.len() + 1,
+			QUESTIONS.len()
+		));
+		question_text.set_scale(22.0);
+		canvas.draw(
+			&question_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 70.0,
+				))
+				.z(z),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 120.0),
+			panel_rect.bottom() - margin,
+		);
+		let mut hint_text = Text::new("This can be changed later in profile.txt.");
+		hint_text.set_scale(16.0);
+		canvas.draw(
+			&hint_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.bottom() - margin - 16.0,
+				))
+				.z(z),
+		);
+	}
+}
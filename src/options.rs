@@ -0,0 +1,192 @@
+//! The settings, that can be changed from the pause screen during a run,
+//! or from the options screen opened from the title screen.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+};
+
+use crate::{
+	audio::MAX_VOLUME,
+	graphics::DepthLayer,
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+const ZOOM: usize = 0;
+const PIXEL_PERFECT: usize = 1;
+const METRICS_OVERLAY: usize = 2;
+const REDUCED_MOTION: usize = 3;
+const REWIND_KEY: usize = 4;
+const VOLUME: usize = 5;
+const ANIMATION_SPEED: usize = 6;
+const INSTANT_MOVES: usize = 7;
+/// How many widgets the settings take in a menu.
+pub const SETTINGS_WIDGET_COUNT: usize = 8;
+
+/// The bounds of the zoom level, in screen pixels per sprite pixel.
+pub const MIN_ZOOM: i32 = 3;
+pub const MAX_ZOOM: i32 = 12;
+
+/// The animation speed at which the animations play at their normal speed,
+/// each step faster or slower adds or removes half of that.
+pub const NORMAL_ANIMATION_SPEED: i32 = 2;
+
+/// The settings that the pause screen or the options screen can change,
+/// to be applied by the game.
+pub struct Settings {
+	pub zoom: i32,
+	pub pixel_perfect: bool,
+	pub metrics_overlay: bool,
+	pub reduced_motion: bool,
+	pub rewind_key: VirtualKeyCode,
+	/// Of the sound effects, from 0 (muted) to `MAX_VOLUME`.
+	pub volume: i32,
+	/// See `NORMAL_ANIMATION_SPEED`.
+	pub animation_speed: i32,
+	/// The animations are skipped, whatever their speed.
+	pub instant_moves: bool,
+}
+
+impl Settings {
+	/// One widget per setting, in the order that `from_widgets` reads them.
+	pub fn widgets(&self) -> [Widget; SETTINGS_WIDGET_COUNT] {
+		[
+			Widget::Slider {
+				label: "Zoom".to_string(),
+				value: self.zoom,
+				min: MIN_ZOOM,
+				max: MAX_ZOOM,
+			},
+			Widget::Toggle { label: "Pixel perfect".to_string(), value: self.pixel_perfect },
+			Widget::Toggle {
+				label: "Profiling overlay".to_string(),
+				value: self.metrics_overlay,
+			},
+			Widget::Toggle { label: "Reduced motion".to_string(), value: self.reduced_motion },
+			Widget::KeyCapture {
+				label: "Rewind key".to_string(),
+				key: self.rewind_key,
+				capturing: false,
+			},
+			Widget::Slider {
+				label: "Volume".to_string(),
+				value: self.volume,
+				min: 0,
+				max: MAX_VOLUME,
+			},
+			Widget::Slider {
+				label: "Animation speed".to_string(),
+				value: self.animation_speed,
+				min: 1,
+				max: 4 * NORMAL_ANIMATION_SPEED,
+			},
+			Widget::Toggle { label: "Instant moves".to_string(), value: self.instant_moves },
+		]
+	}
+
+	/// Reads the settings from the widgets made by `widgets`, that start the given slice.
+	pub fn from_widgets(widgets: &[Widget]) -> Settings {
+		let Widget::Slider { value: zoom, .. } = widgets[ZOOM] else {
+			unreachable!()
+		};
+		let Widget::Toggle { value: pixel_perfect, .. } = widgets[PIXEL_PERFECT] else {
+			unreachable!()
+		};
+		let Widget::Toggle { value: metrics_overlay, .. } = widgets[METRICS_OVERLAY] else {
+			unreachable!()
+		};
+		let Widget::Toggle { value: reduced_motion, .. } = widgets[REDUCED_MOTION] else {
+			unreachable!()
+		};
+		let Widget::KeyCapture { key: rewind_key, .. } = widgets[REWIND_KEY] else {
+			unreachable!()
+		};
+		let Widget::Slider { value: volume, .. } = widgets[VOLUME] else {
+			unreachable!()
+		};
+		let Widget::Slider { value: animation_speed, .. } = widgets[ANIMATION_SPEED] else {
+			unreachable!()
+		};
+		let Widget::Toggle { value: instant_moves, .. } = widgets[INSTANT_MOVES] else {
+			unreachable!()
+		};
+		Settings {
+			zoom,
+			pixel_perfect,
+			metrics_overlay,
+			reduced_motion,
+			rewind_key,
+			volume,
+			animation_speed,
+			instant_moves,
+		}
+	}
+}
+
+/// How many times faster than normal the animations play, zero if they are skipped.
+pub fn animation_speed_factor(animation_speed: i32, instant_moves: bool) -> f32 {
+	if instant_moves {
+		0.0
+	} else {
+		animation_speed as f32 / NORMAL_ANIMATION_SPEED as f32
+	}
+}
+
+/// What the game should do following a key press in the options screen.
+pub enum OptionsAction {
+	Back,
+	ApplySettings(Settings),
+}
+
+/// The settings, out of a run.
+pub struct OptionsScreen {
+	menu: Menu,
+}
+
+impl OptionsScreen {
+	pub fn new(settings: &Settings) -> OptionsScreen {
+		let mut widgets = Vec::from(settings.widgets());
+		widgets.push(Widget::button("Back"));
+		OptionsScreen { menu: Menu::new(widgets) }
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<OptionsAction> {
+		if keycode == VirtualKeyCode::Escape && !self.menu.is_capturing() {
+			return Some(OptionsAction::Back);
+		}
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(SETTINGS_WIDGET_COUNT) => Some(OptionsAction::Back),
+			MenuEvent::Changed(_) => Some(OptionsAction::ApplySettings(Settings::from_widgets(
+				&self.menu.widgets,
+			))),
+			MenuEvent::Activated(_) => None,
+		}
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(400.0, 400.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let mut title_text = Text::new("Options");
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 70.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
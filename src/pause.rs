@@ -0,0 +1,111 @@
+//! Pause screen, with some settings.
+//!
+//! The seed of the run is displayed there, for the player to share it or to replay it later.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+};
+
+use crate::{
+	graphics::DepthLayer,
+	options::{Settings, SETTINGS_WIDGET_COUNT},
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+const RESUME: usize = 0;
+/// The settings come after the resume button.
+const FIRST_SETTING: usize = 1;
+const BOOKMARK: usize = FIRST_SETTING + SETTINGS_WIDGET_COUNT;
+const QUIT: usize = BOOKMARK + 1;
+
+/// What the game should do following a key press in the pause screen.
+pub enum PauseAction {
+	Resume,
+	Quit,
+	ApplySettings(Settings),
+	/// Bookmark the seed of the run, with the given note.
+	Bookmark(String),
+}
+
+pub struct PauseScreen {
+	menu: Menu,
+	seed: u64,
+}
+
+impl PauseScreen {
+	pub fn new(settings: &Settings, seed: u64) -> PauseScreen {
+		let mut widgets = vec![Widget::button("Resume")];
+		widgets.extend(settings.widgets());
+		widgets.push(Widget::TextEntry {
+			label: "Bookmark seed, note".to_string(),
+			text: String::new(),
+			max_length: 24,
+		});
+		widgets.push(Widget::button("Quit"));
+		PauseScreen { menu: Menu::new(widgets), seed }
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<PauseAction> {
+		if keycode == VirtualKeyCode::Escape && !self.menu.is_capturing() {
+			return Some(PauseAction::Resume);
+		}
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(RESUME) => Some(PauseAction::Resume),
+			MenuEvent::Activated(QUIT) => Some(PauseAction::Quit),
+			MenuEvent::Activated(BOOKMARK) => {
+				let Widget::TextEntry { label, text, .. } = &mut self.menu.widgets[BOOKMARK] else {
+					unreachable!()
+				};
+				*label = "Bookmarked! Again, note".to_string();
+				Some(PauseAction::Bookmark(std::mem::take(text)))
+			},
+			MenuEvent::Changed(index) if (FIRST_SETTING..BOOKMARK).contains(&index) => Some(
+				PauseAction::ApplySettings(Settings::from_widgets(&self.menu.widgets[FIRST_SETTING..])),
+			),
+			MenuEvent::Activated(_) | MenuEvent::Changed(_) => None,
+		}
+	}
+
+	pub fn text_input(&mut self, character: char) {
+		self.menu.text_input(character);
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(400.0, 480.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let z = DepthLayer::Popup.to_z_value();
+		let mut title_text = Text::new("Paused");
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+		);
+		let mut seed_text = Text::new(format!("Seed {}", self.seed));
+		seed_text.set_scale(18.0);
+		canvas.draw(
+			&seed_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 44.0,
+				))
+				.color(Color::new(0.7, 0.7, 0.7, 1.0))
+				.z(z),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 70.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
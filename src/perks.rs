@@ -0,0 +1,140 @@
+//! Perks, chosen by the player when the bunny levels up.
+//!
+//! Killing enemies grants experience, and every level up lets the player pick one perk among
+//! three drawn from the registry, in a modal screen that pauses the game until a choice is made.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+};
+use rand::seq::SliceRandom;
+
+use crate::{
+	gameplay::LogicalWorld,
+	graphics::DepthLayer,
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+/// A permanent stat modifier for the rest of the run.
+#[derive(Clone, Copy)]
+pub enum Perk {
+	MaxHp,
+	Force,
+	Vision,
+	RedoCap,
+}
+
+/// All the perks that can be offered.
+const PERKS: [Perk; 4] = [Perk::MaxHp, Perk::Force, Perk::Vision, Perk::RedoCap];
+
+/// How many perks are offered to choose from at a level up.
+const CHOICE_COUNT: usize = 3;
+
+impl Perk {
+	fn name(self) -> &'static str {
+		match self {
+			Perk::MaxHp => "+1 max HP",
+			Perk::Force => "+1 force",
+			Perk::Vision => "+1 vision",
+			Perk::RedoCap => "+1 redo cap",
+		}
+	}
+
+	fn description(self) -> &'static str {
+		match self {
+			Perk::MaxHp => "Tougher, and healed by 1.",
+			Perk::Force => "Push heavier chains of objects.",
+			Perk::Vision => "See one tile further.",
+			Perk::RedoCap => "Hold one more redo heart.",
+		}
+	}
+
+	fn apply(self, lw: &mut LogicalWorld) {
+		match self {
+			Perk::MaxHp => lw.raise_player_max_hp(1),
+			Perk::Force => lw.progression.bonus_force += 1,
+			Perk::Vision => lw.progression.bonus_vision += 1,
+			Perk::RedoCap => lw.max_redo_count += 1,
+		}
+	}
+}
+
+/// The modal screen offering perks at a level up.
+pub struct PerkChoice {
+	choices: Vec<Perk>,
+	/// One button per choice.
+	menu: Menu,
+}
+
+impl PerkChoice {
+	/// There is a choice to make if the player leveled up and didn't pick a perk for it yet.
+	pub fn if_pending(lw: &LogicalWorld) -> Option<PerkChoice> {
+		(lw.progression.pending_perk_choices > 0).then(|| {
+			let choices: Vec<_> =
+				PERKS.choose_multiple(&mut lw.seeded_rng("perks"), CHOICE_COUNT).copied().collect();
+			let menu = Menu::new(
+				choices
+					.iter()
+					.map(|perk| Widget::Button {
+						label: perk.name().to_string(),
+						description: Some(perk.description().to_string()),
+						icon: None,
+					})
+					.collect(),
+			);
+			PerkChoice { choices, menu }
+		})
+	}
+
+	/// When a perk is confirmed, returns the given world in which the player got it
+	/// in exchange for one pending level up.
+	pub fn key_down(&mut self, keycode: VirtualKeyCode, lw: &LogicalWorld) -> Option<LogicalWorld> {
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(index) => {
+				let mut lw = lw.clone();
+				self.choices[index].apply(&mut lw);
+				lw.progression.pending_perk_choices -= 1;
+				Some(lw.updated_visibility())
+			},
+			MenuEvent::Changed(_) => None,
+		}
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		level: i32,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 360.0, screen_size);
+		let z = DepthLayer::Popup.to_z_value();
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let mut title_text = Text::new(format!("Level {level}!"));
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+		);
+		let mut subtitle_text = Text::new("Pick a perk:");
+		subtitle_text.set_scale(18.0);
+		canvas.draw(
+			&subtitle_text,
+			DrawParam::default()
+				.dest(Vec2::new(
+					panel_rect.x + margin,
+					panel_rect.y + margin + 60.0,
+				))
+				.z(z),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 100.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
@@ -0,0 +1,169 @@
+//! Photo mode, to take nice screenshots of the world.
+//!
+//! While in photo mode, the game is frozen, the interface is hidden, the camera can be moved
+//! around freely and zoomed beyond what the game uses, some lighting and color filters can be
+//! toggled, and screenshots can be rendered at a higher resolution than the window's.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, Image, ImageEncodingFormat, ImageFormat, Sampler},
+	Context, GameResult,
+};
+
+use crate::{
+	gameplay::{LogicalTransition, LogicalWorld},
+	graphics::{Camera, DrawOptions, GraphicalWorld},
+	spritesheet::SpritesheetStuff,
+};
+
+/// A color filter applied to the world in photo mode.
+#[derive(Clone, Copy)]
+enum PhotoFilter {
+	None,
+	Sepia,
+	Night,
+	Gloom,
+}
+
+impl PhotoFilter {
+	fn next(self) -> PhotoFilter {
+		match self {
+			PhotoFilter::None => PhotoFilter::Sepia,
+			PhotoFilter::Sepia => PhotoFilter::Night,
+			PhotoFilter::Night => PhotoFilter::Gloom,
+			PhotoFilter::Gloom => PhotoFilter::None,
+		}
+	}
+
+	fn color(self) -> Color {
+		match self {
+			PhotoFilter::None => Color::WHITE,
+			PhotoFilter::Sepia => Color::new(1.0, 0.85, 0.6, 1.0),
+			PhotoFilter::Night => Color::new(0.45, 0.55, 1.0, 1.0),
+			PhotoFilter::Gloom => Color::new(0.55, 0.55, 0.55, 1.0),
+		}
+	}
+}
+
+pub struct PhotoMode {
+	/// The free camera, that starts where the game camera was.
+	camera: Camera,
+	/// The same world as what the game shows, but with everything lit (and visible).
+	lit_graphical_world: GraphicalWorld,
+	/// Should the lit world be displayed instead of what the player can currently see?
+	everything_lit: bool,
+	filter: PhotoFilter,
+	/// Screenshots are rendered at the window resolution multiplied by this factor.
+	supersample_factor: u32,
+}
+
+impl PhotoMode {
+	pub fn new(lw: &LogicalWorld, camera: &Camera) -> PhotoMode {
+		let lit_graphical_world = GraphicalWorld::from_logical_world_transition(
+			&LogicalTransition::from(lw.clone().with_everything_visible()),
+		);
+		PhotoMode {
+			camera: camera.clone(),
+			lit_graphical_world,
+			everything_lit: false,
+			filter: PhotoFilter::None,
+			supersample_factor: 2,
+		}
+	}
+
+	pub fn move_camera(&mut self, direction: Vec2) {
+		self.camera.move_target(direction);
+	}
+
+	pub fn zoom(&mut self, delta: i32) {
+		// Way beyond what the game allows, this is for pictures.
+		self.camera.zoom(delta, 1, 40);
+	}
+
+	pub fn toggle_lighting(&mut self) {
+		self.everything_lit = !self.everything_lit;
+	}
+
+	pub fn cycle_filter(&mut self) {
+		self.filter = self.filter.next();
+	}
+
+	pub fn set_supersample_factor(&mut self, factor: u32) {
+		self.supersample_factor = factor;
+	}
+
+	pub fn animate(&mut self, frame_dt: std::time::Duration) {
+		self.camera.animate(frame_dt);
+	}
+
+	fn draw_options(&self, screen_size: Vec2) -> DrawOptions {
+		DrawOptions { screen_size, world_filter: self.filter.color() }
+	}
+
+	fn graphical_world<'a>(
+		&'a self,
+		game_graphical_world: &'a GraphicalWorld,
+	) -> &'a GraphicalWorld {
+		if self.everything_lit {
+			&self.lit_graphical_world
+		} else {
+			game_graphical_world
+		}
+	}
+
+	pub fn draw(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		game_graphical_world: &GraphicalWorld,
+		screen_size: Vec2,
+	) -> GameResult {
+		self.graphical_world(game_graphical_world).draw(
+			ctx,
+			canvas,
+			spritesheet_stuff,
+			&self.camera,
+			&self.draw_options(screen_size),
+		)
+	}
+
+	/// Renders the current view to an offscreen image at the chosen supersample factor,
+	/// and saves it as a PNG file in the user directory.
+	pub fn take_screenshot(
+		&self,
+		ctx: &mut Context,
+		spritesheet_stuff: &SpritesheetStuff,
+		game_graphical_world: &GraphicalWorld,
+		screen_size: Vec2,
+	) -> GameResult {
+		let factor = self.supersample_factor;
+		let image_size = screen_size * factor as f32;
+		let image = Image::new_canvas_image(
+			ctx,
+			ImageFormat::Rgba8UnormSrgb,
+			image_size.x as u32,
+			image_size.y as u32,
+			1,
+		);
+		let mut canvas = Canvas::from_image(ctx, image.clone(), Color::BLACK);
+		canvas.set_sampler(Sampler::nearest_clamp());
+		self.graphical_world(game_graphical_world).draw(
+			ctx,
+			&mut canvas,
+			spritesheet_stuff,
+			&self.camera.scaled_zoom(factor as i32),
+			&self.draw_options(image_size),
+		)?;
+		canvas.finish(ctx)?;
+		let timestamp = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map(|duration| duration.as_millis())
+			.unwrap_or(0);
+		image.encode(
+			ctx,
+			ImageEncodingFormat::Png,
+			format!("/photo-{timestamp}.png"),
+		)
+	}
+}
@@ -0,0 +1,199 @@
+//! The player profile, preferences that persist from one launch to the next.
+//!
+//! It is the `profile.txt` file of the user data directory, with one `key: value` line per
+//! preference. There is no profile before the first launch, when the player is asked a few
+//! questions to pick the preferences that suit them. It also remembers the handcrafted levels
+//! that the player solved (and in how few turns), and the results of their daily challenges.
+
+use std::path::PathBuf;
+
+use ggez::{winit::event::VirtualKeyCode, Context};
+
+use crate::{daily::DailyResult, gameplay::AssistOptions, options::NORMAL_ANIMATION_SPEED};
+
+/// Which keys the player's hands are on, the other controls are placed around them.
+#[derive(Clone, Copy)]
+pub enum ControlScheme {
+	/// `WASD` or `ZQSD`, the left hand is on the letters.
+	Letters,
+	/// The right hand is on the arrows.
+	Arrows,
+}
+
+impl ControlScheme {
+	fn name(self) -> &'static str {
+		match self {
+			ControlScheme::Letters => "letters",
+			ControlScheme::Arrows => "arrows",
+		}
+	}
+
+	fn from_name(name: &str) -> Option<ControlScheme> {
+		match name {
+			"letters" => Some(ControlScheme::Letters),
+			"arrows" => Some(ControlScheme::Arrows),
+			_ => None,
+		}
+	}
+
+	/// The key that uses a rewind token, close to the movement keys.
+	pub fn rewind_key(self) -> VirtualKeyCode {
+		match self {
+			ControlScheme::Letters => VirtualKeyCode::T,
+			ControlScheme::Arrows => VirtualKeyCode::RShift,
+		}
+	}
+}
+
+pub struct Profile {
+	pub assist: AssistOptions,
+	pub control_scheme: ControlScheme,
+	/// The ids of the handcrafted levels solved so far.
+	pub solved_levels: Vec<String>,
+	/// The fewest turns in which each solved level was solved, by id.
+	best_turns: Vec<(String, i32)>,
+	/// One per daily challenge tried, oldest first.
+	daily_results: Vec<DailyResult>,
+	/// The settings of the animations, see `Settings`.
+	pub animation_speed: i32,
+	pub instant_moves: bool,
+	file_path: PathBuf,
+}
+
+impl Profile {
+	fn file_path(ctx: &Context) -> PathBuf {
+		ctx.fs.user_data_dir().join("profile.txt")
+	}
+
+	/// Returns `None` if there is no profile yet.
+	pub fn load(ctx: &Context) -> Option<Profile> {
+		let file_path = Profile::file_path(ctx);
+		let content = std::fs::read_to_string(&file_path).ok()?;
+		let mut profile = Profile {
+			assist: AssistOptions::default(),
+			control_scheme: ControlScheme::Letters,
+			solved_levels: vec![],
+			best_turns: vec![],
+			daily_results: vec![],
+			animation_speed: NORMAL_ANIMATION_SPEED,
+			instant_moves: false,
+			file_path,
+		};
+		// Unknown or invalid lines are ignored, the preference keeps its default value.
+		for line in content.lines() {
+			match line.split_once(':').map(|(key, value)| (key.trim(), value.trim())) {
+				Some(("assist", names)) => profile.assist = AssistOptions::from_names(names),
+				Some(("controls", name)) => {
+					if let Some(control_scheme) = ControlScheme::from_name(name) {
+						profile.control_scheme = control_scheme;
+					}
+				},
+				Some(("solved", ids)) =>
+					profile.solved_levels =
+						ids.split(',').filter(|id| !id.is_empty()).map(str::to_string).collect(),
+				Some(("best", bests)) =>
+					profile.best_turns = bests
+						.split(',')
+						.filter_map(|best| {
+							let (id, turns) = best.rsplit_once('=')?;
+							Some((id.to_string(), turns.parse().ok()?))
+						})
+						.collect(),
+				Some(("daily", result)) => profile.daily_results.extend(DailyResult::from_text(result)),
+				Some(("animation_speed", speed)) =>
+					if let Ok(speed) = speed.parse() {
+						profile.animation_speed = speed;
+					},
+				Some(("instant_moves", instant_moves)) =>
+					profile.instant_moves = instant_moves == "yes",
+				_ => {},
+			}
+		}
+		Some(profile)
+	}
+
+	/// Creates the profile, and saves it right away.
+	pub fn new(ctx: &Context, assist: AssistOptions, control_scheme: ControlScheme) -> Profile {
+		let profile = Profile {
+			assist,
+			control_scheme,
+			solved_levels: vec![],
+			best_turns: vec![],
+			daily_results: vec![],
+			animation_speed: NORMAL_ANIMATION_SPEED,
+			instant_moves: false,
+			file_path: Profile::file_path(ctx),
+		};
+		profile.save();
+		profile
+	}
+
+	fn save(&self) {
+		let mut content = format!(
+			"assist: {}\ncontrols: {}\nsolved: {}\nbest: {}\nanimation_speed: {}\ninstant_moves: {}\n",
+			self.assist.names().join(","),
+			self.control_scheme.name(),
+			self.solved_levels.join(","),
+			self
+				.best_turns
+				.iter()
+				.map(|(id, turns)| format!("{id}={turns}"))
+				.collect::<Vec<_>>()
+				.join(","),
+			self.animation_speed,
+			if self.instant_moves { "yes" } else { "no" }
+		);
+		for result in self.daily_results.iter() {
+			content.push_str(&format!("daily: {}\n", result.to_text()));
+		}
+		// Failing to persist is not a big deal, the questions will just be asked again.
+		if let Some(dir) = self.file_path.parent() {
+			let _ = std::fs::create_dir_all(dir);
+		}
+		let _ = std::fs::write(&self.file_path, content);
+	}
+
+	/// Saves the settings of the animations right away, if they changed.
+	pub fn set_animations(&mut self, animation_speed: i32, instant_moves: bool) {
+		if (self.animation_speed, self.instant_moves) != (animation_speed, instant_moves) {
+			self.animation_speed = animation_speed;
+			self.instant_moves = instant_moves;
+			self.save();
+		}
+	}
+
+	/// Remembers that the level was solved in the given number of turns (keeping the best of
+	/// all the times it was solved), and saves it right away.
+	pub fn mark_solved(&mut self, level_id: &str, turns: i32) {
+		let is_first_solve = !self.solved_levels.iter().any(|id| id == level_id);
+		let is_new_best = self.best_turns(level_id).is_none_or(|best| turns < best);
+		if is_first_solve {
+			self.solved_levels.push(level_id.to_string());
+		}
+		if is_new_best {
+			self.best_turns.retain(|(id, _)| id != level_id);
+			self.best_turns.push((level_id.to_string(), turns));
+		}
+		if is_first_solve || is_new_best {
+			self.save();
+		}
+	}
+
+	/// The fewest turns in which the level was solved, if it was.
+	pub fn best_turns(&self, level_id: &str) -> Option<i32> {
+		self.best_turns.iter().find(|(id, _)| id == level_id).map(|(_, turns)| *turns)
+	}
+
+	/// The result of the daily challenge of the given date, if it was tried.
+	pub fn daily_result(&self, date: &str) -> Option<&DailyResult> {
+		self.daily_results.iter().find(|result| result.date == date)
+	}
+
+	/// Records the result of a daily challenge (replacing the previous result of that day),
+	/// and saves it right away.
+	pub fn record_daily_result(&mut self, result: DailyResult) {
+		self.daily_results.retain(|other| other.date != result.date);
+		self.daily_results.push(result);
+		self.save();
+	}
+}
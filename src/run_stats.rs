@@ -0,0 +1,71 @@
+//! Statistics of the run, displayed on the game over screen and in a panel toggled with `Tab`.
+//!
+//! They are kept in the logical world and counted from the logical events of the transitions,
+//! so they follow the simulation: a redo takes back what the undone moves counted.
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Text},
+};
+
+use crate::{
+	gameplay::RunStats,
+	graphics::DepthLayer,
+	widgets::{centered_rect, draw_panel},
+};
+
+fn stats_lines(stats: &RunStats) -> Vec<String> {
+	vec![
+		format!("Turns taken: {}", stats.turns),
+		format!("Tiles moved: {}", stats.tiles_moved),
+		format!("Objects pushed: {}", stats.objs_pushed),
+		format!("Enemies killed: {}", stats.enemies_killed),
+		format!("Damages dealt: {}", stats.damages_dealt),
+		format!("Damages taken: {}", stats.damages_taken),
+		format!("Walls mined: {}", stats.walls_mined),
+	]
+}
+
+/// Draws the statistics in a panel, under the given title and above the given hint.
+pub fn draw_stats_panel(
+	canvas: &mut Canvas,
+	stats: &RunStats,
+	title: &str,
+	hint: &str,
+	screen_size: Vec2,
+) {
+	let panel_rect = centered_rect(400.0, 380.0, screen_size);
+	let z = DepthLayer::Popup.to_z_value();
+	draw_panel(canvas, panel_rect);
+	let margin = 30.0;
+	let mut title_text = Text::new(title);
+	title_text.set_scale(40.0);
+	canvas.draw(
+		&title_text,
+		DrawParam::default().dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin)).z(z),
+	);
+	let mut stats_text = Text::new(stats_lines(stats).join("\n"));
+	stats_text.set_scale(20.0);
+	canvas.draw(
+		&stats_text,
+		DrawParam::default()
+			.dest(Vec2::new(
+				panel_rect.x + margin,
+				panel_rect.y + margin + 70.0,
+			))
+			.z(z),
+	);
+	let mut hint_text = Text::new(hint);
+	hint_text.set_scale(16.0);
+	hint_text.set_bounds(Vec2::new(panel_rect.w - margin * 2.0, f32::INFINITY));
+	canvas.draw(
+		&hint_text,
+		DrawParam::default()
+			.dest(Vec2::new(
+				panel_rect.x + margin,
+				panel_rect.bottom() - margin - 16.0,
+			))
+			.color(Color::new(0.7, 0.7, 0.7, 1.0))
+			.z(z),
+	);
+}
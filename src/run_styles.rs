@@ -0,0 +1,134 @@
+//! Run styles, self-imposed challenges that a run may have followed without the player ever
+//! declaring them, like not killing anything.
+//!
+//! The logical events of the run are watched to see which styles are broken. The styles still
+//! standing when the run is won are the badges it earned, displayed on the victory screen and
+//! kept in the run history, a file of the player profile with one line per won run.
+
+use std::path::PathBuf;
+
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	gameplay::{LogicalEvent, LogicalTransition, Obj},
+	generation::RunConfig,
+};
+
+#[derive(Clone, Copy)]
+pub enum RunStyle {
+	/// No kills.
+	Pacifist,
+	/// No damage taken.
+	Untouched,
+	/// No redo used.
+	NoRegrets,
+	/// No heart, redo heart, rewind token or note picked up.
+	Ascetic,
+}
+
+impl RunStyle {
+	pub fn name(self) -> &'static str {
+		match self {
+			RunStyle::Pacifist => "Pacifist",
+			RunStyle::Untouched => "Untouched",
+			RunStyle::NoRegrets => "No regrets",
+			RunStyle::Ascetic => "Ascetic",
+		}
+	}
+
+	pub fn description(self) -> &'static str {
+		match self {
+			RunStyle::Pacifist => "killed nothing",
+			RunStyle::Untouched => "took no damage",
+			RunStyle::NoRegrets => "used no redo",
+			RunStyle::Ascetic => "picked nothing up",
+		}
+	}
+}
+
+/// Keeps track of what happened during the run that breaks some styles.
+///
+/// What was undone by a redo still happened, redoing a hit doesn't make the run untouched.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RunStyleTracker {
+	killed: bool,
+	damaged: bool,
+	redo_used: bool,
+	picked_up: bool,
+}
+
+impl RunStyleTracker {
+	pub fn new() -> RunStyleTracker {
+		RunStyleTracker { killed: false, damaged: false, redo_used: false, picked_up: false }
+	}
+
+	/// To be given every transition of the run as it is applied.
+	pub fn observe(&mut self, transition: &LogicalTransition) {
+		let lw = &transition.resulting_lw;
+		for logical_event in transition.logical_events.iter() {
+			match logical_event {
+				LogicalEvent::Killed { obj: Obj::Bunny { .. }, .. } => self.damaged = true,
+				LogicalEvent::Killed { .. } => self.killed = true,
+				LogicalEvent::Hit { at, damages }
+					if *damages > 0 && matches!(lw.obj(*at), Some(Obj::Bunny { .. })) =>
+					self.damaged = true,
+				LogicalEvent::Healed { .. }
+				| LogicalEvent::RedoGained { .. }
+				| LogicalEvent::RewindGained { .. }
+				| LogicalEvent::GoldGained { .. }
+				| LogicalEvent::Bought { .. }
+				| LogicalEvent::NoteRead { .. } => self.picked_up = true,
+				_ => {},
+			}
+		}
+	}
+
+	pub fn observe_redo(&mut self) {
+		self.redo_used = true;
+	}
+
+	/// The styles that were not broken so far.
+	pub fn unbroken_styles(&self) -> Vec<RunStyle> {
+		[
+			(RunStyle::Pacifist, self.killed),
+			(RunStyle::Untouched, self.damaged),
+			(RunStyle::NoRegrets, self.redo_used),
+			(RunStyle::Ascetic, self.picked_up),
+		]
+		.into_iter()
+		.filter_map(|(style, broken)| (!broken).then_some(style))
+		.collect()
+	}
+}
+
+/// The won runs, from oldest to most recent, persisted in the player profile.
+pub struct RunHistory {
+	file_path: PathBuf,
+}
+
+impl RunHistory {
+	pub fn load(ctx: &Context) -> RunHistory {
+		RunHistory { file_path: ctx.fs.user_data_dir().join("run_history.txt") }
+	}
+
+	/// Adds a line for a won run, with the badges it earned.
+	pub fn record_victory(&self, run_config: &RunConfig, badges: &[RunStyle]) {
+		let badge_names: Vec<_> = badges.iter().map(|style| style.name()).collect();
+		let badges_text = if badge_names.is_empty() {
+			"no badge".to_string()
+		} else {
+			badge_names.join(", ")
+		};
+		let mut content = std::fs::read_to_string(&self.file_path).unwrap_or_default();
+		content.push_str(&format!(
+			"Won run ({}): {badges_text}\n",
+			run_config.summary()
+		));
+		// Failing to persist is not a big deal, the run was still won.
+		if let Some(dir) = self.file_path.parent() {
+			let _ = std::fs::create_dir_all(dir);
+		}
+		let _ = std::fs::write(&self.file_path, content);
+	}
+}
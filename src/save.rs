@@ -0,0 +1,51 @@
+//! Quick save and quick load of the current run, so that it survives the game being closed.
+//!
+//! `F5` saves the run in `save.json` in the user data directory, overwriting the previous save,
+//! and `F9` loads it back, during a run or from the title screen. The history of the previous
+//! worlds is saved with it, so the moves made before saving can still be redone after loading,
+//! and so is what the run did that counts for its styles (the encounter director is part of the
+//! worlds).
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{gameplay::LogicalWorld, generation::RunConfig, run_styles::RunStyleTracker};
+
+/// What is needed to pick a run up where it was left.
+#[derive(Serialize, Deserialize)]
+pub struct SavedRun {
+	pub run_config: RunConfig,
+	pub logical_world: LogicalWorld,
+	pub previous_logical_worlds: VecDeque<LogicalWorld>,
+	pub turn_checksums: Vec<u64>,
+	pub world_before_game_turn: Option<LogicalWorld>,
+	// Saves from before it was saved load with no style broken.
+	#[serde(default)]
+	pub run_styles: RunStyleTracker,
+}
+
+fn file_path(ctx: &Context) -> PathBuf {
+	ctx.fs.user_data_dir().join("save.json")
+}
+
+impl SavedRun {
+	pub fn save(&self, ctx: &Context) -> Result<(), String> {
+		let content = serde_json::to_string(self).map_err(|error| error.to_string())?;
+		let file_path = file_path(ctx);
+		if let Some(dir) = file_path.parent() {
+			std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+		}
+		std::fs::write(file_path, content).map_err(|error| error.to_string())
+	}
+
+	pub fn exists(ctx: &Context) -> bool {
+		file_path(ctx).exists()
+	}
+
+	pub fn load(ctx: &Context) -> Result<SavedRun, String> {
+		let content = std::fs::read_to_string(file_path(ctx)).map_err(|error| error.to_string())?;
+		serde_json::from_str(&content).map_err(|error| error.to_string())
+	}
+}
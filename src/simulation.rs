@@ -0,0 +1,160 @@
+//! Headless driving of runs, a whole turn at a time like the game does it, for tests, bots
+//! or a server to play without any window.
+//!
+//! The game uses the same functions to play its turns, so a run driven from here plays out
+//! the same as if it was played with the same moves in the game.
+
+use glam::IVec2;
+
+use crate::{
+	gameplay::{LogicalTransition, LogicalWorld, PlayerAction},
+	generation::{generate_level, RunConfig, FINAL_DEPTH},
+	metrics,
+};
+
+/// Plays the turn of the game that follows the given transition of the player's move: the
+/// explosives, the machines and the statuses do their thing, then the agents move one by one.
+/// Returns the transitions of the turn, in order, each following from the world of the previous.
+/// The encounter director of the world keeps track of the turn as it goes.
+pub fn play_game_turn(
+	mut transition: LogicalTransition,
+	run_config: &RunConfig,
+) -> Vec<LogicalTransition> {
+	let _transition_build_timer = metrics::time("transition build");
+	let mut stats = transition.resulting_lw.stats.clone();
+	let mut looks = transition.resulting_lw.looks.clone();
+	let activity_events = transition.resulting_lw.update_activity_area(run_config.activity_radius);
+	transition.resulting_lw.give_move_token_to_agents();
+	transition.resulting_lw.spread_scent();
+	if run_config.encounter_director {
+		let mut director = std::mem::take(&mut transition.resulting_lw.director);
+		director.nudge_agents(&mut transition.resulting_lw, run_config);
+		transition.resulting_lw.director = director;
+	}
+	let mut transitions = vec![];
+	if let Some(altars_transition) = transition.resulting_lw.awaken_altars() {
+		transitions.push(altars_transition.clone());
+		transition = altars_transition;
+	}
+	if let Some(bombs_transition) = transition.resulting_lw.tick_bombs() {
+		transitions.push(bombs_transition.clone());
+		transition = bombs_transition;
+	}
+	// Each wave of barrels exploding is a transition of its own, to see the chain reaction.
+	while let Some(barrels_transition) = transition.resulting_lw.explode_barrels() {
+		transitions.push(barrels_transition.clone());
+		transition = barrels_transition;
+	}
+	if let Some(machines_transition) = transition.resulting_lw.run_machines() {
+		transitions.push(machines_transition.clone());
+		transition = machines_transition;
+	}
+	if let Some(conveyors_transition) = transition.resulting_lw.run_conveyors() {
+		transitions.push(conveyors_transition.clone());
+		transition = conveyors_transition;
+	}
+	if let Some(magnets_transition) = transition.resulting_lw.run_magnets() {
+		transitions.push(magnets_transition.clone());
+		transition = magnets_transition;
+	}
+	if let Some(lasers_transition) = transition.resulting_lw.fire_lasers() {
+		transitions.push(lasers_transition.clone());
+		transition = lasers_transition;
+	}
+	if let Some(spikes_transition) = transition.resulting_lw.run_spikes() {
+		transitions.push(spikes_transition.clone());
+		transition = spikes_transition;
+	}
+	if let Some(status_transition) = transition.resulting_lw.apply_statuses() {
+		transitions.push(status_transition.clone());
+		transition = status_transition;
+	}
+	while let Some(mut next_transition) = transition.resulting_lw.handle_move_for_one_agent() {
+		let mut director = std::mem::take(&mut next_transition.resulting_lw.director);
+		director.observe(&next_transition);
+		next_transition.resulting_lw.director = director;
+		transitions.push(next_transition.clone());
+		transition = next_transition;
+	}
+	// Agents waking up or falling dormant is not worth a transition of its own to animate.
+	if !activity_events.is_empty() {
+		match transitions.first_mut() {
+			Some(first_transition) => first_transition.logical_events.extend(activity_events),
+			None => transitions.push(LogicalTransition {
+				logical_events: activity_events,
+				resulting_lw: transition.resulting_lw.clone(),
+			}),
+		}
+	}
+	// Each transition follows from the world of the previous one without its statistics
+	// being counted nor its looks being carried, that is done once the whole turn is known.
+	for transition in transitions.iter_mut() {
+		transition.resulting_lw.stats = stats.clone();
+		transition.resulting_lw.looks = looks.clone();
+		*transition = transition.clone().with_looks_carried().counted_in_stats(None);
+		stats = transition.resulting_lw.stats.clone();
+		looks = transition.resulting_lw.looks.clone();
+	}
+	transitions
+}
+
+/// The level that the player gets to when going through the exit of the given level,
+/// the depth of the run config being that of the new level.
+pub fn next_level(run_config: &RunConfig, previous_lw: &LogicalWorld) -> LogicalWorld {
+	let mut lw = generate_level(run_config);
+	lw.carry_over_from(previous_lw);
+	lw.updated_visibility()
+}
+
+/// A run, played without the game around it.
+pub struct Simulation {
+	pub lw: LogicalWorld,
+	pub run_config: RunConfig,
+}
+
+impl Simulation {
+	pub fn new(run_config: RunConfig) -> Simulation {
+		let lw = generate_level(&run_config).updated_visibility();
+		Simulation { lw, run_config }
+	}
+
+	/// Plays a move of the player and the turn of the game that follows, see `player_act`.
+	pub fn player_move(&mut self, direction: IVec2) -> Vec<LogicalTransition> {
+		self.player_act(PlayerAction::Move(direction))
+	}
+
+	/// Plays an action of the player and the turn of the game that follows, and returns all the
+	/// transitions, in order. Going through the exit leads to the next level right away.
+	/// Nothing happens once the run is over, or if the action cannot be done.
+	pub fn player_act(&mut self, action: PlayerAction) -> Vec<LogicalTransition> {
+		if !self.lw.has_player() {
+			return vec![];
+		}
+		let Some(transition) = self.lw.player_act(action) else {
+			return vec![];
+		};
+		self.lw = transition.resulting_lw.clone();
+		if self.lw.player_exited() {
+			if self.run_config.depth < FINAL_DEPTH {
+				self.run_config.depth += 1;
+				self.lw = next_level(&self.run_config, &self.lw);
+			}
+			return vec![transition];
+		}
+		let mut transitions = play_game_turn(transition.clone(), &self.run_config);
+		if let Some(last_transition) = transitions.last() {
+			self.lw = last_transition.resulting_lw.clone();
+		}
+		transitions.insert(0, transition);
+		transitions
+	}
+
+	pub fn is_won(&self) -> bool {
+		self.lw.player_exited() && self.run_config.depth == FINAL_DEPTH
+	}
+
+	/// The bunny is dead (redos are for the game to offer, there is none here).
+	pub fn is_lost(&self) -> bool {
+		!self.lw.has_player() && !self.lw.player_exited()
+	}
+}
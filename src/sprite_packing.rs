@@ -0,0 +1,61 @@
+//! Packing of the individual sprites of `assets/sprites/` into the spritesheet.
+//!
+//! Used by the build script, and by the asset hot-reloading of debug builds
+//! that has to pack the sprites the exact same way.
+
+use std::path::Path;
+
+use image::{GenericImage, RgbaImage};
+
+pub const SHEET_SIZE: u32 = 128;
+
+/// A sprite in the packed spritesheet, its rect is in pixels.
+pub struct PackedSprite {
+	/// The name of the sprite's file, without the extension.
+	pub name: String,
+	pub x: u32,
+	pub y: u32,
+	pub w: u32,
+	pub h: u32,
+}
+
+/// Every PNG in the given folder (but not in its subfolders) is packed.
+pub fn pack_sprites(sprites_dir: &Path) -> Result<(RgbaImage, Vec<PackedSprite>), String> {
+	let mut sprites: Vec<(String, RgbaImage)> = vec![];
+	let entries = std::fs::read_dir(sprites_dir).map_err(|error| error.to_string())?;
+	for entry in entries {
+		let path = entry.map_err(|error| error.to_string())?.path();
+		if path.extension().is_some_and(|extension| extension == "png") {
+			let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+			let image =
+				image::open(&path).map_err(|error| format!("{}: {error}", path.display()))?.to_rgba8();
+			sprites.push((name, image));
+		}
+	}
+	// Tallest first packs shelves tightly, names then make the layout not depend on the file system.
+	sprites.sort_by(|(name_a, image_a), (name_b, image_b)| {
+		image_b.height().cmp(&image_a.height()).then(name_a.cmp(name_b))
+	});
+
+	// Shelf packing, sprites are placed left to right in rows as tall as their first sprite.
+	let mut sheet = RgbaImage::new(SHEET_SIZE, SHEET_SIZE);
+	let mut layout = vec![];
+	let (mut x, mut shelf_y, mut shelf_height) = (0, 0, 0);
+	for (name, image) in sprites.into_iter() {
+		if x + image.width() > SHEET_SIZE {
+			x = 0;
+			shelf_y += shelf_height;
+			shelf_height = 0;
+		}
+		shelf_height = shelf_height.max(image.height());
+		if shelf_y + shelf_height > SHEET_SIZE {
+			return Err(format!(
+				"the sprites don't fit in a {SHEET_SIZE}x{SHEET_SIZE} spritesheet anymore"
+			));
+		}
+		sheet.copy_from(&image, x, shelf_y).unwrap();
+		layout.push(PackedSprite { name, x, y: shelf_y, w: image.width(), h: image.height() });
+		x += image.width();
+	}
+	Ok((sheet, layout))
+}
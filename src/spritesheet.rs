@@ -1,4 +1,8 @@
 //! Spritesheet related matters, such as loading or providing the rect of a sprite in the sheet.
+//!
+//! The spritesheet is assembled by the build script from the PNGs in `assets/sprites/`.
+//! Adding a sprite is a matter of dropping its file there and adding a variant here
+//! that maps to the rect constant named after the file.
 
 use ggez::{
 	glam::IVec2,
@@ -11,11 +15,37 @@ pub struct SpritesheetStuff {
 	pub spritesheet: Image,
 	/// Used as a mask to multiply it by a color for like hit effect red blinking.
 	pub spritesheet_white: Image,
+	/// Desaturated, for the tiles that are remembered but out of sight.
+	pub spritesheet_grey: Image,
 }
 
 impl SpritesheetStuff {
 	pub fn new(ctx: &mut Context) -> GameResult<SpritesheetStuff> {
-		let mut image = image::load_from_memory(include_bytes!("../assets/spritesheet.png")).unwrap();
+		let image =
+			image::load_from_memory(include_bytes!(concat!(env!("OUT_DIR"), "/spritesheet.png")))
+				.unwrap();
+		Ok(SpritesheetStuff::from_image(ctx, image))
+	}
+
+	/// Reassembles the spritesheet from the sprite files as they are now.
+	/// Fails if the sprites do not pack like they did at build time anymore.
+	#[cfg(debug_assertions)]
+	pub fn reloaded(ctx: &mut Context) -> Result<SpritesheetStuff, String> {
+		let (sheet, packed) = crate::sprite_packing::pack_sprites(&crate::hot_reload::sprites_dir())?;
+		let same_layout = packed.len() == layout::PACKED.len()
+			&& packed.iter().zip(layout::PACKED).all(|(sprite, (name, rect))| {
+				sprite.name == *name && [sprite.x, sprite.y, sprite.w, sprite.h] == *rect
+			});
+		if !same_layout {
+			return Err("sprites were added, removed or resized, this requires a rebuild".to_string());
+		}
+		Ok(SpritesheetStuff::from_image(
+			ctx,
+			image::DynamicImage::ImageRgba8(sheet),
+		))
+	}
+
+	fn from_image(ctx: &mut Context, mut image: image::DynamicImage) -> SpritesheetStuff {
 		let spritesheet = Image::from_pixels(
 			&ctx.gfx,
 			image.as_rgba8().unwrap().as_bytes(),
@@ -24,6 +54,22 @@ impl SpritesheetStuff {
 			image.height(),
 		);
 
+		let mut grey_image = image.clone();
+		grey_image.as_mut_rgba8().unwrap().pixels_mut().for_each(|pixel| {
+			let [red, green, blue, _alpha] = pixel.0.map(f32::from);
+			let luminance = (0.299 * red + 0.587 * green + 0.114 * blue) as u8;
+			pixel.0[0] = luminance;
+			pixel.0[1] = luminance;
+			pixel.0[2] = luminance;
+		});
+		let spritesheet_grey = Image::from_pixels(
+			&ctx.gfx,
+			grey_image.as_rgba8().unwrap().as_bytes(),
+			ImageFormat::Rgba8UnormSrgb,
+			grey_image.width(),
+			grey_image.height(),
+		);
+
 		// Paint the spritesheet in white.
 		image.as_mut_rgba8().unwrap().pixels_mut().for_each(|pixel| {
 			if pixel.0[3] != 0 {
@@ -40,7 +86,7 @@ impl SpritesheetStuff {
 			image.height(),
 		);
 
-		Ok(SpritesheetStuff { spritesheet, spritesheet_white })
+		SpritesheetStuff { spritesheet, spritesheet_white, spritesheet_grey }
 	}
 }
 
@@ -52,71 +98,211 @@ pub enum SpriteFromSheet {
 	Shield,
 	Rock,
 	Bunny,
+	/// The frames of the bunny twitching an ear while it waits.
+	BunnyIdle(u8),
+	/// The frames of the bunny hopping from a tile to the next.
+	BunnyWalk(u8),
 	Slime,
+	/// The frames of the slime squishing in place.
+	SlimeSquish(u8),
 	Pickaxe,
 	Exit,
 	VisionGem,
+	VisionSword,
 	Key,
 	Door,
 	Rope,
 	Shroomer,
+	Archer,
+	Ghost,
+	GolemBoss,
 	Shroom,
 	Bush,
+	Note,
+	Sparkle,
+	FlameAura,
+	FrostAura,
+	StatusBurning,
+	StatusPoisoned,
+	StatusStunned,
+	StatusFrozen,
+	Piston,
+	PistonHead,
+	PressurePlate,
+	Hole,
+	Ice,
+	FragileFloor,
+	FragileFloorCracked,
+	Spikes,
+	SpikesArmed,
+	Rubble,
+	Scrap,
+	MedalGold,
+	MedalSilver,
+	MedalBronze,
+	CursedMirror,
+	Coin,
+	Price,
+	KeyShard,
+	ExitSealed,
+	Torch,
+	Magnet,
+	Crate,
+	StrengthPotion,
+	SlimeCorpse,
+	Star,
 	Heart,
 	RedoHeart,
+	RewindToken,
 	Fish(IVec2),
+	Statue(IVec2),
+	Altar,
+	Bomb,
+	Barrel,
+	BarrelSetOff,
+	Bow,
+	Raft,
+	/// The frames of the animated water.
+	Water(u8),
+	/// The frames of the animated lava.
+	Lava(u8),
+	Gate,
+	OpenGate,
+	Teleporter,
+	Conveyor(IVec2),
+	Arrow(IVec2),
+	LaserEmitter(IVec2),
+	MirrorRising,
+	MirrorFalling,
+	Golem,
 	Digit(u8),
 	Slash,
 }
 
 impl SpriteFromSheet {
 	pub fn rect_in_spritesheet(&self) -> Rect {
-		// Wild non-aligned sprites.
-		if let SpriteFromSheet::Digit(digit) = self {
-			assert!(*digit <= 9);
-			let x = digit * 4;
-			let y = 16;
-			return Rect::new(x as f32 / 128.0, y as f32 / 128.0, 3.0 / 128.0, 5.0 / 128.0);
-		} else if let SpriteFromSheet::Slash = self {
-			let x = 10 * 4;
-			let y = 16;
-			return Rect::new(x as f32 / 128.0, y as f32 / 128.0, 3.0 / 128.0, 5.0 / 128.0);
-		}
-
-		// Now we handle 8x8 sprites aligned on the 8x8-tiles grid.
-		let (x, y) = match self {
-			SpriteFromSheet::Wall => (0, 0),
-			SpriteFromSheet::Floor => (0, 1),
-			SpriteFromSheet::Sword => (1, 0),
-			SpriteFromSheet::Shield => (2, 0),
-			SpriteFromSheet::Rock => (3, 0),
-			SpriteFromSheet::Bunny => (4, 0),
-			SpriteFromSheet::Slime => (5, 0),
-			SpriteFromSheet::Pickaxe => (6, 0),
-			SpriteFromSheet::Exit => (7, 0),
-			SpriteFromSheet::VisionGem => (8, 0),
-			SpriteFromSheet::Key => (9, 0),
-			SpriteFromSheet::Door => (10, 0),
-			SpriteFromSheet::Rope => (11, 0),
-			SpriteFromSheet::Shroomer => (12, 0),
-			SpriteFromSheet::Shroom => (13, 0),
-			SpriteFromSheet::Bush => (14, 0),
-			SpriteFromSheet::Heart => (1, 1),
-			SpriteFromSheet::RedoHeart => (2, 1),
-			SpriteFromSheet::Fish(IVec2 { x: -1, y: 0 }) => (3, 1),
-			SpriteFromSheet::Fish(IVec2 { x: 1, y: 0 }) => (4, 1),
-			SpriteFromSheet::Fish(IVec2 { x: 0, y: -1 }) => (5, 1),
-			SpriteFromSheet::Fish(IVec2 { x: 0, y: 1 }) => (6, 1),
+		use layout::*;
+		match self {
+			SpriteFromSheet::Wall => WALL,
+			SpriteFromSheet::Floor => FLOOR,
+			SpriteFromSheet::Sword => SWORD,
+			SpriteFromSheet::Shield => SHIELD,
+			SpriteFromSheet::Rock => ROCK,
+			SpriteFromSheet::Bunny => BUNNY,
+			SpriteFromSheet::BunnyIdle(frame) => BUNNY_IDLE[*frame as usize],
+			SpriteFromSheet::BunnyWalk(frame) => BUNNY_WALK[*frame as usize],
+			SpriteFromSheet::Slime => SLIME,
+			SpriteFromSheet::SlimeSquish(frame) => SLIME_SQUISH[*frame as usize],
+			SpriteFromSheet::Pickaxe => PICKAXE,
+			SpriteFromSheet::Exit => EXIT,
+			SpriteFromSheet::VisionGem => VISION_GEM,
+			SpriteFromSheet::VisionSword => VISION_SWORD,
+			SpriteFromSheet::Key => KEY,
+			SpriteFromSheet::Door => DOOR,
+			SpriteFromSheet::Rope => ROPE,
+			SpriteFromSheet::Shroomer => SHROOMER,
+			SpriteFromSheet::Archer => ARCHER,
+			SpriteFromSheet::Ghost => GHOST,
+			SpriteFromSheet::GolemBoss => GOLEM_BOSS,
+			SpriteFromSheet::Shroom => SHROOM,
+			SpriteFromSheet::Bush => BUSH,
+			SpriteFromSheet::Note => NOTE,
+			SpriteFromSheet::Sparkle => SPARKLE,
+			SpriteFromSheet::FlameAura => FLAME_AURA,
+			SpriteFromSheet::FrostAura => FROST_AURA,
+			SpriteFromSheet::StatusBurning => STATUS_BURNING,
+			SpriteFromSheet::StatusPoisoned => STATUS_POISONED,
+			SpriteFromSheet::StatusStunned => STATUS_STUNNED,
+			SpriteFromSheet::StatusFrozen => STATUS_FROZEN,
+			SpriteFromSheet::Piston => PISTON,
+			SpriteFromSheet::PistonHead => PISTON_HEAD,
+			SpriteFromSheet::PressurePlate => PRESSURE_PLATE,
+			SpriteFromSheet::Hole => HOLE,
+			SpriteFromSheet::Ice => ICE,
+			SpriteFromSheet::FragileFloor => FRAGILE_FLOOR,
+			SpriteFromSheet::FragileFloorCracked => FRAGILE_FLOOR_CRACKED,
+			SpriteFromSheet::Spikes => SPIKES,
+			SpriteFromSheet::SpikesArmed => SPIKES_ARMED,
+			SpriteFromSheet::Rubble => RUBBLE,
+			SpriteFromSheet::Scrap => SCRAP,
+			SpriteFromSheet::MedalGold => MEDAL_GOLD,
+			SpriteFromSheet::MedalSilver => MEDAL_SILVER,
+			SpriteFromSheet::MedalBronze => MEDAL_BRONZE,
+			SpriteFromSheet::CursedMirror => CURSED_MIRROR,
+			SpriteFromSheet::Coin => COIN,
+			SpriteFromSheet::Price => PRICE,
+			SpriteFromSheet::KeyShard => KEY_SHARD,
+			SpriteFromSheet::ExitSealed => EXIT_SEALED,
+			SpriteFromSheet::Torch => TORCH,
+			SpriteFromSheet::Magnet => MAGNET,
+			SpriteFromSheet::Crate => CRATE,
+			SpriteFromSheet::StrengthPotion => STRENGTH_POTION,
+			SpriteFromSheet::SlimeCorpse => SLIME_CORPSE,
+			SpriteFromSheet::Star => STAR,
+			SpriteFromSheet::Heart => HEART,
+			SpriteFromSheet::RedoHeart => REDO_HEART,
+			SpriteFromSheet::RewindToken => REWIND_TOKEN,
+			SpriteFromSheet::Fish(IVec2 { x: -1, y: 0 }) => FISH_LEFT,
+			SpriteFromSheet::Fish(IVec2 { x: 1, y: 0 }) => FISH_RIGHT,
+			SpriteFromSheet::Fish(IVec2 { x: 0, y: -1 }) => FISH_UP,
+			SpriteFromSheet::Fish(IVec2 { x: 0, y: 1 }) => FISH_DOWN,
 			SpriteFromSheet::Fish(invalid_direction) => {
 				panic!("direction {invalid_direction} is not a valid fish direction")
 			},
-			SpriteFromSheet::Digit(_) | SpriteFromSheet::Slash => unreachable!("Handled above"),
-		};
-		Rect::new(
-			x as f32 * 8.0 / 128.0,
-			y as f32 * 8.0 / 128.0,
-			8.0 / 128.0,
-			8.0 / 128.0,
-		)
+			SpriteFromSheet::Arrow(IVec2 { x: -1, y: 0 }) => ARROW_LEFT,
+			SpriteFromSheet::Arrow(IVec2 { x: 1, y: 0 }) => ARROW_RIGHT,
+			SpriteFromSheet::Arrow(IVec2 { x: 0, y: -1 }) => ARROW_UP,
+			SpriteFromSheet::Arrow(IVec2 { x: 0, y: 1 }) => ARROW_DOWN,
+			SpriteFromSheet::Arrow(invalid_direction) => {
+				panic!("direction {invalid_direction} is not a valid arrow direction")
+			},
+			SpriteFromSheet::Conveyor(IVec2 { x: -1, y: 0 }) => CONVEYOR_LEFT,
+			SpriteFromSheet::Conveyor(IVec2 { x: 1, y: 0 }) => CONVEYOR_RIGHT,
+			SpriteFromSheet::Conveyor(IVec2 { x: 0, y: -1 }) => CONVEYOR_UP,
+			SpriteFromSheet::Conveyor(IVec2 { x: 0, y: 1 }) => CONVEYOR_DOWN,
+			SpriteFromSheet::Conveyor(invalid_direction) => {
+				panic!("direction {invalid_direction} is not a valid conveyor direction")
+			},
+			SpriteFromSheet::Statue(IVec2 { x: -1, y: 0 }) => STATUE_LEFT,
+			SpriteFromSheet::Statue(IVec2 { x: 1, y: 0 }) => STATUE_RIGHT,
+			SpriteFromSheet::Statue(IVec2 { x: 0, y: -1 }) => STATUE_UP,
+			SpriteFromSheet::Statue(IVec2 { x: 0, y: 1 }) => STATUE_DOWN,
+			SpriteFromSheet::Statue(invalid_direction) => {
+				panic!("direction {invalid_direction} is not a valid statue direction")
+			},
+			SpriteFromSheet::LaserEmitter(IVec2 { x: -1, y: 0 }) => LASER_EMITTER_LEFT,
+			SpriteFromSheet::LaserEmitter(IVec2 { x: 1, y: 0 }) => LASER_EMITTER_RIGHT,
+			SpriteFromSheet::LaserEmitter(IVec2 { x: 0, y: -1 }) => LASER_EMITTER_UP,
+			SpriteFromSheet::LaserEmitter(IVec2 { x: 0, y: 1 }) => LASER_EMITTER_DOWN,
+			SpriteFromSheet::LaserEmitter(invalid_direction) => {
+				panic!("direction {invalid_direction} is not a valid laser emitter direction")
+			},
+			SpriteFromSheet::MirrorRising => MIRROR_RISING,
+			SpriteFromSheet::MirrorFalling => MIRROR_FALLING,
+			SpriteFromSheet::Altar => ALTAR,
+			SpriteFromSheet::Bomb => BOMB,
+			SpriteFromSheet::Barrel => BARREL,
+			SpriteFromSheet::BarrelSetOff => BARREL_SET_OFF,
+			SpriteFromSheet::Bow => BOW,
+			SpriteFromSheet::Raft => RAFT,
+			SpriteFromSheet::Gate => GATE,
+			SpriteFromSheet::OpenGate => OPEN_GATE,
+			SpriteFromSheet::Teleporter => TELEPORTER,
+			SpriteFromSheet::Water(frame) => [WATER_0, WATER_1, WATER_2][*frame as usize],
+			SpriteFromSheet::Lava(frame) => [LAVA_0, LAVA_1][*frame as usize],
+			SpriteFromSheet::Golem => GOLEM,
+			SpriteFromSheet::Digit(digit) => [
+				DIGIT_0, DIGIT_1, DIGIT_2, DIGIT_3, DIGIT_4, DIGIT_5, DIGIT_6, DIGIT_7, DIGIT_8,
+				DIGIT_9,
+			][*digit as usize],
+			SpriteFromSheet::Slash => SLASH,
+		}
 	}
 }
+
+/// The rects of the sprites in the spritesheet, as packed by the build script.
+mod layout {
+	use ggez::graphics::Rect;
+
+	include!(concat!(env!("OUT_DIR"), "/sprite_layout.rs"));
+}
@@ -0,0 +1,91 @@
+//! A hasher that gives the same hashes on every platform and with every version of Rust,
+//! unlike the `DefaultHasher` of the standard library, whose algorithm may change.
+//!
+//! It is FNV-1a, fast on small inputs and good enough to tell states apart, not meant to
+//! resist collisions made on purpose. Integers are hashed as little-endian bytes, and sizes
+//! (like the lengths of collections) as 64-bit integers, for the hashes not to depend on the
+//! platform either.
+
+use std::hash::Hasher;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+pub struct StableHasher {
+	state: u64,
+}
+
+impl StableHasher {
+	pub fn new() -> StableHasher {
+		StableHasher { state: FNV_OFFSET_BASIS }
+	}
+}
+
+impl Hasher for StableHasher {
+	fn finish(&self) -> u64 {
+		self.state
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.state ^= *byte as u64;
+			self.state = self.state.wrapping_mul(FNV_PRIME);
+		}
+	}
+
+	fn write_u16(&mut self, value: u16) {
+		self.write(&value.to_le_bytes());
+	}
+	fn write_u32(&mut self, value: u32) {
+		self.write(&value.to_le_bytes());
+	}
+	fn write_u64(&mut self, value: u64) {
+		self.write(&value.to_le_bytes());
+	}
+	fn write_u128(&mut self, value: u128) {
+		self.write(&value.to_le_bytes());
+	}
+	fn write_usize(&mut self, value: usize) {
+		self.write_u64(value as u64);
+	}
+	fn write_i16(&mut self, value: i16) {
+		self.write_u16(value as u16);
+	}
+	fn write_i32(&mut self, value: i32) {
+		self.write_u32(value as u32);
+	}
+	fn write_i64(&mut self, value: i64) {
+		self.write_u64(value as u64);
+	}
+	fn write_i128(&mut self, value: i128) {
+		self.write_u128(value as u128);
+	}
+	fn write_isize(&mut self, value: isize) {
+		self.write_u64(value as u64);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::hash::Hash;
+
+	use super::*;
+
+	fn hash(value: impl Hash) -> u64 {
+		let mut hasher = StableHasher::new();
+		value.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	#[test]
+	fn hashes_do_not_change() {
+		// Test vectors of FNV-1a.
+		assert_eq!(StableHasher::new().finish(), 0xcbf29ce484222325);
+		let mut hasher = StableHasher::new();
+		hasher.write(b"a");
+		assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+		// Sizes are hashed the same on every platform.
+		assert_eq!(hash(1u64), hash(1usize));
+		assert_eq!(hash((3i32, "seed")), 0xaa7cc1ceeccc1160);
+	}
+}
@@ -0,0 +1,186 @@
+//! Title screen, displayed at launch, where a run can be set up before it starts.
+//!
+//! A seed can be typed to play a specific level, like one shared by an other player.
+//! The run saved with `F5` can be continued from there, and the options can be changed.
+//! The handcrafted levels of the campaign and of the level browser, and the daily challenge,
+//! are played from there too.
+
+use std::time::Instant;
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Rect, Text, TextLayout},
+	winit::event::VirtualKeyCode,
+};
+
+use crate::{
+	daily::DailyResult,
+	generation::{seed_from_text, RunConfig},
+	graphics::{draw_popup_sprite, DepthLayer},
+	spritesheet::{SpriteFromSheet, SpritesheetStuff},
+	widgets::{draw_panel, Menu, MenuEvent, Widget},
+};
+
+const PLAY: usize = 0;
+const CAMPAIGN: usize = 1;
+const LEVELS: usize = 2;
+const DAILY: usize = 3;
+const CONTINUE: usize = 4;
+const SEED: usize = 5;
+const SEALED_EXITS: usize = 6;
+const FAVORITES: usize = 7;
+const UNLOCKS: usize = 8;
+const EDITOR: usize = 9;
+const OPTIONS: usize = 10;
+
+/// What the game should do following a key press in the title screen.
+pub enum TitleAction {
+	Play(RunConfig),
+	PlayDaily,
+	/// Load the saved run.
+	Continue,
+	OpenCampaign,
+	OpenLevelBrowser,
+	OpenFavorites,
+	OpenUnlocks,
+	OpenEditor,
+	OpenOptions,
+}
+
+pub struct TitleScreen {
+	menu: Menu,
+	has_saved_run: bool,
+	/// Today's daily challenge was tried already.
+	daily_tried: bool,
+	/// The logo is animated from that moment.
+	shown_since: Instant,
+}
+
+impl TitleScreen {
+	/// The daily result is that of today's daily challenge, if it was tried already.
+	pub fn new(has_saved_run: bool, daily_result: Option<&DailyResult>) -> TitleScreen {
+		let menu = Menu::new(vec![
+			Widget::button("Play"),
+			Widget::button("Campaign"),
+			Widget::button("Levels"),
+			Widget::button(match daily_result {
+				Some(result) => format!("Daily challenge ({} today)", result.description()),
+				None => "Daily challenge".to_string(),
+			}),
+			Widget::button(if has_saved_run {
+				"Continue"
+			} else {
+				"Continue (no saved run)"
+			}),
+			Widget::TextEntry { label: "Seed".to_string(), text: String::new(), max_length: 20 },
+			Widget::Toggle { label: "Sealed exits".to_string(), value: false },
+			Widget::button("Favorites"),
+			Widget::button("Unlocks"),
+			Widget::button("Level editor"),
+			Widget::button("Options"),
+		]);
+		TitleScreen {
+			menu,
+			has_saved_run,
+			daily_tried: daily_result.is_some(),
+			shown_since: Instant::now(),
+		}
+	}
+
+	/// An empty seed means a random one.
+	fn run_config(&self) -> RunConfig {
+		let Widget::TextEntry { text, .. } = &self.menu.widgets[SEED] else {
+			unreachable!()
+		};
+		let Widget::Toggle { value: sealed_exits, .. } = self.menu.widgets[SEALED_EXITS] else {
+			unreachable!()
+		};
+		let mut run_config = RunConfig { sealed_exits, ..RunConfig::new() };
+		if !text.trim().is_empty() {
+			run_config.seed = seed_from_text(text);
+		}
+		run_config
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<TitleAction> {
+		match self.menu.key_down(keycode)? {
+			MenuEvent::Activated(PLAY | SEED) => Some(TitleAction::Play(self.run_config())),
+			MenuEvent::Activated(CAMPAIGN) => Some(TitleAction::OpenCampaign),
+			MenuEvent::Activated(LEVELS) => Some(TitleAction::OpenLevelBrowser),
+			MenuEvent::Activated(DAILY) => (!self.daily_tried).then_some(TitleAction::PlayDaily),
+			MenuEvent::Activated(CONTINUE) => self.has_saved_run.then_some(TitleAction::Continue),
+			MenuEvent::Activated(FAVORITES) => Some(TitleAction::OpenFavorites),
+			MenuEvent::Activated(UNLOCKS) => Some(TitleAction::OpenUnlocks),
+			MenuEvent::Activated(EDITOR) => Some(TitleAction::OpenEditor),
+			MenuEvent::Activated(OPTIONS) => Some(TitleAction::OpenOptions),
+			MenuEvent::Activated(_) | MenuEvent::Changed(_) => None,
+		}
+	}
+
+	pub fn text_input(&mut self, character: char) {
+		self.menu.text_input(character);
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let z = DepthLayer::Popup.to_z_value();
+		draw_panel(canvas, Rect::new(0.0, 0.0, screen_size.x, screen_size.y));
+
+		// The logo, the bunny pushing a rock back and forth, hopping as it goes.
+		let time = self.shown_since.elapsed().as_secs_f32();
+		let push = (time * 1.5).sin() * 30.0;
+		let hop = -((time * 6.0).sin().abs()) * 12.0;
+		let logo_center = Vec2::new(screen_size.x / 2.0, 200.0);
+		let sprite_size = 80.0;
+		draw_popup_sprite(
+			canvas,
+			spritesheet_stuff,
+			&SpriteFromSheet::Bunny,
+			logo_center + Vec2::new(-sprite_size / 2.0 + push, hop),
+			sprite_size,
+		);
+		draw_popup_sprite(
+			canvas,
+			spritesheet_stuff,
+			&SpriteFromSheet::Rock,
+			logo_center + Vec2::new(sprite_size / 2.0 + push, 0.0),
+			sprite_size,
+		);
+		let mut title_text = Text::new("PushDg");
+		title_text.set_scale(64.0);
+		title_text.set_layout(TextLayout::center());
+		canvas.draw(
+			&title_text,
+			DrawParam::default().dest(Vec2::new(screen_size.x / 2.0, 300.0)).z(z),
+		);
+		let mut version_text = Text::new(format!("v{}", env!("CARGO_PKG_VERSION")));
+		version_text.set_scale(16.0);
+		canvas.draw(
+			&version_text,
+			DrawParam::default()
+				.dest(Vec2::new(10.0, screen_size.y - 26.0))
+				.color(Color::new(0.7, 0.7, 0.7, 1.0))
+				.z(z),
+		);
+
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(screen_size.x / 2.0 - 150.0, 370.0),
+			screen_size.y - 40.0,
+		);
+		let mut hint_text = Text::new("Leave the seed empty for a random level.");
+		hint_text.set_scale(16.0);
+		canvas.draw(
+			&hint_text,
+			DrawParam::default()
+				.dest(Vec2::new(screen_size.x / 2.0 - 150.0, screen_size.y - 60.0))
+				.color(Color::new(0.7, 0.7, 0.7, 1.0))
+				.z(z),
+		);
+	}
+}
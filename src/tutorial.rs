@@ -0,0 +1,233 @@
+//! Tutorial cards that pop up the first time some kind of object is encountered.
+//!
+//! When an object of a kind that was never seen before becomes visible, a small card presenting
+//! it (its sprite, its name and a one-line hint) is displayed and the game waits for the player
+//! to dismiss it. The kinds already presented are remembered in a file of the player profile
+//! so that players who already know the game are not interrupted again.
+//!
+//! The same hints are given when inspecting an object by hovering it with the mouse.
+
+use std::{
+	collections::{HashSet, VecDeque},
+	path::PathBuf,
+};
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Quad, Rect, Text},
+	Context,
+};
+
+use crate::{
+	gameplay::{Enchantment, LogicalWorld, Look, Mirroring, Obj},
+	graphics::{draw_popup_sprite, obj_to_sprite, DepthLayer},
+	spritesheet::SpritesheetStuff,
+};
+
+/// What is displayed when inspecting the object: its name, its hint and its stats.
+/// The level may have given it a name of its own, the hint telling what it really is.
+pub fn obj_inspection(obj: &Obj, look: Option<&Look>) -> String {
+	let mut stats = vec![];
+	if let Some(hp) = obj.hp() {
+		stats.push(format!("HP {hp}"));
+	}
+	stats.push(format!("mass {}", obj.mass()));
+	if let Obj::Bunny { force, .. } = obj {
+		stats.push(format!("force {force}"));
+	}
+	stats.push(format!("damages {}", obj.damages()));
+	if let Some(held) = obj.held() {
+		stats.push(format!("holding {}", held.name().to_lowercase()));
+	}
+	for status in obj.statuses() {
+		stats.push(format!(
+			"{} for {} turns",
+			status.name(),
+			status.turns_left()
+		));
+	}
+	let name = look
+		.filter(|look| look.is_for(obj))
+		.and_then(|look| look.name.as_deref())
+		.unwrap_or(obj.name());
+	format!("{}\n{}\n{}", name, obj_hint(obj), stats.join(", "))
+}
+
+/// One line of advice about an object, shown on its card.
+fn obj_hint(obj: &Obj) -> &'static str {
+	match obj {
+		Obj::Wall => "It just stays there. Nothing can push it.",
+		Obj::Sword { enchantment: None } => "Deals 3 damages, to an enemy or to you.",
+		Obj::Sword { enchantment: Some(Enchantment::Flaming) } =>
+			"Deals 3 damages and sets enemies on fire for 3 turns.",
+		Obj::Sword { enchantment: Some(Enchantment::Frost) } =>
+			"Deals 3 damages and freezes enemies for 2 turns.",
+		Obj::Shield => "Deals no damages, put it between you and an enemy.",
+		Obj::Pickaxe { enchantment: None } => "Push it into a wall to mine it.",
+		Obj::Pickaxe { enchantment: Some(Enchantment::Flaming) } =>
+			"Mines walls, and sets enemies on fire for 3 turns.",
+		Obj::Pickaxe { enchantment: Some(Enchantment::Frost) } =>
+			"Mines walls, and freezes enemies for 2 turns.",
+		Obj::Rock => "The average pushable object, deals 1 damage.",
+		Obj::Exit => "Walk through it to leave the level.",
+		Obj::VisionGem => "Stand next to it to see through walls.",
+		Obj::VisionSword => "Deals 3 damages and lets you see through walls.",
+		Obj::Heart => "Walk into it to restore your HP.",
+		Obj::RedoHeart => "Walk into it to gain a redo.",
+		Obj::RewindToken => "Walk into it, then press T to replay the enemies' last moves.",
+		Obj::Door => "Push a key into it to open it.",
+		Obj::Key => "Push it into a door to open the door.",
+		Obj::Rope => "Pulls what is behind it when pushed.",
+		Obj::Bush => "Can be pushed, but nothing can be seen through it.",
+		Obj::Note => "Walk into it to read it, it will be kept in your journal.",
+		Obj::Piston { .. } => "Pushes hard when something is on its pressure plate.",
+		Obj::Gate { .. } => "Opens while something is on its pressure plate, push a rock there.",
+		Obj::PistonHead => "The extended part of a piston, it retracts next turn.",
+		Obj::Statue { .. } => "Turns to face where it is pushed, make its group face the altar.",
+		Obj::LaserEmitter { .. } => "Its beam hurts what it ends on every turn. Mirrors turn it.",
+		Obj::Mirror { .. } => "Turns laser beams aside, push it to send them elsewhere.",
+		Obj::Altar { .. } => "Gives a reward once all the nearby statues face it.",
+		Obj::Raft => "Push it onto water to make a way across, heavy things sink in water.",
+		Obj::Bow => "Push it to shoot an arrow ahead, it deals 2 damages to what it hits.",
+		Obj::Torch => "Hold it or stand next to it to see farther.",
+		Obj::Magnet => "Pulls the nearest metal thing in line with it, one tile per turn.",
+		Obj::Crate { .. } => "Hit it with a sword to break it open, something may be inside.",
+		Obj::StrengthPotion => "Walk into it to drink it, and push one more mass from then on.",
+		Obj::Arrow { .. } => "Flies straight until it hits something, deals 2 damages.",
+		Obj::Bomb { fuse: None } =>
+			"Walk into it to take it, then press B and a direction to place it.",
+		Obj::Bomb { fuse: Some(_) } =>
+			"Explodes when its countdown runs out, deals 3 damages around.",
+		Obj::Barrel { set_off: false } =>
+			"Explodes when hit or caught in an explosion, deals 3 damages around.",
+		Obj::Barrel { set_off: true } => "About to explode, setting off the barrels around it!",
+		Obj::CursedMirror => "Touching it swaps left and right for your next 9 moves.",
+		Obj::Coin => "Walk into it to take it, and spend it in shops.",
+		Obj::KeyShard => "Walk into it to take it. Gather them all to unseal the exit.",
+		Obj::Bunny { .. } => "This is you! Push things around, carefully.",
+		Obj::SlimeCorpse => "Push it into a slime, it deals 3 damages. Squelch!",
+		Obj::Slime { .. } => "Charges you when in a straight line, deals 2 damages.",
+		Obj::Shroomer { .. } => "Leaves shrooms behind and poisons you when adjacent.",
+		Obj::Archer { .. } =>
+			"Shoots you from afar when in a clear straight line, backs away when adjacent.",
+		Obj::Ghost { .. } => "Floats through walls to you, only a sword can hurt it.",
+		Obj::GolemBoss { .. } =>
+			"Guards the exit. Too big to push, it stomps you every other turn for 2 damages.",
+		Obj::Shroom { .. } => "Sacrifices itself to hit you if you are adjacent.",
+		Obj::Fish { .. } => "Swims back and forth on its own.",
+		Obj::Golem { mirroring: Mirroring::Reversed, .. } =>
+			"Copies each of your moves, going the opposite way.",
+		Obj::Golem { mirroring: Mirroring::Rotated, .. } =>
+			"Copies each of your moves, turned a quarter clockwise.",
+	}
+}
+
+pub struct Tutorial {
+	/// Names of the kinds of objects that were already presented (or that are pending).
+	seen: HashSet<String>,
+	/// Objects that have a card waiting to be dismissed, the first one is the one displayed.
+	pending_cards: VecDeque<Obj>,
+	/// Where the names of the seen kinds of objects are persisted, one per line.
+	seen_file_path: PathBuf,
+}
+
+impl Tutorial {
+	/// Loads the kinds of objects already seen from the player profile, if any.
+	pub fn load(ctx: &Context) -> Tutorial {
+		let seen_file_path = ctx.fs.user_data_dir().join("seen_objs.txt");
+		let seen = std::fs::read_to_string(&seen_file_path)
+			.map(|content| content.lines().map(|line| line.to_string()).collect())
+			.unwrap_or_default();
+		Tutorial { seen, pending_cards: VecDeque::new(), seen_file_path }
+	}
+
+	/// Queues a card for every visible object of a kind never seen before.
+	pub fn discover_visible_objs(&mut self, lw: &LogicalWorld) {
+		for (_coords, tile) in lw.tiles() {
+			if let Some(obj) = tile.obj.as_ref() {
+				if tile.visible && self.seen.insert(obj.name().to_string()) {
+					self.pending_cards.push_back(obj.clone());
+				}
+			}
+		}
+	}
+
+	/// While a card is displayed, the game is expected to ignore the player's moves.
+	pub fn is_showing_card(&self) -> bool {
+		!self.pending_cards.is_empty()
+	}
+
+	pub fn dismiss_card(&mut self) {
+		if let Some(obj) = self.pending_cards.pop_front() {
+			self.save_seen(obj.name());
+		}
+	}
+
+	fn save_seen(&self, name: &str) {
+		// Only the kinds that were actually dismissed are persisted,
+		// so that closing the game while a card is displayed doesn't skip it next time.
+		let mut content = std::fs::read_to_string(&self.seen_file_path).unwrap_or_default();
+		content.push_str(name);
+		content.push('\n');
+		// Failing to persist is not a big deal, the card will just be shown again next run.
+		if let Some(dir) = self.seen_file_path.parent() {
+			let _ = std::fs::create_dir_all(dir);
+		}
+		let _ = std::fs::write(&self.seen_file_path, content);
+	}
+
+	/// Draws the card of the first pending object, if any.
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let Some(obj) = self.pending_cards.front() else {
+			return;
+		};
+		// Along the bottom edge, out of the way of the bunny at the center.
+		let card_width = 600.0;
+		let card_rect = Rect::new(
+			(screen_size.x - card_width) / 2.0,
+			screen_size.y - 240.0,
+			card_width,
+			160.0,
+		);
+		let z = DepthLayer::Popup.to_z_value();
+		canvas.draw(
+			&Quad,
+			DrawParam::default().dest_rect(card_rect).color(Color::new(0.1, 0.1, 0.15, 0.95)).z(z),
+		);
+		let sprite_height = 100.0;
+		draw_popup_sprite(
+			canvas,
+			spritesheet_stuff,
+			&obj_to_sprite(obj),
+			Vec2::new(card_rect.x + 30.0, card_rect.y + 30.0) + Vec2::splat(sprite_height / 2.0),
+			sprite_height,
+		);
+		let text_x = card_rect.x + 60.0 + sprite_height;
+		let mut name_text = Text::new(obj.name());
+		name_text.set_scale(32.0);
+		canvas.draw(
+			&name_text,
+			DrawParam::default().dest(Vec2::new(text_x, card_rect.y + 30.0)).z(z),
+		);
+		let mut hint_text = Text::new(obj_hint(obj));
+		hint_text.set_scale(18.0).set_bounds(Vec2::new(card_rect.right() - text_x - 20.0, 100.0));
+		canvas.draw(
+			&hint_text,
+			DrawParam::default().dest(Vec2::new(text_x, card_rect.y + 75.0)).z(z),
+		);
+		let mut dismiss_text = Text::new("Press Enter to continue");
+		dismiss_text.set_scale(14.0);
+		canvas.draw(
+			&dismiss_text,
+			DrawParam::default()
+				.dest(Vec2::new(text_x, card_rect.bottom() - 30.0))
+				.color(Color::new(0.6, 0.6, 0.6, 1.0))
+				.z(z),
+		);
+	}
+}
@@ -0,0 +1,198 @@
+//! Meta-progression, unlocks earned across runs that make the next runs start better equipped.
+//!
+//! Some things done during runs are counted from one run to the next, like slimes killed,
+//! and reaching the goal of an unlock adds its item to the starting room of the runs started
+//! from then on. The counts are persisted in `unlocks.txt` in the user data directory, with one
+//! `name: count` line per count, and the progress towards each unlock is listed on the unlocks
+//! screen (opened from the title screen).
+//!
+//! What was undone by a redo still happened, like for the run styles.
+
+use std::path::PathBuf;
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, DrawParam, Text},
+	winit::event::VirtualKeyCode,
+	Context,
+};
+
+use crate::{
+	gameplay::{LogicalEvent, LogicalTransition, Obj},
+	generation::Unlock,
+	graphics::{obj_to_sprite, DepthLayer},
+	spritesheet::SpritesheetStuff,
+	widgets::{centered_rect, draw_panel, Menu, MenuEvent, Widget},
+};
+
+/// Something done during runs that is counted across runs.
+#[derive(Clone, Copy)]
+enum Feat {
+	SlimesKilled,
+	WallsMined,
+	/// Exits taken by the bunny, the last one of a run winning it.
+	LevelsCleared,
+}
+
+impl Feat {
+	fn all() -> [Feat; 3] {
+		[Feat::SlimesKilled, Feat::WallsMined, Feat::LevelsCleared]
+	}
+
+	/// The key of its line in the file.
+	fn name(self) -> &'static str {
+		match self {
+			Feat::SlimesKilled => "slimes killed",
+			Feat::WallsMined => "walls mined",
+			Feat::LevelsCleared => "levels cleared",
+		}
+	}
+
+	fn index(self) -> usize {
+		self as usize
+	}
+}
+
+/// What counts towards the unlock, and how many are needed.
+fn goal(unlock: Unlock) -> (Feat, i32) {
+	match unlock {
+		Unlock::Pickaxe => (Feat::SlimesKilled, 50),
+		Unlock::Bomb => (Feat::WallsMined, 30),
+		Unlock::RedoHeart => (Feat::LevelsCleared, 10),
+	}
+}
+
+pub struct Unlocks {
+	/// The count of each feat, in the order of `Feat::all`.
+	counts: Vec<i32>,
+	file_path: PathBuf,
+}
+
+impl Unlocks {
+	pub fn load(ctx: &Context) -> Unlocks {
+		let file_path = ctx.fs.user_data_dir().join("unlocks.txt");
+		let content = std::fs::read_to_string(&file_path).unwrap_or_default();
+		let mut counts = vec![0; Feat::all().len()];
+		// Unknown or invalid lines are ignored, the count starts from zero.
+		for line in content.lines() {
+			let Some((name, count)) = line.split_once(':') else {
+				continue;
+			};
+			let feat = Feat::all().into_iter().find(|feat| feat.name() == name.trim());
+			if let (Some(feat), Ok(count)) = (feat, count.trim().parse()) {
+				counts[feat.index()] = count;
+			}
+		}
+		Unlocks { counts, file_path }
+	}
+
+	fn save(&self) {
+		let content: String = Feat::all()
+			.into_iter()
+			.map(|feat| format!("{}: {}\n", feat.name(), self.counts[feat.index()]))
+			.collect();
+		// Failing to persist is not a big deal, the feats will just have to be done again.
+		if let Some(dir) = self.file_path.parent() {
+			let _ = std::fs::create_dir_all(dir);
+		}
+		let _ = std::fs::write(&self.file_path, content);
+	}
+
+	/// To be given every transition of the run as it is applied.
+	/// The counts are saved right away when they change.
+	pub fn observe(&mut self, transition: &LogicalTransition) {
+		let mut changed = false;
+		for logical_event in transition.logical_events.iter() {
+			let feat = match logical_event {
+				LogicalEvent::Killed { obj: Obj::Slime { .. }, .. } => Feat::SlimesKilled,
+				LogicalEvent::Mined { .. } => Feat::WallsMined,
+				LogicalEvent::Exit { obj: Obj::Bunny { .. }, .. } => Feat::LevelsCleared,
+				_ => continue,
+			};
+			self.counts[feat.index()] += 1;
+			changed = true;
+		}
+		if changed {
+			self.save();
+		}
+	}
+
+	fn is_unlocked(&self, unlock: Unlock) -> bool {
+		let (feat, needed) = goal(unlock);
+		self.counts[feat.index()] >= needed
+	}
+
+	/// The unlocks earned so far, to be given to the runs started from now on.
+	pub fn unlocked(&self) -> Vec<Unlock> {
+		Unlock::all().into_iter().filter(|&unlock| self.is_unlocked(unlock)).collect()
+	}
+}
+
+/// Lists the unlocks and the progress towards each of them.
+pub struct UnlocksScreen {
+	menu: Menu,
+}
+
+impl UnlocksScreen {
+	pub fn new(unlocks: &Unlocks) -> UnlocksScreen {
+		let mut widgets: Vec<_> = Unlock::all()
+			.into_iter()
+			.map(|unlock| {
+				let (feat, needed) = goal(unlock);
+				let count = unlocks.counts[feat.index()];
+				let obj = unlock.starting_obj();
+				let description = if unlocks.is_unlocked(unlock) {
+					"Unlocked, runs start with it.".to_string()
+				} else {
+					format!(
+						"Runs start with it after {needed} {}, {count} so far.",
+						feat.name()
+					)
+				};
+				Widget::Button {
+					label: obj.name().to_string(),
+					description: Some(description),
+					icon: Some(obj_to_sprite(&obj)),
+				}
+			})
+			.collect();
+		widgets.push(Widget::button("Back"));
+		UnlocksScreen { menu: Menu::new(widgets) }
+	}
+
+	/// Returns `true` when the screen is to be closed.
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> bool {
+		if keycode == VirtualKeyCode::Escape {
+			return true;
+		}
+		matches!(
+			self.menu.key_down(keycode),
+			Some(MenuEvent::Activated(index)) if index == Unlock::all().len()
+		)
+	}
+
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		screen_size: Vec2,
+	) {
+		let panel_rect = centered_rect(500.0, 600.0, screen_size);
+		draw_panel(canvas, panel_rect);
+		let margin = 30.0;
+		let mut title_text = Text::new("Unlocks");
+		title_text.set_scale(40.0);
+		canvas.draw(
+			&title_text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x + margin, panel_rect.y + margin))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+		self.menu.draw(
+			canvas,
+			spritesheet_stuff,
+			Vec2::new(panel_rect.x + margin, panel_rect.y + margin + 70.0),
+			panel_rect.bottom() - margin,
+		);
+	}
+}
@@ -0,0 +1,210 @@
+//! Weather, cosmetic particles drawn over the world in screen space: embers rising near lava,
+//! water dripping near water, and spores falling near shrooms.
+//!
+//! There are no biomes as such, the tiles in sight around the bunny make one: each layer of
+//! particles gets thicker with how much of its biome is around, and a bit thicker with the depth.
+//! The particles are faint and small to never hide what is under them, and the reduced motion
+//! setting disables them.
+
+use std::time::Duration;
+
+use ggez::{
+	glam::{IVec2, Vec2},
+	graphics::{Canvas, Color, DrawParam, Quad, Rect},
+};
+use rand::Rng;
+
+use crate::{
+	gameplay::{Ground, LogicalWorld, Obj, Tile},
+	generation::filled_rect,
+	graphics::DepthLayer,
+};
+
+/// How far around the bunny (in tiles) the tiles are looked at to pick the weather.
+const RADIUS: i32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WeatherLayer {
+	Embers,
+	Drips,
+	Spores,
+}
+
+const LAYERS: [WeatherLayer; 3] = [
+	WeatherLayer::Embers,
+	WeatherLayer::Drips,
+	WeatherLayer::Spores,
+];
+
+impl WeatherLayer {
+	/// Is the tile a part of the biome of this layer?
+	fn is_of_biome(self, tile: &Tile) -> bool {
+		match self {
+			WeatherLayer::Embers => matches!(tile.ground, Ground::Lava),
+			WeatherLayer::Drips => matches!(tile.ground, Ground::Water),
+			WeatherLayer::Spores => {
+				matches!(tile.obj, Some(Obj::Shroom { .. } | Obj::Shroomer { .. }))
+			},
+		}
+	}
+
+	/// How many particles there are when the biome is all around.
+	fn max_particles(self) -> usize {
+		match self {
+			WeatherLayer::Embers => 40,
+			WeatherLayer::Drips => 25,
+			WeatherLayer::Spores => 35,
+		}
+	}
+
+	/// How many particles there are for each tile of the biome in sight.
+	fn particles_per_tile(self) -> f32 {
+		match self {
+			WeatherLayer::Embers => 2.0,
+			WeatherLayer::Drips => 1.0,
+			WeatherLayer::Spores => 4.0,
+		}
+	}
+
+	/// In screen pixels per second.
+	fn velocity(self) -> Vec2 {
+		match self {
+			WeatherLayer::Embers => Vec2::new(0.0, -60.0),
+			WeatherLayer::Drips => Vec2::new(0.0, 400.0),
+			WeatherLayer::Spores => Vec2::new(10.0, 25.0),
+		}
+	}
+
+	/// How far (in screen pixels) the particles sway from side to side as they go.
+	fn sway(self) -> f32 {
+		match self {
+			WeatherLayer::Embers => 15.0,
+			WeatherLayer::Drips => 0.0,
+			WeatherLayer::Spores => 30.0,
+		}
+	}
+
+	/// In screen pixels, the drips being streaks.
+	fn size(self) -> Vec2 {
+		match self {
+			WeatherLayer::Embers => Vec2::new(4.0, 4.0),
+			WeatherLayer::Drips => Vec2::new(2.0, 12.0),
+			WeatherLayer::Spores => Vec2::new(3.0, 3.0),
+		}
+	}
+
+	fn color(self) -> Color {
+		match self {
+			WeatherLayer::Embers => Color::new(1.0, 0.5, 0.1, 0.6),
+			WeatherLayer::Drips => Color::new(0.5, 0.7, 1.0, 0.4),
+			WeatherLayer::Spores => Color::new(0.8, 0.9, 0.6, 0.4),
+		}
+	}
+}
+
+struct Particle {
+	layer: WeatherLayer,
+	/// Where it would be without the sway, in screen pixels.
+	position: Vec2,
+	/// Where it is in its sway, in radians.
+	sway_phase: f32,
+}
+
+impl Particle {
+	fn position(&self) -> Vec2 {
+		self.position + Vec2::new(self.layer.sway() * self.sway_phase.sin(), 0.0)
+	}
+}
+
+/// The particles of all the layers, that come and go with what is around the bunny.
+pub struct Weather {
+	particles: Vec<Particle>,
+}
+
+impl Weather {
+	pub fn new() -> Weather {
+		Weather { particles: vec![] }
+	}
+
+	/// Are there no particles to animate?
+	pub fn is_clear(&self) -> bool {
+		self.particles.is_empty()
+	}
+
+	/// How many particles each layer should have, from what is in sight around the bunny.
+	fn target_particles(lw: &LogicalWorld) -> [usize; LAYERS.len()] {
+		let Some(player_coords) = lw.player_coords() else {
+			return [0; LAYERS.len()];
+		};
+		let tiles: Vec<&Tile> = filled_rect(
+			player_coords - IVec2::splat(RADIUS),
+			IVec2::splat(2 * RADIUS + 1),
+		)
+		.into_iter()
+		.filter_map(|coords| lw.tile(coords).filter(|tile| tile.visible))
+		.collect();
+		let depth_factor = 1.0 + 0.1 * (lw.depth - 1).clamp(0, 10) as f32;
+		LAYERS.map(|layer| {
+			let biome_tiles = tiles.iter().filter(|tile| layer.is_of_biome(tile)).count();
+			let particles = biome_tiles as f32 * layer.particles_per_tile() * depth_factor;
+			(particles as usize).min(layer.max_particles())
+		})
+	}
+
+	/// Moves the particles, the ones that leave the screen coming back from the other side
+	/// if their layer still has its biome around. Expected to be called once per frame.
+	pub fn animate(
+		&mut self,
+		lw: &LogicalWorld,
+		reduced_motion: bool,
+		frame_dt: Duration,
+		screen_size: Vec2,
+	) {
+		if reduced_motion {
+			self.particles.clear();
+			return;
+		}
+		let dt = frame_dt.as_secs_f32();
+		for particle in self.particles.iter_mut() {
+			particle.position += particle.layer.velocity() * dt;
+			particle.sway_phase += dt;
+		}
+		let targets = Weather::target_particles(lw);
+		let on_screen = |particle: &Particle| {
+			let margin = particle.layer.size().y + particle.layer.sway();
+			(-margin..screen_size.y + margin).contains(&particle.position.y)
+		};
+		self.particles.retain(on_screen);
+		let mut rng = rand::thread_rng();
+		for (layer, target) in LAYERS.into_iter().zip(targets) {
+			let count = self.particles.iter().filter(|particle| particle.layer == layer).count();
+			// A few at a time, for the weather to set in instead of appearing all at once.
+			for _ in count..target.min(count + 2) {
+				let y = if layer.velocity().y < 0.0 {
+					screen_size.y
+				} else {
+					0.0
+				};
+				self.particles.push(Particle {
+					layer,
+					position: Vec2::new(rng.gen_range(0.0..screen_size.x.max(1.0)), y),
+					sway_phase: rng.gen_range(0.0..std::f32::consts::TAU),
+				});
+			}
+		}
+	}
+
+	pub fn draw(&self, canvas: &mut Canvas) {
+		for particle in self.particles.iter() {
+			let position = particle.position();
+			let size = particle.layer.size();
+			canvas.draw(
+				&Quad,
+				DrawParam::default()
+					.dest_rect(Rect::new(position.x, position.y, size.x, size.y))
+					.color(particle.layer.color())
+					.z(DepthLayer::Weather.to_z_value()),
+			);
+		}
+	}
+}
@@ -0,0 +1,356 @@
+//! A minimal widget toolkit for the menus, navigable with the keyboard.
+//!
+//! A menu is a vertical list of widgets, one of which is selected. Keys are first translated
+//! into menu inputs (previous, next, decrease, increase, confirm), so that the menus don't
+//! depend on which device or which keys produced them. The screens that have a menu own it
+//! and keep it around (it is retained, not rebuilt every frame), they only have to forward
+//! the keys to it and react to what it reports.
+
+use std::time::{Duration, Instant};
+
+use ggez::{
+	glam::Vec2,
+	graphics::{Canvas, Color, DrawParam, Quad, Rect, Text},
+	winit::event::VirtualKeyCode,
+	Context, GameResult,
+};
+
+use crate::{
+	graphics::{draw_popup_sprite, DepthLayer},
+	spritesheet::{SpriteFromSheet, SpritesheetStuff},
+};
+
+/// What a key means to a menu.
+enum MenuInput {
+	Previous,
+	Next,
+	Decrease,
+	Increase,
+	Confirm,
+}
+
+impl MenuInput {
+	fn from_key(keycode: VirtualKeyCode) -> Option<MenuInput> {
+		use VirtualKeyCode as K;
+		match keycode {
+			K::Z | K::W | K::Up => Some(MenuInput::Previous),
+			K::S | K::Down => Some(MenuInput::Next),
+			K::Q | K::A | K::Left => Some(MenuInput::Decrease),
+			K::D | K::Right => Some(MenuInput::Increase),
+			K::Return | K::Space => Some(MenuInput::Confirm),
+			_ => None,
+		}
+	}
+}
+
+pub enum Widget {
+	/// Something to pick, like an item in a list.
+	Button {
+		label: String,
+		description: Option<String>,
+		icon: Option<SpriteFromSheet>,
+	},
+	Toggle {
+		label: String,
+		value: bool,
+	},
+	Slider {
+		label: String,
+		value: i32,
+		min: i32,
+		max: i32,
+	},
+	/// Captures the next key pressed once confirmed, to bind a control to it.
+	KeyCapture {
+		label: String,
+		key: VirtualKeyCode,
+		capturing: bool,
+	},
+	/// Text typed while it is selected. Only the arrows navigate away from it,
+	/// as the other navigation keys are letters that may be typed.
+	TextEntry {
+		label: String,
+		text: String,
+		max_length: usize,
+	},
+}
+
+impl Widget {
+	pub fn button(label: impl Into<String>) -> Widget {
+		Widget::Button { label: label.into(), description: None, icon: None }
+	}
+
+	fn text(&self) -> String {
+		match self {
+			Widget::Button { label, .. } => label.clone(),
+			Widget::Toggle { label, value } => {
+				format!("{label}: {}", if *value { "on" } else { "off" })
+			},
+			Widget::Slider { label, value, min, max } => {
+				let left = if value > min { "<" } else { " " };
+				let right = if value < max { ">" } else { " " };
+				format!("{label}: {left} {value} {right}")
+			},
+			Widget::KeyCapture { label, key, capturing } =>
+				if *capturing {
+					format!("{label}: press a key...")
+				} else {
+					format!("{label}: {key:?}")
+				},
+			Widget::TextEntry { label, text, .. } => format!("{label}: {text}_"),
+		}
+	}
+}
+
+/// What happened to a menu following a key press.
+pub enum MenuEvent {
+	/// The button (or text entry) at this index was confirmed.
+	Activated(usize),
+	/// The value of the widget at this index changed.
+	Changed(usize),
+}
+
+pub struct Menu {
+	pub widgets: Vec<Widget>,
+	selected: usize,
+}
+
+impl Menu {
+	pub fn new(widgets: Vec<Widget>) -> Menu {
+		assert!(!widgets.is_empty());
+		Menu { widgets, selected: 0 }
+	}
+
+	/// Is a key capture widget waiting for a key?
+	/// It takes every key until it gets one, which the menu's screen should let it have.
+	pub fn is_capturing(&self) -> bool {
+		self.widgets.iter().any(|widget| matches!(widget, Widget::KeyCapture { capturing: true, .. }))
+	}
+
+	pub fn key_down(&mut self, keycode: VirtualKeyCode) -> Option<MenuEvent> {
+		let index = self.selected;
+		if let Widget::KeyCapture { key, capturing: capturing @ true, .. } = &mut self.widgets[index]
+		{
+			*capturing = false;
+			// Escape cancels the capture, so that it can't be bound to something.
+			if keycode == VirtualKeyCode::Escape {
+				return None;
+			}
+			*key = keycode;
+			return Some(MenuEvent::Changed(index));
+		}
+		if let Widget::TextEntry { text, .. } = &mut self.widgets[index] {
+			match keycode {
+				VirtualKeyCode::Up | VirtualKeyCode::Down => {},
+				VirtualKeyCode::Back => {
+					return text.pop().map(|_char| MenuEvent::Changed(index));
+				},
+				VirtualKeyCode::Return => return Some(MenuEvent::Activated(index)),
+				_ => return None,
+			}
+		}
+		let input = MenuInput::from_key(keycode)?;
+		let widget_count = self.widgets.len();
+		match (input, &mut self.widgets[index]) {
+			(MenuInput::Previous, _) => {
+				self.selected = (self.selected + widget_count - 1) % widget_count;
+				None
+			},
+			(MenuInput::Next, _) => {
+				self.selected = (self.selected + 1) % widget_count;
+				None
+			},
+			(MenuInput::Confirm, Widget::Button { .. }) => Some(MenuEvent::Activated(index)),
+			(
+				MenuInput::Confirm | MenuInput::Decrease | MenuInput::Increase,
+				Widget::Toggle { value, .. },
+			) => {
+				*value = !*value;
+				Some(MenuEvent::Changed(index))
+			},
+			(MenuInput::Decrease, Widget::Slider { value, min, .. }) if *value > *min => {
+				*value -= 1;
+				Some(MenuEvent::Changed(index))
+			},
+			(MenuInput::Increase, Widget::Slider { value, max, .. }) if *value < *max => {
+				*value += 1;
+				Some(MenuEvent::Changed(index))
+			},
+			(MenuInput::Confirm, Widget::KeyCapture { capturing, .. }) => {
+				*capturing = true;
+				None
+			},
+			_ => None,
+		}
+	}
+
+	/// Typed characters go to the selected text entry, if any.
+	pub fn text_input(&mut self, character: char) -> Option<MenuEvent> {
+		let index = self.selected;
+		match &mut self.widgets[index] {
+			Widget::TextEntry { text, max_length, .. }
+				if !character.is_control() && text.chars().count() < *max_length =>
+			{
+				text.push(character);
+				Some(MenuEvent::Changed(index))
+			},
+			_ => None,
+		}
+	}
+
+	/// Draws the widgets from top to bottom, starting at the given position,
+	/// as long as they fit above the given bottom. If the selected widget would not fit,
+	/// the first widgets are skipped so that it does.
+	pub fn draw(
+		&self,
+		canvas: &mut Canvas,
+		spritesheet_stuff: &SpritesheetStuff,
+		top_left: Vec2,
+		bottom: f32,
+	) {
+		let z = DepthLayer::Popup.to_z_value();
+		let line_height = |widget: &Widget| match widget {
+			Widget::Button { description: Some(_), .. } => 60.0,
+			_ => 40.0,
+		};
+		let mut first_index = 0;
+		while self.widgets[first_index..=self.selected].iter().map(line_height).sum::<f32>()
+			> bottom - top_left.y
+			&& first_index < self.selected
+		{
+			first_index += 1;
+		}
+		let mut y = top_left.y;
+		for (index, widget) in self.widgets.iter().enumerate().skip(first_index) {
+			let description = match widget {
+				Widget::Button { description, .. } => description.as_ref(),
+				_ => None,
+			};
+			let line_height = line_height(widget);
+			if y + line_height > bottom {
+				break;
+			}
+			let mut x = top_left.x;
+			if let Widget::Button { icon: Some(icon), .. } = widget {
+				draw_popup_sprite(
+					canvas,
+					spritesheet_stuff,
+					icon,
+					Vec2::new(x + 16.0, y + 16.0),
+					32.0,
+				);
+				x += 48.0;
+			}
+			let selected = index == self.selected;
+			let mut text = Text::new(if selected {
+				format!("> {}", widget.text())
+			} else {
+				format!("  {}", widget.text())
+			});
+			text.set_scale(24.0);
+			let color = if selected {
+				Color::YELLOW
+			} else {
+				Color::new(0.7, 0.7, 0.7, 1.0)
+			};
+			canvas.draw(
+				&text,
+				DrawParam::default().dest(Vec2::new(x, y + 4.0)).color(color).z(z),
+			);
+			if let Some(description) = description {
+				let mut description_text = Text::new(description.as_str());
+				description_text.set_scale(16.0);
+				canvas.draw(
+					&description_text,
+					DrawParam::default()
+						.dest(Vec2::new(x + 28.0, y + 32.0))
+						.color(Color::new(0.7, 0.7, 0.7, 1.0))
+						.z(z),
+				);
+			}
+			y += line_height;
+		}
+	}
+}
+
+/// A small panel with some text next to the given point (like the mouse cursor),
+/// kept inside the screen.
+pub fn draw_tooltip(
+	ctx: &Context,
+	canvas: &mut Canvas,
+	text: &str,
+	at: Vec2,
+	screen_size: Vec2,
+) -> GameResult {
+	let margin = 8.0;
+	let width = 300.0;
+	let mut text = Text::new(text);
+	text.set_scale(16.0).set_bounds(Vec2::new(width - margin * 2.0, f32::INFINITY));
+	let height = text.measure(ctx)?.y + margin * 2.0;
+	let top_left = (at + Vec2::new(16.0, 16.0)).min(screen_size - Vec2::new(width, height));
+	draw_panel(canvas, Rect::new(top_left.x, top_left.y, width, height));
+	canvas.draw(
+		&text,
+		DrawParam::default().dest(top_left + Vec2::splat(margin)).z(DepthLayer::Popup.to_z_value()),
+	);
+	Ok(())
+}
+
+/// A short message at the bottom of the screen that goes away by itself, like "Run saved".
+pub struct Toast {
+	text: String,
+	shown_since: Instant,
+}
+
+impl Toast {
+	const DURATION: Duration = Duration::from_secs(3);
+
+	pub fn new(text: impl Into<String>) -> Toast {
+		Toast { text: text.into(), shown_since: Instant::now() }
+	}
+
+	pub fn is_over(&self) -> bool {
+		self.shown_since.elapsed() >= Toast::DURATION
+	}
+
+	pub fn draw(&self, canvas: &mut Canvas, screen_size: Vec2) {
+		let margin = 10.0;
+		let width = 500.0;
+		let mut text = Text::new(self.text.as_str());
+		text.set_scale(18.0).set_bounds(Vec2::new(width - margin * 2.0, f32::INFINITY));
+		let panel_rect = Rect::new(
+			(screen_size.x - width) / 2.0,
+			screen_size.y - 80.0,
+			width,
+			40.0,
+		);
+		draw_panel(canvas, panel_rect);
+		canvas.draw(
+			&text,
+			DrawParam::default()
+				.dest(Vec2::new(panel_rect.x, panel_rect.y) + Vec2::splat(margin))
+				.z(DepthLayer::Popup.to_z_value()),
+		);
+	}
+}
+
+/// The rect of the given size in the middle of the screen, where the menu panels go.
+pub fn centered_rect(width: f32, height: f32, screen_size: Vec2) -> Rect {
+	Rect::new(
+		(screen_size.x - width) / 2.0,
+		(screen_size.y - height) / 2.0,
+		width,
+		height,
+	)
+}
+
+/// The background of a menu screen.
+pub fn draw_panel(canvas: &mut Canvas, panel_rect: Rect) {
+	canvas.draw(
+		&Quad,
+		DrawParam::default()
+			.dest_rect(panel_rect)
+			.color(Color::new(0.1, 0.1, 0.15, 0.95))
+			.z(DepthLayer::Popup.to_z_value()),
+	);
+}
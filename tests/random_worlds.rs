@@ -0,0 +1,228 @@
+//! Random small worlds played with random actions, to flush out the situations that no one
+//! thought of, checking after every transition what should always hold.
+
+use pushdg::{
+	gameplay::{four_directions, AssistOptions, LogicalEvent, Obj},
+	simulation::play_game_turn,
+	Action, Generator, Transition, World,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// What the tiles of the random worlds are picked from, as legend lines of the level format.
+const LEGEND: &[&str] = &[
+	"s = floor slime:3",
+	"a = floor archer:2",
+	"h = floor ghost:2",
+	"g = floor golem:reversed",
+	"m = floor shroomer:2",
+	"f = water fish:right",
+	"c = floor crate:coin",
+	"b = floor bomb:2",
+	"x = floor barrel",
+	"w = floor bow",
+	"k = floor pickaxe",
+	"S = floor sword",
+	"d = floor shield",
+	"r = floor raft",
+	"R = floor rope",
+	"C = floor coin",
+	"K = floor key",
+	"D = floor door",
+	"H = floor heart",
+	"^ = spikes:armed",
+	"v = spikes",
+	"F = fragile",
+	"T = teleporter:1",
+	"> = conveyor:right",
+	"< = conveyor:left",
+	"p = plate:0",
+	"G = floor gate:0",
+	"P = floor piston:down:0",
+	"L = floor laser:left",
+	"/ = floor mirror:rising",
+	"M = floor magnet",
+];
+
+/// The characters with a default meaning, to be picked from too.
+const DEFAULT_CHARS: &[char] = &['#', 'o', '~', '_', '=', '%'];
+
+fn random_level_str(rng: &mut StdRng) -> String {
+	let chars: Vec<char> = LEGEND
+		.iter()
+		.map(|line| line.chars().next().unwrap())
+		.chain(DEFAULT_CHARS.iter().copied())
+		.collect();
+	let width = rng.gen_range(4..=8);
+	let height = rng.gen_range(3..=6);
+	let mut grid: Vec<Vec<char>> = (0..height)
+		.map(|_| {
+			(0..width)
+				.map(|_| {
+					if rng.gen_bool(0.5) {
+						'.'
+					} else {
+						*chars.choose(rng).unwrap()
+					}
+				})
+				.collect()
+		})
+		.collect();
+	grid[rng.gen_range(0..height)][rng.gen_range(0..width)] = '@';
+	let mut level_str = LEGEND.join("\n");
+	level_str.push_str("\n@ = floor bunny:5:5\n---\n");
+	for row in grid {
+		level_str.extend(row);
+		level_str.push('\n');
+	}
+	level_str
+}
+
+fn random_action(rng: &mut StdRng) -> Action {
+	let direction = *four_directions().choose(rng).unwrap();
+	match rng.gen_range(0..10) {
+		0 => Action::PlaceBomb(direction),
+		1 => Action::PickUpOrDrop(direction),
+		_ => Action::Move(direction),
+	}
+}
+
+/// Walls, machine parts and shrooms come and go without an event that says so (walls grow
+/// back around what an explosion opened, gates close, pistons extend, shroomers leave shrooms
+/// behind), they are not counted.
+fn counted_objs(lw: &World) -> usize {
+	lw.coords_where(|tile| {
+		tile.obj.as_ref().is_some_and(|obj| {
+			!matches!(
+				obj,
+				Obj::Wall | Obj::Gate { .. } | Obj::PistonHead | Obj::Shroom { .. }
+			)
+		})
+	})
+	.len()
+}
+
+/// How many objects the events of a transition may have made disappear, and appear.
+fn removed_and_added(logical_events: &[LogicalEvent]) -> (usize, usize) {
+	let (mut removed, mut added) = (0, 0);
+	for logical_event in logical_events {
+		match logical_event {
+			LogicalEvent::DoorOpenedWithKey { .. } => removed += 2,
+			LogicalEvent::Killed { .. }
+			| LogicalEvent::Mined { .. }
+			| LogicalEvent::Squelched { .. }
+			| LogicalEvent::Crafted { .. }
+			| LogicalEvent::Healed { .. }
+			| LogicalEvent::RedoGained { .. }
+			| LogicalEvent::RewindGained { .. }
+			| LogicalEvent::BombGained { .. }
+			| LogicalEvent::GoldGained { .. }
+			| LogicalEvent::KeyShardGained { .. }
+			| LogicalEvent::StrengthGained { .. }
+			| LogicalEvent::CrateBroken { .. }
+			| LogicalEvent::Bought { .. }
+			| LogicalEvent::NoteRead { .. }
+			| LogicalEvent::MirrorCursed { .. }
+			| LogicalEvent::Exit { .. }
+			| LogicalEvent::MoveInto { .. }
+			| LogicalEvent::Stomped { .. }
+			| LogicalEvent::PickedUp { .. }
+			| LogicalEvent::Exploded { .. }
+			| LogicalEvent::FellIntoHole { .. }
+			| LogicalEvent::Sank { .. }
+			| LogicalEvent::RaftMoored { .. }
+			| LogicalEvent::BurntUp { .. } => removed += 1,
+			LogicalEvent::BombPlaced { .. }
+			| LogicalEvent::Dropped { .. }
+			| LogicalEvent::ArrowShot { .. } => added += 1,
+			_ => {},
+		}
+	}
+	(removed, added)
+}
+
+/// Panics with the level and the actions so far if something that should hold does not.
+fn check(previous_lw: &World, transition: &Transition, players_turn: bool, context: &str) {
+	let lw = &transition.resulting_lw;
+	// Validation covers one object per tile (multi-tile objects included), no dead object
+	// left, no object over a hole, and the bunny's HP.
+	if let Err(error) = lw.validate(players_turn) {
+		panic!("Invalid world {context}: {error}");
+	}
+	for coords in lw.coords_where(|tile| tile.obj.is_some()) {
+		let obj = lw.obj(coords).unwrap();
+		if let (Some(hp), Some(max_hp)) = (obj.hp(), obj.max_hp()) {
+			assert!(
+				hp <= max_hp,
+				"{} at {coords} has {hp} HP out of {max_hp} {context}",
+				obj.name()
+			);
+		}
+	}
+	// Only what has HP is hit, what has none is broken or killed right away.
+	for logical_event in transition.logical_events.iter() {
+		if let LogicalEvent::Hit { at, damages } = logical_event {
+			let had_hp = previous_lw.occupant(*at).and_then(Obj::hp).is_some()
+				|| lw.occupant(*at).and_then(Obj::hp).is_some();
+			let killed = transition.logical_events.iter().any(
+				|logical_event| matches!(logical_event, LogicalEvent::Killed { at: killed_at, .. } if killed_at == at),
+			);
+			assert!(
+				had_hp || killed || *damages == 0,
+				"Something at {at} without HP was hit {context}"
+			);
+		}
+	}
+	// Objects don't appear or disappear without something happening to them.
+	let (before, after) = (counted_objs(previous_lw), counted_objs(lw));
+	let (removed, added) = removed_and_added(&transition.logical_events);
+	assert!(
+		after + removed >= before && after <= before + added,
+		"There were {before} objects and there are {after}, with {removed} removed and \
+		 {added} added {context}"
+	);
+}
+
+#[test]
+fn random_worlds_played_at_random_stay_sound() {
+	let run_config = Generator {
+		seed: 0,
+		assist: AssistOptions::default(),
+		encounter_director: false,
+		..Generator::new()
+	};
+	for world_index in 0..300 {
+		let mut rng = StdRng::seed_from_u64(world_index);
+		let level_str = random_level_str(&mut rng);
+		let mut lw =
+			World::from_level_str(&level_str).unwrap().generated_walls_outside().updated_visibility();
+		lw.bombs = 2;
+		let mut actions = vec![];
+		for _ in 0..30 {
+			if !lw.has_player() || lw.player_exited() {
+				break;
+			}
+			let action = random_action(&mut rng);
+			actions.push(action);
+			let context = format!("after {actions:?} in the level\n{level_str}");
+			let Some(transition) = lw.player_act(action) else {
+				continue;
+			};
+			check(&lw, &transition, false, &context);
+			let mut previous_lw = transition.resulting_lw.clone();
+			let transitions = play_game_turn(transition.clone(), &run_config);
+			for game_transition in transitions.iter() {
+				check(
+					&previous_lw,
+					game_transition,
+					false,
+					&format!("in the game's turn {context}"),
+				);
+				previous_lw = game_transition.resulting_lw.clone();
+			}
+			lw = previous_lw;
+			if let Err(error) = lw.validate(true) {
+				panic!("Invalid world at the end of the game's turn {context}: {error}");
+			}
+		}
+	}
+}
@@ -0,0 +1,31 @@
+//! Runs driven headlessly through the library, as a bot or a server would,
+//! with the items of the root of the crate only where they are enough.
+
+use pushdg::{
+	gameplay::{four_directions, AssistOptions},
+	Action, Generator, Simulation,
+};
+
+/// The same moves played from the same seed play out the same, which replays, redos and
+/// daily challenges rely on.
+#[test]
+fn same_seed_same_moves_same_run() {
+	let run_config = Generator { seed: 12345, assist: AssistOptions::default(), ..Generator::new() };
+	let mut simulations = [
+		Simulation::new(run_config.clone()),
+		Simulation::new(run_config),
+	];
+	assert_eq!(simulations[0].lw.checksum(), simulations[1].lw.checksum());
+	let directions = four_directions();
+	for turn in 0..40_usize {
+		let direction = directions[(turn * 3 + turn / 4) % 4];
+		for simulation in simulations.iter_mut() {
+			simulation.player_act(Action::Move(direction));
+		}
+		assert_eq!(
+			simulations[0].lw.checksum(),
+			simulations[1].lw.checksum(),
+			"The runs diverged on turn {turn}"
+		);
+	}
+}